@@ -1,9 +1,104 @@
-use darling::{Error, FromField, FromMeta};
+use darling::{Error, FromDeriveInput, FromField, FromMeta};
 use proc_macro::TokenStream;
 use proc_macro2::Span;
-use quote::{ToTokens, quote};
+use quote::{ToTokens, quote, quote_spanned};
 use syn::{Attribute, Data, DeriveInput, Expr, Fields, Lit, Meta, Type, parse_macro_input};
 
+/// Parsed representation of struct-level #[cnfg(...)] attributes.
+#[derive(Debug, FromDeriveInput)]
+#[darling(attributes(cnfg))]
+struct CnfgStruct {
+    /// Application name used for platform config directory discovery.
+    #[darling(default)]
+    app_name: Option<String>,
+
+    /// Text shown above the usage line of `--help` instead of the doc comment.
+    #[darling(default)]
+    about: Option<String>,
+
+    /// Text shown at the end of `--help`.
+    #[darling(default)]
+    after_help: Option<String>,
+
+    /// Prefix of the application's env vars, checked for unbound names.
+    #[darling(default)]
+    env_prefix: Option<String>,
+
+    /// Read fields without `env` from a name derived from their path.
+    #[darling(default)]
+    auto_env: bool,
+
+    /// Generate a read-only accessor per field that records the read for
+    /// `cnfg::audit`.
+    #[darling(default)]
+    accessors: bool,
+
+    /// Version printed for `--version` (bare form uses `CARGO_PKG_VERSION`).
+    #[darling(default)]
+    version: Option<VersionAttr>,
+
+    /// Start defaults from the struct's `Default` impl.
+    #[darling(default)]
+    use_default: bool,
+
+    /// `.env` files to read, or `false` to read none.
+    #[darling(default)]
+    dotenv: Option<DotenvAttr>,
+}
+
+/// Represents `#[cnfg(dotenv = false)]`, `#[cnfg(dotenv = ".env.test")]`,
+/// or `#[cnfg(dotenv(".env", ".env.test"))]`.
+#[derive(Debug, Clone)]
+enum DotenvAttr {
+    Disabled,
+    Search,
+    Files(Vec<String>),
+}
+
+impl FromMeta for DotenvAttr {
+    fn from_bool(value: bool) -> Result<Self, Error> {
+        Ok(if value {
+            DotenvAttr::Search
+        } else {
+            DotenvAttr::Disabled
+        })
+    }
+
+    fn from_string(value: &str) -> Result<Self, Error> {
+        Ok(DotenvAttr::Files(vec![value.to_string()]))
+    }
+
+    fn from_list(items: &[darling::ast::NestedMeta]) -> Result<Self, Error> {
+        items
+            .iter()
+            .map(|item| match item {
+                darling::ast::NestedMeta::Lit(Lit::Str(s)) => Ok(s.value()),
+                other => Err(Error::custom("expected a file path string").with_span(other)),
+            })
+            .collect::<Result<_, _>>()
+            .map(DotenvAttr::Files)
+    }
+}
+
+/// Represents `#[cnfg(version)]` or `#[cnfg(version = "1.2.3")]`.
+#[derive(Debug, Clone)]
+enum VersionAttr {
+    /// bare form: the deriving crate's `CARGO_PKG_VERSION`
+    Crate,
+    /// explicit form: `#[cnfg(version = "1.2.3")]`
+    Explicit(String),
+}
+
+impl FromMeta for VersionAttr {
+    fn from_word() -> Result<Self, Error> {
+        Ok(VersionAttr::Crate)
+    }
+
+    fn from_string(value: &str) -> Result<Self, Error> {
+        Ok(VersionAttr::Explicit(value.to_string()))
+    }
+}
+
 /// Parsed representation of a field with #[cnfg(...)] attributes.
 #[derive(Debug, FromField)]
 #[darling(attributes(cnfg))]
@@ -14,54 +109,194 @@ struct CnfgField {
     #[darling(default)]
     default: Option<syn::Lit>,
 
+    /// Function computing the default (`fn() -> T` or `fn() -> serde_json::Value`).
+    #[darling(default)]
+    default_fn: Option<syn::Path>,
+
     #[darling(default)]
     env: Option<String>,
 
-    /// CLI flag support (bare or explicit).
+    /// Other env var names read when `env` is unset, e.g. the old name of a
+    /// renamed variable. May repeat.
+    #[darling(default, multiple)]
+    env_alias: Vec<String>,
+
+    /// Accept `<ENV>_FILE` pointing at a file with the value.
     #[darling(default)]
+    allow_file: bool,
+
+    /// CLI flag support (bare, explicit flag, or options list).
+    #[darling(default, with = parse_cli_attr)]
     cli: Option<CliAttr>,
 
+    /// Integer byte count accepting `512MB` / `2GiB` style values.
+    #[darling(default)]
+    bytes: bool,
+
+    /// Function decoding env/CLI strings, `fn(&str) -> Result<T, String>`.
+    #[darling(default)]
+    parse_with: Option<syn::Path>,
+
+    /// Accepted CLI values, e.g. `values("debug", "info")`.
+    #[darling(default)]
+    values: Vec<syn::LitStr>,
+
     #[darling(default)]
     required: bool,
 
+    /// Required when the sibling at this dotted path is set and not `false`.
+    #[darling(default)]
+    required_if: Option<String>,
+
+    /// Required unless the sibling at this dotted path is set and not `false`.
+    #[darling(default)]
+    required_unless: Option<String>,
+
     #[darling(default)]
     nested: bool,
 
+    /// Subcommand selected by a leading positional (`Option<Nested>` only).
+    #[darling(default)]
+    command: Option<CommandAttr>,
+
+    /// Deprecation note, e.g. `deprecated = "use `listen` instead"`.
+    #[darling(default)]
+    deprecated: Option<String>,
+
+    /// Old dotted path whose values migrate to this field.
+    #[darling(default)]
+    renamed_from: Option<String>,
+
+    /// How layers combine, e.g. `merge = "append"` for lists.
+    #[darling(default)]
+    merge: Option<MergeAttr>,
+
+    /// Whether the env var beats the flag, e.g. `precedence = "env_over_cli"`;
+    /// `--set` overrides win either way.
+    #[darling(default)]
+    precedence: Option<PrecedenceAttr>,
+
+    /// Map collecting keys that match no declared field.
+    #[darling(default)]
+    extra: bool,
+
+    /// Sensitive value (password, token) kept out of fingerprints.
+    #[darling(default)]
+    secret: bool,
+
+    /// Secret manager reference used when no layer sets the field, e.g.
+    /// `secret_ref = "projects/x/secrets/db-pass"`. Implies `secret`.
+    #[darling(default)]
+    secret_ref: Option<String>,
+
+    /// Resolve a relative path against the directory of the config file
+    /// that set it.
+    #[darling(default)]
+    relative_to_config: bool,
+
     #[darling(default, multiple, rename = "validate")]
     validators: Vec<ValidatorAttr>,
 }
 
-/// Represents `#[cnfg(cli)]` or `#[cnfg(cli = "--flag")]`.
+/// Represents `#[cnfg(command)]` or `#[cnfg(command = "name")]`.
 #[derive(Debug, Clone)]
-enum CliAttr {
-    /// bare form: `#[cnfg(cli)]`
-    Flag,
-    /// explicit flag form: `#[cnfg(cli = "--custom")]`
-    Custom(String),
+enum CommandAttr {
+    /// bare form: the kebab-cased field name
+    Field,
+    /// explicit form: `#[cnfg(command = "db-migrate")]`
+    Named(String),
+}
+
+impl FromMeta for CommandAttr {
+    fn from_word() -> Result<Self, Error> {
+        Ok(CommandAttr::Field)
+    }
+
+    fn from_string(value: &str) -> Result<Self, Error> {
+        Ok(CommandAttr::Named(value.to_string()))
+    }
+}
+
+/// Represents `#[cnfg(merge = "replace" | "append" | "union")]`.
+#[derive(Debug, Clone, Copy)]
+enum MergeAttr {
+    Replace,
+    Append,
+    Union,
+}
+
+impl FromMeta for MergeAttr {
+    fn from_string(value: &str) -> Result<Self, Error> {
+        match value {
+            "replace" => Ok(MergeAttr::Replace),
+            "append" => Ok(MergeAttr::Append),
+            "union" => Ok(MergeAttr::Union),
+            other => Err(Error::unknown_value(other)),
+        }
+    }
+}
+
+/// Represents `#[cnfg(precedence = "env_over_cli" | "cli_over_env")]`.
+#[derive(Debug, Clone, Copy)]
+enum PrecedenceAttr {
+    EnvOverCli,
+    CliOverEnv,
+}
+
+impl FromMeta for PrecedenceAttr {
+    fn from_string(value: &str) -> Result<Self, Error> {
+        match value {
+            "env_over_cli" => Ok(PrecedenceAttr::EnvOverCli),
+            "cli_over_env" => Ok(PrecedenceAttr::CliOverEnv),
+            other => Err(Error::unknown_value(other)),
+        }
+    }
+}
+
+/// Represents `#[cnfg(cli)]`, `#[cnfg(cli = "--flag")]`, or
+/// `#[cnfg(cli(flag = "--flag", value_name = "PORT"))]`.
+#[derive(Debug, Clone, Default, FromMeta)]
+struct CliAttr {
+    /// Explicit flag name; the kebab-cased field name when unset.
+    #[darling(default)]
+    flag: Option<String>,
+    /// Placeholder shown in help (`--port <PORT>`).
+    #[darling(default)]
+    value_name: Option<String>,
+    /// Parsed, but only listed by `--help-all`.
+    #[darling(default)]
+    hidden: bool,
 }
 
-impl FromMeta for CliAttr {
-    fn from_meta(meta: &syn::Meta) -> Result<Self, Error> {
+impl CliAttr {
+    fn from_attr(meta: &syn::Meta) -> Result<Self, Error> {
         match meta {
-            syn::Meta::Path(_) => Ok(CliAttr::Flag),
+            syn::Meta::Path(_) => Ok(CliAttr::default()),
             syn::Meta::NameValue(nv) => match &nv.value {
                 syn::Expr::Lit(expr_lit) => parse_cli_lit(&expr_lit.lit),
                 other => Err(Error::custom("expected a literal value").with_span(other)),
             },
-            syn::Meta::List(list) => Err(Error::custom(
-                "unsupported cli format; use #[cnfg(cli)] or #[cnfg(cli = \"--flag\")]",
-            )
-            .with_span(list)),
+            syn::Meta::List(list) => {
+                let items = darling::ast::NestedMeta::parse_meta_list(list.tokens.clone())?;
+                Self::from_list(&items)
+            }
         }
     }
 }
 
+fn parse_cli_attr(meta: &syn::Meta) -> Result<Option<CliAttr>, Error> {
+    CliAttr::from_attr(meta).map(Some)
+}
+
 fn parse_cli_lit(lit: &Lit) -> Result<CliAttr, Error> {
     match lit {
-        Lit::Str(s) => Ok(CliAttr::Custom(s.value())),
+        Lit::Str(s) => Ok(CliAttr {
+            flag: Some(s.value()),
+            ..CliAttr::default()
+        }),
         Lit::Bool(b) => {
             if b.value() {
-                Ok(CliAttr::Flag)
+                Ok(CliAttr::default())
             } else {
                 Err(Error::custom(
                     "use #[cnfg(cli)] to enable CLI parsing; remove the attribute to disable it",
@@ -73,27 +308,229 @@ fn parse_cli_lit(lit: &Lit) -> Result<CliAttr, Error> {
     }
 }
 
-/// Validator attributes: range, regex, url.
+/// Validator attributes: range, regex, url, the filesystem checks, the
+/// string formats (email, hostname, uuid, semver), port, cidr, and the
+/// profile-conditional `forbid_in_profile` and `when_profile`.
 #[derive(Debug, FromMeta)]
 #[darling(rename_all = "kebab-case")]
 enum ValidatorAttr {
     Range(RangeArgs),
     Regex(String),
-    Url,
+    Url(UrlArgs),
+    #[darling(rename = "file_exists")]
+    FileExists,
+    #[darling(rename = "dir_exists")]
+    DirExists,
+    Readable,
+    Email,
+    Hostname,
+    Uuid,
+    Semver,
+    Port(PortArgs),
+    Cidr,
+    #[darling(rename = "forbid_in_profile")]
+    ForbidInProfile(ForbidArgs),
+    #[darling(rename = "when_profile")]
+    WhenProfile(WhenProfileArgs),
+}
+
+/// `forbid_in_profile(profile = "prod", value = "0.0.0.0")`.
+#[derive(Debug, FromMeta)]
+struct ForbidArgs {
+    profile: String,
+    value: Lit,
+}
+
+/// `when_profile(profile = "prod", url(schemes = ["https"]), ...)`: the
+/// listed validators only run in that profile.
+#[derive(Debug)]
+struct WhenProfileArgs {
+    profile: String,
+    validators: Vec<ValidatorAttr>,
+}
+
+impl FromMeta for WhenProfileArgs {
+    fn from_list(items: &[darling::ast::NestedMeta]) -> Result<Self, Error> {
+        let mut profile = None;
+        let mut validators = Vec::new();
+        for item in items {
+            match item {
+                darling::ast::NestedMeta::Meta(syn::Meta::NameValue(nv))
+                    if nv.path.is_ident("profile") =>
+                {
+                    profile = Some(String::from_expr(&nv.value)?);
+                }
+                darling::ast::NestedMeta::Meta(meta) => {
+                    let validator = ValidatorAttr::from_list(std::slice::from_ref(item))?;
+                    if matches!(validator, ValidatorAttr::WhenProfile(_)) {
+                        return Err(
+                            Error::custom("`when_profile` cannot be nested").with_span(meta)
+                        );
+                    }
+                    validators.push(validator);
+                }
+                other => return Err(Error::custom("expected a validator").with_span(other)),
+            }
+        }
+        let profile = profile.ok_or_else(|| Error::missing_field("profile"))?;
+        Ok(WhenProfileArgs {
+            profile,
+            validators,
+        })
+    }
+}
+
+/// `port` or `port(unprivileged)`.
+#[derive(Debug, Default, FromMeta)]
+#[darling(from_word = PortArgs::from_word)]
+struct PortArgs {
+    #[darling(default)]
+    unprivileged: bool,
+}
+
+impl PortArgs {
+    /// Bare `port` allows every port from 1.
+    fn from_word() -> Result<Self, Error> {
+        Ok(PortArgs::default())
+    }
+}
+
+/// `url` or `url(schemes = ["https"], require_host)`.
+#[derive(Debug, Default, FromMeta)]
+#[darling(from_word = UrlArgs::from_word)]
+struct UrlArgs {
+    #[darling(default)]
+    schemes: Vec<syn::LitStr>,
+    #[darling(default)]
+    require_host: bool,
+}
+
+impl UrlArgs {
+    /// Bare `url` takes no options.
+    fn from_word() -> Result<Self, Error> {
+        Ok(UrlArgs::default())
+    }
 }
 
 #[derive(Debug, Default, FromMeta)]
 struct RangeArgs {
     #[darling(default)]
-    min: Option<f64>,
+    min: Option<Bound>,
     #[darling(default)]
-    max: Option<f64>,
+    max: Option<Bound>,
+}
+
+/// A range bound: a number, a byte size like `"64MiB"` for `bytes` fields,
+/// or a date like `"2024-01-01"` for date and date-time fields.
+#[derive(Debug, Clone)]
+struct Bound {
+    value: f64,
+    byte_size: bool,
+    /// The bound as written, when it is a date; `value` holds its Unix time.
+    date: Option<String>,
+}
+
+impl Bound {
+    fn number(value: f64) -> Self {
+        Bound {
+            value,
+            byte_size: false,
+            date: None,
+        }
+    }
+
+    /// The bound as shown in messages.
+    fn shown(&self) -> String {
+        self.date.clone().unwrap_or_else(|| self.value.to_string())
+    }
+}
+
+impl FromMeta for Bound {
+    fn from_string(value: &str) -> Result<Self, Error> {
+        if let Ok(number) = value.trim().parse::<f64>() {
+            return Ok(Bound::number(number));
+        }
+        if looks_like_date(value) {
+            return parse_date_bound(value)
+                .map(|seconds| Bound {
+                    value: seconds,
+                    byte_size: false,
+                    date: Some(value.to_string()),
+                })
+                .ok_or_else(|| {
+                    Error::custom(format!(
+                        "expected a date like `2024-01-01` or an RFC 3339 date-time, found `{value}`"
+                    ))
+                });
+        }
+        parse_byte_size(value)
+            .map(|bytes| Bound {
+                value: bytes as f64,
+                byte_size: true,
+                date: None,
+            })
+            .ok_or_else(|| {
+                Error::custom(format!("expected a number or byte size, found `{value}`"))
+            })
+    }
+
+    fn from_value(value: &Lit) -> Result<Self, Error> {
+        let number = match value {
+            Lit::Str(s) => return Self::from_string(&s.value()),
+            Lit::Int(i) => i.base10_parse::<f64>(),
+            Lit::Float(f) => f.base10_parse::<f64>(),
+            _ => return Err(Error::unexpected_lit_type(value)),
+        };
+        number.map_err(Error::from).map(Bound::number)
+    }
 }
 
 #[proc_macro_derive(Cnfg, attributes(cnfg))]
 pub fn derive_cnfg(input: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(input as DeriveInput);
+    expand(parse_macro_input!(input as DeriveInput), false)
+}
+
+/// Derive only `cnfg::Validate` from the `#[cnfg(validate(...))]`
+/// attributes, for structs that are checked but never loaded, such as
+/// request payloads.
+#[proc_macro_derive(CnfgValidate, attributes(cnfg))]
+pub fn derive_cnfg_validate(input: TokenStream) -> TokenStream {
+    expand(parse_macro_input!(input as DeriveInput), true)
+}
+
+/// Expand `#[derive(Cnfg)]`, or with `validate_only` just its `Validate`
+/// impl.
+fn expand(input: DeriveInput, validate_only: bool) -> TokenStream {
+    let opts = CnfgStruct::from_derive_input(&input).expect("parse #[cnfg] struct attributes");
     let name = input.ident;
+    let app_name_tokens = option_str_tokens(opts.app_name.as_deref());
+    let env_prefix_tokens = option_str_tokens(opts.env_prefix.as_deref());
+    let auto_env_flag = opts.auto_env;
+    let about_tokens = option_str_tokens(opts.about.as_deref());
+    let after_help_tokens = option_str_tokens(opts.after_help.as_deref());
+    let version_tokens = match &opts.version {
+        None => quote! { None },
+        Some(VersionAttr::Crate) => quote! { Some(env!("CARGO_PKG_VERSION")) },
+        Some(VersionAttr::Explicit(v)) => quote! { Some(#v) },
+    };
+    let dotenv_tokens = match &opts.dotenv {
+        None | Some(DotenvAttr::Search) => quote! { cnfg::Dotenv::Search },
+        Some(DotenvAttr::Disabled) => quote! { cnfg::Dotenv::Disabled },
+        Some(DotenvAttr::Files(files)) => quote! {
+            cnfg::Dotenv::Files(vec![#(std::path::PathBuf::from(#files)),*])
+        },
+    };
+    let rename_all = match serde_rename_all(&input.attrs) {
+        Ok(rule) => rule,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let defaults_base = if opts.use_default {
+        quote! {
+            cnfg::util::default_base::<Self>(<Self as cnfg::ConfigMeta>::required_fields())
+        }
+    } else {
+        quote! { cnfg::__private::serde_json::Map::new() }
+    };
 
     let struct_doc_tokens = doc_option_tokens(doc_from_attrs(&input.attrs));
 
@@ -110,54 +547,267 @@ pub fn derive_cnfg(input: TokenStream) -> TokenStream {
     let mut cli_spec_stmts = Vec::new();
     let mut required_stmts = Vec::new();
     let mut validate_body = Vec::new();
+    let mut command_stmts = Vec::new();
+    let mut list_stmts = Vec::new();
+    let mut schema_stmts = Vec::new();
+    let mut extra_seen = false;
+    let mut errors: Option<syn::Error> = None;
+    let mut flags: Vec<(String, syn::Ident)> = Vec::new();
+    let mut envs: Vec<(String, syn::Ident)> = Vec::new();
+    let mut sections: Vec<(String, Type, syn::Ident)> = Vec::new();
+    let mut audit_stmts = Vec::new();
+    let mut accessor_fns = Vec::new();
 
     for f in fields {
         let cf = CnfgField::from_field(f).expect("parse #[cnfg] attributes");
         let ident = cf.ident.clone().expect("cnfg requires named fields");
         let fname = ident.to_string();
-        let path_lit = syn::LitStr::new(&fname, Span::call_site());
-        let field_name_lit = path_lit.clone();
+        // Paths follow the serialized key so merged values line up with serde.
+        let key = serde_rename(&f.attrs)
+            .or_else(|| rename_all.map(|rule| rule.apply(&fname)))
+            .unwrap_or_else(|| fname.clone());
+        let path_lit = syn::LitStr::new(&key, Span::call_site());
+        let field_name_lit = syn::LitStr::new(&fname, Span::call_site());
         let required_flag = cf.required;
-        let nested_flag = cf.nested;
+        if required_flag && (cf.required_if.is_some() || cf.required_unless.is_some()) {
+            panic!(
+                "field `{fname}`: `required` cannot be combined with `required_if` or `required_unless`"
+            );
+        }
+        let required_if_tokens = option_str_tokens(cf.required_if.as_deref());
+        let required_unless_tokens = option_str_tokens(cf.required_unless.as_deref());
+        let allow_file_flag = cf.allow_file;
+        let command_flag = cf.command.is_some();
+        if command_flag && cf.nested {
+            panic!("field `{fname}`: `command` already implies `nested`");
+        }
+        let nested_flag = cf.nested || command_flag;
         let field_doc_for_field = doc_option_tokens(doc_from_attrs(&f.attrs));
         let field_doc_for_cli = field_doc_for_field.clone();
         let env_tokens = option_str_tokens(cf.env.as_deref());
+        let deprecated_tokens = option_str_tokens(cf.deprecated.as_deref());
+        let renamed_from_tokens = option_str_tokens(cf.renamed_from.as_deref());
         let (is_option, inner_ty) = option_inner(&cf.ty);
-        let nested_ty = if nested_flag && is_option {
+        let is_list = vec_inner(inner_ty).is_some();
+        let nested_list = nested_flag && is_list;
+        if command_flag && is_list {
+            panic!("field `{fname}`: #[cnfg(command)] cannot be a list");
+        }
+        let nested_ty = if nested_list {
+            vec_inner(inner_ty).expect("list element type")
+        } else if nested_flag && is_option {
             inner_ty
         } else {
             &cf.ty
         };
 
+        if cf.bytes && !is_int(vec_inner(inner_ty).unwrap_or(inner_ty)) {
+            panic!("field `{fname}`: #[cnfg(bytes)] requires an integer type");
+        }
+        if cf.parse_with.is_some() && (nested_flag || cf.bytes || cf.extra) {
+            panic!(
+                "field `{fname}`: `parse_with` cannot be combined with `nested`, `bytes`, or `extra`"
+            );
+        }
         let mut field_kind = kind_for_type(&cf.ty);
         if nested_flag {
             field_kind = quote! { cnfg::Kind::Object };
+        } else if cf.bytes {
+            field_kind = quote! { cnfg::Kind::Bytes };
+        } else if cf.parse_with.is_some() {
+            field_kind = quote! { cnfg::Kind::Custom };
+        }
+        // The decoder returns the whole field value (the list for a `Vec`),
+        // checked against the field type at compile time.
+        let parse_tokens = match &cf.parse_with {
+            None => quote! { None },
+            Some(func) => quote! {
+                Some((|raw: &str| -> Result<cnfg::__private::serde_json::Value, String> {
+                    let value: #inner_ty = #func(raw)?;
+                    cnfg::__private::serde_json::to_value(value).map_err(|e| e.to_string())
+                }) as cnfg::ParseFn)
+            },
+        };
+
+        let merge_tokens = match cf.merge {
+            None => quote! { None },
+            Some(strategy) => {
+                let collection = is_list || is_map(inner_ty);
+                match strategy {
+                    MergeAttr::Replace if !collection && !nested_flag => panic!(
+                        "field `{fname}`: merge = \"replace\" needs a list, map, or nested field"
+                    ),
+                    MergeAttr::Append | MergeAttr::Union if !is_list => {
+                        panic!("field `{fname}`: merge = \"append\"/\"union\" needs a list field")
+                    }
+                    _ => {}
+                }
+                let variant = match strategy {
+                    MergeAttr::Replace => quote! { Replace },
+                    MergeAttr::Append => quote! { Append },
+                    MergeAttr::Union => quote! { Union },
+                };
+                quote! { Some(cnfg::MergeStrategy::#variant) }
+            }
+        };
+
+        let precedence_tokens = match cf.precedence {
+            None => quote! { None },
+            Some(PrecedenceAttr::EnvOverCli) => quote! { Some(cnfg::Precedence::EnvOverCli) },
+            Some(PrecedenceAttr::CliOverEnv) => quote! { Some(cnfg::Precedence::CliOverEnv) },
+        };
+        if cf.precedence.is_some() && nested_flag {
+            panic!("field `{fname}`: `precedence` applies to fields, not nested sections");
+        }
+
+        let extra_flag = cf.extra;
+        let secret_flag = cf.secret || cf.secret_ref.is_some();
+        if cf.secret_ref.is_some()
+            && (nested_flag || cf.default.is_some() || cf.default_fn.is_some())
+        {
+            panic!("field `{fname}`: `secret_ref` cannot be combined with `nested` or a default");
+        }
+        let secret_ref_tokens = option_str_tokens(cf.secret_ref.as_deref());
+        let relative_flag = cf.relative_to_config;
+        if relative_flag && !(is_ident(inner_ty, &["PathBuf"]) || is_string_type(inner_ty)) {
+            panic!(
+                "field `{fname}`: #[cnfg(relative_to_config)] expects a PathBuf or String field"
+            );
+        }
+        if extra_flag {
+            if extra_seen {
+                panic!("field `{fname}`: only one #[cnfg(extra)] field is allowed per struct");
+            }
+            if !is_map(inner_ty) || is_option || nested_flag {
+                panic!("field `{fname}`: #[cnfg(extra)] requires a HashMap/BTreeMap field");
+            }
+            if cf.env.is_some() || cf.cli.is_some() {
+                panic!("field `{fname}`: #[cnfg(extra)] fields cannot bind env vars or flags");
+            }
+            extra_seen = true;
         }
 
         let default_literal = cf.default.as_ref().map(default_literal);
         let default_tokens_field = option_str_tokens(default_literal.as_deref());
         let default_tokens_cli = default_tokens_field.clone();
 
-        if let Some(lit) = cf.default.clone() {
+        if cf.default.is_some() && cf.default_fn.is_some() {
+            panic!("field `{fname}`: `default` and `default_fn` are mutually exclusive");
+        }
+        if let Some(lit) = &cf.default {
+            if let Err(err) = check_default(&fname, lit, inner_ty, cf.bytes, &cf.validators) {
+                push_error(&mut errors, err);
+            }
+        }
+
+        if extra_flag {
             defaults_kv.push(quote! {
-                map.insert(#fname.to_string(), serde_json::json!(#lit));
+                map.entry(#key).or_insert_with(|| cnfg::__private::serde_json::Value::Object(cnfg::__private::serde_json::Map::new()));
             });
-        } else if nested_flag {
+        } else if let Some(lit) = cf.default.clone() {
+            defaults_kv.push(quote! {
+                map.insert(#key.to_string(), cnfg::__private::serde_json::json!(#lit));
+            });
+        } else if let Some(func) = &cf.default_fn {
+            defaults_kv.push(quote! {
+                match cnfg::__private::serde_json::to_value(#func()) {
+                    Ok(value) => {
+                        map.insert(#key.to_string(), value);
+                    }
+                    Err(err) => {
+                        failure.get_or_insert_with(|| format!("default_fn for `{}`: {err}", #key));
+                    }
+                }
+            });
+        } else if nested_list {
+            // Element defaults are applied per element while loading.
+            if !is_option {
+                defaults_kv.push(quote! {
+                    map.entry(#key).or_insert_with(|| cnfg::__private::serde_json::Value::Array(Vec::new()));
+                });
+            }
+        } else if nested_flag && !command_flag {
+            // Attribute defaults of the nested type override any `Default`
+            // base value for the section.
             defaults_kv.push(quote! {
-                map.insert(#fname.to_string(), <#nested_ty as cnfg::ConfigMeta>::defaults_json());
+                let nested = <#nested_ty as cnfg::ConfigMeta>::defaults_json();
+                match map.get_mut(#key) {
+                    Some(existing) if existing.is_object() => cnfg::merge::merge(existing, nested),
+                    _ => {
+                        map.insert(#key.to_string(), nested);
+                    }
+                }
             });
         }
 
-        field_spec_stmts.push(quote! {
-            items.push(cnfg::FieldSpec {
+        if nested_flag {
+            defaults_kv.push(quote! {
+                if let Some(err) = <#nested_ty as cnfg::ConfigMeta>::defaults_error() {
+                    failure.get_or_insert_with(|| err.to_string());
+                }
+            });
+        }
+
+        if let Some(env) = &cf.env {
+            envs.push((env.clone(), ident.clone()));
+        } else if !cf.env_alias.is_empty() {
+            panic!("field `{fname}`: `env_alias` needs an `env` name");
+        }
+        for alias in &cf.env_alias {
+            envs.push((alias.clone(), ident.clone()));
+        }
+        let env_alias_lits = &cf.env_alias;
+        if nested_flag && !nested_list && !command_flag {
+            sections.push((key.replace('_', "-"), nested_ty.clone(), ident.clone()));
+        }
+        if nested_flag && !nested_list {
+            audit_stmts.push(quote! {
+                <#nested_ty as cnfg::ConfigMeta>::audit_reads(&format!("{prefix}{}.", #path_lit), out);
+            });
+        }
+        if opts.accessors {
+            if fname == "load" {
+                panic!("field `load`: #[cnfg(accessors)] would clash with `load()`");
+            }
+            let ty = &cf.ty;
+            let docs = f.attrs.iter().filter(|attr| attr.path().is_ident("doc"));
+            accessor_fns.push(quote! {
+                #(#docs)*
+                pub fn #ident(&self) -> &#ty {
+                    cnfg::audit::record::<Self>(#path_lit);
+                    &self.#ident
+                }
+            });
+        }
+
+        let field_spec = quote! {
+            cnfg::FieldSpec {
                 name: #field_name_lit,
                 env: #env_tokens,
+                env_aliases: &[#(#env_alias_lits),*],
                 path: #path_lit,
                 doc: #field_doc_for_field,
                 kind: #field_kind,
                 default: #default_tokens_field,
                 required: #required_flag,
-            });
+                required_if: #required_if_tokens,
+                required_unless: #required_unless_tokens,
+                optional: #is_option,
+                allow_file: #allow_file_flag,
+                list: #is_list,
+                deprecated: #deprecated_tokens,
+                renamed_from: #renamed_from_tokens,
+                merge: #merge_tokens,
+                extra: #extra_flag,
+                secret: #secret_flag,
+                secret_ref: #secret_ref_tokens,
+                relative_to_config: #relative_flag,
+                parse: #parse_tokens,
+                precedence: #precedence_tokens,
+            }
+        };
+        field_spec_stmts.push(quote! {
+            items.push(#field_spec);
         });
 
         if required_flag {
@@ -166,20 +816,38 @@ pub fn derive_cnfg(input: TokenStream) -> TokenStream {
             });
         }
 
+        let mut cli_spec = quote! { None };
         if let Some(cli_attr) = &cf.cli {
-            let flag_raw = match cli_attr {
-                CliAttr::Flag => fname.replace('_', "-"),
-                CliAttr::Custom(explicit) => explicit.trim_start_matches("--").to_string(),
+            let flag_raw = match &cli_attr.flag {
+                None => fname.replace('_', "-"),
+                Some(explicit) => explicit.trim_start_matches("--").to_string(),
             };
+            let has_url = cf
+                .validators
+                .iter()
+                .any(|v| matches!(v, ValidatorAttr::Url(_)));
+            let value_name = cli_attr.value_name.as_deref().or(has_url.then_some("URL"));
+            let value_name_tokens = option_str_tokens(value_name);
+            let hidden_flag = cli_attr.hidden;
             let flag_lit = syn::LitStr::new(&flag_raw, Span::call_site());
-            let cli_kind = kind_for_type(&cf.ty);
-            let takes_value_tokens = if is_bool(inner_ty) {
+            flags.push((flag_raw.clone(), ident.clone()));
+            let cli_kind = if cf.bytes {
+                quote! { cnfg::Kind::Bytes }
+            } else if cf.parse_with.is_some() {
+                quote! { cnfg::Kind::Custom }
+            } else {
+                kind_for_type(&cf.ty)
+            };
+            // A decoded list comes from one value, so the flag does not repeat.
+            let multiple_flag = is_list && cf.parse_with.is_none();
+            let takes_value_tokens = if is_bool(inner_ty) && !is_list && cf.parse_with.is_none() {
                 quote! { false }
             } else {
                 quote! { true }
             };
-            cli_spec_stmts.push(quote! {
-                items.push(cnfg::CliSpec {
+            let values = &cf.values;
+            let spec = quote! {
+                cnfg::CliSpec {
                     flag: #flag_lit,
                     field: #field_name_lit,
                     kind: #cli_kind,
@@ -188,87 +856,304 @@ pub fn derive_cnfg(input: TokenStream) -> TokenStream {
                     takes_value: #takes_value_tokens,
                     default: #default_tokens_cli,
                     required: #required_flag,
-                });
+                    multiple: #multiple_flag,
+                    values: &[#(#values),*],
+                    value_name: #value_name_tokens,
+                    hidden: #hidden_flag,
+                    parse: #parse_tokens,
+                }
+            };
+            cli_spec_stmts.push(quote! {
+                items.push(#spec);
             });
+            cli_spec = quote! { Some(#spec) };
         }
 
-        for v in cf.validators.iter() {
+        let nested_schema = quote! { <#nested_ty as cnfg::ConfigMeta>::schema() };
+        let section = |kind: proc_macro2::TokenStream, schema: proc_macro2::TokenStream| {
+            quote! {
+                nodes.push(cnfg::schema::Node::Section(cnfg::schema::Section {
+                    spec: #field_spec,
+                    kind: cnfg::schema::SectionKind::#kind,
+                    schema: #schema,
+                }));
+            }
+        };
+        schema_stmts.push(match &cf.command {
+            Some(command) => {
+                let command_name = command_name(command, &fname);
+                section(quote! { Command(#command_name) }, nested_schema)
+            }
+            None if nested_list => section(quote! { List }, nested_schema),
+            None if nested_flag => section(
+                quote! { Nested },
+                quote! { #nested_schema.with_prefix(#path_lit) },
+            ),
+            None => quote! {
+                nodes.push(cnfg::schema::Node::Field(cnfg::schema::Field {
+                    spec: #field_spec,
+                    cli: #cli_spec,
+                }));
+            },
+        });
+
+        let validators = cf.validators.iter().flat_map(|v| match v {
+            ValidatorAttr::WhenProfile(args) => args
+                .validators
+                .iter()
+                .map(|inner| (Some(args.profile.as_str()), inner))
+                .collect(),
+            other => vec![(None, other)],
+        });
+        for (profile, v) in validators {
+            let start = validate_body.len();
             match v {
                 ValidatorAttr::Range(args) => {
-                    let checks = range_checks(&ident, &cf.ty, args.min, args.max);
-                    validate_body.push(checks);
+                    let bounds = [&args.min, &args.max];
+                    let byte_bound = bounds.iter().copied().flatten().any(|b| b.byte_size);
+                    if byte_bound && !cf.bytes {
+                        panic!("field `{fname}`: byte-size range bounds need #[cnfg(bytes)]");
+                    }
+                    let date_bounds = bounds
+                        .iter()
+                        .copied()
+                        .flatten()
+                        .filter(|b| b.date.is_some());
+                    if is_date_type(inner_ty) {
+                        if date_bounds.count() != bounds.iter().copied().flatten().count() {
+                            panic!(
+                                "field `{fname}`: range bounds of a date field must be dates, e.g. `min = \"2024-01-01\"`"
+                            );
+                        }
+                        validate_body.push(date_range_check(&ident, &key, &cf.ty, args));
+                    } else if date_bounds.count() > 0 {
+                        panic!("field `{fname}`: date range bounds need a date or date-time field");
+                    } else {
+                        let checks = range_checks(
+                            &ident,
+                            &key,
+                            &cf.ty,
+                            args.min.as_ref().map(|b| b.value),
+                            args.max.as_ref().map(|b| b.value),
+                        );
+                        validate_body.push(checks);
+                    }
                 }
                 ValidatorAttr::Regex(pattern) => {
                     if is_string_type(&cf.ty) {
                         if is_option_type(&cf.ty) {
                             validate_body.push(quote! {
                                 if let Some(s) = &self.#ident {
-                                    let re = regex::Regex::new(#pattern).expect("invalid regex");
+                                    let re = cnfg::__private::regex::Regex::new(#pattern).expect("invalid regex");
                                     if !re.is_match(s) {
-                                        errs.push(cnfg::error::Issue {
-                                            field: #fname.to_string(),
-                                            kind: cnfg::error::IssueKind::Regex,
-                                            message: format!("regex not matched: {}", #pattern),
-                                        });
+                                        errs.push(cnfg::error::Issue::regex(#key, #pattern));
                                     }
                                 }
                             });
                         } else {
                             validate_body.push(quote! {
-                                let re = regex::Regex::new(#pattern).expect("invalid regex");
+                                let re = cnfg::__private::regex::Regex::new(#pattern).expect("invalid regex");
                                 if !re.is_match(&self.#ident) {
-                                    errs.push(cnfg::error::Issue {
-                                        field: #fname.to_string(),
-                                        kind: cnfg::error::IssueKind::Regex,
-                                        message: format!("regex not matched: {}", #pattern),
-                                    });
+                                    errs.push(cnfg::error::Issue::regex(#key, #pattern));
                                 }
                             });
                         }
                     }
                 }
-                ValidatorAttr::Url => {
+                ValidatorAttr::Url(args) => {
                     if is_string_type(&cf.ty) {
-                        if is_option_type(&cf.ty) {
-                            validate_body.push(quote! {
-                                if let Some(s) = &self.#ident {
-                                    if url::Url::parse(s).is_err() {
-                                        errs.push(cnfg::error::Issue {
-                                            field: #fname.to_string(),
-                                            kind: cnfg::error::IssueKind::Url,
-                                            message: "invalid URL".to_string(),
-                                        });
-                                    }
-                                }
-                            });
-                        } else {
-                            validate_body.push(quote! {
-                                if url::Url::parse(&self.#ident).is_err() {
-                                    errs.push(cnfg::error::Issue {
-                                        field: #fname.to_string(),
-                                        kind: cnfg::error::IssueKind::Url,
-                                        message: "invalid URL".to_string(),
-                                    });
-                                }
-                            });
+                        let schemes = &args.schemes;
+                        let require_host = args.require_host;
+                        validate_body.push(string_check(
+                            &ident,
+                            &key,
+                            &cf.ty,
+                            quote! { cnfg::error::IssueKind::Url },
+                            quote! { cnfg::validators::url(__v, &[#(#schemes),*], #require_host) },
+                        ));
+                    }
+                }
+                ValidatorAttr::Port(args) => {
+                    let (optional, inner) = option_inner(&cf.ty);
+                    if !is_int(inner) {
+                        panic!("field `{fname}`: validate(port) expects an integer field");
+                    }
+                    let value = if optional {
+                        quote! { self.#ident }
+                    } else {
+                        quote! { Some(self.#ident) }
+                    };
+                    let unprivileged = args.unprivileged;
+                    validate_body.push(value_check(
+                        &key,
+                        quote! { cnfg::error::IssueKind::Port },
+                        value,
+                        quote! { cnfg::validators::port(__v, #unprivileged) },
+                    ));
+                }
+                ValidatorAttr::Cidr => {
+                    if !is_string_type(&cf.ty) {
+                        panic!("field `{fname}`: validate(cidr) expects a String field");
+                    }
+                    validate_body.push(string_check(
+                        &ident,
+                        &key,
+                        &cf.ty,
+                        quote! { cnfg::error::IssueKind::Cidr },
+                        quote! { cnfg::validators::cidr(__v) },
+                    ));
+                }
+                ValidatorAttr::Email
+                | ValidatorAttr::Hostname
+                | ValidatorAttr::Uuid
+                | ValidatorAttr::Semver => {
+                    let (name, check, kind) = match v {
+                        ValidatorAttr::Email => ("email", quote! { email }, quote! { Email }),
+                        ValidatorAttr::Hostname => {
+                            ("hostname", quote! { hostname }, quote! { Hostname })
                         }
+                        ValidatorAttr::Uuid => ("uuid", quote! { uuid }, quote! { Uuid }),
+                        _ => ("semver", quote! { semver }, quote! { Semver }),
+                    };
+                    if !is_string_type(&cf.ty) {
+                        panic!("field `{fname}`: validate({name}) expects a String field");
                     }
+                    validate_body.push(string_check(
+                        &ident,
+                        &key,
+                        &cf.ty,
+                        quote! { cnfg::error::IssueKind::#kind },
+                        quote! { cnfg::validators::#check(__v) },
+                    ));
+                }
+                ValidatorAttr::FileExists | ValidatorAttr::DirExists | ValidatorAttr::Readable => {
+                    let (name, check) = match v {
+                        ValidatorAttr::FileExists => ("file_exists", quote! { file_exists }),
+                        ValidatorAttr::DirExists => ("dir_exists", quote! { dir_exists }),
+                        _ => ("readable", quote! { readable }),
+                    };
+                    let checks = path_check(
+                        &ident,
+                        &key,
+                        &cf.ty,
+                        quote! { cnfg::error::IssueKind::Path },
+                        quote! { cnfg::validators::#check(__v) },
+                    )
+                    .unwrap_or_else(|| {
+                        panic!(
+                            "field `{fname}`: validate({name}) expects a PathBuf or String field"
+                        )
+                    });
+                    validate_body.push(checks);
                 }
+                ValidatorAttr::ForbidInProfile(args) => {
+                    if nested_flag {
+                        panic!(
+                            "field `{fname}`: forbid_in_profile applies to fields, not nested sections"
+                        );
+                    }
+                    let forbidden_profile = &args.profile;
+                    let value = &args.value;
+                    let check = quote! {
+                        if let Ok(__v) = cnfg::__private::serde_json::to_value(&self.#ident) {
+                            let forbidden = cnfg::__private::serde_json::json!(#value);
+                            if let Err(message) =
+                                cnfg::validators::forbid_value(&__v, &forbidden, #forbidden_profile)
+                            {
+                                errs.push(
+                                    cnfg::error::Issue::new(
+                                        #key,
+                                        cnfg::error::IssueKind::Forbidden,
+                                        message,
+                                    )
+                                    .with_param("profile", #forbidden_profile),
+                                );
+                            }
+                        }
+                    };
+                    validate_body.push(quote! {
+                        if cnfg::validators::in_profile(#forbidden_profile) {
+                            #check
+                        }
+                    });
+                }
+                ValidatorAttr::WhenProfile(_) => unreachable!("flattened above"),
+            }
+            if let Some(profile) = profile {
+                let checks: Vec<_> = validate_body.drain(start..).collect();
+                validate_body.push(quote! {
+                    if cnfg::validators::in_profile(#profile) {
+                        #(#checks)*
+                    }
+                });
             }
         }
 
-        if nested_flag {
+        if let Some(command) = &cf.command {
+            if !is_option {
+                panic!("field `{fname}`: #[cnfg(command)] requires an Option<...> field");
+            }
+            let command_name = command_name(command, &fname);
+            let field_doc = doc_option_tokens(doc_from_attrs(&f.attrs));
+            command_stmts.push(quote! {
+                items.push(cnfg::CommandSpec {
+                    name: #command_name,
+                    path: #path_lit,
+                    doc: #field_doc,
+                    cli_specs: <#nested_ty as cnfg::ConfigMeta>::cli_specs,
+                    defaults_json: <#nested_ty as cnfg::ConfigMeta>::defaults_json,
+                });
+            });
+            // Checked only while the command is selected.
+            required_stmts.push(quote! {
+                for nested in <#nested_ty as cnfg::ConfigMeta>::required_fields() {
+                    required.push(cnfg::util::leak_string(format!("{}.{nested}", #path_lit)));
+                }
+            });
+        }
+
+        if nested_list {
             let prefix = path_lit.clone();
-            field_spec_stmts.push(quote! {
-                for nested in <#nested_ty as cnfg::ConfigMeta>::field_specs() {
+            list_stmts.push(quote! {
+                items.push(cnfg::ListSpec {
+                    path: #prefix,
+                    field_specs: <#nested_ty as cnfg::ConfigMeta>::field_specs,
+                    required_fields: <#nested_ty as cnfg::ConfigMeta>::required_fields,
+                    defaults_json: <#nested_ty as cnfg::ConfigMeta>::defaults_json,
+                    lists: <#nested_ty as cnfg::ConfigMeta>::lists,
+                });
+            });
+            let items = if is_option {
+                quote! { self.#ident.iter().flatten() }
+            } else {
+                quote! { self.#ident.iter() }
+            };
+            validate_body.push(quote! {
+                for (idx, item) in #items.enumerate() {
+                    if let Err(nested_errs) = <#nested_ty as cnfg::Validate>::validate(item) {
+                        errs.extend(nested_errs.with_prefix(&format!("{}[{idx}]", #prefix)));
+                    }
+                }
+            });
+        } else if nested_flag {
+            let prefix = path_lit.clone();
+            list_stmts.push(quote! {
+                for nested in <#nested_ty as cnfg::ConfigMeta>::lists() {
                     items.push(nested.with_prefix(#prefix));
                 }
             });
-            cli_spec_stmts.push(quote! {
-                for nested in <#nested_ty as cnfg::ConfigMeta>::cli_specs() {
+            field_spec_stmts.push(quote! {
+                for nested in <#nested_ty as cnfg::ConfigMeta>::field_specs() {
                     items.push(nested.with_prefix(#prefix));
                 }
             });
+            if !command_flag {
+                cli_spec_stmts.push(quote! {
+                    for nested in <#nested_ty as cnfg::ConfigMeta>::cli_specs() {
+                        items.push(nested.with_prefix(#prefix));
+                    }
+                });
+            }
             if !is_option {
                 required_stmts.push(quote! {
                     for nested in <#nested_ty as cnfg::ConfigMeta>::required_fields() {
@@ -294,12 +1179,93 @@ pub fn derive_cnfg(input: TokenStream) -> TokenStream {
         }
     }
 
+    for (what, bound) in [("flag", &flags), ("env var", &envs)] {
+        for (idx, (name, ident)) in bound.iter().enumerate() {
+            let shown = if what == "flag" {
+                format!("--{name}")
+            } else {
+                name.clone()
+            };
+            if let Some((_, first)) = bound[..idx].iter().find(|(other, _)| other == name) {
+                let message = format!("{what} `{shown}` is bound by both `{first}` and `{ident}`");
+                push_error(&mut errors, syn::Error::new(first.span(), message.clone()));
+                push_error(&mut errors, syn::Error::new(ident.span(), message));
+            }
+        }
+    }
+    if let Some(errors) = errors {
+        return errors.to_compile_error().into();
+    }
+
+    let validate_impl = quote! {
+        impl cnfg::Validate for #name {
+            fn validate(&self) -> Result<(), cnfg::ValidationErrors> {
+                let mut errs = cnfg::ValidationErrors::new();
+                #(#validate_body)*
+                if errs.is_empty() { Ok(()) } else { Err(errs) }
+            }
+        }
+    };
+    if validate_only {
+        return validate_impl.into();
+    }
+
+    // Collisions with nested types are only known once their `BINDINGS`
+    // are, so they are asserted in constants spanned at the outer field.
+    let mut binding_checks = Vec::new();
+    for (prefix, nested_ty, section) in &sections {
+        let nested = quote! { <#nested_ty as cnfg::ConfigMeta>::BINDINGS };
+        for (flag, ident) in &flags {
+            let Some(rest) = flag
+                .strip_prefix(prefix.as_str())
+                .and_then(|rest| rest.strip_prefix('-'))
+            else {
+                continue;
+            };
+            let message = format!(
+                "flag `--{flag}` of `{ident}` is also produced by nested field `{section}`"
+            );
+            binding_checks.push(quote_spanned! {ident.span()=>
+                const _: () = assert!(!#nested.has_flag(#rest), #message);
+            });
+        }
+        for (env, ident) in &envs {
+            let message = format!(
+                "env var `{env}` of `{ident}` is also bound inside nested field `{section}`"
+            );
+            binding_checks.push(quote_spanned! {ident.span()=>
+                const _: () = assert!(!#nested.has_env(#env), #message);
+            });
+        }
+    }
+    for (idx, (_, first_ty, first)) in sections.iter().enumerate() {
+        for (_, other_ty, other) in &sections[idx + 1..] {
+            let message = format!("nested fields `{first}` and `{other}` bind the same env var");
+            binding_checks.push(quote_spanned! {other.span()=>
+                const _: () = assert!(
+                    !<#first_ty as cnfg::ConfigMeta>::BINDINGS
+                        .shares_env(&<#other_ty as cnfg::ConfigMeta>::BINDINGS),
+                    #message
+                );
+            });
+        }
+    }
+    let binding_flags = flags.iter().map(|(flag, _)| flag);
+    let binding_envs = envs.iter().map(|(env, _)| env);
+    let binding_nested = sections.iter().map(|(prefix, nested_ty, _)| {
+        quote! { (#prefix, &<#nested_ty as cnfg::ConfigMeta>::BINDINGS) }
+    });
+
     let tokens = quote! {
         impl cnfg::ConfigMeta for #name {
-            fn defaults_json() -> serde_json::Value {
-                let mut map = serde_json::Map::new();
-                #(#defaults_kv)*
-                serde_json::Value::Object(map)
+            fn defaults_json() -> cnfg::__private::serde_json::Value {
+                <Self as cnfg::ConfigMeta>::defaults().into_owned()
+            }
+            fn defaults() -> std::borrow::Cow<'static, cnfg::__private::serde_json::Value> {
+                std::borrow::Cow::Borrowed(&Self::__cnfg_defaults().0)
+            }
+            fn defaults_error() -> Option<&'static str> {
+                Self::__cnfg_defaults().1.as_deref()
             }
             fn field_specs() -> &'static [cnfg::FieldSpec] {
                 static FIELD_SPECS: std::sync::OnceLock<Vec<cnfg::FieldSpec>> = std::sync::OnceLock::new();
@@ -328,16 +1294,68 @@ pub fn derive_cnfg(input: TokenStream) -> TokenStream {
             fn doc() -> Option<&'static str> {
                 #struct_doc_tokens
             }
-        }
-
-        impl cnfg::Validate for #name {
-            fn validate(&self) -> Result<(), cnfg::ValidationErrors> {
-                let mut errs = cnfg::ValidationErrors::new();
-                #(#validate_body)*
-                if errs.is_empty() { Ok(()) } else { Err(errs) }
+            fn about() -> Option<&'static str> {
+                #about_tokens
+            }
+            fn after_help() -> Option<&'static str> {
+                #after_help_tokens
+            }
+            fn app_name() -> Option<&'static str> {
+                #app_name_tokens
+            }
+            fn env_prefix() -> Option<&'static str> {
+                #env_prefix_tokens
             }
+            fn auto_env() -> bool {
+                #auto_env_flag
+            }
+            fn version() -> Option<&'static str> {
+                #version_tokens
+            }
+            fn dotenv() -> cnfg::Dotenv {
+                #dotenv_tokens
+            }
+            fn commands() -> &'static [cnfg::CommandSpec] {
+                static COMMANDS: std::sync::OnceLock<Vec<cnfg::CommandSpec>> = std::sync::OnceLock::new();
+                COMMANDS.get_or_init(|| {
+                    let mut items = Vec::new();
+                    #(#command_stmts)*
+                    items
+                }).as_slice()
+            }
+            fn lists() -> &'static [cnfg::ListSpec] {
+                static LISTS: std::sync::OnceLock<Vec<cnfg::ListSpec>> = std::sync::OnceLock::new();
+                LISTS.get_or_init(|| {
+                    let mut items = Vec::new();
+                    #(#list_stmts)*
+                    items
+                }).as_slice()
+            }
+            fn schema() -> cnfg::Schema {
+                let mut nodes = Vec::new();
+                #(#schema_stmts)*
+                cnfg::Schema {
+                    doc: <Self as cnfg::ConfigMeta>::doc(),
+                    nodes,
+                }
+            }
+            fn audit_reads(prefix: &str, out: &mut Vec<String>) {
+                for path in cnfg::audit::recorded::<Self>() {
+                    out.push(format!("{prefix}{path}"));
+                }
+                #(#audit_stmts)*
+            }
+            const BINDINGS: cnfg::types::Bindings = cnfg::types::Bindings {
+                flags: &[#(#binding_flags),*],
+                envs: &[#(#binding_envs),*],
+                nested: &[#(#binding_nested),*],
+            };
         }
 
+        #(#binding_checks)*
+
+        #validate_impl
+
         impl cnfg::LoaderExt for #name {
             fn validate(&self) -> Result<(), cnfg::ValidationErrors> {
                 <Self as cnfg::Validate>::validate(self)
@@ -345,10 +1363,25 @@ pub fn derive_cnfg(input: TokenStream) -> TokenStream {
         }
 
         impl #name {
+            /// The defaults object and why building it failed, if it did.
+            #[doc(hidden)]
+            fn __cnfg_defaults() -> &'static (cnfg::__private::serde_json::Value, Option<String>) {
+                static DEFAULTS: std::sync::OnceLock<(cnfg::__private::serde_json::Value, Option<String>)> = std::sync::OnceLock::new();
+                DEFAULTS.get_or_init(|| {
+                    let mut map = #defaults_base;
+                    #[allow(unused_mut)]
+                    let mut failure: Option<String> = None;
+                    #(#defaults_kv)*
+                    (cnfg::__private::serde_json::Value::Object(map), failure)
+                })
+            }
+
             /// Load config using defaults, files, env, CLI, and validations.
             pub fn load() -> Result<Self, cnfg::CnfgError> {
                 <Self as cnfg::LoaderExt>::load()
             }
+
+            #(#accessor_fns)*
         }
     };
     tokens.into()
@@ -357,20 +1390,32 @@ pub fn derive_cnfg(input: TokenStream) -> TokenStream {
 // ---------- helpers ----------
 
 fn kind_for_type(ty: &Type) -> proc_macro2::TokenStream {
-    let (is_option, inner) = option_inner(ty);
-    let t = if is_option { inner } else { ty };
+    let (_, inner) = option_inner(ty);
+    let t = vec_inner(inner).unwrap_or(inner);
     if is_bool(t) {
         quote! { cnfg::Kind::Bool }
     } else if is_int(t) {
         quote! { cnfg::Kind::Int }
     } else if is_float(t) {
         quote! { cnfg::Kind::Float }
-    } else {
+    } else if is_ident(t, &["IpAddr"]) {
+        quote! { cnfg::Kind::IpAddr }
+    } else if is_ident(t, &["SocketAddr"]) {
+        quote! { cnfg::Kind::SocketAddr }
+    } else if is_ident(t, &["PathBuf"]) {
+        quote! { cnfg::Kind::Path }
+    } else if is_ident(t, &["String", "char"]) {
         quote! { cnfg::Kind::String }
+    } else if is_ident(t, &["DateTime", "OffsetDateTime"]) {
+        quote! { cnfg::Kind::DateTime }
+    } else if is_ident(t, &["NaiveDate", "Date"]) {
+        quote! { cnfg::Kind::Date }
+    } else {
+        quote! { cnfg::Kind::Any }
     }
 }
 
-fn option_inner<'a>(ty: &'a Type) -> (bool, &'a Type) {
+fn option_inner(ty: &Type) -> (bool, &Type) {
     if let Type::Path(tp) = ty {
         if tp.path.segments.len() == 1 && tp.path.segments[0].ident == "Option" {
             if let syn::PathArguments::AngleBracketed(ab) = &tp.path.segments[0].arguments {
@@ -383,6 +1428,25 @@ fn option_inner<'a>(ty: &'a Type) -> (bool, &'a Type) {
     (false, ty)
 }
 
+/// Element type of `Vec<T>`, if `ty` is a vector.
+fn vec_inner(ty: &Type) -> Option<&Type> {
+    if let Type::Path(tp) = ty {
+        let seg = tp.path.segments.last()?;
+        if seg.ident == "Vec" {
+            if let syn::PathArguments::AngleBracketed(ab) = &seg.arguments {
+                if let Some(syn::GenericArgument::Type(inner)) = ab.args.first() {
+                    return Some(inner);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn is_map(ty: &Type) -> bool {
+    is_ident(ty, &["HashMap", "BTreeMap", "IndexMap"])
+}
+
 fn is_option_type(ty: &Type) -> bool {
     option_inner(ty).0
 }
@@ -408,11 +1472,131 @@ fn is_float(ty: &Type) -> bool {
     is_ident(ty, &["f32", "f64"])
 }
 
+/// Compile-time twin of `cnfg::util::parse_byte_size` for range bounds.
+fn parse_byte_size(raw: &str) -> Option<u64> {
+    let text = raw.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '_'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "m" | "mb" => 1000u64.pow(2),
+        "g" | "gb" => 1000u64.pow(3),
+        "t" | "tb" => 1000u64.pow(4),
+        "p" | "pb" => 1000u64.pow(5),
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        "pib" => 1 << 50,
+        _ => return None,
+    };
+    number
+        .replace('_', "")
+        .parse::<u64>()
+        .ok()?
+        .checked_mul(multiplier)
+}
+
+/// `chrono` and `time` date and date-time types, optionally in an `Option`.
+fn is_date_type(ty: &Type) -> bool {
+    let (_, inner) = option_inner(ty);
+    is_ident(inner, &["DateTime", "OffsetDateTime", "NaiveDate", "Date"])
+}
+
+/// Whether a range bound starts like `YYYY-MM-DD`.
+fn looks_like_date(raw: &str) -> bool {
+    let bytes = raw.as_bytes();
+    bytes.len() >= 10 && bytes[..4].iter().all(u8::is_ascii_digit) && bytes[4] == b'-'
+}
+
+/// Unix time of a `YYYY-MM-DD` date (midnight UTC) or an RFC 3339
+/// date-time, mirroring `cnfg::datetime::parse_bound`.
+fn parse_date_bound(raw: &str) -> Option<f64> {
+    let number = |text: &str| -> Option<i64> {
+        if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        text.parse().ok()
+    };
+    let clock = |text: &str| -> Option<i64> {
+        let bytes = text.as_bytes();
+        if bytes.len() != 8 || bytes[2] != b':' || bytes[5] != b':' {
+            return None;
+        }
+        let hour = number(&text[..2]).filter(|h| *h < 24)?;
+        let minute = number(&text[3..5]).filter(|m| *m < 60)?;
+        let second = number(&text[6..]).filter(|s| *s <= 60)?;
+        Some(hour * 3600 + minute * 60 + second.min(59))
+    };
+    let date = raw.get(..10)?;
+    let bytes = date.as_bytes();
+    if bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+    let (year, month, day) = (
+        number(&date[..4])?,
+        number(&date[5..7])?,
+        number(&date[8..])?,
+    );
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    if !(1..=month_days).contains(&day) {
+        return None;
+    }
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let days = era * 146_097 + year_of_era * 365 + year_of_era / 4 - year_of_era / 100
+        + day_of_year
+        - 719_468;
+    let midnight = (days * 86_400) as f64;
+    let rest = &raw[10..];
+    if rest.is_empty() {
+        return Some(midnight);
+    }
+    let rest = rest.strip_prefix(['T', 't', ' '])?;
+    let seconds = clock(rest.get(..8)?)?;
+    let mut rest = &rest[8..];
+    let mut fraction = 0.0;
+    if let Some(after) = rest.strip_prefix('.') {
+        let end = after
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(after.len());
+        fraction = format!("0.{}", after.get(..end).filter(|f| !f.is_empty())?)
+            .parse()
+            .ok()?;
+        rest = &after[end..];
+    }
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.as_bytes().first()? {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let hm = rest.get(1..).filter(|hm| hm.len() == 5)?;
+            sign * clock(&format!("{hm}:00"))?
+        }
+    };
+    Some(midnight + (seconds - offset) as f64 + fraction)
+}
+
 fn is_int(ty: &Type) -> bool {
     is_ident(
         ty,
         &[
-            "i8", "i16", "i32", "i64", "i128", "u8", "u16", "u32", "u64", "u128",
+            "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
         ],
     )
 }
@@ -426,8 +1610,89 @@ fn is_ident(ty: &Type, names: &[&str]) -> bool {
     false
 }
 
+/// Run `check` (an expression over `__v: &str` returning
+/// `Result<(), String>`) on a `String` or `Option<String>` field.
+fn string_check(
+    ident: &syn::Ident,
+    key: &str,
+    ty: &Type,
+    kind: proc_macro2::TokenStream,
+    check: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let value = if is_option_type(ty) {
+        quote! { self.#ident.as_deref() }
+    } else {
+        quote! { Some(self.#ident.as_str()) }
+    };
+    value_check(key, kind, value, check)
+}
+
+/// Like [`string_check`] with `__v: &Path`, for `PathBuf` and `String`
+/// fields (optionally wrapped in `Option`).
+fn path_check(
+    ident: &syn::Ident,
+    key: &str,
+    ty: &Type,
+    kind: proc_macro2::TokenStream,
+    check: proc_macro2::TokenStream,
+) -> Option<proc_macro2::TokenStream> {
+    let (optional, inner) = option_inner(ty);
+    let value = match (
+        optional,
+        is_ident(inner, &["PathBuf"]),
+        is_string_type(inner),
+    ) {
+        (false, true, _) => quote! { Some(self.#ident.as_path()) },
+        (true, true, _) => quote! { self.#ident.as_deref() },
+        (false, _, true) => quote! { Some(std::path::Path::new(&self.#ident)) },
+        (true, _, true) => quote! { self.#ident.as_deref().map(std::path::Path::new) },
+        _ => return None,
+    };
+    Some(value_check(key, kind, value, check))
+}
+
+/// `validate(range(...))` on a date or date-time field, comparing with
+/// `cnfg::validators::date_range`.
+fn date_range_check(
+    ident: &syn::Ident,
+    key: &str,
+    ty: &Type,
+    args: &RangeArgs,
+) -> proc_macro2::TokenStream {
+    let value = if is_option_type(ty) {
+        quote! { self.#ident.as_ref() }
+    } else {
+        quote! { Some(&self.#ident) }
+    };
+    let min = option_str_tokens(args.min.as_ref().and_then(|b| b.date.as_deref()));
+    let max = option_str_tokens(args.max.as_ref().and_then(|b| b.date.as_deref()));
+    value_check(
+        key,
+        quote! { cnfg::error::IssueKind::Range },
+        value,
+        quote! { cnfg::validators::date_range(__v, #min, #max) },
+    )
+}
+
+/// Push an issue of `kind` when `check` fails for the `Some` value of `value`.
+fn value_check(
+    key: &str,
+    kind: proc_macro2::TokenStream,
+    value: proc_macro2::TokenStream,
+    check: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    quote! {
+        if let Some(__v) = #value {
+            if let Err(message) = #check {
+                errs.push(cnfg::error::Issue::new(#key, #kind, message));
+            }
+        }
+    }
+}
+
 fn range_checks(
     ident: &syn::Ident,
+    key: &str,
     ty: &Type,
     min: Option<f64>,
     max: Option<f64>,
@@ -441,76 +1706,156 @@ fn range_checks(
     {
         return quote! {};
     }
-
-    let fname = ident.to_string();
-
+    let outside = match (min, max) {
+        (Some(min), Some(max)) => quote! { !(#min..=#max).contains(&__f) },
+        (Some(min), None) => quote! { __f < #min },
+        (None, Some(max)) => quote! { __f > #max },
+        (None, None) => return quote! {},
+    };
+    let bound = |b: Option<f64>| match b {
+        Some(b) => quote! { Some(#b) },
+        None => quote! { None },
+    };
+    let (min, max) = (bound(min), bound(max));
+    let check = quote! {
+        if #outside {
+            errs.push(cnfg::error::Issue::range(#key, #min, #max, __f));
+        }
+    };
     if is_option_type(ty) {
-        let min_clause = min
-            .map(|m| {
-                quote! {
-                    if __f < #m as f64 {
-                        errs.push(cnfg::error::Issue {
-                            field: #fname.to_string(),
-                            kind: cnfg::error::IssueKind::Range,
-                            message: format!("must be >= {}", #m),
-                        });
-                    }
-                }
-            })
-            .unwrap_or_else(|| quote! {});
-        let max_clause = max
-            .map(|m| {
-                quote! {
-                    if __f > #m as f64 {
-                        errs.push(cnfg::error::Issue {
-                            field: #fname.to_string(),
-                            kind: cnfg::error::IssueKind::Range,
-                            message: format!("must be <= {}", #m),
-                        });
-                    }
-                }
-            })
-            .unwrap_or_else(|| quote! {});
         quote! {
             if let Some(__v) = &self.#ident {
                 let __f: f64 = (*__v) as f64;
-                #min_clause
-                #max_clause
+                #check
             }
         }
     } else {
-        let min_clause = min
-            .map(|m| {
-                quote! {
-                    if __f < #m as f64 {
-                        errs.push(cnfg::error::Issue {
-                            field: #fname.to_string(),
-                            kind: cnfg::error::IssueKind::Range,
-                            message: format!("must be >= {}", #m),
-                        });
-                    }
-                }
-            })
-            .unwrap_or_else(|| quote! {});
-        let max_clause = max
-            .map(|m| {
-                quote! {
-                    if __f > #m as f64 {
-                        errs.push(cnfg::error::Issue {
-                            field: #fname.to_string(),
-                            kind: cnfg::error::IssueKind::Range,
-                            message: format!("must be <= {}", #m),
-                        });
-                    }
-                }
-            })
-            .unwrap_or_else(|| quote! {});
         quote! {
             let __f: f64 = (self.#ident) as f64;
-            #min_clause
-            #max_clause
+            #check
+        }
+    }
+}
+
+/// Positional name of a `#[cnfg(command)]` field.
+fn command_name(command: &CommandAttr, fname: &str) -> String {
+    match command {
+        CommandAttr::Field => fname.replace('_', "-"),
+        CommandAttr::Named(name) => name.clone(),
+    }
+}
+
+/// Add `err` to the errors reported together once all fields are parsed.
+fn push_error(errors: &mut Option<syn::Error>, err: syn::Error) {
+    match errors {
+        Some(all) => all.combine(err),
+        None => *errors = Some(err),
+    }
+}
+
+/// Reject a `default = ...` literal that cannot deserialize into a primitive
+/// field type, or that fails the field's range or port validator. Other types
+/// (enums, paths, addresses) accept any literal and fail at load time instead.
+fn check_default(
+    fname: &str,
+    lit: &Lit,
+    ty: &Type,
+    bytes: bool,
+    validators: &[ValidatorAttr],
+) -> Result<(), syn::Error> {
+    let fail = |message: String| {
+        Err(syn::Error::new(
+            lit.span(),
+            format!("field `{fname}`: {message}"),
+        ))
+    };
+    let type_name = ty.to_token_stream().to_string();
+    let value = match lit {
+        Lit::Int(i) if is_int(ty) => {
+            let value = i.base10_parse::<i128>().ok();
+            match value.filter(|v| int_fits(&type_name, *v)) {
+                Some(v) => v as f64,
+                None => return fail(format!("default {i} does not fit in `{type_name}`")),
+            }
+        }
+        Lit::Str(s) if bytes => match parse_byte_size(&s.value()) {
+            Some(v) => v as f64,
+            None => return fail(format!("default \"{}\" is not a byte size", s.value())),
+        },
+        Lit::Int(i) if is_float(ty) => i
+            .base10_parse::<f64>()
+            .map_err(|e| syn::Error::new(lit.span(), e))?,
+        Lit::Float(f) if is_float(ty) => f
+            .base10_parse::<f64>()
+            .map_err(|e| syn::Error::new(lit.span(), e))?,
+        Lit::Str(s) if is_date_type(ty) => match parse_date_bound(&s.value()) {
+            Some(seconds) => seconds,
+            None => {
+                return fail(format!(
+                    "default \"{}\" is not a date or RFC 3339 date-time",
+                    s.value()
+                ));
+            }
+        },
+        Lit::Bool(_) if is_bool(ty) => return Ok(()),
+        Lit::Str(_) if is_string_type(ty) => return Ok(()),
+        _ if is_int(ty) && bytes => {
+            return fail(format!(
+                "expected an integer or byte-size string default for `{type_name}`"
+            ));
+        }
+        _ if is_int(ty) => return fail(format!("expected an integer default for `{type_name}`")),
+        _ if is_float(ty) => return fail(format!("expected a number default for `{type_name}`")),
+        _ if is_bool(ty) => {
+            return fail("expected `true` or `false` as the default for `bool`".into());
+        }
+        _ if is_string_type(ty) => return fail("expected a string default for `String`".into()),
+        _ => return Ok(()),
+    };
+    let shown = default_literal(lit);
+    for validator in validators {
+        match validator {
+            ValidatorAttr::Range(args) => {
+                if let Some(min) = args.min.as_ref().filter(|min| value < min.value) {
+                    return fail(format!(
+                        "default {shown} is below the range minimum {}",
+                        min.shown()
+                    ));
+                }
+                if let Some(max) = args.max.as_ref().filter(|max| value > max.value) {
+                    return fail(format!(
+                        "default {shown} is above the range maximum {}",
+                        max.shown()
+                    ));
+                }
+            }
+            ValidatorAttr::Port(args) => {
+                let min = if args.unprivileged { 1024.0 } else { 1.0 };
+                if !(min..=65535.0).contains(&value) {
+                    return fail(format!("default {shown} is not a valid port ({min}-65535)"));
+                }
+            }
+            _ => {}
         }
     }
+    Ok(())
+}
+
+/// Whether `value` is representable by the integer type named `type_name`.
+fn int_fits(type_name: &str, value: i128) -> bool {
+    let (min, max): (i128, i128) = match type_name {
+        "i8" => (i8::MIN.into(), i8::MAX.into()),
+        "i16" => (i16::MIN.into(), i16::MAX.into()),
+        "i32" => (i32::MIN.into(), i32::MAX.into()),
+        "i64" | "isize" => (i64::MIN.into(), i64::MAX.into()),
+        "u8" => (0, u8::MAX.into()),
+        "u16" => (0, u16::MAX.into()),
+        "u32" => (0, u32::MAX.into()),
+        "u64" | "usize" => (0, u64::MAX.into()),
+        "u128" => (0, i128::MAX),
+        _ => (i128::MIN, i128::MAX),
+    };
+    (min..=max).contains(&value)
 }
 
 fn doc_from_attrs(attrs: &[Attribute]) -> Option<String> {
@@ -536,6 +1881,109 @@ fn doc_from_attrs(attrs: &[Attribute]) -> Option<String> {
     }
 }
 
+/// Deserialize-side name from `#[serde(rename = "...")]` on a field.
+///
+/// Serde `alias`es are not read: a file key matching only an alias maps to
+/// no field. `#[cnfg(renamed_from = "...")]` accepts an old key instead.
+fn serde_rename(attrs: &[Attribute]) -> Option<String> {
+    serde_name_value(attrs, "rename").map(|lit| lit.value())
+}
+
+/// Deserialize-side rule from `#[serde(rename_all = "...")]` on a struct.
+fn serde_rename_all(attrs: &[Attribute]) -> syn::Result<Option<RenameRule>> {
+    serde_name_value(attrs, "rename_all")
+        .map(|lit| RenameRule::parse(&lit))
+        .transpose()
+}
+
+/// Find `name = "..."` or `name(deserialize = "...")` inside `#[serde(...)]`.
+fn serde_name_value(attrs: &[Attribute], name: &str) -> Option<syn::LitStr> {
+    let mut found = None;
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident(name) {
+                if meta.input.peek(syn::Token![=]) {
+                    found = Some(meta.value()?.parse()?);
+                } else {
+                    meta.parse_nested_meta(|inner| {
+                        let lit: syn::LitStr = inner.value()?.parse()?;
+                        if inner.path.is_ident("deserialize") {
+                            found = Some(lit);
+                        }
+                        Ok(())
+                    })?;
+                }
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: Expr = meta.value()?.parse()?;
+            } else if !meta.input.is_empty() && !meta.input.peek(syn::Token![,]) {
+                let _: proc_macro2::TokenTree = meta.input.parse()?;
+            }
+            Ok(())
+        });
+    }
+    found
+}
+
+/// A serde `rename_all` rule.
+#[derive(Clone, Copy)]
+enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    fn parse(lit: &syn::LitStr) -> syn::Result<Self> {
+        Ok(match lit.value().as_str() {
+            "lowercase" => Self::Lower,
+            "UPPERCASE" => Self::Upper,
+            "PascalCase" => Self::Pascal,
+            "camelCase" => Self::Camel,
+            "snake_case" => Self::Snake,
+            "SCREAMING_SNAKE_CASE" => Self::ScreamingSnake,
+            "kebab-case" => Self::Kebab,
+            "SCREAMING-KEBAB-CASE" => Self::ScreamingKebab,
+            other => {
+                return Err(syn::Error::new_spanned(
+                    lit,
+                    format!("unsupported serde rename_all rule `{other}`"),
+                ));
+            }
+        })
+    }
+
+    /// Apply the rule to a snake_case field name.
+    fn apply(self, field: &str) -> String {
+        let words: Vec<&str> = field.split('_').filter(|w| !w.is_empty()).collect();
+        let capitalize = |w: &str| {
+            let mut chars = w.chars();
+            chars
+                .next()
+                .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        };
+        match self {
+            Self::Lower => field.to_lowercase(),
+            Self::Upper => field.to_uppercase(),
+            Self::Pascal => words.iter().map(|w| capitalize(w)).collect(),
+            Self::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, w)| if i == 0 { w.to_string() } else { capitalize(w) })
+                .collect(),
+            Self::Snake => field.to_string(),
+            Self::ScreamingSnake => field.to_uppercase(),
+            Self::Kebab => field.replace('_', "-"),
+            Self::ScreamingKebab => field.replace('_', "-").to_uppercase(),
+        }
+    }
+}
+
 fn doc_option_tokens(doc: Option<String>) -> proc_macro2::TokenStream {
     match doc {
         Some(text) => {
@@ -0,0 +1,174 @@
+//! age-encrypted values in config files.
+//!
+//! A string value of the form `enc:<base64>`, where the payload is an age
+//! ciphertext (`age -r age1... | base64 -w0`), is decrypted while its file
+//! is loaded and then coerced to the field's type. Failures are reported as
+//! [`IssueKind::Decrypt`](crate::error::IssueKind::Decrypt) issues on the
+//! field; the message never includes the plaintext.
+//!
+//! The identity file comes from [`Loader::age_identity`](crate::Loader::age_identity)
+//! or the `CONFIG_AGE_IDENTITY` environment variable. Decryption runs the
+//! `age` binary ([`AgeCommand`]); plug in a native implementation with
+//! [`Loader::age`](crate::Loader::age).
+
+use crate::error::{Issue, IssueKind, ValidationErrors};
+use crate::types::FieldSpec;
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Prefix marking an encrypted value.
+pub const PREFIX: &str = "enc:";
+
+/// Environment variable naming the identity file when the loader has none.
+pub const IDENTITY_ENV: &str = "CONFIG_AGE_IDENTITY";
+
+/// Decrypts single age-encrypted values.
+pub trait AgeDecryptor: Send + Sync {
+    /// Decrypt one value, given as age ASCII armor.
+    fn decrypt(&self, armored: &str) -> Result<String, String>;
+}
+
+/// Decrypts by running `age --decrypt` with an identity file.
+#[derive(Debug, Clone)]
+pub struct AgeCommand {
+    program: PathBuf,
+    identity: PathBuf,
+}
+
+impl AgeCommand {
+    /// Run `age` from `PATH` with the identity file at `identity`.
+    pub fn new(identity: impl Into<PathBuf>) -> Self {
+        Self {
+            program: PathBuf::from("age"),
+            identity: identity.into(),
+        }
+    }
+
+    /// Run this binary instead (e.g. `rage`).
+    pub fn program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    /// The identity file passed with `-i`.
+    pub fn identity(&self) -> &Path {
+        &self.identity
+    }
+}
+
+impl AgeDecryptor for AgeCommand {
+    fn decrypt(&self, armored: &str) -> Result<String, String> {
+        let mut child = Command::new(&self.program)
+            .arg("--decrypt")
+            .arg("-i")
+            .arg(&self.identity)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("could not run {}: {e}", self.program.display()))?;
+        // Dropping stdin closes it, so age sees the end of the input.
+        child
+            .stdin
+            .take()
+            .expect("stdin is piped")
+            .write_all(armored.as_bytes())
+            .map_err(|e| format!("could not write to {}: {e}", self.program.display()))?;
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("{} did not finish: {e}", self.program.display()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "{} --decrypt failed ({}): {}",
+                self.program.display(),
+                output.status,
+                stderr.trim()
+            ));
+        }
+        String::from_utf8(output.stdout).map_err(|_| "decrypted value is not UTF-8".to_string())
+    }
+}
+
+/// Decrypt every `enc:` string in one file, coercing the plaintext with the
+/// field's kind.
+pub(crate) fn decrypt_values(
+    doc: &mut Value,
+    fields: &[FieldSpec],
+    decryptor: Option<&dyn AgeDecryptor>,
+) -> Result<(), ValidationErrors> {
+    fn walk(
+        value: &mut Value,
+        path: &mut String,
+        decrypt: &mut dyn FnMut(&str, &str) -> Result<Value, String>,
+        errs: &mut ValidationErrors,
+    ) {
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map.iter_mut() {
+                    let len = path.len();
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(key);
+                    walk(child, path, decrypt, errs);
+                    path.truncate(len);
+                }
+            }
+            Value::Array(items) => {
+                for (idx, item) in items.iter_mut().enumerate() {
+                    let len = path.len();
+                    path.push_str(&format!("[{idx}]"));
+                    walk(item, path, decrypt, errs);
+                    path.truncate(len);
+                }
+            }
+            Value::String(text) => {
+                let Some(payload) = text.strip_prefix(PREFIX) else {
+                    return;
+                };
+                match decrypt(path, payload) {
+                    Ok(plain) => *value = plain,
+                    Err(message) => {
+                        errs.push(Issue::new(path.clone(), IssueKind::Decrypt, message))
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut decrypt = |path: &str, payload: &str| {
+        let decryptor = decryptor.ok_or_else(|| {
+            format!("encrypted value but no age identity; set {IDENTITY_ENV} or use Loader::age_identity")
+        })?;
+        let plain = decryptor.decrypt(&armor(payload)?)?;
+        crate::loader::coerce_raw(fields, path, plain.strip_suffix('\n').unwrap_or(&plain))
+    };
+    let mut errs = ValidationErrors::new();
+    walk(doc, &mut String::new(), &mut decrypt, &mut errs);
+    if errs.is_empty() { Ok(()) } else { Err(errs) }
+}
+
+/// Wrap the base64 payload of an `enc:` value as age ASCII armor.
+pub(crate) fn armor(payload: &str) -> Result<String, String> {
+    let payload: String = payload.chars().filter(|c| !c.is_whitespace()).collect();
+    let valid = !payload.is_empty()
+        && payload.len() % 4 == 0
+        && payload
+            .trim_end_matches('=')
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/');
+    if !valid {
+        return Err("encrypted value is not padded base64".into());
+    }
+    let mut out = String::from("-----BEGIN AGE ENCRYPTED FILE-----\n");
+    for line in payload.as_bytes().chunks(64) {
+        out.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+        out.push('\n');
+    }
+    out.push_str("-----END AGE ENCRYPTED FILE-----\n");
+    Ok(out)
+}
@@ -0,0 +1,178 @@
+//! Which config fields the application reads.
+//!
+//! `#[cnfg(accessors)]` on a config struct generates a read-only accessor
+//! per field (`cfg.port()` returning `&u16`). Wrapping the loaded config in
+//! [`Audited`] records every accessor call, and at shutdown
+//! [`Audited::report`] lists the fields that were never read, the
+//! candidates for pruning:
+//!
+//! ```ignore
+//! let cfg = Audited::new(AppConfig::load()?);
+//! serve(&cfg);
+//! eprint!("{}", cfg.report());
+//! ```
+//!
+//! Nested sections need their own `#[cnfg(accessors)]` for their fields to
+//! be tracked; otherwise reading the section counts as reading all of it.
+//! Recording is process-wide and starts with the first [`Audited`], so the
+//! accessors cost an atomic load until then.
+
+use crate::types::ConfigMeta;
+use std::any::TypeId;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Paths read through the accessors of each config type, relative to it.
+static READS: OnceLock<Mutex<HashMap<TypeId, BTreeSet<&'static str>>>> = OnceLock::new();
+
+fn reads() -> &'static Mutex<HashMap<TypeId, BTreeSet<&'static str>>> {
+    READS.get_or_init(Default::default)
+}
+
+/// Record that the field at `path` of `T` was read. Called by generated
+/// accessors.
+#[doc(hidden)]
+pub fn record<T: 'static>(path: &'static str) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    reads()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(TypeId::of::<T>())
+        .or_default()
+        .insert(path);
+}
+
+/// Paths of `T` recorded so far, for [`ConfigMeta::audit_reads`].
+#[doc(hidden)]
+pub fn recorded<T: 'static>() -> Vec<&'static str> {
+    reads()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&TypeId::of::<T>())
+        .map(|paths| paths.iter().copied().collect())
+        .unwrap_or_default()
+}
+
+/// Forget every recorded read, e.g. between tests.
+pub fn reset() {
+    reads().lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// A loaded config whose accessor calls are recorded; see the
+/// [module docs](self).
+///
+/// Derefs to the config, so it cannot be changed while audited.
+#[derive(Debug)]
+pub struct Audited<T> {
+    cfg: Arc<T>,
+}
+
+impl<T> Clone for Audited<T> {
+    fn clone(&self) -> Self {
+        Self {
+            cfg: Arc::clone(&self.cfg),
+        }
+    }
+}
+
+impl<T: ConfigMeta + 'static> Audited<T> {
+    /// Start recording and wrap `cfg`.
+    pub fn new(cfg: T) -> Self {
+        Self::from_arc(Arc::new(cfg))
+    }
+
+    /// Like [`Audited::new`] for a config that is already shared, such as
+    /// the [global](crate::global) one.
+    pub fn from_arc(cfg: Arc<T>) -> Self {
+        ENABLED.store(true, Ordering::Relaxed);
+        Self { cfg }
+    }
+
+    /// The shared config.
+    pub fn arc(&self) -> &Arc<T> {
+        &self.cfg
+    }
+
+    /// The fields read and not read so far.
+    pub fn report(&self) -> AuditReport {
+        let mut recorded = Vec::new();
+        T::audit_reads("", &mut recorded);
+        let recorded: BTreeSet<String> = recorded.into_iter().collect();
+        let specs = T::field_specs();
+        let mut read = Vec::new();
+        let mut unused = Vec::new();
+        // Sections are covered by their fields; lists of sections count as
+        // one key.
+        let is_section = |path: &str| {
+            specs.iter().any(|other| {
+                other
+                    .path
+                    .strip_prefix(path)
+                    .is_some_and(|rest| rest.starts_with('.'))
+            })
+        };
+        for spec in specs.iter().filter(|spec| !is_section(spec.path)) {
+            if is_read(spec.path, &recorded) {
+                read.push(spec.path);
+            } else {
+                unused.push(spec.path);
+            }
+        }
+        AuditReport { read, unused }
+    }
+}
+
+impl<T> Deref for Audited<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.cfg
+    }
+}
+
+/// A field counts as read if its accessor was called, or if a section
+/// holding it was read and none of the section's fields were tracked.
+fn is_read(path: &str, recorded: &BTreeSet<String>) -> bool {
+    if recorded.contains(path) {
+        return true;
+    }
+    let mut section = path;
+    while let Some((parent, _)) = section.rsplit_once('.') {
+        section = parent;
+        if recorded.contains(section) {
+            let prefix = format!("{section}.");
+            return !recorded.iter().any(|other| other.starts_with(&prefix));
+        }
+    }
+    false
+}
+
+/// Fields of a config split by whether the application read them, from
+/// [`Audited::report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditReport {
+    /// Dotted paths of fields that were read.
+    pub read: Vec<&'static str>,
+    /// Dotted paths of fields that were never read.
+    pub unused: Vec<&'static str>,
+}
+
+impl fmt::Display for AuditReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.unused.is_empty() {
+            return writeln!(f, "every config key was read");
+        }
+        writeln!(f, "unused config keys ({}):", self.unused.len())?;
+        for path in &self.unused {
+            writeln!(f, "  {path}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+//! Dry-run validation for `--check` and [`LoaderExt::check`](crate::LoaderExt::check).
+
+use crate::error::CnfgError;
+use crate::provenance::Origin;
+use crate::report::Report;
+use std::fmt;
+
+/// Outcome of running the load pipeline without keeping the config.
+#[derive(Debug)]
+pub struct CheckSummary {
+    /// Why loading failed, if it did.
+    pub error: Option<CnfgError>,
+    /// Warnings and provenance gathered up to the point loading stopped.
+    pub report: Report,
+}
+
+impl CheckSummary {
+    /// Whether the config loaded and validated cleanly.
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Print the summary: stdout when it passed, stderr when it failed.
+    pub fn print(&self) {
+        if self.passed() {
+            print!("{self}");
+        } else {
+            eprint!("{self}");
+        }
+    }
+
+    /// Distinct layers that supplied at least one value, lowest precedence first.
+    pub fn layers(&self) -> Vec<&Origin> {
+        let mut layers: Vec<&Origin> = Vec::new();
+        for (_, origin) in self.report.provenance.iter() {
+            if !layers.contains(&origin) {
+                layers.push(origin);
+            }
+        }
+        layers.sort_by_key(|origin| {
+            let rank = match origin {
+                Origin::Default => 0,
+                Origin::File(_) | Origin::Stdin => 1,
+                Origin::Source(_) => 2,
+                Origin::Env(_) => 3,
+                Origin::Cli(_) => 4,
+                Origin::Prompt => 5,
+            };
+            (rank, origin.to_string())
+        });
+        layers
+    }
+}
+
+impl fmt::Display for CheckSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.error {
+            None => writeln!(f, "config check passed")?,
+            Some(CnfgError::Validation(errs)) => {
+                writeln!(f, "config check failed:")?;
+                for issue in errs {
+                    write!(f, "  - {} — {}", issue.field, issue.message)?;
+                    // Type issues already name their layer.
+                    if !issue.message.contains(" (from ") {
+                        if let Some(origin) = self.report.provenance.describe(&issue.field) {
+                            write!(f, " (from {origin})")?;
+                        }
+                    }
+                    writeln!(f)?;
+                }
+            }
+            Some(err) => {
+                writeln!(f, "config check failed:")?;
+                writeln!(f, "  - {err}")?;
+            }
+        }
+
+        let layers = self.layers();
+        if !layers.is_empty() {
+            writeln!(f, "layers:")?;
+            for origin in layers {
+                writeln!(f, "  - {origin}")?;
+            }
+        }
+        if !self.report.warnings.is_empty() {
+            write!(f, "{}", self.report.warnings)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+//! clap interop.
+//!
+//! Large CLIs can keep clap for argument parsing while cnfg owns files, env
+//! vars, and validation. [`augment`] mounts a config's flags and
+//! subcommands onto an existing `clap::Command`, and
+//! [`Loader::clap_matches`](crate::Loader::clap_matches) feeds the parsed
+//! `ArgMatches` back in as the CLI layer:
+//!
+//! ```ignore
+//! let matches = cnfg::clap::augment::<AppConfig>(clap::Command::new("myapp"))
+//!     .arg(clap::Arg::new("dry-run").long("dry-run").action(clap::ArgAction::SetTrue))
+//!     .get_matches();
+//! let cfg = AppConfig::loader().clap_matches(&matches).load()?;
+//! ```
+//!
+//! Only values given on the command line are used, so clap never shadows a
+//! file or env value with a default. Arguments are identified by the field's
+//! dotted path; flags added by the host app must use other ids. cnfg's
+//! built-in flags (`--set`, `--config`, `--check`, ...) are not mounted.
+
+use crate::completions;
+use crate::types::{CliSpec, CommandSpec, ConfigMeta};
+use crate::util::format_doc;
+use ::clap::parser::ValueSource;
+use ::clap::{Arg, ArgAction, ArgMatches, Command};
+
+/// A `clap::Command` named after the config's `app_name` (or the running
+/// binary) holding its flags and subcommands.
+pub fn command<T: ConfigMeta>() -> Command {
+    let name = T::app_name()
+        .map(str::to_string)
+        .unwrap_or_else(completions::current_bin_name);
+    let mut cmd = augment::<T>(Command::new(name));
+    if let Some(doc) = format_doc(T::doc()) {
+        cmd = cmd.about(doc);
+    }
+    if let Some(version) = T::version() {
+        cmd = cmd.version(version);
+    }
+    cmd
+}
+
+/// Add `T`'s flags and `#[cnfg(command)]` subcommands to `cmd`.
+pub fn augment<T: ConfigMeta>(cmd: Command) -> Command {
+    let cmd = cmd.args(args(T::cli_specs()));
+    T::commands()
+        .iter()
+        .fold(cmd, |cmd, spec| cmd.subcommand(subcommand(spec)))
+}
+
+/// One `clap::Arg` per flag. Required flags stay optional for clap because
+/// a file or env var may supply them; cnfg reports them when nothing does.
+pub fn args(specs: &[CliSpec]) -> Vec<Arg> {
+    specs.iter().map(arg).collect()
+}
+
+fn arg(spec: &CliSpec) -> Arg {
+    let mut arg = Arg::new(spec.path).long(spec.flag).hide(spec.hidden);
+    arg = if !spec.takes_value {
+        arg.action(ArgAction::SetTrue)
+    } else {
+        let action = if spec.multiple {
+            ArgAction::Append
+        } else {
+            ArgAction::Set
+        };
+        arg.action(action).value_name(spec.placeholder())
+    };
+    if !spec.values.is_empty() {
+        arg = arg.value_parser(spec.values.to_vec());
+    }
+    if let Some(doc) = format_doc(spec.doc) {
+        arg = arg.help(doc);
+    }
+    arg
+}
+
+fn subcommand(spec: &CommandSpec) -> Command {
+    let mut cmd = Command::new(spec.name).args(args((spec.cli_specs)()));
+    if let Some(doc) = format_doc(spec.doc) {
+        cmd = cmd.about(doc);
+    }
+    cmd
+}
+
+/// Rebuild the command line cnfg would have parsed from `matches`: the
+/// flags given on the command line, then the selected subcommand and
+/// its flags.
+pub(crate) fn to_args(
+    cli: &[CliSpec],
+    commands: &[CommandSpec],
+    matches: &ArgMatches,
+) -> Vec<String> {
+    let mut argv = vec![completions::current_bin_name()];
+    push_flags(&mut argv, cli, matches);
+    if let Some((name, sub)) = matches.subcommand() {
+        if let Some(spec) = commands.iter().find(|cmd| cmd.name == name) {
+            argv.push(name.to_string());
+            push_flags(&mut argv, (spec.cli_specs)(), sub);
+        }
+    }
+    argv
+}
+
+fn push_flags(argv: &mut Vec<String>, specs: &[CliSpec], matches: &ArgMatches) {
+    for spec in specs {
+        let given = matches.try_contains_id(spec.path).unwrap_or(false)
+            && matches.value_source(spec.path) == Some(ValueSource::CommandLine);
+        if !given {
+            continue;
+        }
+        if !spec.takes_value {
+            if matches.get_flag(spec.path) {
+                argv.push(format!("--{}", spec.flag));
+            }
+            continue;
+        }
+        for value in matches.get_raw(spec.path).into_iter().flatten() {
+            argv.push(format!("--{}", spec.flag));
+            argv.push(value.to_string_lossy().into_owned());
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Shell completion scripts generated from [`ConfigMeta::cli_specs`].
+//!
+//! Value hints come from each flag's [`Kind`]: paths complete file names,
+//! flags declared with `#[cnfg(values(...))]` complete their accepted values,
+//! and boolean switches take no value.
+
+use crate::types::{CliSpec, ConfigMeta, Kind};
+use crate::util::format_doc;
+use std::fmt;
+use std::str::FromStr;
+
+/// Shells [`render`] can generate completion scripts for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// Lower-case shell name as accepted by [`Shell::from_str`].
+    pub fn name(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        }
+    }
+}
+
+impl fmt::Display for Shell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            other => Err(format!(
+                "unsupported shell `{other}` (expected bash, zsh or fish)"
+            )),
+        }
+    }
+}
+
+/// Render a completion script for `T`'s flags, registered for `bin`.
+pub fn render<T: ConfigMeta>(shell: Shell, bin: &str) -> String {
+    let specs: Vec<CliSpec> = T::cli_specs()
+        .iter()
+        .filter(|spec| !spec.hidden)
+        .cloned()
+        .collect();
+    match shell {
+        Shell::Bash => bash(&specs, bin),
+        Shell::Zsh => zsh(&specs, bin),
+        Shell::Fish => fish(&specs, bin),
+    }
+}
+
+/// File name of the running executable, used when no name is given.
+pub(crate) fn current_bin_name() -> String {
+    std::env::args_os()
+        .next()
+        .and_then(|arg0| bin_name(&arg0))
+        .unwrap_or_else(|| "app".to_string())
+}
+
+/// Program name from an `argv[0]` such as `/usr/bin/myapp`.
+pub(crate) fn bin_name(arg0: impl AsRef<std::ffi::OsStr>) -> Option<String> {
+    std::path::Path::new(arg0.as_ref())
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .filter(|name| !name.is_empty())
+}
+
+enum Hint<'a> {
+    None,
+    Files,
+    Values(&'a [&'static str]),
+    Any,
+}
+
+fn hint(spec: &CliSpec) -> Hint<'_> {
+    if !spec.takes_value {
+        Hint::None
+    } else if !spec.values.is_empty() {
+        Hint::Values(spec.values)
+    } else if spec.kind == Kind::Path {
+        Hint::Files
+    } else if spec.kind == Kind::Bool {
+        Hint::Values(&["true", "false"])
+    } else {
+        Hint::Any
+    }
+}
+
+fn summary(spec: &CliSpec) -> String {
+    format_doc(spec.doc)
+        .and_then(|doc| doc.lines().next().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn bash(specs: &[CliSpec], bin: &str) -> String {
+    let func: String = bin
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let mut flags: Vec<String> = specs.iter().map(|s| format!("--{}", s.flag)).collect();
+    flags.push("--help".to_string());
+
+    let mut out = String::new();
+    out.push_str(&format!("_{func}() {{\n"));
+    out.push_str("    local cur prev\n");
+    out.push_str("    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    out.push_str("    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+    out.push_str("    case \"$prev\" in\n");
+    for spec in specs {
+        let reply = match hint(spec) {
+            Hint::None => continue,
+            Hint::Files => "COMPREPLY=($(compgen -f -- \"$cur\"))".to_string(),
+            Hint::Values(values) => format!(
+                "COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))",
+                values.join(" ")
+            ),
+            Hint::Any => "COMPREPLY=()".to_string(),
+        };
+        out.push_str(&format!("        --{})\n", spec.flag));
+        out.push_str(&format!("            {reply}\n"));
+        out.push_str("            return 0\n");
+        out.push_str("            ;;\n");
+    }
+    out.push_str("    esac\n");
+    out.push_str(&format!(
+        "    COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n",
+        flags.join(" ")
+    ));
+    out.push_str("}\n");
+    out.push_str(&format!("complete -F _{func} {bin}\n"));
+    out
+}
+
+fn zsh(specs: &[CliSpec], bin: &str) -> String {
+    let mut args = Vec::new();
+    for spec in specs {
+        let repeat = if spec.multiple { "*" } else { "" };
+        let doc = zsh_escape(&summary(spec));
+        let action = match hint(spec) {
+            Hint::None => String::new(),
+            Hint::Files => format!(":{}:_files", spec.kind.name()),
+            Hint::Values(values) => format!(":{}:({})", spec.kind.name(), values.join(" ")),
+            Hint::Any => format!(":{}: ", spec.kind.name()),
+        };
+        args.push(format!("'{repeat}--{}[{doc}]{action}'", spec.flag));
+    }
+    args.push("'(- *)'{-h,--help}'[Print help]'".to_string());
+
+    let mut out = format!("#compdef {bin}\n\n_arguments -s \\\n");
+    let last = args.len() - 1;
+    for (idx, arg) in args.iter().enumerate() {
+        let cont = if idx == last { "" } else { " \\" };
+        out.push_str(&format!("  {arg}{cont}\n"));
+    }
+    out
+}
+
+fn fish(specs: &[CliSpec], bin: &str) -> String {
+    let mut out = String::new();
+    for spec in specs {
+        let mut line = format!("complete -c {bin} -l {}", spec.flag);
+        match hint(spec) {
+            Hint::None => {}
+            Hint::Files => line.push_str(" -r -F"),
+            Hint::Values(values) => {
+                line.push_str(&format!(" -x -a '{}'", fish_escape(&values.join(" "))))
+            }
+            Hint::Any => line.push_str(" -x"),
+        }
+        let doc = summary(spec);
+        if !doc.is_empty() {
+            line.push_str(&format!(" -d '{}'", fish_escape(&doc)));
+        }
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out.push_str(&format!("complete -c {bin} -s h -l help -d 'Print help'\n"));
+    out
+}
+
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+fn fish_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
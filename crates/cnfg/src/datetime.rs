@@ -0,0 +1,171 @@
+//! RFC 3339 dates and date-times.
+//!
+//! Fields whose type is `chrono::DateTime`, `time::OffsetDateTime`
+//! ([`Kind::DateTime`](crate::Kind::DateTime)), `chrono::NaiveDate`, or
+//! `time::Date` ([`Kind::Date`](crate::Kind::Date)) are checked as strings
+//! before deserializing, whichever layer set them, so a bad value reports
+//! the expected format rather than a serde error. TOML date-times are read
+//! as the same strings.
+//!
+//! `validate(range(min = "2024-01-01"))` compares the field's [`Timestamp`],
+//! implemented for the `chrono` and `time` types behind the features of the
+//! same name.
+
+/// A point in time as seconds and nanoseconds since the Unix epoch (UTC).
+pub type Instant = (i64, u32);
+
+/// Parse an RFC 3339 date-time such as `2024-05-01T12:00:00Z` or
+/// `2024-05-01 12:00:00.5+02:00`.
+pub fn parse_date_time(raw: &str) -> Result<Instant, String> {
+    let expected =
+        || format!("expected an RFC 3339 date-time like `2024-05-01T12:00:00Z`, found `{raw}`");
+    let (date, rest) = raw.split_at_checked(10).ok_or_else(expected)?;
+    let days = days(date).ok_or_else(expected)?;
+    let rest = rest
+        .strip_prefix(['T', 't', ' '])
+        .filter(|rest| rest.is_char_boundary(8))
+        .ok_or_else(expected)?;
+    let (time, rest) = rest.split_at(8);
+    let seconds = clock(time, 60).ok_or_else(expected)?;
+    let (nanos, offset) = match rest.strip_prefix('.') {
+        Some(rest) => {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let (fraction, offset) = rest.split_at(end);
+            (fraction_nanos(fraction).ok_or_else(expected)?, offset)
+        }
+        None => (0, rest),
+    };
+    let offset = match offset {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match offset.as_bytes().first() {
+                Some(b'+') => 1,
+                Some(b'-') => -1,
+                _ => return Err(expected()),
+            };
+            let hm = offset
+                .get(1..)
+                .filter(|hm| hm.len() == 5)
+                .ok_or_else(expected)?;
+            sign * clock(&format!("{hm}:00"), 0).ok_or_else(expected)?
+        }
+    };
+    Ok((days * 86_400 + seconds - offset, nanos))
+}
+
+/// Parse an RFC 3339 full date such as `2024-05-01`, as midnight UTC.
+pub fn parse_date(raw: &str) -> Result<Instant, String> {
+    days(raw)
+        .map(|days| (days * 86_400, 0))
+        .ok_or_else(|| format!("expected a date like `2024-05-01`, found `{raw}`"))
+}
+
+/// Parse either form, for range bounds: dates compare as midnight UTC.
+pub fn parse_bound(raw: &str) -> Result<Instant, String> {
+    if raw.len() == 10 {
+        parse_date(raw)
+    } else {
+        parse_date_time(raw)
+    }
+}
+
+/// Days from 1970-01-01 to a valid `YYYY-MM-DD`.
+fn days(date: &str) -> Option<i64> {
+    let bytes = date.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+    let year = digits(&date[..4])?;
+    let month = digits(&date[5..7])?;
+    let day = digits(&date[8..])?;
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    if !(1..=month_days).contains(&day) {
+        return None;
+    }
+    // Days from civil, with March as the first month of the year.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+/// Seconds in `HH:MM:SS`, allowing a leap second when `max_second` is 60.
+fn clock(time: &str, max_second: i64) -> Option<i64> {
+    let bytes = time.as_bytes();
+    if bytes.len() != 8 || bytes[2] != b':' || bytes[5] != b':' {
+        return None;
+    }
+    let hour = digits(&time[..2]).filter(|h| *h < 24)?;
+    let minute = digits(&time[3..5]).filter(|m| *m < 60)?;
+    let second = digits(&time[6..]).filter(|s| *s <= max_second.max(59))?;
+    Some(hour * 3600 + minute * 60 + second.min(59))
+}
+
+fn digits(text: &str) -> Option<i64> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+fn fraction_nanos(fraction: &str) -> Option<u32> {
+    if fraction.is_empty() {
+        return None;
+    }
+    let padded: String = fraction
+        .chars()
+        .chain("000000000".chars())
+        .take(9)
+        .collect();
+    padded.parse().ok()
+}
+
+/// Values comparable with date range bounds.
+pub trait Timestamp {
+    /// Seconds and nanoseconds since the Unix epoch; dates are midnight UTC.
+    fn timestamp(&self) -> Instant;
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> Timestamp for chrono::DateTime<Tz> {
+    fn timestamp(&self) -> Instant {
+        (
+            chrono::DateTime::timestamp(self),
+            self.timestamp_subsec_nanos(),
+        )
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::NaiveDate {
+    fn timestamp(&self) -> Instant {
+        let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid date");
+        (self.signed_duration_since(epoch).num_days() * 86_400, 0)
+    }
+}
+
+#[cfg(feature = "time")]
+impl Timestamp for time::OffsetDateTime {
+    fn timestamp(&self) -> Instant {
+        (self.unix_timestamp(), self.nanosecond())
+    }
+}
+
+#[cfg(feature = "time")]
+impl Timestamp for time::Date {
+    fn timestamp(&self) -> Instant {
+        // Julian day 2440588 is 1970-01-01.
+        ((i64::from(self.to_julian_day()) - 2_440_588) * 86_400, 0)
+    }
+}
@@ -0,0 +1,93 @@
+//! Platform configuration directory discovery.
+//!
+//! When an application name is known (via `#[cnfg(app_name = "...")]` or
+//! [`Loader::app_name`](crate::loader::Loader::app_name)) the loader searches
+//! these directories in addition to the working directory.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// File names probed inside each search directory, in priority order.
+pub const CONFIG_FILE_NAMES: &[&str] = &["config.toml", "config.yaml", "config.yml", "config.json"];
+
+/// Per-user configuration directory for `app`.
+///
+/// - `$XDG_CONFIG_HOME/<app>` when `XDG_CONFIG_HOME` is set to an absolute path.
+/// - macOS: `~/Library/Application Support/<app>`
+/// - Windows: `%APPDATA%\<app>`
+/// - other Unix: `~/.config/<app>`
+pub fn user_config_dir(app: &str) -> Option<PathBuf> {
+    if let Some(xdg) = env_path("XDG_CONFIG_HOME") {
+        return Some(xdg.join(app));
+    }
+    platform_user_dir().map(|dir| dir.join(app))
+}
+
+/// System-wide configuration directory for `app`.
+///
+/// - Windows: `%PROGRAMDATA%\<app>`
+/// - Unix: `/etc/<app>`
+pub fn system_config_dir(app: &str) -> Option<PathBuf> {
+    platform_system_dir().map(|dir| dir.join(app))
+}
+
+/// Return the first existing `config.*` file inside `dir`.
+pub fn find_config_file(dir: &Path) -> Option<PathBuf> {
+    CONFIG_FILE_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// The current user's home directory (`HOME`, or `USERPROFILE` on Windows).
+pub fn home_dir() -> Option<PathBuf> {
+    #[cfg(windows)]
+    {
+        env_path("USERPROFILE").or_else(|| env_path("HOME"))
+    }
+    #[cfg(not(windows))]
+    {
+        env_path("HOME")
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn platform_user_dir() -> Option<PathBuf> {
+    home_dir().map(|home| home.join("Library").join("Application Support"))
+}
+
+#[cfg(windows)]
+fn platform_user_dir() -> Option<PathBuf> {
+    env_path("APPDATA")
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn platform_user_dir() -> Option<PathBuf> {
+    home_dir().map(|home| home.join(".config"))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn platform_user_dir() -> Option<PathBuf> {
+    None
+}
+
+#[cfg(windows)]
+fn platform_system_dir() -> Option<PathBuf> {
+    env_path("PROGRAMDATA")
+}
+
+#[cfg(unix)]
+fn platform_system_dir() -> Option<PathBuf> {
+    Some(PathBuf::from("/etc"))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn platform_system_dir() -> Option<PathBuf> {
+    None
+}
+
+fn env_path(name: &str) -> Option<PathBuf> {
+    env::var_os(name)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
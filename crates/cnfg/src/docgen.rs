@@ -0,0 +1,133 @@
+//! Documentation generators for config schemas.
+
+use crate::error::CnfgError;
+use crate::export;
+use crate::types::{CliSpec, ConfigMeta, FieldSpec, Kind, ListSpec};
+use crate::util::format_doc;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Render every leaf field of `T` (nested structs included) as a Markdown table.
+///
+/// Columns: field path, type, default, env var, CLI flag, required, and the
+/// field's doc comment. Fields of `Vec<Nested>` elements are listed as
+/// `list[].field`.
+pub fn markdown<T: ConfigMeta>() -> String {
+    let mut lines = vec![
+        "| Field | Type | Default | Env | CLI | Required | Description |".to_string(),
+        "| ----- | ---- | ------- | --- | --- | -------- | ----------- |".to_string(),
+    ];
+    push_rows(
+        &mut lines,
+        T::field_specs(),
+        T::lists(),
+        Some(T::cli_specs()),
+        "",
+    );
+    lines.join("\n")
+}
+
+/// Element rows have no env var or flag: those only bind top-level fields.
+fn push_rows(
+    lines: &mut Vec<String>,
+    fields: &[FieldSpec],
+    lists: &[ListSpec],
+    cli_specs: Option<&[CliSpec]>,
+    prefix: &str,
+) {
+    for spec in fields {
+        if spec.kind == Kind::Object {
+            if let Some(list) = lists.iter().find(|list| list.path == spec.path) {
+                let prefix = format!("{prefix}{}[].", spec.path);
+                push_rows(lines, (list.field_specs)(), (list.lists)(), None, &prefix);
+            }
+            continue;
+        }
+        let flag = cli_specs
+            .and_then(|specs| specs.iter().find(|cli| cli.path == spec.path))
+            .map(|cli| format!("`--{}`", cli.flag));
+        let env = cli_specs.and(spec.env);
+        let cells = [
+            code(Some(&format!("{prefix}{}", spec.path))),
+            if spec.list {
+                format!("list of {}", spec.kind.name())
+            } else {
+                spec.kind.name().to_string()
+            },
+            code(spec.default),
+            code(env),
+            flag.unwrap_or_default(),
+            if spec.required { "yes" } else { "no" }.to_string(),
+            escape(&format_doc(spec.doc).unwrap_or_default()),
+        ];
+        lines.push(format!("| {} |", cells.join(" | ")));
+    }
+}
+
+/// Render `cfg` as a Kubernetes `ConfigMap` manifest named `name` in
+/// `namespace`, for use with `envFrom`.
+///
+/// Keys are the env var names of [`crate::export`], so the container reads
+/// the config back from its environment. Set `#[cnfg(secret)]` fields never
+/// enter the ConfigMap: they go to a second document, an `Opaque` `Secret`
+/// named `<name>-secrets`, emitted only when there are any.
+pub fn to_configmap<T: ConfigMeta + Serialize>(
+    cfg: &T,
+    name: &str,
+    namespace: &str,
+) -> Result<String, CnfgError> {
+    let value = serde_json::to_value(cfg).map_err(|e| CnfgError::Serialize(e.to_string()))?;
+    let (secret, public): (Vec<FieldSpec>, Vec<FieldSpec>) = T::field_specs()
+        .iter()
+        .cloned()
+        .partition(|spec| spec.secret);
+    let prefix = T::env_prefix();
+
+    let mut out = manifest("ConfigMap", name, namespace);
+    push_data(&mut out, "data", &export::env_vars(&public, prefix, &value));
+
+    let secrets = export::env_vars(&secret, prefix, &value);
+    if !secrets.is_empty() {
+        out.push_str("---\n");
+        out.push_str(&manifest("Secret", &format!("{name}-secrets"), namespace));
+        out.push_str("type: Opaque\n");
+        push_data(&mut out, "stringData", &secrets);
+    }
+    Ok(out)
+}
+
+fn manifest(kind: &str, name: &str, namespace: &str) -> String {
+    format!(
+        "apiVersion: v1\nkind: {kind}\nmetadata:\n  name: {}\n  namespace: {}\n",
+        yaml_string(name),
+        yaml_string(namespace)
+    )
+}
+
+/// A `key:` map of string values; an empty map is written as `{}`.
+fn push_data(out: &mut String, key: &str, vars: &[(String, String)]) {
+    if vars.is_empty() {
+        out.push_str(&format!("{key}: {{}}\n"));
+        return;
+    }
+    out.push_str(&format!("{key}:\n"));
+    for (name, value) in vars {
+        out.push_str(&format!("  {name}: {}\n", yaml_string(value)));
+    }
+}
+
+/// Double-quoted YAML string (JSON string syntax is valid YAML), so values
+/// like `true` or `8080` stay strings as Kubernetes requires.
+fn yaml_string(text: &str) -> String {
+    Value::String(text.to_string()).to_string()
+}
+
+fn code(value: Option<&str>) -> String {
+    value
+        .map(|v| format!("`{}`", escape(v)))
+        .unwrap_or_default()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|")
+}
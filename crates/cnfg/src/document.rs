@@ -0,0 +1,91 @@
+//! Untyped access to the merged configuration.
+
+use crate::error::{CnfgError, Issue, IssueKind, ValidationErrors};
+use crate::provenance::{Origin, Provenance};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// The merged config as it was deserialized, with the layer behind each
+/// value.
+///
+/// Useful for sections that are not statically typed, e.g. plugin settings
+/// kept in a `serde_json::Value` or `#[serde(flatten)]` map.
+#[derive(Debug, Default, Clone)]
+pub struct Document {
+    value: Value,
+    provenance: Provenance,
+}
+
+impl Document {
+    pub fn new(value: Value, provenance: Provenance) -> Self {
+        Self { value, provenance }
+    }
+
+    /// The whole merged document.
+    pub fn as_value(&self) -> &Value {
+        &self.value
+    }
+
+    pub fn into_value(self) -> Value {
+        self.value
+    }
+
+    /// Raw value at a dotted path, if present.
+    pub fn value(&self, path: &str) -> Option<&Value> {
+        if path.is_empty() {
+            return Some(&self.value);
+        }
+        path.split('.').try_fold(&self.value, |current, segment| {
+            current.as_object()?.get(segment)
+        })
+    }
+
+    /// Deserialize the value at `path`; `Ok(None)` when it is absent or null.
+    pub fn get<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>, CnfgError> {
+        let Some(value) = self.value(path).filter(|value| !value.is_null()) else {
+            return Ok(None);
+        };
+        T::deserialize(value).map(Some).map_err(|err| {
+            let mut errs = ValidationErrors::new();
+            errs.push(Issue::new(
+                path.to_string(),
+                IssueKind::Type,
+                err.to_string(),
+            ));
+            CnfgError::Validation(errs)
+        })
+    }
+
+    /// String value at `path`, if present and a string.
+    pub fn get_str(&self, path: &str) -> Option<&str> {
+        self.value(path)?.as_str()
+    }
+
+    /// Layer that supplied the value at `path`.
+    pub fn origin(&self, path: &str) -> Option<&Origin> {
+        self.provenance.get(path)
+    }
+
+    /// Every leaf path (arrays count as leaves) with its value, sorted.
+    pub fn iter_paths(&self) -> impl Iterator<Item = (String, &Value)> {
+        fn walk<'a>(value: &'a Value, path: String, out: &mut Vec<(String, &'a Value)>) {
+            match value {
+                Value::Object(map) if !map.is_empty() => {
+                    for (key, child) in map {
+                        let child_path = if path.is_empty() {
+                            key.clone()
+                        } else {
+                            format!("{path}.{key}")
+                        };
+                        walk(child, child_path, out);
+                    }
+                }
+                _ if path.is_empty() => {}
+                _ => out.push((path, value)),
+            }
+        }
+        let mut out = Vec::new();
+        walk(&self.value, String::new(), &mut out);
+        out.into_iter()
+    }
+}
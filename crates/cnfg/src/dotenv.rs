@@ -0,0 +1,70 @@
+//! `.env` file handling.
+//!
+//! Before reading the environment the loader copies variables from `.env`
+//! files into the process environment. Variables that are already set are
+//! never replaced, so the real environment always wins.
+//!
+//! By default ([`Dotenv::Search`]) the working directory is searched for
+//! `.env`, `.env.local`, and `.env.<profile>` when a profile is active,
+//! with later files taking precedence. Choose the files with
+//! `#[cnfg(dotenv = ".env.test")]` or [`Loader::dotenv_file`](crate::Loader::dotenv_file),
+//! or turn the step off with `#[cnfg(dotenv = false)]` or
+//! [`Loader::dotenv`](crate::Loader::dotenv).
+
+use crate::error::CnfgError;
+use crate::report::{WarningKind, Warnings};
+use crate::trace;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the active profile when the loader has none.
+pub const PROFILE_ENV: &str = "CONFIG_PROFILE";
+
+/// Which `.env` files are read before the environment is applied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Dotenv {
+    /// `.env`, `.env.local`, then `.env.<profile>` from the working
+    /// directory; missing files are skipped and unreadable ones reported as
+    /// warnings.
+    #[default]
+    Search,
+    /// Exactly these files, later ones taking precedence; each must exist.
+    Files(Vec<PathBuf>),
+    /// No `.env` file is read.
+    Disabled,
+}
+
+impl Dotenv {
+    /// Copy variables from the selected files into the process environment.
+    pub(crate) fn apply(
+        &self,
+        profile: Option<&str>,
+        warnings: &mut Warnings,
+    ) -> Result<(), CnfgError> {
+        match self {
+            Dotenv::Disabled => Ok(()),
+            Dotenv::Search => {
+                let mut files = vec![PathBuf::from(".env"), PathBuf::from(".env.local")];
+                files.extend(profile.map(|name| PathBuf::from(format!(".env.{name}"))));
+                // The first file read wins, so read the highest priority first.
+                for file in files.iter().rev().filter(|file| file.is_file()) {
+                    if let Err(err) = read(file) {
+                        warnings.push(
+                            WarningKind::FileDiscovery,
+                            file.display().to_string(),
+                            format!("ignored: {err}"),
+                        );
+                    }
+                }
+                Ok(())
+            }
+            Dotenv::Files(files) => files.iter().rev().try_for_each(|file| {
+                read(file).map_err(|err| CnfgError::Env(format!("{}: {err}", file.display())))
+            }),
+        }
+    }
+}
+
+fn read(path: &Path) -> Result<(), dotenvy::Error> {
+    trace::event!(DEBUG, path = %path.display(), "reading dotenv file");
+    dotenvy::from_path(path)
+}
@@ -0,0 +1,463 @@
+//! Config schemas assembled at runtime.
+//!
+//! Applications that cannot use `#[derive(Cnfg)]` (plugins composed at
+//! startup, FFI layers) describe their fields with a [`SchemaBuilder`]. The
+//! resulting [`DynamicSchema`] hands out [`Loader`]s that run the same
+//! pipeline as a derived struct (defaults, files, sources, env, CLI, type
+//! checks, required fields, validators) into a `serde_json::Value` or a
+//! `HashMap<String, Value>`.
+//!
+//! ```rust
+//! use cnfg::{FieldDef, Kind, SchemaBuilder};
+//!
+//! let schema = SchemaBuilder::new()
+//!     .field(FieldDef::new("server.port", Kind::Int).default(8080).cli())
+//!     .field(FieldDef::new("server.host", Kind::String).env("PLUGIN_HOST").required())
+//!     .build()
+//!     .expect("valid schema");
+//! let cfg: serde_json::Value = schema
+//!     .loader()
+//!     .args(["plugin", "--server-port", "9000", "--set", "server.host=example.com"])
+//!     .load()
+//!     .expect("load");
+//! assert_eq!(cfg["server"]["port"], 9000);
+//! ```
+
+use crate::dotenv::Dotenv;
+use crate::error::{CnfgError, Issue, IssueKind, ValidationErrors};
+use crate::help::{HelpOptions, render_help};
+use crate::loader::{Loader, Meta};
+use crate::merge::{insert_path, value_at};
+use crate::types::{CliSpec, FieldSpec, Kind};
+use crate::util::leak_string;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+
+type Check = Arc<dyn Fn(&Value) -> Result<(), String> + Send + Sync>;
+
+/// One field registered with a [`SchemaBuilder`], addressed by dotted path.
+#[derive(Clone)]
+pub struct FieldDef {
+    path: String,
+    kind: Kind,
+    doc: Option<String>,
+    default: Option<Value>,
+    env: Option<String>,
+    allow_file: bool,
+    flag: Option<String>,
+    list: bool,
+    required: bool,
+    required_if: Option<String>,
+    required_unless: Option<String>,
+    secret: bool,
+    checks: Vec<Check>,
+}
+
+impl fmt::Debug for FieldDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FieldDef")
+            .field("path", &self.path)
+            .field("kind", &self.kind)
+            .field("default", &self.default)
+            .field("env", &self.env)
+            .field("flag", &self.flag)
+            .field("required", &self.required)
+            .field("checks", &self.checks.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl FieldDef {
+    /// A field at `path` (e.g. `database.url`) holding values of `kind`.
+    pub fn new(path: impl Into<String>, kind: Kind) -> Self {
+        Self {
+            path: path.into(),
+            kind,
+            doc: None,
+            default: None,
+            env: None,
+            allow_file: false,
+            flag: None,
+            list: false,
+            required: false,
+            required_if: None,
+            required_unless: None,
+            secret: false,
+            checks: Vec::new(),
+        }
+    }
+
+    /// Description shown in help output and written by `--init`.
+    pub fn doc(mut self, doc: impl Into<String>) -> Self {
+        self.doc = Some(doc.into());
+        self
+    }
+
+    /// Value used when no layer sets the field.
+    ///
+    /// # Panics
+    ///
+    /// If `value` does not serialize to JSON.
+    pub fn default(mut self, value: impl Serialize) -> Self {
+        self.default = Some(serde_json::to_value(value).expect("default value must serialize"));
+        self
+    }
+
+    /// Read the field from the env var `name`.
+    pub fn env(mut self, name: impl Into<String>) -> Self {
+        self.env = Some(name.into());
+        self
+    }
+
+    /// Also accept `<ENV>_FILE` naming a file with the value.
+    pub fn allow_file(mut self) -> Self {
+        self.allow_file = true;
+        self
+    }
+
+    /// Bind a flag named after the path: `server.max_conns` becomes
+    /// `--server-max-conns`.
+    pub fn cli(self) -> Self {
+        let flag = self.path.replace(['.', '_'], "-");
+        self.flag(flag)
+    }
+
+    /// Bind the flag `--<flag>`.
+    pub fn flag(mut self, flag: impl Into<String>) -> Self {
+        self.flag = Some(flag.into().trim_start_matches("--").to_string());
+        self
+    }
+
+    /// The field holds a list of `kind` values.
+    pub fn list(mut self) -> Self {
+        self.list = true;
+        self
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Require the field when the value at the dotted `path` is set and not
+    /// `false`.
+    pub fn required_if(mut self, path: impl Into<String>) -> Self {
+        self.required_if = Some(path.into());
+        self
+    }
+
+    /// Require the field unless the value at the dotted `path` is set and
+    /// not `false`.
+    pub fn required_unless(mut self, path: impl Into<String>) -> Self {
+        self.required_unless = Some(path.into());
+        self
+    }
+
+    /// Keep the value out of fingerprints.
+    pub fn secret(mut self) -> Self {
+        self.secret = true;
+        self
+    }
+
+    /// Check the merged value when the config is validated; the error
+    /// becomes an [`IssueKind::Custom`] issue for this field.
+    pub fn validate(
+        mut self,
+        check: impl Fn(&Value) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.checks.push(Arc::new(check));
+        self
+    }
+}
+
+/// Collects [`FieldDef`]s into a [`DynamicSchema`].
+#[derive(Debug, Clone, Default)]
+pub struct SchemaBuilder {
+    doc: Option<String>,
+    app_name: Option<String>,
+    env_prefix: Option<String>,
+    version: Option<String>,
+    fields: Vec<FieldDef>,
+}
+
+impl SchemaBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Description printed at the top of `--help`.
+    pub fn doc(mut self, doc: impl Into<String>) -> Self {
+        self.doc = Some(doc.into());
+        self
+    }
+
+    /// Application name used for platform config directory discovery.
+    pub fn app_name(mut self, name: impl Into<String>) -> Self {
+        self.app_name = Some(name.into());
+        self
+    }
+
+    /// Prefix shared by the application's environment variables; see
+    /// [`Loader::env_prefix`].
+    pub fn env_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.env_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Version printed for `--version`.
+    pub fn version(mut self, version: impl Into<String>) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    pub fn field(mut self, field: FieldDef) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// Check the fields for clashes and freeze them into a schema.
+    ///
+    /// Fails with [`CnfgError::Schema`] for empty path segments, a path
+    /// declared twice or both as a value and as a section, and env vars or
+    /// flags bound by two fields.
+    pub fn build(self) -> Result<DynamicSchema, CnfgError> {
+        let invalid = |msg: String| Err(CnfgError::Schema(msg));
+        let mut paths = HashSet::new();
+        let mut envs = HashSet::new();
+        let mut flags = HashSet::new();
+        for field in &self.fields {
+            if field.path.split('.').any(str::is_empty) {
+                return invalid(format!("invalid field path `{}`", field.path));
+            }
+            if !paths.insert(field.path.as_str()) {
+                return invalid(format!("field `{}` is declared twice", field.path));
+            }
+            if let Some(env) = field.env.as_deref().filter(|env| !envs.insert(*env)) {
+                return invalid(format!("env var `{env}` is bound by two fields"));
+            }
+            if let Some(flag) = field.flag.as_deref().filter(|flag| !flags.insert(*flag)) {
+                return invalid(format!("flag `--{flag}` is bound by two fields"));
+            }
+        }
+        for field in &self.fields {
+            if let Some(parent) = parents(&field.path).find(|parent| paths.contains(parent)) {
+                return invalid(format!(
+                    "field `{}` is nested under the value `{parent}`",
+                    field.path
+                ));
+            }
+        }
+
+        let mut field_specs = Vec::new();
+        let mut cli_specs = Vec::new();
+        let mut required = Vec::new();
+        let mut checks = Vec::new();
+        let mut defaults = Value::Object(Default::default());
+        let mut sections = Vec::new();
+        for field in self.fields {
+            let path = leak_string(field.path);
+            for parent in parents(path) {
+                if !sections.contains(&parent) {
+                    sections.push(parent);
+                    field_specs.push(section_spec(parent));
+                }
+            }
+            let doc = field.doc.map(leak_string);
+            let default = field.default.as_ref().map(|value| match value {
+                Value::String(text) => leak_string(text.clone()),
+                other => leak_string(other.to_string()),
+            });
+            if let Some(value) = field.default {
+                let segments: Vec<&str> = path.split('.').collect();
+                insert_path(&mut defaults, &segments, value)
+                    .map_err(|err| CnfgError::Schema(format!("default of `{path}`: {err}")))?;
+            }
+            if let Some(flag) = field.flag {
+                cli_specs.push(CliSpec {
+                    flag: leak_string(flag),
+                    field: last_segment(path),
+                    kind: field.kind,
+                    path,
+                    doc,
+                    takes_value: field.kind != Kind::Bool || field.list,
+                    default,
+                    required: field.required,
+                    multiple: field.list,
+                    values: &[],
+                    value_name: None,
+                    hidden: false,
+                    parse: None,
+                });
+            }
+            if field.required {
+                required.push(path);
+            }
+            checks.extend(field.checks.into_iter().map(|check| (path, check)));
+            field_specs.push(FieldSpec {
+                name: last_segment(path),
+                env: field.env.map(leak_string),
+                env_aliases: &[],
+                path,
+                doc,
+                kind: field.kind,
+                default,
+                required: field.required,
+                required_if: field.required_if.map(leak_string),
+                required_unless: field.required_unless.map(leak_string),
+                optional: !field.required,
+                allow_file: field.allow_file,
+                list: field.list,
+                deprecated: None,
+                renamed_from: None,
+                merge: None,
+                extra: false,
+                secret: field.secret,
+                secret_ref: None,
+                relative_to_config: false,
+                parse: None,
+                precedence: None,
+            });
+        }
+
+        let meta = Meta {
+            fields: Box::leak(field_specs.into_boxed_slice()),
+            cli: Box::leak(cli_specs.into_boxed_slice()),
+            required: Box::leak(required.into_boxed_slice()),
+            commands: &[],
+            lists: &[],
+            defaults: Cow::Owned(defaults),
+            defaults_error: None,
+            doc: self.doc.map(leak_string),
+            about: None,
+            after_help: None,
+            app_name: self.app_name.map(leak_string),
+            env_prefix: self.env_prefix.map(leak_string),
+            auto_env: false,
+            version: self.version.map(leak_string),
+            dotenv: Dotenv::Search,
+        };
+        Ok(DynamicSchema {
+            meta,
+            checks: Arc::new(checks),
+        })
+    }
+}
+
+/// A schema built by [`SchemaBuilder`], playing the role of a derived
+/// struct's [`ConfigMeta`](crate::ConfigMeta) impl.
+#[derive(Clone)]
+pub struct DynamicSchema {
+    meta: Meta,
+    checks: Arc<Vec<(&'static str, Check)>>,
+}
+
+impl fmt::Debug for DynamicSchema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamicSchema")
+            .field("meta", &self.meta)
+            .field("checks", &self.checks.len())
+            .finish()
+    }
+}
+
+impl DynamicSchema {
+    /// Start a [`Loader`] producing `T`, typically `serde_json::Value` or
+    /// `HashMap<String, Value>`.
+    pub fn loader<T: Serialize + DeserializeOwned>(&self) -> Loader<T> {
+        let checks = Arc::clone(&self.checks);
+        Loader::with_meta(
+            self.meta.clone(),
+            Arc::new(move |cfg: &T| {
+                let value = serde_json::to_value(cfg).map_err(|e| {
+                    let mut errs = ValidationErrors::new();
+                    errs.push(Issue::new(String::new(), IssueKind::Type, e.to_string()));
+                    errs
+                })?;
+                validate(&checks, &value)
+            }),
+        )
+    }
+
+    /// Load into a `serde_json::Value` from defaults, files, env, and CLI.
+    pub fn load(&self) -> Result<Value, CnfgError> {
+        self.loader().load()
+    }
+
+    /// Run the registered validators against `value`.
+    pub fn validate(&self, value: &Value) -> Result<(), ValidationErrors> {
+        validate(&self.checks, value)
+    }
+
+    /// Every field, including a spec of kind [`Kind::Object`] for each
+    /// section implied by a dotted path.
+    pub fn field_specs(&self) -> &'static [FieldSpec] {
+        self.meta.fields
+    }
+
+    pub fn cli_specs(&self) -> &'static [CliSpec] {
+        self.meta.cli
+    }
+
+    pub fn defaults_json(&self) -> Value {
+        self.meta.defaults.clone().into_owned()
+    }
+
+    /// Render CLI help text.
+    pub fn help(&self) -> String {
+        render_help(&self.meta, &HelpOptions::default(), false)
+    }
+}
+
+fn validate(checks: &[(&'static str, Check)], value: &Value) -> Result<(), ValidationErrors> {
+    let mut errs = ValidationErrors::new();
+    for (path, check) in checks {
+        let Some(found) = value_at(value, path).filter(|found| !found.is_null()) else {
+            continue;
+        };
+        if let Err(message) = check(found) {
+            errs.push(Issue::custom(path.to_string(), message));
+        }
+    }
+    if errs.is_empty() { Ok(()) } else { Err(errs) }
+}
+
+/// `a`, then `a.b`, for the path `a.b.c`.
+fn parents(path: &str) -> impl Iterator<Item = &str> {
+    path.match_indices('.').map(|(idx, _)| &path[..idx])
+}
+
+fn last_segment(path: &'static str) -> &'static str {
+    path.rsplit('.').next().unwrap_or(path)
+}
+
+fn section_spec(path: &'static str) -> FieldSpec {
+    FieldSpec {
+        name: last_segment(path),
+        env: None,
+        env_aliases: &[],
+        path,
+        doc: None,
+        kind: Kind::Object,
+        default: None,
+        required: false,
+        required_if: None,
+        required_unless: None,
+        optional: false,
+        allow_file: false,
+        list: false,
+        deprecated: None,
+        renamed_from: None,
+        merge: None,
+        extra: false,
+        secret: false,
+        secret_ref: None,
+        relative_to_config: false,
+        parse: None,
+        precedence: None,
+    }
+}
@@ -1,20 +1,189 @@
+use crate::messages::{English, MessageRenderer};
+use std::collections::BTreeMap;
 use std::fmt;
 
 /// A structured validation error for a config field.
+///
+/// `message` is English text ready to show. `expected`, `actual`, and
+/// `params` carry the same facts as data, for building localized or
+/// user-facing messages downstream.
 #[derive(Debug, Clone)]
 pub struct Issue {
     pub field: String,
     pub kind: IssueKind,
     pub message: String,
+    /// What the check wanted, e.g. `>= 13` or a regex pattern.
+    pub expected: Option<String>,
+    /// The offending value as text, when the check saw one.
+    pub actual: Option<String>,
+    pub severity: Severity,
+    /// Named values of the check, e.g. `min` and `max` for a range.
+    pub params: BTreeMap<String, String>,
+}
+
+impl Issue {
+    /// An error with no metadata beyond its message.
+    pub fn new(field: impl Into<String>, kind: IssueKind, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            kind,
+            message: message.into(),
+            expected: None,
+            actual: None,
+            severity: Severity::Error,
+            params: BTreeMap::new(),
+        }
+    }
+
+    /// An [`IssueKind::Custom`] error, for hand-written `Validate` impls.
+    pub fn custom(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(field, IssueKind::Custom, message)
+    }
+
+    /// A required field that no layer set.
+    pub fn missing(field: impl Into<String>) -> Self {
+        Self::new(field, IssueKind::Missing, "required field missing")
+    }
+
+    /// `actual` lies outside `min..=max`; either bound may be open. The
+    /// message names the bound that was crossed.
+    pub fn range<T: PartialOrd + fmt::Display>(
+        field: impl Into<String>,
+        min: Option<T>,
+        max: Option<T>,
+        actual: T,
+    ) -> Self {
+        let expected = match (&min, &max) {
+            (Some(min), Some(max)) => format!("{min}..={max}"),
+            (Some(min), None) => format!(">= {min}"),
+            (None, Some(max)) => format!("<= {max}"),
+            (None, None) => String::new(),
+        };
+        let message = match (&min, &max) {
+            (Some(min), _) if actual < *min => format!("must be >= {min}"),
+            (_, Some(max)) if actual > *max => format!("must be <= {max}"),
+            _ => format!("must be within {expected}"),
+        };
+        let mut issue = Self::new(field, IssueKind::Range, message)
+            .with_expected(expected)
+            .with_actual(actual.to_string());
+        if let Some(min) = min {
+            issue = issue.with_param("min", min.to_string());
+        }
+        if let Some(max) = max {
+            issue = issue.with_param("max", max.to_string());
+        }
+        issue
+    }
+
+    /// A value that does not match `pattern`.
+    pub fn regex(field: impl Into<String>, pattern: &str) -> Self {
+        Self::new(
+            field,
+            IssueKind::Regex,
+            format!("regex not matched: {pattern}"),
+        )
+        .with_expected(pattern)
+        .with_param("pattern", pattern)
+    }
+
+    pub fn with_expected(mut self, expected: impl Into<String>) -> Self {
+        self.expected = Some(expected.into());
+        self
+    }
+
+    pub fn with_actual(mut self, actual: impl Into<String>) -> Self {
+        self.actual = Some(actual.into());
+        self
+    }
+
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    pub fn with_param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.params.insert(name.into(), value.into());
+        self
+    }
+
+    /// Note the layer that set the value, as a ` (from ...)` suffix and the
+    /// `origin` param.
+    pub fn with_origin(mut self, origin: impl Into<String>) -> Self {
+        let origin = origin.into();
+        self.message.push_str(&format!(" (from {origin})"));
+        self.with_param("origin", origin)
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.severity == Severity::Warning
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, English.render(self))
+    }
+}
+
+impl IssueKind {
+    /// Stable lowercase name, e.g. `range`, for keying message catalogs.
+    pub fn name(self) -> &'static str {
+        match self {
+            IssueKind::Missing => "missing",
+            IssueKind::Range => "range",
+            IssueKind::Regex => "regex",
+            IssueKind::Url => "url",
+            IssueKind::Path => "path",
+            IssueKind::Email => "email",
+            IssueKind::Hostname => "hostname",
+            IssueKind::Uuid => "uuid",
+            IssueKind::Semver => "semver",
+            IssueKind::Port => "port",
+            IssueKind::Cidr => "cidr",
+            IssueKind::Forbidden => "forbidden",
+            IssueKind::Type => "type",
+            IssueKind::Interpolation => "interpolation",
+            IssueKind::Decrypt => "decrypt",
+            IssueKind::Secret => "secret",
+            IssueKind::Custom => "custom",
+        }
+    }
+}
+
+/// How serious an [`Issue`] is. The loader reports
+/// [`Severity::Warning`] issues as [`WarningKind::Validation`](crate::WarningKind::Validation)
+/// warnings and loads the config anyway.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Severity {
+    #[default]
+    Error,
+    Warning,
 }
 
 /// The type of validation error.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum IssueKind {
     Missing,
     Range,
     Regex,
     Url,
+    /// A filesystem check (`file_exists`, `dir_exists`, `readable`) failed.
+    Path,
+    Email,
+    Hostname,
+    Uuid,
+    Semver,
+    Port,
+    Cidr,
+    /// A value not allowed in the active profile (`forbid_in_profile`).
+    Forbidden,
+    Type,
+    Interpolation,
+    /// An `enc:` value could not be decrypted.
+    Decrypt,
+    /// A secret manager reference could not be resolved.
+    Secret,
     Custom,
 }
 
@@ -53,6 +222,41 @@ impl ValidationErrors {
         self.issues.extend(other.issues);
     }
 
+    /// Combine two sets of errors, keeping the order of `self` then `other`.
+    pub fn merge(mut self, other: ValidationErrors) -> Self {
+        self.extend(other);
+        self
+    }
+
+    /// Issues for `path` and any field nested below it.
+    pub fn errors_for<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a Issue> + 'a {
+        self.issues.iter().filter(move |issue| {
+            issue.field == path
+                || issue
+                    .field
+                    .strip_prefix(path)
+                    .is_some_and(|rest| rest.starts_with('.') || rest.starts_with('['))
+        })
+    }
+
+    /// Issues keyed by field path, sorted by path.
+    pub fn group_by_field(&self) -> BTreeMap<&str, Vec<&Issue>> {
+        let mut groups: BTreeMap<&str, Vec<&Issue>> = BTreeMap::new();
+        for issue in &self.issues {
+            groups.entry(issue.field.as_str()).or_default().push(issue);
+        }
+        groups
+    }
+
+    /// Each issue as `field: message`, worded by `renderer`; see
+    /// [`crate::messages`].
+    pub fn render(&self, renderer: &dyn MessageRenderer) -> Vec<String> {
+        self.issues
+            .iter()
+            .map(|issue| format!("{}: {}", issue.field, renderer.render(issue)))
+            .collect()
+    }
+
     pub fn with_prefix(mut self, prefix: &str) -> Self {
         for issue in &mut self.issues {
             issue.field = format!("{prefix}.{}", issue.field);
@@ -61,6 +265,32 @@ impl ValidationErrors {
     }
 }
 
+impl IntoIterator for ValidationErrors {
+    type Item = Issue;
+    type IntoIter = std::vec::IntoIter<Issue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.issues.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a ValidationErrors {
+    type Item = &'a Issue;
+    type IntoIter = std::slice::Iter<'a, Issue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.issues.iter()
+    }
+}
+
+impl FromIterator<Issue> for ValidationErrors {
+    fn from_iter<I: IntoIterator<Item = Issue>>(iter: I) -> Self {
+        Self {
+            issues: iter.into_iter().collect(),
+        }
+    }
+}
+
 impl fmt::Display for ValidationErrors {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.issues.is_empty() {
@@ -68,7 +298,7 @@ impl fmt::Display for ValidationErrors {
         }
         writeln!(f, "validation failed:")?;
         for issue in &self.issues {
-            writeln!(f, "  - {} — {}", issue.field, issue.message)?;
+            writeln!(f, "  - {} — {}", issue.field, English.render(issue))?;
         }
         Ok(())
     }
@@ -76,35 +306,169 @@ impl fmt::Display for ValidationErrors {
 
 impl std::error::Error for ValidationErrors {}
 
+/// Why [`insert_path`](crate::merge::insert_path) or a companion could not
+/// follow a path. `at` is the path up to the offending segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathError {
+    /// The path has no segments.
+    Empty,
+    /// A segment is not `key`, `key[index]`, or `[index]`.
+    InvalidSegment(String),
+    /// A key segment met a non-object, or an index segment a non-array.
+    Mismatch {
+        at: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// An index past the end of an array. Inserting at the length appends.
+    OutOfBounds { at: String, len: usize },
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::Empty => write!(f, "path must not be empty"),
+            PathError::InvalidSegment(segment) => write!(f, "invalid path segment `{segment}`"),
+            PathError::Mismatch {
+                at,
+                expected,
+                found,
+            } => write!(f, "expected {expected} at `{at}`, found {found}"),
+            PathError::OutOfBounds { at, len } => {
+                write!(f, "index `{at}` is out of bounds (length {len})")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
 /// The top-level error type for config loading.
+///
+/// Parse variants exist only for the format features that are enabled, so
+/// the enum is non-exhaustive.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum CnfgError {
     Io(std::io::Error),
+    #[cfg(feature = "toml")]
     ParseToml(toml::de::Error),
     ParseJson(serde_json::Error),
+    #[cfg(feature = "yaml")]
     ParseYaml(serde_yaml::Error),
+    #[cfg(feature = "json5")]
+    ParseJson5(json5::Error),
+    #[cfg(feature = "ron")]
+    ParseRon(ron::error::SpannedError),
+    #[cfg(feature = "hcl")]
+    ParseHcl(hcl::Error),
+    ParseIni(String),
+    ParseProperties(String),
     Validation(ValidationErrors),
     Cli(String),
     Env(String),
+    Source(String),
+    /// Rendering a config for [`save_to`](crate::LoaderExt::save_to) failed.
+    Serialize(String),
     HelpPrinted,
+    VersionPrinted,
+    /// `--check` printed a passing summary.
+    CheckPassed,
+    /// `--check` printed a failing summary.
+    CheckFailed,
+    /// `--dump-env` printed the config as environment variables.
+    EnvPrinted,
+    /// `--init` wrote a starter config file to this path.
+    Initialized(std::path::PathBuf),
+    /// A [`SchemaBuilder`](crate::SchemaBuilder) was given clashing fields.
+    Schema(String),
+    /// An encrypted config file or value could not be decrypted.
+    Decrypt(String),
+    /// A config file's includes could not be followed (missing file, cycle,
+    /// or too deep).
+    Include(String),
+    /// Reading, parsing, decrypting, or writing the file at `path` failed.
+    InFile {
+        path: std::path::PathBuf,
+        source: Box<CnfgError>,
+    },
 }
 
 impl fmt::Display for CnfgError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             CnfgError::Io(e) => write!(f, "I/O error: {e}"),
+            #[cfg(feature = "toml")]
             CnfgError::ParseToml(e) => write!(f, "TOML parse error: {e}"),
             CnfgError::ParseJson(e) => write!(f, "JSON parse error: {e}"),
+            #[cfg(feature = "yaml")]
             CnfgError::ParseYaml(e) => write!(f, "YAML parse error: {e}"),
+            #[cfg(feature = "json5")]
+            CnfgError::ParseJson5(e) => write!(f, "JSON5 parse error: {e}"),
+            #[cfg(feature = "ron")]
+            CnfgError::ParseRon(e) => write!(f, "RON parse error: {e}"),
+            #[cfg(feature = "hcl")]
+            CnfgError::ParseHcl(e) => write!(f, "HCL parse error: {e}"),
+            CnfgError::ParseIni(msg) => write!(f, "INI parse error: {msg}"),
+            CnfgError::ParseProperties(msg) => write!(f, "properties parse error: {msg}"),
             CnfgError::Validation(e) => write!(f, "{e}"),
             CnfgError::Cli(msg) => write!(f, "CLI error: {msg}"),
             CnfgError::Env(msg) => write!(f, "Env error: {msg}"),
+            CnfgError::Source(msg) => write!(f, "Source error: {msg}"),
+            CnfgError::Serialize(msg) => write!(f, "serialize error: {msg}"),
             CnfgError::HelpPrinted => write!(f, "help requested"),
+            CnfgError::VersionPrinted => write!(f, "version requested"),
+            CnfgError::CheckPassed => write!(f, "config check passed"),
+            CnfgError::CheckFailed => write!(f, "config check failed"),
+            CnfgError::EnvPrinted => write!(f, "environment printed"),
+            CnfgError::Initialized(path) => write!(f, "wrote {}", path.display()),
+            CnfgError::Schema(msg) => write!(f, "invalid schema: {msg}"),
+            CnfgError::Decrypt(msg) => write!(f, "decryption error: {msg}"),
+            CnfgError::Include(msg) => write!(f, "include error: {msg}"),
+            CnfgError::InFile { path, source } => write!(f, "{}: {source}", path.display()),
         }
     }
 }
 
-impl std::error::Error for CnfgError {}
+impl std::error::Error for CnfgError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CnfgError::InFile { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl CnfgError {
+    /// Note that this error concerns the file at `path`. An error that
+    /// already names a file keeps it.
+    pub fn in_file(self, path: impl Into<std::path::PathBuf>) -> Self {
+        match self {
+            CnfgError::InFile { .. } => self,
+            source => CnfgError::InFile {
+                path: path.into(),
+                source: Box::new(source),
+            },
+        }
+    }
+
+    /// The file this error concerns, if known.
+    pub fn path(&self) -> Option<&std::path::Path> {
+        match self {
+            CnfgError::InFile { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The error without its file context, for matching on the kind of
+    /// failure.
+    pub fn root(&self) -> &CnfgError {
+        match self {
+            CnfgError::InFile { source, .. } => source.root(),
+            other => other,
+        }
+    }
+}
 
 impl From<std::io::Error> for CnfgError {
     fn from(e: std::io::Error) -> Self {
@@ -112,6 +476,7 @@ impl From<std::io::Error> for CnfgError {
     }
 }
 
+#[cfg(feature = "toml")]
 impl From<toml::de::Error> for CnfgError {
     fn from(e: toml::de::Error) -> Self {
         Self::ParseToml(e)
@@ -130,8 +495,30 @@ impl From<ValidationErrors> for CnfgError {
     }
 }
 
+#[cfg(feature = "yaml")]
 impl From<serde_yaml::Error> for CnfgError {
     fn from(e: serde_yaml::Error) -> Self {
         Self::ParseYaml(e)
     }
 }
+
+#[cfg(feature = "json5")]
+impl From<json5::Error> for CnfgError {
+    fn from(e: json5::Error) -> Self {
+        Self::ParseJson5(e)
+    }
+}
+
+#[cfg(feature = "ron")]
+impl From<ron::error::SpannedError> for CnfgError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        Self::ParseRon(e)
+    }
+}
+
+#[cfg(feature = "hcl")]
+impl From<hcl::Error> for CnfgError {
+    fn from(e: hcl::Error) -> Self {
+        Self::ParseHcl(e)
+    }
+}
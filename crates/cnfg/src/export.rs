@@ -0,0 +1,103 @@
+//! The effective config as environment variables.
+//!
+//! [`LoaderExt::to_env_vars`](crate::LoaderExt::to_env_vars) and the
+//! built-in `--dump-env` flag flatten a loaded config into `NAME=value`
+//! pairs, for processes that only read their environment. Each field uses
+//! its `#[cnfg(env)]` name, or else the env prefix followed by its path in
+//! upper case (`database.port` → `MYAPP_DATABASE_PORT`). Lists of plain
+//! values are joined with commas, as env input is parsed; other lists and
+//! maps are written as JSON.
+//!
+//! [`render`] writes the pairs in a form read by `.env` loaders, systemd's
+//! `EnvironmentFile=`, and docker compose's `env_file`. Secret fields are
+//! included: the output is meant for the process that needs them.
+
+use crate::merge::value_at;
+use crate::types::{FieldSpec, Kind};
+use serde_json::Value;
+
+/// `NAME=value` pairs for every field of `value` that is set, in field
+/// order. `prefix` names fields without an env var.
+pub(crate) fn env_vars(
+    fields: &[FieldSpec],
+    prefix: Option<&str>,
+    value: &Value,
+) -> Vec<(String, String)> {
+    let mut vars = Vec::new();
+    for spec in fields.iter().filter(|spec| spec.kind != Kind::Object) {
+        let Some(found) = value_at(value, spec.path).filter(|found| !found.is_null()) else {
+            continue;
+        };
+        let name = match spec.env {
+            Some(env) => env.to_string(),
+            None => path_env_name(prefix, spec.path),
+        };
+        vars.push((name, env_value(found)));
+    }
+    vars
+}
+
+/// `prefix` followed by `path` in upper case with `.` and `-` as `_`, as
+/// read for fields under `#[cnfg(auto_env)]`.
+pub(crate) fn path_env_name(prefix: Option<&str>, path: &str) -> String {
+    format!(
+        "{}{}",
+        prefix.unwrap_or_default(),
+        path.replace(['.', '-'], "_").to_uppercase()
+    )
+}
+
+fn env_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(items) if items.iter().all(is_plain) => {
+            items.iter().map(env_value).collect::<Vec<_>>().join(",")
+        }
+        other => other.to_string(),
+    }
+}
+
+/// A scalar whose text contains no comma, so it survives a comma-joined list.
+fn is_plain(value: &Value) -> bool {
+    match value {
+        Value::String(text) => !text.contains(','),
+        Value::Bool(_) | Value::Number(_) => true,
+        _ => false,
+    }
+}
+
+/// One `NAME=value` line per pair. Values other than plain words are
+/// double-quoted, with `\`, `"`, `$`, and newlines escaped.
+pub fn render(vars: &[(String, String)]) -> String {
+    let mut out = String::new();
+    for (name, value) in vars {
+        out.push_str(name);
+        out.push('=');
+        out.push_str(&quote(value));
+        out.push('\n');
+    }
+    out
+}
+
+fn quote(value: &str) -> String {
+    let plain = value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-.,:/@+%".contains(c));
+    if plain {
+        return value.to_string();
+    }
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' | '"' | '$' | '`' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,133 @@
+//! Stable hashes of the effective config, for logging and drift detection.
+//!
+//! The config is rendered as canonical JSON (keys sorted, no whitespace)
+//! with `#[cnfg(secret)]` fields removed, so rotating a credential does not
+//! change the fingerprint and the fingerprint reveals nothing about it.
+//!
+//! [`canonical_string`] renders the same canonical form readably, with
+//! secrets shown as [`REDACTED`], for snapshot tests and for diffing the
+//! config of two environments.
+
+use crate::types::{ConfigMeta, FieldSpec, ListSpec};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Stands in for the value of a `#[cnfg(secret)]` field in
+/// [`canonical_string`].
+pub const REDACTED: &str = "<redacted>";
+
+/// `sha256:<hex>` digest of `value` (the serialized config for `T`).
+pub fn fingerprint<T: ConfigMeta>(value: &Value) -> String {
+    let mut public = value.clone();
+    hide_secrets(&mut public, T::field_specs(), T::lists(), false);
+    let mut canonical = String::new();
+    write_canonical(&public, &mut canonical, None);
+
+    let digest = Sha256::digest(canonical.as_bytes());
+    let mut out = String::with_capacity(7 + digest.len() * 2);
+    out.push_str("sha256:");
+    for byte in digest.iter() {
+        out.push_str(&format!("{byte:02x}"));
+    }
+    out
+}
+
+/// Deterministic, indented JSON of `value` (the serialized config for `T`):
+/// object keys sorted, `#[cnfg(secret)]` values replaced by [`REDACTED`].
+/// Unset secrets stay `null`.
+pub fn canonical_string<T: ConfigMeta>(value: &Value) -> String {
+    let mut public = value.clone();
+    hide_secrets(&mut public, T::field_specs(), T::lists(), true);
+    let mut out = String::new();
+    write_canonical(&public, &mut out, Some(0));
+    out
+}
+
+/// Remove secret fields, or with `redact` replace their values.
+fn hide_secrets(value: &mut Value, fields: &[FieldSpec], lists: &[ListSpec], redact: bool) {
+    for spec in fields.iter().filter(|spec| spec.secret) {
+        let (parent, key) = spec.path.rsplit_once('.').unwrap_or(("", spec.path));
+        let target = if parent.is_empty() {
+            Some(&mut *value)
+        } else {
+            parent.split('.').try_fold(&mut *value, |current, segment| {
+                current.as_object_mut()?.get_mut(segment)
+            })
+        };
+        let Some(Value::Object(map)) = target else {
+            continue;
+        };
+        if !redact {
+            map.remove(key);
+        } else if let Some(slot) = map.get_mut(key).filter(|slot| !slot.is_null()) {
+            *slot = Value::String(REDACTED.to_string());
+        }
+    }
+    for list in lists {
+        let items = list
+            .path
+            .split('.')
+            .try_fold(&mut *value, |current, segment| {
+                current.as_object_mut()?.get_mut(segment)
+            });
+        if let Some(Value::Array(items)) = items {
+            for item in items {
+                hide_secrets(item, (list.field_specs)(), (list.lists)(), redact);
+            }
+        }
+    }
+}
+
+/// JSON with object keys in sorted order, independent of how `serde_json`
+/// maps are ordered: compact without `indent`, else indented two spaces per
+/// level starting at `indent`.
+fn write_canonical(value: &Value, out: &mut String, indent: Option<usize>) {
+    let inner = indent.map(|depth| depth + 1);
+    let (open, close) = match value {
+        Value::Object(map) if !map.is_empty() => ('{', '}'),
+        Value::Array(items) if !items.is_empty() => ('[', ']'),
+        scalar => return out.push_str(&scalar.to_string()),
+    };
+    out.push(open);
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            for (idx, key) in keys.into_iter().enumerate() {
+                separate(out, idx, inner);
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                if indent.is_some() {
+                    out.push(' ');
+                }
+                write_canonical(&map[key], out, inner);
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                separate(out, idx, inner);
+                write_canonical(item, out, inner);
+            }
+        }
+        _ => unreachable!("scalars returned above"),
+    }
+    if let Some(depth) = indent {
+        newline(out, depth);
+    }
+    out.push(close);
+}
+
+/// Start the `idx`th element of an object or array.
+fn separate(out: &mut String, idx: usize, indent: Option<usize>) {
+    if idx > 0 {
+        out.push(',');
+    }
+    if let Some(depth) = indent {
+        newline(out, depth);
+    }
+}
+
+fn newline(out: &mut String, depth: usize) {
+    out.push('\n');
+    out.push_str(&"  ".repeat(depth));
+}
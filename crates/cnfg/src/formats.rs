@@ -0,0 +1,181 @@
+//! Line-based legacy formats (`.ini`, `.properties`).
+//!
+//! Both parsers produce flat `(dotted.path, raw)` pairs; the loader coerces
+//! each raw string with the matching field's [`Kind`](crate::Kind) via
+//! [`insert_raw`](crate::sources::insert_raw), exactly as string-only
+//! sources do.
+
+/// A parsed `key = value` entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// Dotted path (`[section]` and dotted keys joined with `.`).
+    pub path: String,
+    /// Unparsed value.
+    pub raw: String,
+    /// 1-based line the entry started on.
+    pub line: usize,
+}
+
+/// Parse INI text.
+///
+/// `[section]` headers (dotted names nest further) prefix the keys beneath
+/// them; `key = value` and `key: value` are accepted. Lines starting with
+/// `;` or `#` are comments, and matching surrounding quotes are stripped.
+#[cfg(feature = "ini")]
+pub fn parse_ini(text: &str) -> Result<Vec<Entry>, String> {
+    let mut entries = Vec::new();
+    let mut section = String::new();
+
+    for (idx, raw_line) in text.lines().enumerate() {
+        let line_no = idx + 1;
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix('[') {
+            let name = rest
+                .strip_suffix(']')
+                .ok_or_else(|| format!("line {line_no}: unterminated section header"))?
+                .trim();
+            if name.is_empty() {
+                return Err(format!("line {line_no}: empty section name"));
+            }
+            section = name.to_string();
+            continue;
+        }
+
+        let Some(split) = line.find(['=', ':']) else {
+            return Err(format!("line {line_no}: expected `key = value`"));
+        };
+        let key = line[..split].trim();
+        if key.is_empty() {
+            return Err(format!("line {line_no}: missing key"));
+        }
+        let raw = unquote(line[split + 1..].trim());
+        let path = if section.is_empty() {
+            key.to_string()
+        } else {
+            format!("{section}.{key}")
+        };
+        entries.push(Entry {
+            path,
+            raw: raw.to_string(),
+            line: line_no,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Parse Java `.properties` text.
+///
+/// Keys are separated from values by `=`, `:` or whitespace; `#` and `!`
+/// start comments; a trailing `\` continues the value on the next line; and
+/// the standard escapes (`\n`, `\t`, `\\`, `\uXXXX`, ...) are decoded.
+#[cfg(feature = "properties")]
+pub fn parse_properties(text: &str) -> Result<Vec<Entry>, String> {
+    let mut entries = Vec::new();
+    let mut lines = text.lines().enumerate();
+
+    while let Some((idx, first)) = lines.next() {
+        let line_no = idx + 1;
+        let mut logical = first.trim_start().to_string();
+        if logical.is_empty() || logical.starts_with('#') || logical.starts_with('!') {
+            continue;
+        }
+        while ends_with_continuation(&logical) {
+            logical.pop();
+            match lines.next() {
+                Some((_, next)) => logical.push_str(next.trim_start()),
+                None => break,
+            }
+        }
+
+        let (key, value) = split_property(&logical);
+        let key = unescape(key).map_err(|msg| format!("line {line_no}: {msg}"))?;
+        if key.is_empty() {
+            return Err(format!("line {line_no}: missing key"));
+        }
+        let raw = unescape(value).map_err(|msg| format!("line {line_no}: {msg}"))?;
+        entries.push(Entry {
+            path: key,
+            raw,
+            line: line_no,
+        });
+    }
+
+    Ok(entries)
+}
+
+#[cfg(feature = "ini")]
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return &value[1..value.len() - 1];
+        }
+    }
+    value
+}
+
+/// An odd number of trailing backslashes escapes the line break.
+#[cfg(feature = "properties")]
+fn ends_with_continuation(line: &str) -> bool {
+    line.chars().rev().take_while(|c| *c == '\\').count() % 2 == 1
+}
+
+/// Split at the first unescaped `=`, `:` or whitespace.
+#[cfg(feature = "properties")]
+fn split_property(line: &str) -> (&str, &str) {
+    let mut escaped = false;
+    for (idx, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => escaped = true,
+            '=' | ':' => return (&line[..idx], line[idx + 1..].trim_start()),
+            c if c.is_whitespace() => {
+                let rest = line[idx..].trim_start();
+                let rest = rest
+                    .strip_prefix(['=', ':'])
+                    .map(str::trim_start)
+                    .unwrap_or(rest);
+                return (&line[..idx], rest);
+            }
+            _ => {}
+        }
+    }
+    (line, "")
+}
+
+#[cfg(feature = "properties")]
+fn unescape(text: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('f') => out.push('\u{c}'),
+            Some('u') => {
+                let hex: String = chars.by_ref().take(4).collect();
+                let decoded = u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == 4)
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("invalid unicode escape `\\u{hex}`"))?;
+                out.push(decoded);
+            }
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,98 @@
+//! Process-wide config handle.
+//!
+//! Deeply nested code can read the config without threading a reference
+//! through every call: load it once with
+//! [`LoaderExt::init_global`](crate::LoaderExt::init_global) (or
+//! [`Loader::init_global`](crate::Loader::init_global)), then call
+//! [`LoaderExt::global`](crate::LoaderExt::global) anywhere. Each config
+//! type has its own slot, and nothing is global until it is initialized.
+//!
+//! [`Watcher::publish_global`](crate::Watcher::publish_global) keeps the slot
+//! up to date with hot reloads. Tests can swap in a config for the current
+//! thread with [`scoped`] without touching the process-wide value.
+
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+type Slot = Arc<dyn Any + Send + Sync>;
+
+/// The current config of each type that has been published.
+static SLOTS: OnceLock<RwLock<HashMap<TypeId, Slot>>> = OnceLock::new();
+
+thread_local! {
+    /// Stacks of [`scoped`] overrides, consulted before `SLOTS`.
+    static OVERRIDES: RefCell<HashMap<TypeId, Vec<Slot>>> = RefCell::new(HashMap::new());
+}
+
+fn slots() -> &'static RwLock<HashMap<TypeId, Slot>> {
+    SLOTS.get_or_init(Default::default)
+}
+
+/// Publish `cfg` as the global `T`, replacing any previous value. Handles
+/// obtained earlier keep the config they were given.
+pub fn set<T: Send + Sync + 'static>(cfg: Arc<T>) {
+    slots()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(TypeId::of::<T>(), Arc::new(cfg));
+}
+
+/// The global `T`: the innermost [`scoped`] override on this thread, else
+/// the published value, else `None`.
+pub fn get<T: Send + Sync + 'static>() -> Option<Arc<T>> {
+    let id = TypeId::of::<T>();
+    let scoped = OVERRIDES.with(|overrides| {
+        overrides
+            .borrow()
+            .get(&id)
+            .and_then(|stack| stack.last().cloned())
+    });
+    let slot = scoped.or_else(|| {
+        slots()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&id)
+            .cloned()
+    })?;
+    slot.downcast_ref::<Arc<T>>().cloned()
+}
+
+/// Remove the global `T`, returning it.
+pub fn clear<T: Send + Sync + 'static>() -> Option<Arc<T>> {
+    let slot = slots()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&TypeId::of::<T>())?;
+    slot.downcast_ref::<Arc<T>>().cloned()
+}
+
+/// Run `f` with `cfg` as the global `T` on the current thread only, e.g. to
+/// test code that reads the global config. Overrides nest; other threads
+/// keep seeing the published value.
+pub fn scoped<T: Send + Sync + 'static, R>(cfg: Arc<T>, f: impl FnOnce() -> R) -> R {
+    struct Pop(TypeId);
+
+    impl Drop for Pop {
+        fn drop(&mut self) {
+            OVERRIDES.with(|overrides| {
+                if let Some(stack) = overrides.borrow_mut().get_mut(&self.0) {
+                    stack.pop();
+                }
+            });
+        }
+    }
+
+    let id = TypeId::of::<T>();
+    OVERRIDES.with(|overrides| {
+        overrides
+            .borrow_mut()
+            .entry(id)
+            .or_default()
+            .push(Arc::new(cfg));
+    });
+    // Popped on unwind too, so a failing test leaves no override behind.
+    let _pop = Pop(id);
+    f()
+}
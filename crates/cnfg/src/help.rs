@@ -0,0 +1,460 @@
+//! `--help` rendering.
+//!
+//! The text starts from the struct's doc comment (or
+//! `#[cnfg(about = "...")]`), lists commands and options, and ends with
+//! `#[cnfg(after_help = "...")]`. [`HelpOptions`] adds examples and controls
+//! the layout; pass it to [`LoaderExt::help_with`](crate::LoaderExt::help_with)
+//! or [`Loader::help_options`](crate::Loader::help_options).
+//!
+//! `--help-json` prints the same information as JSON for tooling; see
+//! [`LoaderExt::help_json`](crate::LoaderExt::help_json).
+//!
+//! Descriptions are wrapped to the terminal width ([`terminal_width`]) and
+//! continue under the description column.
+
+use crate::completions;
+use crate::loader::Meta;
+use crate::merge::value_at;
+use crate::types::{CliSpec, CommandSpec, FieldSpec, Kind};
+use crate::util::{format_doc, format_flag};
+use serde_json::{Value, json};
+
+/// Width of the flag column when none is set.
+pub const DEFAULT_COLUMN: usize = 24;
+
+/// Wrap width when stdout is not a terminal and `COLUMNS` is unset.
+pub const DEFAULT_WIDTH: usize = 100;
+
+/// Descriptions are never squeezed narrower than this, even on tiny
+/// terminals.
+const MIN_DESCRIPTION_WIDTH: usize = 20;
+
+/// Width of the terminal: `COLUMNS` when set, else the size reported for
+/// stdout, else `None` (not a terminal).
+pub fn terminal_width() -> Option<usize> {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|cols| cols.trim().parse().ok())
+        .filter(|&cols| cols > 0)
+        .or_else(query_width)
+}
+
+#[cfg(unix)]
+fn query_width() -> Option<usize> {
+    use std::io::IsTerminal;
+    use std::os::fd::AsRawFd;
+
+    let stdout = std::io::stdout();
+    if !stdout.is_terminal() {
+        return None;
+    }
+    // SAFETY: `winsize` is plain data and TIOCGWINSZ only writes into it.
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let status = unsafe { libc::ioctl(stdout.as_raw_fd(), libc::TIOCGWINSZ, &mut size) };
+    (status == 0 && size.ws_col > 0).then_some(usize::from(size.ws_col))
+}
+
+#[cfg(not(unix))]
+fn query_width() -> Option<usize> {
+    None
+}
+
+/// Extra text and layout for rendered help.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpOptions {
+    /// Program name in the usage line; `None` uses `argv[0]`.
+    pub bin_name: Option<String>,
+    /// Printed above the usage line instead of the struct's description.
+    pub about: Option<String>,
+    /// Printed after everything else, e.g. links or a copyright line.
+    pub after_help: Option<String>,
+    /// `(command line, description)` pairs listed under `Examples:`.
+    pub examples: Vec<(String, String)>,
+    /// Width of the flag column (default [`DEFAULT_COLUMN`]).
+    pub column: usize,
+    /// Wrap descriptions to this many characters per line; `None` uses
+    /// [`terminal_width`], falling back to [`DEFAULT_WIDTH`].
+    pub width: Option<usize>,
+}
+
+impl Default for HelpOptions {
+    fn default() -> Self {
+        Self {
+            bin_name: None,
+            about: None,
+            after_help: None,
+            examples: Vec::new(),
+            column: DEFAULT_COLUMN,
+            width: None,
+        }
+    }
+}
+
+impl HelpOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Program name shown in the usage line instead of `argv[0]`.
+    pub fn bin_name(mut self, name: impl Into<String>) -> Self {
+        self.bin_name = Some(name.into());
+        self
+    }
+
+    /// Replace the description printed above the usage line.
+    pub fn about(mut self, text: impl Into<String>) -> Self {
+        self.about = Some(text.into());
+        self
+    }
+
+    /// Text printed at the end of the help.
+    pub fn after_help(mut self, text: impl Into<String>) -> Self {
+        self.after_help = Some(text.into());
+        self
+    }
+
+    /// Add a usage example, e.g. `("myapp --port 80", "Serve on port 80")`.
+    pub fn example(mut self, command: impl Into<String>, description: impl Into<String>) -> Self {
+        self.examples.push((command.into(), description.into()));
+        self
+    }
+
+    /// Width of the flag column.
+    pub fn column(mut self, width: usize) -> Self {
+        self.column = width;
+        self
+    }
+
+    /// Wrap descriptions so lines stay within `width` characters instead of
+    /// the terminal width. `usize::MAX` turns wrapping off.
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    fn bin_name_or_current(&self) -> String {
+        self.bin_name
+            .clone()
+            .unwrap_or_else(completions::current_bin_name)
+    }
+
+    /// `about`, else `#[cnfg(about)]`, else the struct's doc comment.
+    fn about_for(&self, meta: &Meta) -> Option<String> {
+        self.about
+            .clone()
+            .or_else(|| meta.about.map(str::to_string))
+            .or_else(|| format_doc(meta.doc))
+    }
+
+    fn after_help_for(&self, meta: &Meta) -> Option<String> {
+        self.after_help
+            .clone()
+            .or_else(|| meta.after_help.map(str::to_string))
+    }
+
+    /// A `  label  description` row, wrapping the description under its
+    /// column.
+    fn row(&self, lines: &mut Vec<String>, label: &str, description: &str) {
+        if description.is_empty() {
+            lines.push(format!("  {label}"));
+            return;
+        }
+        let indent = self.column + 3;
+        let width = self.width.or_else(terminal_width).unwrap_or(DEFAULT_WIDTH);
+        let wrapped = wrap(
+            description,
+            width.saturating_sub(indent).max(MIN_DESCRIPTION_WIDTH),
+        );
+        let mut wrapped = wrapped.into_iter();
+        let first = wrapped.next().unwrap_or_default();
+        if label.chars().count() > self.column {
+            lines.push(format!("  {label}"));
+            lines.push(format!("{:indent$}{first}", ""));
+        } else {
+            lines.push(format!("  {label:<0$} {first}", self.column));
+        }
+        for line in wrapped {
+            lines.push(format!("{:indent$}{line}", ""));
+        }
+    }
+}
+
+/// Split `text` into lines of at most `width` characters at spaces; longer
+/// words keep a line of their own.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Help for `<binary> --help`; `all` includes hidden flags.
+pub(crate) fn render_help(meta: &Meta, options: &HelpOptions, all: bool) -> String {
+    let mut lines = Vec::new();
+
+    if let Some(about) = options.about_for(meta) {
+        lines.push(about);
+        lines.push(String::new());
+    }
+
+    let bin = options.bin_name_or_current();
+    let usage = usage(&bin, meta.cli);
+    lines.push("Usage:".to_string());
+    lines.push(format!("  {usage}"));
+    if !meta.commands.is_empty() {
+        lines.push(format!("  {usage} <COMMAND> [COMMAND OPTIONS]"));
+        lines.push(String::new());
+        lines.push("Commands:".to_string());
+        for cmd in meta.commands {
+            let doc = format_doc(cmd.doc).unwrap_or_default();
+            options.row(&mut lines, cmd.name, &doc);
+        }
+    }
+
+    let mut builtins = Vec::new();
+    if meta.builtin_flag("set") {
+        builtins.push(("--set <path=value>...", "Override any field by dotted path"));
+    }
+    if meta.builtin_flag("config") {
+        builtins.push((
+            "-c, --config <path>",
+            "Read this config file (`-` for stdin)",
+        ));
+    }
+    if meta.builtin_flag("config-format") {
+        builtins.push((
+            "--config-format <format>",
+            "Parse config files without a known extension as this format",
+        ));
+    }
+    if meta.builtin_flag("check") {
+        builtins.push(("--check", "Validate the configuration and exit"));
+    }
+    if meta.builtin_flag("dump-env") {
+        builtins.push(("--dump-env", "Print the config as env vars and exit"));
+    }
+    if meta.builtin_flag("init") {
+        builtins.push(("--init [path]", "Write a starter config file and exit"));
+        if meta.builtin_flag("force") {
+            builtins.push(("--force", "Let --init replace an existing file"));
+        }
+    }
+    if meta.version.is_some() {
+        builtins.push(("-V, --version", "Print version"));
+    }
+    if meta.builtin_flag("help-json") {
+        builtins.push(("--help-json", "Print the options as JSON"));
+    }
+    if meta.builtin_flag("help-all") && meta.cli.iter().any(|spec| spec.hidden) {
+        builtins.push(("--help-all", "Print help including hidden options"));
+    }
+    push_options(&mut lines, meta.cli, &builtins, options, all);
+
+    if !options.examples.is_empty() {
+        lines.push(String::new());
+        lines.push("Examples:".to_string());
+        for (command, description) in &options.examples {
+            options.row(&mut lines, command, description);
+        }
+    }
+    if let Some(after) = options.after_help_for(meta) {
+        lines.push(String::new());
+        lines.push(after);
+    }
+    lines.join("\n").trim_end().to_string()
+}
+
+/// Help for `<binary> <command> --help`; `all` includes hidden flags.
+pub(crate) fn render_command_help(
+    meta: &Meta,
+    cmd: &CommandSpec,
+    options: &HelpOptions,
+    all: bool,
+) -> String {
+    let mut lines = Vec::new();
+
+    if let Some(doc) = format_doc(cmd.doc) {
+        lines.push(doc);
+        lines.push(String::new());
+    }
+
+    let bin = options.bin_name_or_current();
+    let specs = (cmd.cli_specs)();
+    lines.push("Usage:".to_string());
+    lines.push(format!(
+        "  {}",
+        usage(&format!("{bin} {}", cmd.name), specs)
+    ));
+
+    push_options(&mut lines, specs, &[], options, all);
+    if !meta.cli.is_empty() {
+        lines.push(String::new());
+        lines.push("Global options:".to_string());
+        lines.push(format!("  see `{bin} --help`"));
+    }
+    lines.join("\n").trim_end().to_string()
+}
+
+/// `<program> --required <VALUE> [OPTIONS]`: visible required flags are
+/// spelled out so users see what they must pass.
+fn usage(program: &str, specs: &[CliSpec]) -> String {
+    let mut usage = program.to_string();
+    for spec in specs.iter().filter(|spec| spec.required && !spec.hidden) {
+        let value_name = spec.takes_value.then(|| spec.placeholder());
+        usage.push(' ');
+        usage.push_str(&format_flag(spec.flag, value_name));
+    }
+    usage.push_str(" [OPTIONS]");
+    usage
+}
+
+/// Append an `Options:` section: `specs` (hidden ones only with `all`), then
+/// built-in `(flag, description)` rows.
+fn push_options(
+    lines: &mut Vec<String>,
+    specs: &[CliSpec],
+    builtins: &[(&str, &str)],
+    options: &HelpOptions,
+    all: bool,
+) {
+    let specs: Vec<&CliSpec> = specs.iter().filter(|spec| all || !spec.hidden).collect();
+    if specs.is_empty() && builtins.is_empty() {
+        return;
+    }
+    lines.push(String::new());
+    lines.push("Options:".to_string());
+    for spec in specs {
+        let value_name = spec.takes_value.then(|| spec.placeholder());
+        let mut flag = format_flag(spec.flag, value_name);
+        if spec.multiple {
+            flag.push_str("...");
+        }
+        let mut detail = format_doc(spec.doc).unwrap_or_default();
+        if !spec.values.is_empty() {
+            if !detail.is_empty() {
+                detail.push(' ');
+            }
+            detail.push_str(&format!("[possible values: {}]", spec.values.join(", ")));
+        }
+        if let Some(def) = spec.default {
+            if !detail.is_empty() {
+                detail.push(' ');
+            }
+            detail.push_str(&format!("[default: {def}]"));
+        }
+        if spec.required {
+            if !detail.is_empty() {
+                detail.push(' ');
+            }
+            detail.push_str("(required)");
+        }
+        options.row(lines, &flag, detail.trim());
+    }
+    for (flag, description) in builtins {
+        options.row(lines, flag, description);
+    }
+}
+
+/// The schema behind the help as JSON, printed for `--help-json`.
+///
+/// Holds the program's `name`, `version`, `about`, and `env_prefix`, then
+/// `fields` (every leaf with its env vars, kind, default, and docs), `flags`
+/// (hidden ones included, marked `"hidden": true`), and `commands` with
+/// their own flags. Defaults are typed JSON values.
+pub(crate) fn render_json(meta: &Meta) -> String {
+    let fields: Vec<Value> = meta
+        .fields
+        .iter()
+        .filter(|spec| spec.kind != Kind::Object)
+        .map(|spec| field_json(spec, value_at(&meta.defaults, spec.path)))
+        .collect();
+    let commands: Vec<Value> = meta
+        .commands
+        .iter()
+        .map(|cmd| {
+            json!({
+                "name": cmd.name,
+                "path": cmd.path,
+                "doc": format_doc(cmd.doc),
+                "flags": (cmd.cli_specs)().iter().map(flag_json).collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    let value = json!({
+        "name": meta.app_name,
+        "version": meta.version,
+        "about": meta.about.map(str::to_string).or_else(|| format_doc(meta.doc)),
+        "env_prefix": meta.env_prefix,
+        "fields": fields,
+        "flags": meta.cli.iter().map(flag_json).collect::<Vec<_>>(),
+        "commands": commands,
+    });
+    serde_json::to_string_pretty(&value).expect("help JSON serializes")
+}
+
+fn field_json(spec: &FieldSpec, default: Option<&Value>) -> Value {
+    json!({
+        "path": spec.path,
+        "kind": kind_id(spec.kind),
+        "format": spec.kind.format(),
+        "list": spec.list,
+        "optional": spec.optional,
+        "required": spec.required,
+        "required_if": spec.required_if,
+        "required_unless": spec.required_unless,
+        "default": default.filter(|value| !value.is_null()),
+        "env": spec.env,
+        "env_aliases": spec.env_aliases,
+        "secret": spec.secret,
+        "deprecated": spec.deprecated,
+        "doc": format_doc(spec.doc),
+    })
+}
+
+fn flag_json(spec: &CliSpec) -> Value {
+    json!({
+        "flag": format!("--{}", spec.flag),
+        "path": spec.path,
+        "kind": kind_id(spec.kind),
+        "format": spec.kind.format(),
+        "takes_value": spec.takes_value,
+        "value_name": spec.takes_value.then(|| spec.placeholder()),
+        "multiple": spec.multiple,
+        "values": spec.values,
+        "default": spec.default,
+        "required": spec.required,
+        "hidden": spec.hidden,
+        "doc": format_doc(spec.doc),
+    })
+}
+
+/// Stable identifier for a [`Kind`] in JSON output.
+fn kind_id(kind: Kind) -> &'static str {
+    match kind {
+        Kind::Bool => "bool",
+        Kind::Int => "int",
+        Kind::Float => "float",
+        Kind::String => "string",
+        Kind::IpAddr => "ip_addr",
+        Kind::SocketAddr => "socket_addr",
+        Kind::Path => "path",
+        Kind::Bytes => "bytes",
+        Kind::DateTime => "date_time",
+        Kind::Date => "date",
+        Kind::Object => "object",
+        Kind::Any => "any",
+        Kind::Custom => "custom",
+    }
+}
@@ -0,0 +1,183 @@
+//! Include directives inside config files.
+//!
+//! A top-level `include` key (renamed with
+//! [`Loader::include_key`](crate::Loader::include_key)) names other files to
+//! merge beneath the including one:
+//!
+//! ```toml
+//! include = ["common.toml", "region/*.yaml"]
+//! port = 8080
+//! ```
+//!
+//! - Paths are relative to the including file; standard input includes
+//!   relative to the working directory.
+//! - `*` and `?` match within one path component. Matches are merged in
+//!   name order and a pattern matching nothing is skipped, while a missing
+//!   literal path is an error.
+//! - Included files are merged in the order listed, then the including
+//!   file on top, so its own keys win.
+//! - Includes nest up to [`Loader::include_depth`](crate::Loader::include_depth)
+//!   levels (default [`DEFAULT_DEPTH`]); a file including itself, directly or
+//!   not, is an error.
+
+use serde_json::Value;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Key read when the loader names none.
+pub const DEFAULT_KEY: &str = "include";
+
+/// How deeply includes nest when the loader sets no limit.
+pub const DEFAULT_DEPTH: usize = 8;
+
+/// How the loader follows includes.
+#[derive(Debug, Clone)]
+pub(crate) struct Includes {
+    pub(crate) key: String,
+    pub(crate) max_depth: usize,
+}
+
+impl Default for Includes {
+    fn default() -> Self {
+        Self {
+            key: DEFAULT_KEY.to_string(),
+            max_depth: DEFAULT_DEPTH,
+        }
+    }
+}
+
+impl Includes {
+    /// Remove the include key from `doc`, returning its patterns.
+    pub(crate) fn take(&self, doc: &mut Value) -> Result<Vec<String>, String> {
+        let Some(value) = doc.as_object_mut().and_then(|map| map.remove(&self.key)) else {
+            return Ok(Vec::new());
+        };
+        let bad = || format!("`{}` must be a path or a list of paths", self.key);
+        match value {
+            Value::String(pattern) => Ok(vec![pattern]),
+            Value::Array(items) => items
+                .into_iter()
+                .map(|item| match item {
+                    Value::String(pattern) => Ok(pattern),
+                    _ => Err(bad()),
+                })
+                .collect(),
+            Value::Null => Ok(Vec::new()),
+            _ => Err(bad()),
+        }
+    }
+}
+
+/// Files named by `pattern`, resolved against `base`.
+pub(crate) fn expand(base: &Path, pattern: &str) -> Result<Vec<PathBuf>, String> {
+    let full = normalize(&base.join(pattern));
+    if !has_wildcard(pattern) {
+        return if full.is_file() {
+            Ok(vec![full])
+        } else {
+            Err(format!("included file {} not found", full.display()))
+        };
+    }
+    let mut matches = vec![PathBuf::new()];
+    for component in full.components() {
+        let part = component.as_os_str();
+        let text = part.to_string_lossy();
+        if !matches!(component, Component::Normal(_)) || !has_wildcard(&text) {
+            for path in &mut matches {
+                path.push(part);
+            }
+            continue;
+        }
+        let mut next = Vec::new();
+        for dir in &matches {
+            let Ok(entries) = fs::read_dir(if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            }) else {
+                continue;
+            };
+            let mut names: Vec<_> = entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.file_name())
+                .filter(|name| wildcard_match(&text, &name.to_string_lossy()))
+                .collect();
+            names.sort();
+            next.extend(names.into_iter().map(|name| dir.join(name)));
+        }
+        matches = next;
+    }
+    matches.retain(|path| path.is_file());
+    Ok(matches)
+}
+
+/// Files matching `pattern`, relative to the working directory, in name
+/// order. Nothing matching, or a literal path that is not a file, gives
+/// none.
+pub(crate) fn glob(pattern: &str) -> Vec<PathBuf> {
+    if has_wildcard(pattern) {
+        expand(Path::new(""), pattern).unwrap_or_default()
+    } else {
+        let path = PathBuf::from(pattern);
+        if path.is_file() {
+            vec![path]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Drop `.` components and fold `dir/..` pairs, so included files are
+/// named without detours.
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(out.components().next_back(), Some(Component::Normal(_))) =>
+            {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+pub(crate) fn has_wildcard(text: &str) -> bool {
+    text.contains(['*', '?'])
+}
+
+/// Match `name` against `pattern`, where `*` is any run of characters and
+/// `?` any one character. Hidden files only match a pattern starting with `.`.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    if name.starts_with('.') && !pattern.starts_with('.') {
+        return false;
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    n = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
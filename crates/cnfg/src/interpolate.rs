@@ -0,0 +1,250 @@
+//! Interpolation of `${...}` placeholders in config values.
+//!
+//! Two passes run during loading:
+//!
+//! 1. [`substitute_env`] replaces `${ENV_VAR}` and `${ENV_VAR:-default}` in the
+//!    string values of each config file as it is read, then coerces the
+//!    result to the field's kind (`port = "${PORT:-8080}"` becomes an integer).
+//!    Names that are not `ENV_STYLE` and have no `:-` default are left for the
+//!    second pass.
+//! 2. [`resolve_references`] replaces `${other.key}` references.
+//!
+//! References are resolved once every layer has been merged, so an env or CLI
+//! override of `server.port` is reflected in `"http://${server.host}:${server.port}"`.
+//! Only values written in config files and sources are expanded; env vars,
+//! flags, and defaults are taken as written, `$` included.
+//!
+//! - A string that is exactly one reference takes the referenced value with its
+//!   type (`port = "${server.port}"` stays an integer).
+//! - References inside longer strings are rendered as text.
+//! - `$${` produces a literal `${`; in path fields it is left for path
+//!   expansion, which does the same.
+//! - Unresolvable names in `ENV_STYLE` (upper case) are left untouched for
+//!   path expansion; other unresolvable names are errors.
+
+use crate::error::{Issue, IssueKind, ValidationErrors};
+use crate::merge::value_at;
+use crate::types::{FieldSpec, Kind};
+use serde_json::Value;
+
+/// Substitute environment variables in the string values of one file.
+///
+/// `fields` is used to coerce substituted values to their declared kind.
+pub fn substitute_env(doc: &mut Value, fields: &[FieldSpec]) -> Result<(), ValidationErrors> {
+    collect_issues(doc, |path, value| {
+        let Value::String(text) = value else {
+            return Ok(());
+        };
+        let substituted = substitute_env_str(text)?;
+        if substituted == *text {
+            return Ok(());
+        }
+        let coerced = match fields.iter().find(|spec| spec.path == path) {
+            Some(spec) if !matches!(spec.kind, Kind::String | Kind::Path | Kind::Object) => {
+                crate::loader::parse_for_spec(&substituted, spec)?
+            }
+            _ => Value::String(substituted),
+        };
+        *value = coerced;
+        Ok(())
+    })
+}
+
+/// Resolve every `${path}` reference in the string values of `doc`.
+pub fn resolve_references(doc: &mut Value) -> Result<(), ValidationErrors> {
+    resolve_references_in(doc, |_| true, |_| false)
+}
+
+/// Like [`resolve_references`], expanding only the strings at paths for
+/// which `expand_at` holds; the others can still be referenced. Strings at
+/// paths for which `keep_escapes` holds keep `$${` for path expansion.
+pub(crate) fn resolve_references_in(
+    doc: &mut Value,
+    expand_at: impl Fn(&str) -> bool,
+    keep_escapes: impl Fn(&str) -> bool,
+) -> Result<(), ValidationErrors> {
+    let snapshot = doc.clone();
+    collect_issues(doc, |path, value| {
+        if !expand_at(path) {
+            return Ok(());
+        }
+        if let Value::String(text) = value {
+            let mut stack = vec![path.to_string()];
+            *value = expand(text, &snapshot, &mut stack, !keep_escapes(path))?;
+        }
+        Ok(())
+    })
+}
+
+/// Visit every string leaf with its dotted path, collecting failures as issues.
+fn collect_issues(
+    doc: &mut Value,
+    mut visit: impl FnMut(&str, &mut Value) -> Result<(), String>,
+) -> Result<(), ValidationErrors> {
+    fn walk(
+        value: &mut Value,
+        path: &mut String,
+        visit: &mut dyn FnMut(&str, &mut Value) -> Result<(), String>,
+        errs: &mut ValidationErrors,
+    ) {
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map.iter_mut() {
+                    let len = path.len();
+                    if !path.is_empty() {
+                        path.push('.');
+                    }
+                    path.push_str(key);
+                    walk(child, path, visit, errs);
+                    path.truncate(len);
+                }
+            }
+            Value::Array(items) => {
+                for (idx, item) in items.iter_mut().enumerate() {
+                    let len = path.len();
+                    path.push_str(&format!("[{idx}]"));
+                    walk(item, path, visit, errs);
+                    path.truncate(len);
+                }
+            }
+            Value::String(text) if text.contains('$') => {
+                if let Err(message) = visit(path, value) {
+                    errs.push(Issue::new(path.clone(), IssueKind::Interpolation, message));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut errs = ValidationErrors::new();
+    walk(doc, &mut String::new(), &mut visit, &mut errs);
+    if errs.is_empty() { Ok(()) } else { Err(errs) }
+}
+
+fn substitute_env_str(text: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find('$') {
+        out.push_str(&rest[..idx]);
+        let tail = &rest[idx..];
+        if let Some(after) = tail.strip_prefix("$${") {
+            // Escapes are undone by `resolve_references`.
+            out.push_str("$${");
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix("${") {
+            let end = after
+                .find('}')
+                .ok_or_else(|| format!("unterminated `${{` in `{text}`"))?;
+            let inner = &after[..end];
+            let (name, default) = match inner.split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (inner, None),
+            };
+            if default.is_none() && !is_env_style(name) {
+                out.push_str(&tail[..end + 3]);
+            } else {
+                match (std::env::var(name), default) {
+                    (Ok(val), Some(default)) if val.is_empty() => out.push_str(default),
+                    (Ok(val), _) => out.push_str(&val),
+                    (Err(_), Some(default)) => out.push_str(default),
+                    (Err(_), None) => {
+                        return Err(format!("environment variable `{name}` is not set"));
+                    }
+                }
+            }
+            rest = &after[end + 1..];
+        } else {
+            out.push('$');
+            rest = &tail[1..];
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Expand the references in `text`; `stack` holds the paths being resolved.
+/// `$${` becomes `${` only when `unescape` is set.
+fn expand(
+    text: &str,
+    doc: &Value,
+    stack: &mut Vec<String>,
+    unescape: bool,
+) -> Result<Value, String> {
+    if let Some(name) = whole_reference(text) {
+        return Ok(
+            lookup(name, doc, stack, unescape)?.unwrap_or_else(|| Value::String(text.to_string()))
+        );
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(idx) = rest.find('$') {
+        out.push_str(&rest[..idx]);
+        let tail = &rest[idx..];
+        if let Some(after) = tail.strip_prefix("$${") {
+            out.push_str(if unescape { "${" } else { "$${" });
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix("${") {
+            let end = after
+                .find('}')
+                .ok_or_else(|| format!("unterminated `${{` in `{text}`"))?;
+            let name = &after[..end];
+            match lookup(name, doc, stack, unescape)? {
+                Some(Value::String(s)) => out.push_str(&s),
+                Some(Value::Number(n)) => out.push_str(&n.to_string()),
+                Some(Value::Bool(b)) => out.push_str(&b.to_string()),
+                Some(_) => return Err(format!("cannot interpolate `{name}`: not a scalar")),
+                None => out.push_str(&tail[..end + 3]),
+            }
+            rest = &after[end + 1..];
+        } else {
+            out.push('$');
+            rest = &tail[1..];
+        }
+    }
+    out.push_str(rest);
+    Ok(Value::String(out))
+}
+
+fn whole_reference(text: &str) -> Option<&str> {
+    let name = text.strip_prefix("${")?.strip_suffix('}')?;
+    (!name.contains('}') && !name.contains("${")).then_some(name)
+}
+
+/// Resolve `name`; `Ok(None)` means "leave the reference as written".
+fn lookup(
+    name: &str,
+    doc: &Value,
+    stack: &mut Vec<String>,
+    unescape: bool,
+) -> Result<Option<Value>, String> {
+    let Some(target) = value_at(doc, name) else {
+        return if is_env_style(name) {
+            Ok(None)
+        } else {
+            Err(format!("unresolved reference `${{{name}}}`"))
+        };
+    };
+    match target {
+        Value::String(s) if s.contains('$') => {
+            if let Some(pos) = stack.iter().position(|p| p == name) {
+                let mut chain = stack[pos..].to_vec();
+                chain.push(name.to_string());
+                return Err(format!("reference cycle: {}", chain.join(" -> ")));
+            }
+            stack.push(name.to_string());
+            let resolved = expand(s, doc, stack, unescape);
+            stack.pop();
+            resolved.map(Some)
+        }
+        Value::Null => Err(format!("reference `${{{name}}}` points at a null value")),
+        other => Ok(Some(other.clone())),
+    }
+}
+
+fn is_env_style(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
@@ -1,12 +1,119 @@
 //! cnfg – declarative configuration loading and validation.
 
+#[cfg(feature = "age")]
+pub mod age;
+pub mod audit;
+pub mod check;
+#[cfg(feature = "clap")]
+pub mod clap;
+pub mod completions;
+pub mod datetime;
+pub mod dirs;
+pub mod docgen;
+pub mod document;
+pub mod dotenv;
+pub mod dynamic;
 pub mod error;
+pub mod export;
+pub mod fingerprint;
+#[cfg(any(feature = "ini", feature = "properties"))]
+pub mod formats;
+pub mod global;
+pub mod help;
+pub mod include;
+pub mod interpolate;
 pub mod loader;
 pub mod merge;
+pub mod messages;
+pub mod pretty;
+pub mod prompt;
+pub mod provenance;
+pub mod report;
+pub mod run;
+pub mod save;
+pub mod schema;
+#[cfg(feature = "sops")]
+pub mod sops;
+pub mod sources;
+pub mod spans;
+pub mod test;
+mod trace;
 pub mod types;
 pub mod util;
+pub mod validators;
+pub mod watch;
 
-pub use cnfg_derive::Cnfg;
+pub use audit::{AuditReport, Audited};
+pub use check::CheckSummary;
+/// Derive [`ConfigMeta`], [`Validate`], and [`LoaderExt`] for a struct.
+///
+/// Literal defaults are checked at compile time: they must fit the field's
+/// type and satisfy its `range` and `port` validators.
+///
+/// ```compile_fail
+/// #[derive(serde::Serialize, serde::Deserialize, cnfg::Cnfg)]
+/// struct Server {
+///     #[cnfg(default = 80, validate(range(min = 1024)))]
+///     port: u16,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(serde::Serialize, serde::Deserialize, cnfg::Cnfg)]
+/// struct Server {
+///     #[cnfg(default = "many")]
+///     workers: u32,
+/// }
+/// ```
+///
+/// Two fields may not bind the same env var or produce the same flag,
+/// including flags prefixed by a nested section:
+///
+/// ```compile_fail
+/// #[derive(serde::Serialize, serde::Deserialize, cnfg::Cnfg)]
+/// struct Database {
+///     #[cnfg(cli)]
+///     url: String,
+/// }
+///
+/// #[derive(serde::Serialize, serde::Deserialize, cnfg::Cnfg)]
+/// struct App {
+///     #[cnfg(cli)]
+///     database_url: String, // also `--database-url`
+///     #[cnfg(nested)]
+///     database: Database,
+/// }
+/// ```
+pub use cnfg_derive::{Cnfg, CnfgValidate};
+pub use completions::Shell;
+pub use document::Document;
+pub use dotenv::Dotenv;
+pub use dynamic::{DynamicSchema, FieldDef, SchemaBuilder};
 pub use error::{CnfgError, ValidationErrors};
-pub use loader::LoaderExt;
-pub use types::{CliSpec, ConfigMeta, FieldSpec, Kind, Validate};
+pub use help::HelpOptions;
+pub use loader::{Loader, LoaderExt};
+pub use merge::MergeStrategy;
+pub use prompt::{Prompter, TerminalPrompter};
+pub use provenance::{Origin, Provenance};
+pub use report::{Report, Warning, WarningKind, Warnings};
+pub use run::run;
+pub use save::{Format, SaveOptions};
+pub use schema::Schema;
+#[cfg(feature = "async")]
+pub use sources::AsyncSource;
+pub use sources::Source;
+pub use sources::retry::RetryPolicy;
+pub use spans::Location;
+pub use types::{
+    CliSpec, CommandSpec, ConfigMeta, FieldSpec, Kind, ListSpec, ParseFn, Precedence, Validate,
+};
+pub use watch::{OnRefreshError, RefreshEvent, RefreshPolicy, RefreshStats, Watcher};
+
+/// Re-exports used by `#[derive(Cnfg)]` expansions, so `cnfg` is the only
+/// dependency a deriving crate needs. Not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use regex;
+    pub use serde_json;
+    pub use url;
+}
@@ -1,12 +1,45 @@
+#[cfg(feature = "age")]
+use crate::age::{AgeCommand, AgeDecryptor};
+use crate::check::CheckSummary;
+use crate::completions::{self, Shell};
+use crate::datetime;
+use crate::dirs;
+use crate::document::Document;
+use crate::dotenv::{self, Dotenv};
 use crate::error::{CnfgError, Issue, IssueKind, ValidationErrors};
-use crate::merge::{insert_path, merge};
-use crate::types::{ConfigMeta, Kind};
-use crate::util::{format_doc, format_flag};
+use crate::export;
+use crate::global;
+use crate::help::{HelpOptions, render_command_help, render_help, render_json};
+use crate::include::{self, Includes};
+use crate::merge::{
+    has_value_at, insert_path, merge, merge_with, resolve_nulls, take_at, value_at, value_at_mut,
+};
+use crate::prompt::{Prompter, TerminalPrompter};
+use crate::provenance::Origin;
+use crate::report::{Report, WarningKind, Warnings};
+use crate::run;
+use crate::save::{self, Format, SaveOptions};
+#[cfg(feature = "sops")]
+use crate::sops::{SopsCommand, SopsDecryptor};
+#[cfg(feature = "async")]
+use crate::sources::AsyncSource;
+use crate::sources::Source;
+use crate::sources::retry::RetryPolicy;
+use crate::sources::secrets::{self, Resolvers, SecretResolver};
+use crate::spans::{self, Location};
+use crate::trace;
+use crate::types::{CliSpec, CommandSpec, ConfigMeta, FieldSpec, Kind, ListSpec, Precedence};
+use crate::util::{expand_path, parse_byte_size, parse_int};
 use serde::Serialize;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
 
 /// Trait implemented for every `#[derive(Cnfg)]` struct.
 ///
@@ -16,241 +49,2729 @@ pub trait LoaderExt: ConfigMeta + Serialize + Sized {
     where
         for<'de> Self: serde::Deserialize<'de>,
     {
-        // Load a .env file if present (ignore missing files).
-        let _ = dotenvy::dotenv();
+        Loader::<Self>::new().load()
+    }
+
+    /// Like [`LoaderExt::load`], also returning the non-fatal [`Report`].
+    fn load_with_report() -> Result<(Self, Report), CnfgError> {
+        Loader::<Self>::new().load_with_report()
+    }
+
+    /// Like [`LoaderExt::load`], prompting on the terminal for required
+    /// fields that no layer set; see [`Loader::interactive`].
+    fn load_interactive() -> Result<Self, CnfgError> {
+        Loader::<Self>::new().interactive(true).load()
+    }
+
+    /// Load the config and publish it as the process-wide value returned by
+    /// [`LoaderExt::global`]; see [`crate::global`].
+    fn init_global() -> Result<Arc<Self>, CnfgError>
+    where
+        Self: DeserializeOwned + Send + Sync + 'static,
+    {
+        Loader::<Self>::new().init_global()
+    }
+
+    /// The config published with [`LoaderExt::init_global`].
+    ///
+    /// # Panics
+    ///
+    /// If no config of this type has been published; see
+    /// [`LoaderExt::try_global`].
+    fn global() -> Arc<Self>
+    where
+        Self: Send + Sync + 'static,
+    {
+        Self::try_global().unwrap_or_else(|| {
+            panic!(
+                "{} was read before init_global()",
+                std::any::type_name::<Self>()
+            )
+        })
+    }
+
+    /// The config published with [`LoaderExt::init_global`], if any.
+    fn try_global() -> Option<Arc<Self>>
+    where
+        Self: Send + Sync + 'static,
+    {
+        global::get::<Self>()
+    }
+
+    /// Start a [`Loader`] for this config to customise how it is assembled.
+    fn loader() -> Loader<Self> {
+        Loader::new()
+    }
+
+    /// Render CLI help text.
+    fn help() -> String {
+        Self::help_with(&HelpOptions::default())
+    }
+
+    /// Render CLI help text with extra sections and layout from `options`.
+    fn help_with(options: &HelpOptions) -> String {
+        render_help(&Meta::of::<Self>(), options, false)
+    }
+
+    /// Render CLI help text including `#[cnfg(cli(hidden))]` flags, as
+    /// printed for `--help-all`.
+    fn help_all() -> String {
+        render_help(&Meta::of::<Self>(), &HelpOptions::default(), true)
+    }
+
+    /// Flags, env vars, kinds, defaults, and docs as pretty-printed JSON, as
+    /// printed for `--help-json`; see [`crate::help`].
+    fn help_json() -> String {
+        render_json(&Meta::of::<Self>())
+    }
+
+    /// Render help for the subcommand called `name`, if there is one.
+    fn command_help(name: &str) -> Option<String> {
+        Self::commands()
+            .iter()
+            .find(|cmd| cmd.name == name)
+            .map(|cmd| {
+                render_command_help(&Meta::of::<Self>(), cmd, &HelpOptions::default(), false)
+            })
+    }
+
+    /// Print CLI help text to stdout.
+    fn print_help() {
+        println!("{}", Self::help());
+    }
+
+    /// `<name> <version>` line printed for `--version`, if a version is set.
+    fn version_line() -> Option<String> {
+        Meta::of::<Self>().version_line()
+    }
+
+    /// Render a `shell` completion script for the running binary's flags.
+    ///
+    /// Use [`completions::render`] to register under a different name.
+    fn completions(shell: Shell) -> String {
+        completions::render::<Self>(shell, &completions::current_bin_name())
+    }
+
+    /// Render this config, after re-running validation, in the given format.
+    ///
+    /// Keys follow field declaration order; see [`SaveOptions`] for doc
+    /// comment output.
+    fn render_as(&self, options: impl Into<SaveOptions>) -> Result<String, CnfgError> {
+        self.validate()?;
+        render_fields(Self::field_specs(), self, options.into())
+    }
+
+    /// Write this config to `path`, e.g. to generate a config on first run.
+    fn save_to(
+        &self,
+        path: impl AsRef<Path>,
+        options: impl Into<SaveOptions>,
+    ) -> Result<(), CnfgError> {
+        let rendered = self.render_as(options)?;
+        let path = path.as_ref();
+        fs::write(path, rendered).map_err(|e| CnfgError::from(e).in_file(path))?;
+        Ok(())
+    }
+
+    /// Stable `sha256:<hex>` hash of this config's effective values, with
+    /// `#[cnfg(secret)]` fields left out. Equal configs hash equally across
+    /// processes and hosts.
+    fn fingerprint(&self) -> Result<String, CnfgError> {
+        let value = serde_json::to_value(self).map_err(|e| CnfgError::Serialize(e.to_string()))?;
+        Ok(crate::fingerprint::fingerprint::<Self>(&value))
+    }
+
+    /// Deterministic rendering of this config's effective values for
+    /// snapshot tests and drift audits: indented JSON with sorted keys and
+    /// `#[cnfg(secret)]` values redacted; see
+    /// [`fingerprint::canonical_string`](crate::fingerprint::canonical_string).
+    fn to_canonical_string(&self) -> Result<String, CnfgError> {
+        let value = serde_json::to_value(self).map_err(|e| CnfgError::Serialize(e.to_string()))?;
+        Ok(crate::fingerprint::canonical_string::<Self>(&value))
+    }
+
+    /// This config's values as `NAME=value` environment variables, as
+    /// printed for `--dump-env`; see [`crate::export`].
+    fn to_env_vars(&self) -> Result<Vec<(String, String)>, CnfgError> {
+        let value = serde_json::to_value(self).map_err(|e| CnfgError::Serialize(e.to_string()))?;
+        Ok(export::env_vars(
+            Self::field_specs(),
+            Self::env_prefix(),
+            &value,
+        ))
+    }
+
+    /// Whether this config's [`fingerprint`](LoaderExt::fingerprint) differs
+    /// from one recorded earlier.
+    fn has_changed_since(&self, fingerprint: &str) -> Result<bool, CnfgError> {
+        Ok(self.fingerprint()? != fingerprint)
+    }
+
+    /// Write a starter config file with defaults and doc comments to `path`;
+    /// see [`Loader::init`].
+    fn init(path: impl AsRef<Path>, format: Format) -> Result<Self, CnfgError>
+    where
+        for<'de> Self: serde::Deserialize<'de>,
+    {
+        Loader::<Self>::new().init(path, SaveOptions::new(format).docs(true))
+    }
+
+    /// Load and validate without keeping the config, print a summary of
+    /// issues, layers, and warnings, and return whether it passed.
+    fn check() -> bool {
+        let summary = Loader::<Self>::new().check();
+        summary.print();
+        summary.passed()
+    }
+
+    /// Run validations for this config (injected by derive macro).
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+/// Builder over the load pipeline.
+///
+/// Options start from the struct-level `#[cnfg(...)]` attributes and can be
+/// overridden before calling [`Loader::load`].
+pub struct Loader<T> {
+    app_name: Option<String>,
+    profile: Option<String>,
+    config_format: Option<String>,
+    env_files: bool,
+    env_case_insensitive: bool,
+    dotenv: Dotenv,
+    args: Option<Vec<String>>,
+    help: HelpOptions,
+    exit_code: i32,
+    precedence: Precedence,
+    prompter: Option<Arc<dyn Prompter>>,
+    force: bool,
+    // Set by `init`, which builds a config for a file that does not exist yet.
+    skip_files: bool,
+    pub(crate) sources: Vec<Arc<dyn Source>>,
+    #[cfg(feature = "async")]
+    async_sources: Vec<Arc<dyn AsyncSource>>,
+    retry: RetryPolicy,
+    secrets: FileSecrets,
+    includes: Includes,
+    config_globs: Vec<String>,
+    resolvers: Resolvers,
+    meta: Meta,
+    check: Check<T>,
+}
+
+/// Decryptors for encrypted config files and values, by feature.
+#[derive(Clone, Default)]
+struct FileSecrets {
+    #[cfg(feature = "sops")]
+    sops: Option<Arc<dyn SopsDecryptor>>,
+    #[cfg(feature = "age")]
+    age: Option<Arc<dyn AgeDecryptor>>,
+}
+
+/// A fetched [`Source`] layer.
+pub(crate) struct Layer {
+    name: String,
+    value: Value,
+    /// Why a fallback value stands in for a failed fetch.
+    stale: Option<String>,
+}
+
+/// Validation run on the deserialized config (`LoaderExt::validate` for
+/// derived types).
+pub(crate) type Check<T> = Arc<dyn Fn(&T) -> Result<(), ValidationErrors> + Send + Sync>;
+
+/// Schema metadata the pipeline runs against: a derived type's
+/// [`ConfigMeta`], or one assembled at runtime by a
+/// [`SchemaBuilder`](crate::dynamic::SchemaBuilder).
+#[derive(Debug, Clone)]
+pub(crate) struct Meta {
+    pub(crate) fields: &'static [FieldSpec],
+    pub(crate) cli: &'static [CliSpec],
+    pub(crate) required: &'static [&'static str],
+    pub(crate) commands: &'static [CommandSpec],
+    pub(crate) lists: &'static [ListSpec],
+    pub(crate) defaults: Cow<'static, Value>,
+    pub(crate) defaults_error: Option<&'static str>,
+    pub(crate) doc: Option<&'static str>,
+    pub(crate) about: Option<&'static str>,
+    pub(crate) after_help: Option<&'static str>,
+    pub(crate) app_name: Option<&'static str>,
+    pub(crate) env_prefix: Option<&'static str>,
+    pub(crate) auto_env: bool,
+    pub(crate) version: Option<&'static str>,
+    pub(crate) dotenv: Dotenv,
+}
+
+impl Meta {
+    pub(crate) fn of<T: ConfigMeta>() -> Self {
+        Self {
+            fields: T::field_specs(),
+            cli: T::cli_specs(),
+            required: T::required_fields(),
+            commands: T::commands(),
+            lists: T::lists(),
+            defaults: T::defaults(),
+            defaults_error: T::defaults_error(),
+            doc: T::doc(),
+            about: T::about(),
+            after_help: T::after_help(),
+            app_name: T::app_name(),
+            env_prefix: T::env_prefix(),
+            auto_env: T::auto_env(),
+            version: T::version(),
+            dotenv: T::dotenv(),
+        }
+    }
+
+    /// `<name> <version>` line printed for `--version`, if a version is set.
+    fn version_line(&self) -> Option<String> {
+        let name = self
+            .app_name
+            .map(str::to_string)
+            .unwrap_or_else(completions::current_bin_name);
+        self.version.map(|version| format!("{name} {version}"))
+    }
+
+    /// Whether a built-in flag such as `--check` is handled by the loader
+    /// (no field claims it).
+    pub(crate) fn builtin_flag(&self, flag: &str) -> bool {
+        !self.cli.iter().any(|spec| spec.flag == flag)
+    }
+}
+
+impl<T> Clone for Loader<T> {
+    fn clone(&self) -> Self {
+        Self {
+            app_name: self.app_name.clone(),
+            profile: self.profile.clone(),
+            config_format: self.config_format.clone(),
+            env_files: self.env_files,
+            env_case_insensitive: self.env_case_insensitive,
+            dotenv: self.dotenv.clone(),
+            args: self.args.clone(),
+            help: self.help.clone(),
+            exit_code: self.exit_code,
+            precedence: self.precedence,
+            prompter: self.prompter.clone(),
+            force: self.force,
+            skip_files: self.skip_files,
+            sources: self.sources.clone(),
+            #[cfg(feature = "async")]
+            async_sources: self.async_sources.clone(),
+            retry: self.retry.clone(),
+            secrets: self.secrets.clone(),
+            includes: self.includes.clone(),
+            config_globs: self.config_globs.clone(),
+            resolvers: self.resolvers.clone(),
+            meta: self.meta.clone(),
+            check: Arc::clone(&self.check),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Loader<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("Loader");
+        debug
+            .field("app_name", &self.app_name)
+            .field("profile", &self.profile)
+            .field("config_format", &self.config_format)
+            .field("env_files", &self.env_files)
+            .field("env_case_insensitive", &self.env_case_insensitive)
+            .field("dotenv", &self.dotenv)
+            .field("args", &self.args)
+            .field("help", &self.help)
+            .field("exit_code", &self.exit_code)
+            .field("precedence", &self.precedence)
+            .field("config_globs", &self.config_globs)
+            .field("interactive", &self.prompter.is_some())
+            .field(
+                "sources",
+                &self.sources.iter().map(|s| s.name()).collect::<Vec<_>>(),
+            );
+        #[cfg(feature = "async")]
+        debug.field(
+            "async_sources",
+            &self
+                .async_sources
+                .iter()
+                .map(|s| s.name())
+                .collect::<Vec<_>>(),
+        );
+        debug.finish()
+    }
+}
+
+impl<T: LoaderExt> Default for Loader<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: LoaderExt> Loader<T> {
+    /// Create a loader seeded from the struct attributes of `T`.
+    pub fn new() -> Self {
+        Self::with_meta(Meta::of::<T>(), Arc::new(|cfg: &T| cfg.validate()))
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Loader<T> {
+    pub(crate) fn with_meta(meta: Meta, check: Check<T>) -> Self {
+        Self {
+            app_name: meta.app_name.map(str::to_string),
+            profile: None,
+            config_format: None,
+            env_files: false,
+            env_case_insensitive: false,
+            dotenv: meta.dotenv.clone(),
+            args: None,
+            help: HelpOptions::default(),
+            exit_code: run::ERROR_EXIT_CODE,
+            precedence: Precedence::default(),
+            prompter: None,
+            force: false,
+            skip_files: false,
+            sources: Vec::new(),
+            #[cfg(feature = "async")]
+            async_sources: Vec::new(),
+            retry: RetryPolicy::default(),
+            secrets: FileSecrets::default(),
+            includes: Includes::default(),
+            config_globs: Vec::new(),
+            resolvers: Resolvers::default(),
+            meta,
+            check,
+        }
+    }
+
+    /// Application name used to discover platform config directories.
+    ///
+    /// Overrides `#[cnfg(app_name = "...")]`.
+    pub fn app_name(mut self, name: impl Into<String>) -> Self {
+        self.app_name = Some(name.into());
+        self
+    }
+
+    /// Prefix shared by the application's environment variables (e.g.
+    /// `MYAPP_`). Set variables under it that no field reads are reported
+    /// as [`WarningKind::UnusedEnv`], catching typos like
+    /// `MYAPP_DATABSE_PORT`. Overrides `#[cnfg(env_prefix = "...")]`.
+    pub fn env_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.meta.env_prefix = Some(crate::util::leak_string(prefix.into()));
+        self
+    }
+
+    /// Read fields that have no `env` name from the env prefix followed by
+    /// their upper-cased path (`database.port` → `DATABASE_PORT`, or
+    /// `MYAPP_DATABASE_PORT` with a prefix). Overrides `#[cnfg(auto_env)]`.
+    pub fn auto_env(mut self, enabled: bool) -> Self {
+        self.meta.auto_env = enabled;
+        self
+    }
+
+    /// Whether env vars or flags win for fields without their own
+    /// `#[cnfg(precedence = "...")]`. Defaults to
+    /// [`Precedence::CliOverEnv`].
+    pub fn precedence(mut self, precedence: Precedence) -> Self {
+        self.precedence = precedence;
+        self
+    }
+
+    /// Name of the active profile (e.g. `production`); overrides
+    /// `CONFIG_PROFILE`.
+    pub fn profile(mut self, name: impl Into<String>) -> Self {
+        self.profile = Some(name.into());
+        self
+    }
+
+    /// Format (`yaml`, `toml`, ...) of config files whose extension names no
+    /// known format, such as a mounted `config`; overrides `CONFIG_FORMAT`.
+    /// `--config-format` overrides both.
+    pub fn config_format(mut self, format: impl Into<String>) -> Self {
+        self.config_format = Some(format.into());
+        self
+    }
+
+    /// Which `.env` files are read; overrides `#[cnfg(dotenv = ...)]`. See
+    /// [`crate::dotenv`].
+    pub fn dotenv(mut self, dotenv: Dotenv) -> Self {
+        self.dotenv = dotenv;
+        self
+    }
+
+    /// Read `path` as a `.env` file instead of searching the working
+    /// directory. Files added later take precedence.
+    pub fn dotenv_file(mut self, path: impl Into<PathBuf>) -> Self {
+        match &mut self.dotenv {
+            Dotenv::Files(files) => files.push(path.into()),
+            other => *other = Dotenv::Files(vec![path.into()]),
+        }
+        self
+    }
+
+    /// Accept the `<ENV>_FILE` convention for every env-backed field, not just
+    /// those marked `#[cnfg(allow_file)]`.
+    pub fn env_files(mut self, enabled: bool) -> Self {
+        self.env_files = enabled;
+        self
+    }
+
+    /// Match env var names regardless of case, for platforms that
+    /// normalize them. Provenance records the name that was actually set.
+    pub fn env_case_insensitive(mut self, enabled: bool) -> Self {
+        self.env_case_insensitive = enabled;
+        self
+    }
+
+    /// Parse these arguments instead of `std::env::args()`.
+    ///
+    /// The first item is the program name, as with `std::env::args()`.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = Some(args.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Take the CLI layer from arguments already parsed by clap, e.g. with
+    /// flags mounted by [`crate::clap::augment`]. Replaces [`Loader::args`].
+    #[cfg(feature = "clap")]
+    pub fn clap_matches(mut self, matches: &::clap::ArgMatches) -> Self {
+        self.args = Some(crate::clap::to_args(
+            self.meta.cli,
+            self.meta.commands,
+            matches,
+        ));
+        self
+    }
+
+    /// Prompt on the terminal for required fields left unset by every layer,
+    /// instead of reporting them as missing.
+    ///
+    /// Prompting only happens when stdin and stderr are terminals; see
+    /// [`crate::prompt`].
+    pub fn interactive(self, enabled: bool) -> Self {
+        if enabled {
+            self.prompter(TerminalPrompter)
+        } else {
+            Self {
+                prompter: None,
+                ..self
+            }
+        }
+    }
+
+    /// Like [`Loader::interactive`], asking through a custom [`Prompter`].
+    pub fn prompter(mut self, prompter: impl Prompter + 'static) -> Self {
+        self.prompter = Some(Arc::new(prompter));
+        self
+    }
+
+    /// Let [`Loader::init`] and `--init` replace an existing file.
+    pub fn force(mut self, enabled: bool) -> Self {
+        self.force = enabled;
+        self
+    }
+
+    /// Add a layer merged after config files and before env/CLI.
+    ///
+    /// Sources are merged in the order they are added.
+    pub fn source(mut self, source: impl Source + 'static) -> Self {
+        self.sources.push(Arc::new(source));
+        self
+    }
+
+    /// Retry failed source fetches under `policy`, optionally falling back
+    /// to the last value each source returned; see [`RetryPolicy`].
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Resolve `secret://` references and `#[cnfg(secret_ref)]` fields
+    /// handled by `resolver`; see [`crate::sources::secrets`].
+    ///
+    /// Resolvers are tried in the order they are added.
+    pub fn secret_resolver(mut self, resolver: impl SecretResolver + 'static) -> Self {
+        self.resolvers.push(Arc::new(resolver));
+        self
+    }
+
+    /// How long resolved secrets are reused (default: five minutes);
+    /// `Duration::ZERO` fetches them on every load.
+    pub fn secret_cache_ttl(mut self, ttl: std::time::Duration) -> Self {
+        self.resolvers.ttl = ttl;
+        self
+    }
+
+    /// Decrypt sops-encrypted config files with `decryptor` instead of the
+    /// `sops` binary.
+    #[cfg(feature = "sops")]
+    pub fn sops(mut self, decryptor: impl SopsDecryptor + 'static) -> Self {
+        self.secrets.sops = Some(Arc::new(decryptor));
+        self
+    }
+
+    /// Decrypt `enc:` values in config files with the age identity file at
+    /// `path`; overrides `CONFIG_AGE_IDENTITY`. See [`crate::age`].
+    #[cfg(feature = "age")]
+    pub fn age_identity(self, path: impl Into<PathBuf>) -> Self {
+        self.age(AgeCommand::new(path))
+    }
+
+    /// Decrypt `enc:` values in config files with `decryptor` instead of
+    /// the `age` binary.
+    #[cfg(feature = "age")]
+    pub fn age(mut self, decryptor: impl AgeDecryptor + 'static) -> Self {
+        self.secrets.age = Some(Arc::new(decryptor));
+        self
+    }
+
+    /// Top-level key listing the files a config file includes (default
+    /// `include`); see [`crate::include`].
+    pub fn include_key(mut self, key: impl Into<String>) -> Self {
+        self.includes.key = key.into();
+        self
+    }
+
+    /// How many levels of includes are followed before loading fails
+    /// (default [`include::DEFAULT_DEPTH`]).
+    pub fn include_depth(mut self, depth: usize) -> Self {
+        self.includes.max_depth = depth;
+        self
+    }
+
+    /// Also merge every file matching `pattern`, such as
+    /// `/etc/myapp/conf.d/*.toml`, in name order above the config files.
+    /// Repeatable, with later patterns merged on top. `*` and `?` match
+    /// within one path component, and a pattern matching nothing is
+    /// skipped.
+    pub fn config_glob(mut self, pattern: impl Into<String>) -> Self {
+        self.config_globs.push(pattern.into());
+        self
+    }
+
+    /// Extra sections and layout for the help printed on `--help`.
+    pub fn help_options(mut self, options: HelpOptions) -> Self {
+        self.help = options;
+        self
+    }
+
+    /// Add a layer fetched asynchronously by [`Loader::load_async`].
+    ///
+    /// Async sources are merged after every blocking [`Source`], in the
+    /// order they are added.
+    #[cfg(feature = "async")]
+    pub fn async_source(mut self, source: impl AsyncSource + 'static) -> Self {
+        self.async_sources.push(Arc::new(source));
+        self
+    }
+
+    /// Build the config from defaults, files, env, and CLI, then validate it.
+    pub fn load(self) -> Result<T, CnfgError> {
+        self.load_with_report().map(|(cfg, _)| cfg)
+    }
+
+    /// Load the config and publish it as the process-wide value; see
+    /// [`crate::global`].
+    pub fn init_global(self) -> Result<Arc<T>, CnfgError>
+    where
+        T: Send + Sync + 'static,
+    {
+        let cfg = Arc::new(self.load()?);
+        global::set(Arc::clone(&cfg));
+        Ok(cfg)
+    }
+
+    /// Exit code [`Loader::run`] uses when loading fails (default
+    /// [`run::ERROR_EXIT_CODE`]).
+    pub fn exit_code(mut self, code: i32) -> Self {
+        self.exit_code = code;
+        self
+    }
+
+    /// Load the config and call `f` with it, exiting the process when
+    /// loading stops early; see [`crate::run`].
+    pub fn run<R>(self, f: impl FnOnce(T) -> R) -> R {
+        let code = self.exit_code;
+        match self.load() {
+            Ok(cfg) => f(cfg),
+            Err(err) => run::exit(err, code),
+        }
+    }
+
+    /// Like [`Loader::load`], also returning warnings about ignored or
+    /// suspicious input.
+    ///
+    /// A built-in `--check` flag runs the pipeline, prints a
+    /// [`CheckSummary`], and returns [`CnfgError::CheckPassed`] or
+    /// [`CnfgError::CheckFailed`] instead of a config.
+    pub fn load_with_report(self) -> Result<(T, Report), CnfgError> {
+        match self.fetch_sources() {
+            Ok(layers) => self.finish(layers),
+            Err(err) if self.check_requested() => {
+                let summary = CheckSummary {
+                    error: Some(err),
+                    report: Report::default(),
+                };
+                summary.print();
+                Err(CnfgError::CheckFailed)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Async twin of [`Loader::load`] for use inside a tokio runtime.
+    ///
+    /// Every source, blocking or async, is fetched concurrently; blocking
+    /// sources run on tokio's blocking pool. Files, env, and CLI are still
+    /// read synchronously.
+    #[cfg(feature = "async")]
+    pub async fn load_async(self) -> Result<T, CnfgError>
+    where
+        T: 'static,
+    {
+        self.load_async_with_report().await.map(|(cfg, _)| cfg)
+    }
+
+    /// Async twin of [`Loader::load_with_report`].
+    #[cfg(feature = "async")]
+    pub async fn load_async_with_report(mut self) -> Result<(T, Report), CnfgError>
+    where
+        T: 'static,
+    {
+        let fields = self.meta.fields;
+        let blocking: Vec<_> = self
+            .sources
+            .iter()
+            .map(|source| {
+                let source = Arc::clone(source);
+                let retry = self.retry.clone();
+                tokio::task::spawn_blocking(move || {
+                    let name = source.name();
+                    let result = retry.fetch(&name, || source.load(fields));
+                    (name, result)
+                })
+            })
+            .collect();
+        let pending: Vec<_> = std::mem::take(&mut self.async_sources)
+            .into_iter()
+            .map(|source| {
+                let retry = self.retry.clone();
+                tokio::spawn(async move {
+                    let name = source.name();
+                    let result = retry.fetch_async(&name, || source.load(fields)).await;
+                    (name, result)
+                })
+            })
+            .collect();
+
+        let mut layers = Vec::with_capacity(blocking.len() + pending.len());
+        for handle in blocking.into_iter().chain(pending) {
+            let (name, result) = handle
+                .await
+                .map_err(|e| CnfgError::Source(format!("source task failed: {e}")))?;
+            let (value, stale) = result?;
+            layers.push(Layer { name, value, stale });
+        }
+        self.finish(layers)
+    }
+
+    /// Write a starter config file to `path` and return the config it holds.
+    ///
+    /// The file holds the defaults plus any values from sources, env, and
+    /// CLI (config files are not read), after prompting for missing required
+    /// fields when [`Loader::interactive`] is on. The config is validated
+    /// before anything is written, and an existing file is only replaced
+    /// with [`Loader::force`].
+    pub fn init(
+        self,
+        path: impl AsRef<Path>,
+        options: impl Into<SaveOptions>,
+    ) -> Result<T, CnfgError> {
+        let layers = self.fetch_sources()?;
+        self.write_init(layers, path.as_ref(), options.into())
+    }
+
+    fn write_init(
+        mut self,
+        layers: Vec<Layer>,
+        path: &Path,
+        options: SaveOptions,
+    ) -> Result<T, CnfgError> {
+        if path.exists() && !self.force {
+            return Err(CnfgError::Io(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} already exists; pass --force to overwrite",
+                    path.display()
+                ),
+            )));
+        }
+        self.skip_files = true;
+        let fields = self.meta.fields;
+        let cfg = self.assemble(layers, &mut Report::default())?;
+        fs::write(path, render_fields(fields, &cfg, options)?)
+            .map_err(|e| CnfgError::from(e).in_file(path))?;
+        Ok(cfg)
+    }
+
+    /// Run the whole pipeline and validation, keeping the outcome and report
+    /// rather than the config.
+    pub fn check(self) -> CheckSummary {
+        match self.fetch_sources() {
+            Ok(layers) => self.check_with(layers),
+            Err(err) => CheckSummary {
+                error: Some(err),
+                report: Report::default(),
+            },
+        }
+    }
+
+    /// The profile set with [`Loader::profile`], else `CONFIG_PROFILE`.
+    fn active_profile(&self) -> Option<String> {
+        self.profile
+            .clone()
+            .or_else(|| env::var(dotenv::PROFILE_ENV).ok())
+            .filter(|name| !name.is_empty())
+    }
+
+    fn check_requested(&self) -> bool {
+        self.builtin_requested("check")
+    }
+
+    /// Whether the built-in `--<flag>` was passed and no field claims it.
+    fn builtin_requested(&self, flag: &str) -> bool {
+        if !self.meta.builtin_flag(flag) {
+            return false;
+        }
+        let wanted = format!("--{flag}");
+        match &self.args {
+            Some(args) => args.iter().skip(1).any(|arg| *arg == wanted),
+            None => env::args().skip(1).any(|arg| arg == wanted),
+        }
+    }
+
+    /// Fetch every blocking [`Source`] layer, in the order they were added.
+    fn fetch_sources(&self) -> Result<Vec<Layer>, CnfgError> {
+        trace::stage!("sources");
+        #[cfg(feature = "async")]
+        if let Some(source) = self.async_sources.first() {
+            return Err(CnfgError::Source(format!(
+                "{}: async sources require Loader::load_async",
+                source.name()
+            )));
+        }
+        self.sources
+            .iter()
+            .map(|source| {
+                let name = source.name();
+                let (value, stale) = self.retry.fetch(&name, || source.load(self.meta.fields))?;
+                Ok(Layer { name, value, stale })
+            })
+            .collect()
+    }
+
+    /// `--init [path]` and `--force`, when handled by the loader.
+    fn init_requested(&self) -> Option<(PathBuf, bool)> {
+        if !self.meta.builtin_flag("init") {
+            return None;
+        }
+        let args: Vec<String> = match &self.args {
+            Some(args) => args.iter().skip(1).cloned().collect(),
+            None => env::args().skip(1).collect(),
+        };
+        let path = args.iter().enumerate().find_map(|(idx, arg)| {
+            if let Some(inline) = arg.strip_prefix("--init=") {
+                return Some(PathBuf::from(inline));
+            }
+            (arg == "--init").then(|| match args.get(idx + 1) {
+                Some(next) if !next.starts_with('-') => PathBuf::from(next),
+                _ => PathBuf::from(DEFAULT_INIT_FILE),
+            })
+        })?;
+        let force = self.meta.builtin_flag("force") && args.iter().any(|arg| arg == "--force");
+        Some((path, force))
+    }
+
+    /// Shared tail of the sync and async entry points, handling `--check`,
+    /// `--init`, and `--dump-env`.
+    fn finish(mut self, layers: Vec<Layer>) -> Result<(T, Report), CnfgError> {
+        if let Some((path, force)) = self.init_requested() {
+            let options = Format::from_path(&path)
+                .map(|format| SaveOptions::new(format).docs(true))
+                .ok_or_else(|| {
+                    CnfgError::Cli(format!(
+                        "--init: cannot infer a format from {}; use .toml, .yaml, or .json",
+                        path.display()
+                    ))
+                })?;
+            if self.prompter.is_none() {
+                self.prompter = Some(Arc::new(TerminalPrompter));
+            }
+            self.force |= force;
+            self.write_init(layers, &path, options)?;
+            println!("wrote {}", path.display());
+            return Err(CnfgError::Initialized(path));
+        }
+
+        if self.check_requested() {
+            let summary = self.check_with(layers);
+            summary.print();
+            return Err(if summary.passed() {
+                CnfgError::CheckPassed
+            } else {
+                CnfgError::CheckFailed
+            });
+        }
+
+        if self.builtin_requested("dump-env") {
+            let fields = self.meta.fields;
+            let prefix = self.meta.env_prefix;
+            let cfg = self.assemble(layers, &mut Report::default())?;
+            let value =
+                serde_json::to_value(&cfg).map_err(|e| CnfgError::Serialize(e.to_string()))?;
+            print!(
+                "{}",
+                export::render(&export::env_vars(fields, prefix, &value))
+            );
+            return Err(CnfgError::EnvPrinted);
+        }
+
+        let mut report = Report::default();
+        let cfg = self.assemble(layers, &mut report)?;
+        Ok((cfg, report))
+    }
+
+    fn check_with(self, layers: Vec<Layer>) -> CheckSummary {
+        let mut report = Report::default();
+        let error = self.assemble(layers, &mut report).err();
+        CheckSummary { error, report }
+    }
+
+    fn assemble(self, layers: Vec<Layer>, report: &mut Report) -> Result<T, CnfgError> {
+        trace::stage!("load");
+        // Copy `.env` files into the environment (never replacing set vars).
+        self.dotenv
+            .apply(self.active_profile().as_deref(), &mut report.warnings)?;
+        let profile = self.active_profile();
+
+        let meta = &self.meta;
+        let args = self.args.unwrap_or_else(|| env::args().collect());
+        let command = select_command(meta, &args);
+
+        // 1. Start with defaults (including those of the selected command).
+        if let Some(err) = meta.defaults_error {
+            return Err(CnfgError::Serialize(err.to_string()));
+        }
+        let mut defaults = Cow::Borrowed(&*meta.defaults);
+        if let Some(cmd) = command {
+            let segments: Vec<&str> = cmd.path.split('.').collect();
+            insert_path(defaults.to_mut(), &segments, (cmd.defaults_json)())
+                .expect("command sections are absent from the defaults");
+        }
+        report.provenance.record_value(&defaults, &Origin::Default);
+        trace::event!(
+            DEBUG,
+            values = trace::leaf_count(&defaults),
+            "applied defaults"
+        );
+        let mut acc = defaults.clone().into_owned();
+
+        // 2. Load config files (`--config`, CONFIG_FILE, or discovered layers).
+        let choice = FileChoice::from_args(meta, &args, self.config_format.as_deref())?;
+        if !self.skip_files {
+            let file = FileLoad {
+                meta,
+                format: choice.format.as_deref(),
+                profile: profile.as_deref(),
+                secrets: &self.secrets,
+                includes: &self.includes,
+            };
+            if let Some(file) = load_config_file(
+                &file,
+                self.app_name.as_deref(),
+                &choice.paths,
+                &self.config_globs,
+                report,
+            )? {
+                merge_with(&mut acc, file, meta.fields);
+            }
+        }
+
+        // 2b. Overlay additional sources (remote stores, mounted dirs, ...).
+        for Layer { name, value, stale } in layers {
+            trace::event!(DEBUG, source = %name, values = trace::leaf_count(&value), "merged source");
+            if let Some(note) = stale {
+                report.warnings.push(WarningKind::StaleSource, &name, note);
+            }
+            report
+                .provenance
+                .record_value(&value, &Origin::Source(name));
+            merge_with(&mut acc, value, meta.fields);
+        }
+
+        // 3. Overlay environment variables. Values that fail to parse are
+        //    collected with the rest of the issues rather than stopping here.
+        let mut errs = ValidationErrors::new();
+        let lookup = EnvLookup::new(self.env_files, self.env_case_insensitive);
+        let resolving = !self.resolvers.is_empty();
+        apply_environment(meta, &mut acc, &lookup, resolving, report, &mut errs)?;
+        if let Some(prefix) = meta.env_prefix {
+            warn_unused_env(meta, prefix, &lookup, &mut report.warnings);
+        }
+        trace::event!(
+            DEBUG,
+            overrides = trace::overrides(&report.provenance, |o| matches!(o, Origin::Env(_))),
+            "applied environment"
+        );
+
+        // 4. Overlay CLI flags, except over env values of fields where the
+        //    environment takes precedence; `--set` still wins there.
+        let env_wins: Vec<(&FieldSpec, Origin)> = meta
+            .fields
+            .iter()
+            .filter(|spec| spec.precedence.unwrap_or(self.precedence) == Precedence::EnvOverCli)
+            .filter_map(|spec| match report.provenance.get(spec.path) {
+                Some(origin @ Origin::Env(_)) => Some((spec, origin.clone())),
+                _ => None,
+            })
+            .collect();
+        let mut cli_values = parse_cli(
+            meta, &self.help, args, command, resolving, report, &mut errs,
+        )?;
+        for (spec, origin) in env_wins {
+            let from_set = matches!(
+                report.provenance.get(spec.path),
+                Some(Origin::Cli(flag)) if flag.starts_with("--set ")
+            );
+            if from_set || take_at(&mut cli_values, spec.path).is_none() {
+                continue;
+            }
+            if let (Some(Origin::Cli(flag)), Origin::Env(env)) =
+                (report.provenance.get(spec.path), &origin)
+            {
+                let message = format!("ignored; {env} takes precedence");
+                report
+                    .warnings
+                    .push(WarningKind::Shadowed, flag.clone(), message);
+            }
+            report.provenance.record(spec.path, origin);
+        }
+        merge_with(&mut acc, cli_values, meta.fields);
+        trace::event!(
+            DEBUG,
+            overrides = trace::overrides(&report.provenance, |o| matches!(o, Origin::Cli(_))),
+            command = command.map(|cmd| cmd.name),
+            "applied flags"
+        );
+
+        // 4b. Sections of commands that were not selected stay `None`.
+        for cmd in meta.commands {
+            if command.is_none_or(|selected| selected.path != cmd.path) {
+                take_at(&mut acc, cmd.path);
+            }
+        }
+
+        // 4c. An explicit `null` unsets a field (see `resolve_nulls`), and
+        //     every element of a `Vec<Nested>` list starts from the element
+        //     type's defaults.
+        let rejected = resolve_nulls(&mut acc, &defaults, meta.fields);
+        reject_nulls(&rejected, "", report, &mut errs);
+        for_each_item(&mut acc, meta.lists, "", &mut |list, prefix, item| {
+            let item_defaults = (list.defaults_json)();
+            let mut merged = item_defaults.clone();
+            merge(&mut merged, std::mem::take(item));
+            let rejected = resolve_nulls(&mut merged, &item_defaults, (list.field_specs)());
+            reject_nulls(&rejected, prefix, report, &mut errs);
+            *item = merged;
+        });
+
+        // 4d. Ask for required fields that are still unset.
+        if let Some(prompter) = self.prompter.as_deref().filter(|p| p.available()) {
+            prompt_missing(meta, &mut acc, command, prompter, report)?;
+        }
+
+        // 4e. Fetch secrets named by `secret://` values and `secret_ref`
+        //     fields; the document keeps the references.
+        let secret_refs =
+            self.resolvers
+                .resolve_all(&mut acc, meta.fields, &mut report.provenance, &mut errs);
+
+        // 5. Resolve `${path}` references in values from files and sources
+        //    (fetched secrets are plaintext, not templates), normalise typed
+        //    string values, and check required fields before deserializing.
+        trace::stage!("validate");
+        let written = |path: &str| {
+            matches!(
+                report.provenance.get(path),
+                Some(Origin::File(_) | Origin::Stdin | Origin::Source(_))
+            ) && !secret_refs.iter().any(|(secret, _)| secret == path)
+        };
+        let mut path_fields: Vec<String> = path_specs(meta.fields, "").collect();
+        for_each_item(&mut acc, meta.lists, "", &mut |list, prefix, _| {
+            path_fields.extend(path_specs((list.field_specs)(), prefix));
+        });
+        let path_field = |path: &str| path_fields.iter().any(|field| field == path);
+        if let Err(interpolation_errs) =
+            crate::interpolate::resolve_references_in(&mut acc, written, path_field)
+        {
+            errs.extend(interpolation_errs);
+        }
+        normalize_values(&mut acc, meta.fields, "", &mut errs);
+        anchor_paths(&mut acc, meta.fields, report);
+        check_types(&acc, meta.fields, "", report, &mut errs);
+        check_required(meta, &acc, command, &mut errs);
+        for_each_item(&mut acc, meta.lists, "", &mut |list, prefix, item| {
+            normalize_values(item, (list.field_specs)(), prefix, &mut errs);
+            check_types(item, (list.field_specs)(), prefix, report, &mut errs);
+            for path in (list.required_fields)() {
+                if !has_value_at(item, path) {
+                    errs.push(Issue::missing(format!("{prefix}{path}")));
+                }
+            }
+            check_conditional((list.field_specs)(), item, prefix, &mut errs);
+            collect_extras(item, (list.field_specs)());
+        });
+        if !errs.is_empty() {
+            trace::event!(DEBUG, issues = errs.len(), "validation failed");
+            return Err(CnfgError::Validation(errs));
+        }
+        collect_extras(&mut acc, meta.fields);
+
+        // 6. Deserialize into the target struct, reporting the failing path.
+        //    Borrowing `acc` lets the report keep it without a copy.
+        let result: Result<T, _> = serde_path_to_error::deserialize(&acc);
+        secrets::redact(&mut acc, &secret_refs);
+        report.document = Document::new(acc, report.provenance.clone());
+        let cfg = result.map_err(|err| {
+            let field = err.path().to_string();
+            let origin = report.provenance.describe(&field);
+            let mut issue = Issue::new(field, IssueKind::Type, err.inner().to_string());
+            if let Some(origin) = origin {
+                issue = issue.with_origin(origin);
+            }
+            let mut errs = ValidationErrors::new();
+            errs.push(issue);
+            CnfgError::Validation(errs)
+        })?;
+
+        // 7. Run user-defined validations (from derive macro), noting where
+        //    each failing value was set. Profile-conditional checks see this
+        //    load's profile, and warning-severity issues are reported without
+        //    failing the load.
+        let checked = crate::validators::with_profile(profile, || (self.check)(&cfg));
+        if let Err(errs) = checked {
+            let mut failed = ValidationErrors::new();
+            for mut issue in errs {
+                if let Some(origin) = report.provenance.describe(&issue.field) {
+                    issue = issue.with_origin(origin);
+                }
+                if issue.is_warning() {
+                    report
+                        .warnings
+                        .push(WarningKind::Validation, issue.field, issue.message);
+                } else {
+                    failed.push(issue);
+                }
+            }
+            if !failed.is_empty() {
+                trace::event!(DEBUG, issues = failed.len(), "validation failed");
+                return Err(CnfgError::Validation(failed));
+            }
+        }
+
+        trace::event!(
+            DEBUG,
+            fields = report.provenance.iter().count(),
+            "config loaded"
+        );
+        Ok(cfg)
+    }
+}
+
+/// A config document to load.
+#[derive(Clone)]
+enum ConfigInput {
+    File(PathBuf),
+    /// Standard input, selected with `-`.
+    Stdin,
+}
+
+impl ConfigInput {
+    /// The file's path, or `<stdin>`, for messages.
+    fn label(&self) -> &Path {
+        match self {
+            ConfigInput::File(path) => path,
+            ConfigInput::Stdin => Path::new("<stdin>"),
+        }
+    }
+}
+
+/// Formats accepted by `--config-format`, as file extensions.
+const FILE_FORMATS: &[&str] = &[
+    "toml",
+    "yaml",
+    "yml",
+    "json",
+    "json5",
+    "ron",
+    "hcl",
+    "ini",
+    "properties",
+];
+
+/// The config file chosen on the command line.
+#[derive(Default)]
+struct FileChoice {
+    /// Every `--config <path>` or `-c <path>`, in order; `-` reads standard
+    /// input.
+    paths: Vec<String>,
+    /// `--config-format <format>`, else [`Loader::config_format`], else
+    /// `CONFIG_FORMAT`, for standard input and files without a known
+    /// extension.
+    format: Option<String>,
+}
+
+impl FileChoice {
+    fn from_args(
+        meta: &Meta,
+        args: &[String],
+        fallback_format: Option<&str>,
+    ) -> Result<Self, CnfgError> {
+        let mut choice = Self::default();
+        let mut args = args.iter().skip(1);
+        while let Some(arg) = args.next() {
+            let Some((flag, inline)) = config_flag(meta, arg) else {
+                continue;
+            };
+            // `-` is a value here, so take the next argument as is.
+            let value = match inline {
+                Some(inline) => inline.to_string(),
+                None => args
+                    .next()
+                    .cloned()
+                    .ok_or_else(|| CnfgError::Cli(format!("missing value for {flag}")))?,
+            };
+            if flag == "--config" {
+                choice.paths.push(value);
+            } else {
+                let format = file_format(&value)
+                    .map_err(|msg| CnfgError::Cli(format!("--config-format: {msg}")))?;
+                choice.format = Some(format);
+            }
+        }
+        if choice.format.is_none() {
+            choice.format = match fallback_format {
+                Some(value) => Some(
+                    file_format(value)
+                        .map_err(|msg| CnfgError::Cli(format!("config_format: {msg}")))?,
+                ),
+                None => match env::var(FORMAT_ENV) {
+                    Ok(value) if !value.is_empty() => Some(
+                        file_format(&value)
+                            .map_err(|msg| CnfgError::Env(format!("{FORMAT_ENV}: {msg}")))?,
+                    ),
+                    _ => None,
+                },
+            };
+        }
+        Ok(choice)
+    }
+}
+
+/// Environment variable naming the format of files without a known
+/// extension, like `--config-format`.
+const FORMAT_ENV: &str = "CONFIG_FORMAT";
+
+/// A format name as its file extension, if it is one of [`FILE_FORMATS`].
+fn file_format(value: &str) -> Result<String, String> {
+    let format = value.to_ascii_lowercase();
+    if FILE_FORMATS.contains(&format.as_str()) {
+        Ok(format)
+    } else {
+        Err(format!(
+            "unknown format `{value}`; use toml, yaml, json, json5, ron, hcl, ini, or properties"
+        ))
+    }
+}
+
+/// Split a built-in `--config`/`--config-format` argument into the flag and
+/// its inline (`--flag=value`) value. `-c` is short for `--config`.
+fn config_flag<'a>(meta: &Meta, arg: &'a str) -> Option<(&'static str, Option<&'a str>)> {
+    [
+        ("--config", "--config"),
+        ("--config", "-c"),
+        ("--config-format", "--config-format"),
+    ]
+    .into_iter()
+    .find_map(|(flag, spelling)| {
+        if !meta.builtin_flag(&flag[2..]) {
+            return None;
+        }
+        match arg.strip_prefix(spelling)? {
+            "" => Some((flag, None)),
+            rest => rest.strip_prefix('=').map(|value| (flag, Some(value))),
+        }
+    })
+}
+
+/// Split `CONFIG_FILE` into its files, separated by commas or the platform's
+/// path list separator (`:`, or `;` on Windows).
+fn split_config_files(list: &str) -> Vec<String> {
+    list.split(',')
+        .flat_map(|part| env::split_paths(part).collect::<Vec<_>>())
+        .filter(|path| !path.as_os_str().is_empty())
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Collect config file layers.
+///
+/// `--config` (repeatable) or `CONFIG_FILE` (a list) selects the files,
+/// where `-` reads standard input and wildcards expand to the matching
+/// files. Otherwise the system directory, the user directory (both only
+/// when an app name is known), and the working directory are searched.
+/// Files matching `globs` follow. Every file is merged in order, each above
+/// the files it includes.
+fn load_config_file(
+    file: &FileLoad,
+    app_name: Option<&str>,
+    paths: &[String],
+    globs: &[String],
+    report: &mut Report,
+) -> Result<Option<Value>, CnfgError> {
+    trace::stage!("files");
+    let selected = if !paths.is_empty() {
+        Some((paths.to_vec(), "--config"))
+    } else {
+        env::var("CONFIG_FILE")
+            .ok()
+            .map(|list| (split_config_files(&list), "CONFIG_FILE"))
+    };
+    let mut layers: Vec<ConfigInput> = if let Some((paths, by)) = selected {
+        paths
+            .into_iter()
+            .flat_map(|path| {
+                trace::event!(DEBUG, path = %path, "config file selected by {by}");
+                let inputs: Vec<ConfigInput> = if path == "-" {
+                    vec![ConfigInput::Stdin]
+                } else if include::has_wildcard(&path) {
+                    include::glob(&path)
+                        .into_iter()
+                        .map(ConfigInput::File)
+                        .collect()
+                } else {
+                    vec![ConfigInput::File(PathBuf::from(path))]
+                };
+                for input in &inputs {
+                    report.warnings.push(
+                        WarningKind::FileDiscovery,
+                        input.label().display().to_string(),
+                        format!("selected by {by}; directory discovery skipped"),
+                    );
+                }
+                inputs
+            })
+            .collect()
+    } else {
+        let mut dirs_to_search = Vec::new();
+        if let Some(app) = app_name {
+            dirs_to_search.extend(dirs::system_config_dir(app));
+            dirs_to_search.extend(dirs::user_config_dir(app));
+        }
+        dirs_to_search.push(PathBuf::from("."));
+        dirs_to_search
+            .iter()
+            .filter_map(|dir| discover_in_dir(dir, &mut report.warnings))
+            .map(ConfigInput::File)
+            .collect()
+    };
+    for pattern in globs {
+        let matched = include::glob(pattern);
+        trace::event!(DEBUG, pattern = %pattern, files = matched.len(), "expanded config glob");
+        layers.extend(matched.into_iter().map(ConfigInput::File));
+    }
 
-        // 1. Start with defaults.
-        let mut acc = Self::defaults_json();
+    if layers.is_empty() {
+        trace::event!(DEBUG, "no config file found");
+        return Ok(None);
+    }
 
-        // 2. Load config file (CONFIG_FILE env or default names).
-        if let Some(file) = load_config_file()? {
-            merge(&mut acc, file);
-        }
+    let mut acc = Value::Object(Default::default());
+    for input in &layers {
+        let value = file.load(input, &mut Vec::new(), report)?;
+        merge_with(&mut acc, value, file.meta.fields);
+    }
+    Ok(Some(acc))
+}
 
-        // 3. Overlay environment variables.
-        apply_environment::<Self>(&mut acc)?;
+/// Settings shared by a config file and everything it includes.
+struct FileLoad<'a> {
+    meta: &'a Meta,
+    format: Option<&'a str>,
+    /// The active profile, whose section of each file is merged over it.
+    profile: Option<&'a str>,
+    secrets: &'a FileSecrets,
+    includes: &'a Includes,
+}
 
-        // 4. Overlay CLI flags.
-        let cli_values = parse_cli::<Self>()?;
-        merge(&mut acc, cli_values);
+impl FileLoad<'_> {
+    /// Load `input` and its includes, merged beneath it. `chain` holds the
+    /// files including this one, outermost first.
+    fn load(
+        &self,
+        input: &ConfigInput,
+        chain: &mut Vec<PathBuf>,
+        report: &mut Report,
+    ) -> Result<Value, CnfgError> {
+        let fields = self.meta.fields;
+        let path = input.label();
+        let canonical = match input {
+            ConfigInput::File(path) => fs::canonicalize(path).unwrap_or_else(|_| path.clone()),
+            ConfigInput::Stdin => path.to_path_buf(),
+        };
+        if chain.contains(&canonical) {
+            let cycle: Vec<String> = chain
+                .iter()
+                .chain([&canonical])
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(CnfgError::Include(format!(
+                "include cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+        if chain.len() > self.includes.max_depth {
+            return Err(CnfgError::Include(format!(
+                "{}: includes nest deeper than {} levels",
+                path.display(),
+                self.includes.max_depth
+            )));
+        }
 
-        // 5. Check required fields on the assembled value before deserializing.
-        let mut errs = ValidationErrors::new();
-        check_required::<Self>(&acc, &mut errs);
-        if !errs.is_empty() {
-            return Err(CnfgError::Validation(errs));
+        let (mut value, mut locations) = load_file_value(input, self.format, fields, self.secrets)?;
+        apply_file_profile(&mut value, &mut locations, fields, self.profile);
+        trace::event!(
+            DEBUG,
+            path = %path.display(),
+            values = trace::leaf_count(&value),
+            "loaded config file"
+        );
+        crate::interpolate::substitute_env(&mut value, fields)?;
+        let patterns = self
+            .includes
+            .take(&mut value)
+            .map_err(|msg| CnfgError::Include(format!("{}: {msg}", path.display())))?;
+        #[cfg(feature = "age")]
+        {
+            let from_env = env::var_os(crate::age::IDENTITY_ENV)
+                .map(|path| Arc::new(AgeCommand::new(path)) as Arc<dyn AgeDecryptor>);
+            let age = self.secrets.age.clone().or(from_env);
+            crate::age::decrypt_values(&mut value, fields, age.as_deref())?;
+        }
+        migrate_renamed(fields, &mut value, path, &mut report.warnings);
+        warn_deprecated(fields, &value, path, &mut report.warnings);
+        warn_unknown_keys(fields, &value, path, &mut report.warnings);
+
+        let mut acc = Value::Object(Default::default());
+        if !patterns.is_empty() {
+            let base = match input {
+                ConfigInput::File(path) => path.parent().map(Path::to_path_buf).unwrap_or_default(),
+                ConfigInput::Stdin => PathBuf::new(),
+            };
+            chain.push(canonical);
+            for pattern in &patterns {
+                let found = include::expand(&base, pattern)
+                    .map_err(|msg| CnfgError::Include(format!("{}: {msg}", path.display())))?;
+                for included in found {
+                    trace::event!(DEBUG, path = %included.display(), from = %path.display(), "including config file");
+                    let layer = self.load(&ConfigInput::File(included), chain, report)?;
+                    merge_with(&mut acc, layer, fields);
+                }
+            }
+            chain.pop();
         }
 
-        // 6. Deserialize into the target struct.
-        let cfg: Self = serde_json::from_value(acc)?;
+        let origin = match input {
+            ConfigInput::File(path) => Origin::File(path.clone()),
+            ConfigInput::Stdin => Origin::Stdin,
+        };
+        report.provenance.record_value(&value, &origin);
+        report.provenance.record_locations(locations);
+        merge_with(&mut acc, value, fields);
+        Ok(acc)
+    }
+}
 
-        // 7. Run user-defined validations (from derive macro).
-        cfg.validate()?;
+/// Keys holding per-profile sections of a config file: `[profile.<name>]`
+/// in TOML, `profiles: {<name>: ...}` in YAML.
+const PROFILE_KEYS: &[&str] = &["profile", "profiles"];
 
-        Ok(cfg)
+/// Merge the active profile's section of a file over the file's top-level
+/// values and drop every profile section. A key that names a field is
+/// left alone.
+fn apply_file_profile(
+    value: &mut Value,
+    locations: &mut BTreeMap<String, Location>,
+    fields: &[FieldSpec],
+    profile: Option<&str>,
+) {
+    for key in PROFILE_KEYS {
+        let claimed = fields.iter().any(|spec| spec.segments()[0] == *key);
+        let Some(map) = value.as_object_mut().filter(|_| !claimed) else {
+            continue;
+        };
+        let Some(Value::Object(mut sections)) = map.remove(*key) else {
+            continue;
+        };
+        let prefix = format!("{key}.");
+        let section = profile.and_then(|name| sections.remove(name));
+        let located = std::mem::take(locations);
+        for (path, location) in located {
+            let Some(rest) = path.strip_prefix(&prefix) else {
+                locations.entry(path).or_insert(location);
+                continue;
+            };
+            if let Some(inner) = profile.and_then(|name| rest.strip_prefix(name)) {
+                if let Some(inner) = inner.strip_prefix('.') {
+                    locations.insert(inner.to_string(), location);
+                }
+            }
+        }
+        if let Some(section @ Value::Object(_)) = section {
+            merge_with(value, section, fields);
+        }
     }
+}
 
-    /// Render CLI help text.
-    fn help() -> String {
-        render_help::<Self>()
+/// Pick the config file in `dir`, warning about lower-priority siblings.
+fn discover_in_dir(dir: &Path, warnings: &mut Warnings) -> Option<PathBuf> {
+    let mut found = dirs::CONFIG_FILE_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .filter(|candidate| candidate.is_file());
+    let chosen = found.next()?;
+    trace::event!(DEBUG, path = %chosen.display(), "discovered config file");
+    for shadowed in found {
+        trace::event!(DEBUG, path = %shadowed.display(), "config file shadowed");
+        warnings.push(
+            WarningKind::FileDiscovery,
+            shadowed.display().to_string(),
+            format!("ignored; {} takes precedence", chosen.display()),
+        );
     }
+    Some(chosen)
+}
 
-    /// Print CLI help text to stdout.
-    fn print_help() {
-        println!("{}", Self::help());
+/// Move values found under `renamed_from` paths to their current location.
+fn migrate_renamed(fields: &[FieldSpec], value: &mut Value, file: &Path, warnings: &mut Warnings) {
+    for spec in fields {
+        let Some(old) = spec.renamed_from else {
+            continue;
+        };
+        let Some(moved) = take_at(value, old) else {
+            continue;
+        };
+        warnings.push(
+            WarningKind::Deprecated,
+            old,
+            format!("renamed to `{}` (found in {})", spec.path, file.display()),
+        );
+        if !has_value_at(value, spec.path) {
+            // A non-section in the way is left for type checking to report.
+            let _ = insert_path(value, &spec.segments(), moved);
+        }
     }
+}
 
-    /// Run validations for this config (injected by derive macro).
-    fn validate(&self) -> Result<(), ValidationErrors>;
+/// Warn when a file sets a field marked `#[cnfg(deprecated = "...")]`.
+fn warn_deprecated(fields: &[FieldSpec], value: &Value, file: &Path, warnings: &mut Warnings) {
+    for spec in fields {
+        if let Some(note) = spec.deprecated {
+            if has_value_at(value, spec.path) {
+                warnings.push(
+                    WarningKind::Deprecated,
+                    spec.path,
+                    format!("deprecated: {note} (set in {})", file.display()),
+                );
+            }
+        }
+    }
 }
 
-fn load_config_file() -> Result<Option<Value>, CnfgError> {
-    if let Ok(path) = env::var("CONFIG_FILE") {
-        return load_file_value(&path).map(Some);
+/// Warn about keys in a file that map to no declared field.
+fn warn_unknown_keys(fields: &[FieldSpec], value: &Value, file: &Path, warnings: &mut Warnings) {
+    fn walk(
+        fields: &[FieldSpec],
+        value: &Value,
+        prefix: &str,
+        file: &Path,
+        warnings: &mut Warnings,
+    ) {
+        let Value::Object(map) = value else {
+            return;
+        };
+        let collects_extras = fields
+            .iter()
+            .any(|spec| spec.extra && spec.parent_path() == prefix);
+        for (key, child) in map {
+            let path = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}.{key}")
+            };
+            match fields.iter().find(|spec| spec.path == path) {
+                Some(spec) if spec.kind == Kind::Object => {
+                    walk(fields, child, &path, file, warnings)
+                }
+                Some(_) => {}
+                None if collects_extras => {}
+                None => warnings.push(
+                    WarningKind::UnknownKey,
+                    &path,
+                    format!("unknown key in {}; ignored", file.display()),
+                ),
+            }
+        }
     }
+    walk(fields, value, "", file, warnings);
+}
 
-    for candidate in &["config.toml", "config.yaml", "config.yml", "config.json"] {
-        if Path::new(candidate).exists() {
-            return load_file_value(candidate).map(Some);
+/// Read and parse a config file, noting where each key was written.
+///
+/// `format` applies when the extension names no known format; standard
+/// input has no extension, so it needs one.
+fn load_file_value(
+    input: &ConfigInput,
+    format: Option<&str>,
+    fields: &[FieldSpec],
+    secrets: &FileSecrets,
+) -> Result<(Value, BTreeMap<String, Location>), CnfgError> {
+    let path = input.label();
+    let stdin = matches!(input, ConfigInput::Stdin);
+    let known_ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .filter(|ext| !stdin && FILE_FORMATS.contains(ext));
+    let ext = match (known_ext, format) {
+        (Some(ext), _) | (None, Some(ext)) => ext,
+        (None, None) if stdin => {
+            return Err(CnfgError::Cli(
+                "reading config from stdin needs --config-format".into(),
+            ));
         }
+        (None, None) => path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default(),
+    };
+    // Usage errors already name the file; everything else gets it as context.
+    let in_file = |err: CnfgError| match err {
+        CnfgError::Cli(_) => err,
+        err => err.in_file(path),
+    };
+    let mut data = if stdin {
+        read_stdin()?
+    } else {
+        crate::util::read_text(path).map_err(|e| in_file(e.into()))?
+    };
+    let mut value = parse_file_value(path, ext, &data, fields).map_err(in_file)?;
+    if is_sops_encrypted(&value) {
+        data = decrypt_sops(path, &data, secrets).map_err(in_file)?;
+        value = parse_file_value(path, ext, &data, fields).map_err(in_file)?;
     }
+    Ok((value, spans::locate_format(ext, &data)))
+}
+
+/// Standard input, read once per process so reloads see the same document.
+fn read_stdin() -> Result<String, CnfgError> {
+    static STDIN: OnceLock<Result<String, String>> = OnceLock::new();
+    STDIN
+        .get_or_init(|| {
+            let mut data = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut data)
+                .map_err(|e| e.to_string())
+                .and_then(|_| crate::util::decode_text(data))
+        })
+        .clone()
+        .map_err(|msg| CnfgError::Cli(format!("could not read config from stdin: {msg}")))
+}
+
+/// Whether a parsed file carries sops metadata.
+fn is_sops_encrypted(value: &Value) -> bool {
+    value
+        .get("sops")
+        .is_some_and(|meta| meta.get("mac").is_some() && meta.get("version").is_some())
+}
+
+#[cfg(feature = "sops")]
+fn decrypt_sops(path: &Path, data: &str, secrets: &FileSecrets) -> Result<String, CnfgError> {
+    trace::event!(DEBUG, path = %path.display(), "decrypting sops file");
+    let sops = secrets
+        .sops
+        .clone()
+        .unwrap_or_else(|| Arc::new(SopsCommand::new()));
+    sops.decrypt(path, data).map_err(CnfgError::Decrypt)
+}
 
-    Ok(None)
+#[cfg(not(feature = "sops"))]
+fn decrypt_sops(path: &Path, _data: &str, _secrets: &FileSecrets) -> Result<String, CnfgError> {
+    Err(CnfgError::Cli(format!(
+        "sops support disabled but attempted to load encrypted {}",
+        path.display()
+    )))
 }
 
-fn load_file_value(path: &str) -> Result<Value, CnfgError> {
-    let data = fs::read_to_string(path)?;
-    if path.ends_with(".toml") {
+fn parse_file_value(
+    path: &Path,
+    ext: &str,
+    data: &str,
+    fields: &[FieldSpec],
+) -> Result<Value, CnfgError> {
+    if ext == "toml" {
         #[cfg(feature = "toml")]
         {
-            let t: toml::Value = toml::from_str(&data)?;
-            Ok(serde_json::to_value(t)?)
+            let t: toml::Value = toml::from_str(data)?;
+            Ok(toml_to_json(t))
         }
         #[cfg(not(feature = "toml"))]
         {
             Err(CnfgError::Cli(format!(
-                "toml support disabled but attempted to load {path}"
+                "toml support disabled but attempted to load {}",
+                path.display()
             )))
         }
-    } else if path.ends_with(".yaml") || path.ends_with(".yml") {
+    } else if ext == "yaml" || ext == "yml" {
         #[cfg(feature = "yaml")]
         {
-            let y: serde_json::Value = serde_yaml::from_str(&data)?;
+            let y: serde_json::Value = serde_yaml::from_str(data)?;
             Ok(y)
         }
         #[cfg(not(feature = "yaml"))]
         {
             Err(CnfgError::Cli(format!(
-                "yaml support disabled but attempted to load {path}"
+                "yaml support disabled but attempted to load {}",
+                path.display()
+            )))
+        }
+    } else if ext == "json" {
+        Ok(serde_json::from_str(data)?)
+    } else if ext == "json5" {
+        #[cfg(feature = "json5")]
+        {
+            Ok(json5::from_str(data)?)
+        }
+        #[cfg(not(feature = "json5"))]
+        {
+            Err(CnfgError::Cli(format!(
+                "json5 support disabled but attempted to load {}",
+                path.display()
+            )))
+        }
+    } else if ext == "ron" {
+        #[cfg(feature = "ron")]
+        {
+            // `ron::Value` reads `(key: value)` structs as maps; deserializing
+            // straight into JSON would see them as tuples.
+            let value: ron::Value = ron::from_str(data)?;
+            value.into_rust().map_err(|code| {
+                CnfgError::ParseRon(ron::error::SpannedError {
+                    code,
+                    position: ron::error::Position { line: 0, col: 0 },
+                })
+            })
+        }
+        #[cfg(not(feature = "ron"))]
+        {
+            Err(CnfgError::Cli(format!(
+                "ron support disabled but attempted to load {}",
+                path.display()
+            )))
+        }
+    } else if ext == "hcl" {
+        #[cfg(feature = "hcl")]
+        {
+            // Labelled blocks (`service "api" { ... }`) nest one object per label.
+            Ok(hcl::from_str(data)?)
+        }
+        #[cfg(not(feature = "hcl"))]
+        {
+            Err(CnfgError::Cli(format!(
+                "hcl support disabled but attempted to load {}",
+                path.display()
+            )))
+        }
+    } else if ext == "ini" {
+        #[cfg(feature = "ini")]
+        {
+            let entries = crate::formats::parse_ini(data).map_err(CnfgError::ParseIni)?;
+            entries_to_value(entries, fields).map_err(CnfgError::ParseIni)
+        }
+        #[cfg(not(feature = "ini"))]
+        {
+            let _ = fields;
+            Err(CnfgError::Cli(format!(
+                "ini support disabled but attempted to load {}",
+                path.display()
+            )))
+        }
+    } else if ext == "properties" {
+        #[cfg(feature = "properties")]
+        {
+            let entries =
+                crate::formats::parse_properties(data).map_err(CnfgError::ParseProperties)?;
+            entries_to_value(entries, fields).map_err(CnfgError::ParseProperties)
+        }
+        #[cfg(not(feature = "properties"))]
+        {
+            let _ = fields;
+            Err(CnfgError::Cli(format!(
+                "properties support disabled but attempted to load {}",
+                path.display()
             )))
         }
-    } else if path.ends_with(".json") {
-        Ok(serde_json::from_str(&data)?)
     } else {
         Err(CnfgError::Cli(format!(
-            "unknown config extension for {path}; use .toml, .yaml, .yml, or .json"
+            "unknown config extension for {}; use .toml, .yaml, .yml, .json, .json5, .ron, .hcl, .ini, or .properties, or name the format with --config-format or CONFIG_FORMAT",
+            path.display()
         )))
     }
 }
 
-fn apply_environment<T: ConfigMeta>(root: &mut Value) -> Result<(), CnfgError> {
-    for spec in T::field_specs() {
-        if let Some(env_name) = spec.env {
-            if let Ok(val) = env::var(env_name) {
-                let parsed = parse_literal(&val, spec.kind)
-                    .map_err(|msg| CnfgError::Env(format!("{env_name}: {msg}")))?;
-                insert_path(root, &spec.segments(), parsed);
+/// TOML values as JSON, with date-times as their RFC 3339 strings.
+#[cfg(feature = "toml")]
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(text) => Value::String(text),
+        toml::Value::Integer(n) => Value::from(n),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Build a nested object from flat entries, coercing each value by field kind.
+#[cfg(any(feature = "ini", feature = "properties"))]
+fn entries_to_value(
+    entries: Vec<crate::formats::Entry>,
+    fields: &[FieldSpec],
+) -> Result<Value, String> {
+    let mut root = Value::Object(Default::default());
+    for entry in entries {
+        crate::sources::insert_raw(&mut root, fields, &entry.path, &entry.raw)
+            .map_err(|msg| format!("line {}: {msg}", entry.line))?;
+    }
+    Ok(root)
+}
+
+fn apply_environment(
+    meta: &Meta,
+    root: &mut Value,
+    lookup: &EnvLookup,
+    resolving: bool,
+    report: &mut Report,
+    errs: &mut ValidationErrors,
+) -> Result<(), CnfgError> {
+    trace::stage!("environment");
+    for spec in meta.fields {
+        let Some(primary) = env_name(meta, spec) else {
+            continue;
+        };
+        let names: Vec<&str> = std::iter::once(primary.as_ref())
+            .chain(spec.env_aliases.iter().copied())
+            .collect();
+        let (env_name, raw) = match lookup.get(&names) {
+            Some((name, val)) => {
+                if val.is_empty() {
+                    report
+                        .warnings
+                        .push(WarningKind::EmptyEnv, &name, "set but empty");
+                }
+                (name, val)
             }
+            None if lookup.files || spec.allow_file => {
+                let mut found = None;
+                for name in &names {
+                    if let Some(val) = read_env_file(name)? {
+                        found = Some((name.to_string(), val));
+                        break;
+                    }
+                }
+                match found {
+                    Some(found) => found,
+                    None => continue,
+                }
+            }
+            None => continue,
+        };
+        let env_name = env_name.as_str();
+        if let Some(note) = spec.deprecated {
+            report.warnings.push(
+                WarningKind::Deprecated,
+                env_name,
+                format!("deprecated: {note}"),
+            );
+        }
+        let parsed = match parse_input(&raw, spec, resolving) {
+            Ok(parsed) => parsed,
+            Err(msg) => {
+                errs.push(parse_issue(env_name, msg));
+                continue;
+            }
+        };
+        let inserted = if spec.merge.is_some() {
+            let mut layer = Value::Object(Default::default());
+            let inserted = insert_path(&mut layer, &spec.segments(), parsed);
+            merge_with(root, layer, meta.fields);
+            inserted
+        } else {
+            insert_path(root, &spec.segments(), parsed)
+        };
+        if let Err(err) = inserted {
+            errs.push(parse_issue(env_name, err.to_string()));
+            continue;
         }
+        trace::event!(TRACE, env = env_name, field = spec.path, "applied env var");
+        report
+            .provenance
+            .record(spec.path, Origin::Env(env_name.to_string()));
     }
     Ok(())
 }
 
-fn parse_cli<T: LoaderExt>() -> Result<Value, CnfgError> {
-    let mut args = env::args().skip(1);
+/// How env var names are matched while reading the environment.
+struct EnvLookup {
+    /// Accept `<ENV>_FILE` for every field (see [`Loader::env_files`]).
+    files: bool,
+    /// Upper-cased name to actual name of every set variable, when names
+    /// match regardless of case.
+    folded: Option<BTreeMap<String, String>>,
+}
+
+impl EnvLookup {
+    fn new(files: bool, case_insensitive: bool) -> Self {
+        let folded = case_insensitive.then(|| {
+            env::vars_os()
+                .filter_map(|(name, _)| name.into_string().ok())
+                .map(|name| (name.to_ascii_uppercase(), name))
+                .collect()
+        });
+        Self { files, folded }
+    }
+
+    /// The first of `names` that is set, with the name it was found under.
+    fn get(&self, names: &[&str]) -> Option<(String, String)> {
+        names.iter().find_map(|name| {
+            if let Ok(val) = env::var(name) {
+                return Some((name.to_string(), val));
+            }
+            let actual = self.folded.as_ref()?.get(&name.to_ascii_uppercase())?;
+            env::var(actual).ok().map(|val| (actual.clone(), val))
+        })
+    }
+
+    /// Whether the set variable `actual` is read for the declared `name`.
+    fn matches(&self, name: &str, actual: &str) -> bool {
+        if self.folded.is_some() {
+            name.eq_ignore_ascii_case(actual)
+        } else {
+            name == actual
+        }
+    }
+}
+
+/// The variable `spec` is read from: its `env` name, else with auto env a
+/// name derived from its path.
+fn env_name(meta: &Meta, spec: &FieldSpec) -> Option<Cow<'static, str>> {
+    match spec.env {
+        Some(env) => Some(Cow::Borrowed(env)),
+        None if meta.auto_env && spec.kind != Kind::Object && !spec.extra => Some(Cow::Owned(
+            export::path_env_name(meta.env_prefix, spec.path),
+        )),
+        None => None,
+    }
+}
+
+/// Warn about variables starting with `prefix` that no field reads,
+/// suggesting the closest declared name.
+fn warn_unused_env(meta: &Meta, prefix: &str, lookup: &EnvLookup, warnings: &mut Warnings) {
+    let mut known: Vec<String> = Vec::new();
+    for spec in meta.fields {
+        let primary = env_name(meta, spec);
+        let names = primary
+            .as_deref()
+            .into_iter()
+            .chain(spec.env_aliases.iter().copied());
+        for env_name in names {
+            known.push(env_name.to_string());
+            if lookup.files || spec.allow_file {
+                known.push(format!("{env_name}_FILE"));
+            }
+        }
+    }
+    let mut unused: Vec<String> = env::vars_os()
+        .filter_map(|(name, _)| name.into_string().ok())
+        .filter(|name| lookup.matches(prefix, name.get(..prefix.len()).unwrap_or_default()))
+        .filter(|name| !known.iter().any(|known| lookup.matches(known, name)))
+        .collect();
+    unused.sort();
+    for name in unused {
+        let suggestion = known
+            .iter()
+            .map(|candidate| (edit_distance(&name, candidate), candidate))
+            .filter(|(distance, candidate)| *distance <= 2.max(candidate.len() / 5))
+            .min_by_key(|(distance, _)| *distance);
+        let message = match suggestion {
+            Some((_, candidate)) => format!("set but unused; did you mean `{candidate}`?"),
+            None => "set but unused".to_string(),
+        };
+        warnings.push(WarningKind::UnusedEnv, name, message);
+    }
+}
+
+/// Levenshtein distance between two names.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let next = (diagonal + usize::from(ca != *cb))
+                .min(row[j] + 1)
+                .min(row[j + 1] + 1);
+            diagonal = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+    row[b.len()]
+}
+
+/// Read the file named by `<env_name>_FILE` (Docker/Kubernetes secrets convention).
+fn read_env_file(env_name: &str) -> Result<Option<String>, CnfgError> {
+    let file_var = format!("{env_name}_FILE");
+    let Ok(path) = env::var(&file_var) else {
+        return Ok(None);
+    };
+    let contents = fs::read(&path)
+        .map_err(|e| e.to_string())
+        .and_then(crate::util::decode_text)
+        .map_err(|e| CnfgError::Env(format!("{file_var}: cannot read {path}: {e}")))?;
+    Ok(Some(contents.trim_end_matches(['\n', '\r']).to_string()))
+}
+
+/// File written by a bare `--init`.
+#[cfg(feature = "toml")]
+const DEFAULT_INIT_FILE: &str = "config.toml";
+#[cfg(all(not(feature = "toml"), feature = "yaml"))]
+const DEFAULT_INIT_FILE: &str = "config.yaml";
+#[cfg(not(any(feature = "toml", feature = "yaml")))]
+const DEFAULT_INIT_FILE: &str = "config.json";
+
+/// Find the subcommand named by the first positional argument, skipping
+/// the values of global flags that precede it.
+fn select_command(meta: &Meta, args: &[String]) -> Option<&'static CommandSpec> {
+    if meta.commands.is_empty() {
+        return None;
+    }
+    let mut args = args.iter().skip(1).peekable();
+    while let Some(arg) = args.next() {
+        // Built-in flags taking values, skipped as in `parse_cli`.
+        if let Some((_, inline)) = config_flag(meta, arg) {
+            if inline.is_none() {
+                args.next();
+            }
+        } else if arg == "--init" && meta.builtin_flag("init") {
+            args.next_if(|next| !next.starts_with('-'));
+        } else if let Some(flag) = arg.strip_prefix("--") {
+            let takes_value = match meta.cli.iter().find(|spec| spec.flag == flag) {
+                Some(spec) => spec.takes_value,
+                None => flag == "set",
+            };
+            if takes_value {
+                args.next();
+            }
+        } else if !arg.starts_with('-') {
+            return meta.commands.iter().find(|cmd| cmd.name == arg);
+        }
+    }
+    None
+}
+
+fn parse_cli(
+    meta: &Meta,
+    help: &HelpOptions,
+    args: Vec<String>,
+    command: Option<&'static CommandSpec>,
+    resolving: bool,
+    report: &mut Report,
+    errs: &mut ValidationErrors,
+) -> Result<Value, CnfgError> {
+    trace::stage!("cli");
+    let program = args.first().and_then(completions::bin_name);
+    let mut args = args.into_iter().skip(1).peekable();
     let mut cli_val = Value::Object(Default::default());
+    // Flags after the command name belong to the command first.
+    let mut active: Option<&'static CommandSpec> = None;
+    // `--set path=value` overrides, applied after every other flag.
+    let builtin_set = meta.builtin_flag("set");
+    let mut overrides = Vec::new();
 
     while let Some(arg) = args.next() {
-        if arg == "--help" || arg == "-h" {
-            <T as LoaderExt>::print_help();
+        let all = arg == "--help-all" && meta.builtin_flag("help-all");
+        if arg == "--help" || arg == "-h" || all {
+            let mut help = help.clone();
+            help.bin_name = help.bin_name.or(program);
+            match active {
+                Some(cmd) => println!("{}", render_command_help(meta, cmd, &help, all)),
+                None => println!("{}", render_help(meta, &help, all)),
+            }
             return Err(CnfgError::HelpPrinted);
         }
 
+        if arg == "--help-json" && meta.builtin_flag("help-json") {
+            println!("{}", render_json(meta));
+            return Err(CnfgError::HelpPrinted);
+        }
+
+        if arg == "--version" || arg == "-V" {
+            if let Some(line) = meta.version_line() {
+                println!("{line}");
+                return Err(CnfgError::VersionPrinted);
+            }
+        }
+
+        if arg == "--check" && meta.builtin_flag("check") {
+            continue;
+        }
+
+        // `--dump-env` was handled after loading.
+        if arg == "--dump-env" && meta.builtin_flag("dump-env") {
+            continue;
+        }
+
+        // `--config` and `--config-format` were handled with the files.
+        if let Some((_, inline)) = config_flag(meta, &arg) {
+            if inline.is_none() {
+                args.next();
+            }
+            continue;
+        }
+
+        // `--init [path]` and `--force` were handled before parsing.
+        if meta.builtin_flag("init") {
+            if arg == "--init" {
+                args.next_if(|next| !next.starts_with('-'));
+                continue;
+            }
+            if arg.starts_with("--init=") || (arg == "--force" && meta.builtin_flag("force")) {
+                continue;
+            }
+        }
+
+        if builtin_set && (arg == "--set" || arg.starts_with("--set=")) {
+            let assignment = match arg.strip_prefix("--set=") {
+                Some(inline) => inline.to_string(),
+                None => args
+                    .next()
+                    .ok_or_else(|| CnfgError::Cli("missing value for --set".into()))?,
+            };
+            let (path, raw) = assignment.split_once('=').ok_or_else(|| {
+                CnfgError::Cli(format!("--set: expected `path=value`, got `{assignment}`"))
+            })?;
+            overrides.push((path.trim().to_string(), raw.to_string()));
+            continue;
+        }
+
         if !arg.starts_with("--") {
+            if let Some(cmd) = command.filter(|cmd| active.is_none() && cmd.name == arg) {
+                active = Some(cmd);
+                continue;
+            }
             return Err(CnfgError::Cli(format!(
                 "unexpected positional argument `{arg}`"
             )));
         }
 
         let flag = arg.trim_start_matches("--");
-        let spec = T::cli_specs()
-            .iter()
-            .find(|s| s.flag == flag)
-            .ok_or_else(|| CnfgError::Cli(format!("unknown flag --{flag}")))?;
+        let command_spec = active.and_then(|cmd| {
+            (cmd.cli_specs)()
+                .iter()
+                .find(|s| s.flag == flag)
+                .map(|spec| (spec, format!("{}.{}", cmd.path, spec.path)))
+        });
+        let (spec, path) = match command_spec {
+            Some(found) => found,
+            None => meta
+                .cli
+                .iter()
+                .find(|s| s.flag == flag)
+                .map(|spec| (spec, spec.path.to_string()))
+                .ok_or_else(|| CnfgError::Cli(format!("unknown flag --{flag}")))?,
+        };
+        let segments: Vec<&str> = path.split('.').collect();
+
+        let field = meta.fields.iter().find(|field| field.path == path);
+        if let Some(note) = field.and_then(|field| field.deprecated) {
+            report.warnings.push(
+                WarningKind::Deprecated,
+                format!("--{flag}"),
+                format!("deprecated: {note}"),
+            );
+        }
+        trace::event!(TRACE, flag = %flag, field = %path, "applied flag");
+        report
+            .provenance
+            .record(&path, Origin::Cli(format!("--{flag}")));
 
-        if spec.takes_value {
+        let inserted = if spec.takes_value {
             let value = args
                 .next()
                 .ok_or_else(|| CnfgError::Cli(format!("missing value for --{flag}")))?;
-            let parsed = parse_literal(&value, spec.kind)
-                .map_err(|msg| CnfgError::Cli(format!("--{flag}: {msg}")))?;
-            insert_path(&mut cli_val, &spec.segments(), parsed);
+            if !spec.values.is_empty() && !spec.values.contains(&value.as_str()) {
+                let expected = spec.values.join(", ");
+                errs.push(parse_issue(
+                    &format!("--{flag}"),
+                    format!("expected one of {expected}, got `{value}`"),
+                ));
+                continue;
+            }
+            let secret_ref = field.is_some_and(|field| field.secret_ref.is_some());
+            let parsed = if keeps_reference(&value, secret_ref, resolving) {
+                Ok(Value::String(value.clone()))
+            } else if let Some(parse) = spec.parse {
+                parse(&value)
+            } else {
+                parse_literal(&value, spec.kind)
+            };
+            let parsed = match parsed {
+                Ok(parsed) => parsed,
+                Err(msg) => {
+                    errs.push(parse_issue(&format!("--{flag}"), msg));
+                    continue;
+                }
+            };
+            if spec.multiple {
+                // Repeated flags accumulate into a list.
+                match value_at_mut(&mut cli_val, &path) {
+                    Some(Value::Array(items)) => {
+                        items.push(parsed);
+                        Ok(())
+                    }
+                    _ => insert_path(&mut cli_val, &segments, Value::Array(vec![parsed])),
+                }
+            } else {
+                insert_path(&mut cli_val, &segments, parsed)
+            }
         } else {
-            insert_path(&mut cli_val, &spec.segments(), Value::Bool(true));
+            insert_path(&mut cli_val, &segments, Value::Bool(true))
+        };
+        if let Err(err) = inserted {
+            errs.push(parse_issue(&format!("--{flag}"), err.to_string()));
+        }
+    }
+
+    for (path, raw) in overrides {
+        let known = meta.fields.iter().any(|spec| {
+            spec.path == path
+                || path
+                    .strip_prefix(spec.path)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        });
+        if !known {
+            return Err(CnfgError::Cli(format!("--set: unknown field `{path}`")));
         }
+        if let Err(msg) = crate::sources::insert_raw(&mut cli_val, meta.fields, &path, &raw) {
+            let msg = msg.strip_prefix(&format!("{path}: ")).unwrap_or(&msg);
+            errs.push(parse_issue(&format!("--set {path}"), msg.to_string()));
+            continue;
+        }
+        report
+            .provenance
+            .record(&path, Origin::Cli(format!("--set {path}")));
     }
 
     Ok(cli_val)
 }
 
+/// An env var or flag whose value could not be parsed, named by `input`.
+fn parse_issue(input: &str, message: String) -> Issue {
+    Issue::new(input.to_string(), IssueKind::Type, message)
+}
+
+/// Coerce a raw string written at `path` (from a decrypted or fetched
+/// secret) to the kind of the field there; unknown paths stay strings.
+pub(crate) fn coerce_raw(fields: &[FieldSpec], path: &str, raw: &str) -> Result<Value, String> {
+    match fields.iter().find(|spec| spec.path == path) {
+        Some(spec) if !matches!(spec.kind, Kind::String | Kind::Path | Kind::Object) => {
+            parse_for_spec(raw, spec)
+        }
+        _ => Ok(Value::String(raw.to_string())),
+    }
+}
+
+/// Parse a raw string for `spec`, splitting comma-separated lists unless
+/// the field has its own decoder.
+pub(crate) fn parse_for_spec(raw: &str, spec: &FieldSpec) -> Result<Value, String> {
+    parse_input(raw, spec, false)
+}
+
+/// [`parse_for_spec`] for env vars, which may hold `secret://` references
+/// when `resolving` (a secret resolver is configured).
+fn parse_input(raw: &str, spec: &FieldSpec, resolving: bool) -> Result<Value, String> {
+    let reference = |text: &str| keeps_reference(text, spec.secret_ref.is_some(), resolving);
+    if reference(raw) {
+        Ok(Value::String(raw.to_string()))
+    } else if let Some(parse) = spec.parse {
+        parse(raw)
+    } else if spec.list {
+        raw.split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| {
+                if reference(item) {
+                    Ok(Value::String(item.to_string()))
+                } else {
+                    parse_literal(item, spec.kind)
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array)
+    } else {
+        parse_literal(raw, spec.kind)
+    }
+}
+
+/// Whether `raw` is a `secret://` reference to keep as written until its
+/// secret is fetched and typed. Without a resolver or a `secret_ref` on
+/// the field nothing would fetch it, so it is type-checked like any value.
+fn keeps_reference(raw: &str, secret_ref: bool, resolving: bool) -> bool {
+    raw.starts_with(secrets::SCHEME) && (resolving || secret_ref)
+}
+
 fn parse_literal(raw: &str, kind: Kind) -> Result<Value, String> {
     match kind {
-        Kind::Bool => match raw {
-            "1" | "true" | "TRUE" | "True" => Ok(Value::Bool(true)),
-            "0" | "false" | "FALSE" | "False" => Ok(Value::Bool(false)),
-            _ => Err("expected a boolean".into()),
-        },
-        Kind::Int => raw
-            .parse::<i64>()
-            .map(|v| Value::Number(v.into()))
-            .map_err(|_| "expected an integer".into()),
+        Kind::Bool => {
+            let is = |words: [&str; 4]| words.iter().any(|word| raw.eq_ignore_ascii_case(word));
+            if is(["1", "true", "yes", "on"]) {
+                Ok(Value::Bool(true))
+            } else if is(["0", "false", "no", "off"]) {
+                Ok(Value::Bool(false))
+            } else {
+                Err("expected a boolean (true/false, yes/no, on/off, or 1/0)".into())
+            }
+        }
+        Kind::Int => parse_int(raw)
+            .map(Value::Number)
+            .ok_or_else(|| "expected an integer".into()),
         Kind::Float => raw
             .parse::<f64>()
             .ok()
             .and_then(serde_json::Number::from_f64)
             .map(Value::Number)
             .ok_or_else(|| "expected a float".into()),
-        Kind::String => Ok(Value::String(raw.to_string())),
+        Kind::Bytes => parse_byte_size(raw).map(|v| Value::Number(v.into())),
+        Kind::String | Kind::Path | Kind::Any | Kind::Custom => Ok(Value::String(raw.to_string())),
+        Kind::IpAddr | Kind::SocketAddr | Kind::DateTime | Kind::Date => {
+            check_format(raw, kind)?;
+            Ok(Value::String(raw.to_string()))
+        }
         Kind::Object => Err("cannot assign composite value from string".into()),
     }
 }
 
-fn check_required<T: ConfigMeta>(value: &Value, errs: &mut ValidationErrors) {
-    if T::required_fields().is_empty() {
-        return;
+/// Check the syntax of kinds kept as strings until serde reads them.
+fn check_format(raw: &str, kind: Kind) -> Result<(), String> {
+    match kind {
+        Kind::IpAddr => raw.parse::<std::net::IpAddr>().map(drop).map_err(|_| {
+            format!("expected an IP address like `127.0.0.1` or `::1`, found `{raw}`")
+        }),
+        Kind::SocketAddr => raw.parse::<std::net::SocketAddr>().map(drop).map_err(|_| {
+            format!(
+                "expected a socket address like `127.0.0.1:8080` or `[::1]:8080`, found `{raw}`"
+            )
+        }),
+        Kind::DateTime => datetime::parse_date_time(raw).map(drop),
+        Kind::Date => datetime::parse_date(raw).map(drop),
+        _ => Ok(()),
     }
-    for path in T::required_fields() {
-        if !value_has_path(value, path) {
-            errs.push(Issue {
-                field: (*path).to_string(),
-                kind: IssueKind::Missing,
-                message: "required field missing".into(),
-            });
+}
+
+/// Move keys that match no declared field into their struct's
+/// `#[cnfg(extra)]` map.
+fn collect_extras(root: &mut Value, fields: &[FieldSpec]) {
+    for spec in fields.iter().filter(|spec| spec.extra) {
+        let parent = spec.parent_path();
+        let target = if parent.is_empty() {
+            Some(&mut *root)
+        } else {
+            value_at_mut(root, parent)
+        };
+        let Some(Value::Object(map)) = target else {
+            continue;
+        };
+        let declared = |key: &str| {
+            fields.iter().any(|field| {
+                field.parent_path() == parent && field.path.rsplit('.').next() == Some(key)
+            })
+        };
+        let unknown: Vec<String> = map.keys().filter(|key| !declared(key)).cloned().collect();
+        let mut extras = serde_json::Map::new();
+        for key in unknown {
+            if let Some(value) = map.remove(&key) {
+                extras.insert(key, value);
+            }
+        }
+        let field_key = spec.path.rsplit('.').next().unwrap_or(spec.path);
+        match map.get_mut(field_key) {
+            Some(Value::Object(existing)) => existing.extend(extras),
+            // A non-map value fails deserialization with its own error.
+            Some(other) if !other.is_null() => {}
+            _ => {
+                map.insert(field_key.to_string(), Value::Object(extras));
+            }
         }
     }
 }
 
-fn value_has_path(value: &Value, path: &str) -> bool {
-    let mut current = value;
-    for segment in path.split('.') {
-        match current {
-            Value::Object(map) => match map.get(segment) {
-                Some(next) => current = next,
-                None => return false,
-            },
-            _ => return false,
+/// Visit every element of every list in `lists` (and of lists nested inside
+/// those elements), passing the element's `path[index].` issue prefix.
+fn for_each_item(
+    root: &mut Value,
+    lists: &[ListSpec],
+    prefix: &str,
+    visit: &mut dyn FnMut(&ListSpec, &str, &mut Value),
+) {
+    for list in lists {
+        let Some(Value::Array(items)) = value_at_mut(root, list.path) else {
+            continue;
+        };
+        for (idx, item) in items.iter_mut().enumerate() {
+            let item_prefix = format!("{prefix}{}[{idx}].", list.path);
+            visit(list, &item_prefix, item);
+            for_each_item(item, (list.lists)(), &item_prefix, visit);
         }
     }
-    !matches!(current, Value::Null)
 }
 
-fn render_help<T: ConfigMeta>() -> String {
-    let mut lines = Vec::new();
+/// The full paths of the `PathBuf` fields in `fields`, below `prefix`.
+fn path_specs<'a>(fields: &'a [FieldSpec], prefix: &'a str) -> impl Iterator<Item = String> + 'a {
+    fields
+        .iter()
+        .filter(|spec| spec.kind == Kind::Path)
+        .map(move |spec| format!("{prefix}{}", spec.path))
+}
 
-    if let Some(doc) = format_doc(T::doc()) {
-        lines.push(doc);
-        lines.push(String::new());
+/// Validate address and date fields, expand path fields, and convert byte
+/// sizes regardless of which source set them.
+fn normalize_values(
+    root: &mut Value,
+    fields: &[FieldSpec],
+    prefix: &str,
+    errs: &mut ValidationErrors,
+) {
+    for spec in fields {
+        let path = format!("{prefix}{}", spec.path);
+        if spec.kind == Kind::Bytes {
+            if let Some(value) = value_at_mut(root, spec.path) {
+                normalize_bytes(&path, value, errs);
+            }
+            continue;
+        }
+        if !matches!(
+            spec.kind,
+            Kind::IpAddr | Kind::SocketAddr | Kind::DateTime | Kind::Date | Kind::Path
+        ) {
+            continue;
+        }
+        let Some(Value::String(raw)) = value_at_mut(root, spec.path) else {
+            continue;
+        };
+        let result = if spec.kind == Kind::Path {
+            expand_path(raw).map(|expanded| *raw = expanded)
+        } else {
+            check_format(raw, spec.kind)
+        };
+        if let Err(message) = result {
+            errs.push(Issue::new(path, IssueKind::Type, message));
+        }
     }
+}
 
-    lines.push("Usage:".to_string());
-    lines.push("  <binary> [OPTIONS]".to_string());
+/// Resolve relative `#[cnfg(relative_to_config)]` paths against the
+/// directory of the config file that set them.
+fn anchor_paths(root: &mut Value, fields: &[FieldSpec], report: &Report) {
+    for spec in fields.iter().filter(|spec| spec.relative_to_config) {
+        let Some(Origin::File(file)) = report.provenance.get(spec.path) else {
+            continue;
+        };
+        let Some(Value::String(raw)) = value_at_mut(root, spec.path) else {
+            continue;
+        };
+        if Path::new(raw.as_str()).is_relative() {
+            let dir = file.parent().unwrap_or(Path::new(""));
+            *raw = dir.join(raw.as_str()).to_string_lossy().into_owned();
+        }
+    }
+}
 
-    if !T::cli_specs().is_empty() {
-        lines.push(String::new());
-        lines.push("Options:".to_string());
-        for spec in T::cli_specs() {
-            let flag = format_flag(spec.flag, spec.takes_value);
-            let mut detail = format_doc(spec.doc).unwrap_or_default();
-            if let Some(def) = spec.default {
-                if !detail.is_empty() {
-                    detail.push(' ');
-                }
-                detail.push_str(&format!("[default: {def}]"));
+fn normalize_bytes(path: &str, value: &mut Value, errs: &mut ValidationErrors) {
+    match value {
+        Value::String(raw) => match parse_byte_size(raw) {
+            Ok(bytes) => *value = Value::Number(bytes.into()),
+            Err(message) => errs.push(Issue::new(path.to_string(), IssueKind::Type, message)),
+        },
+        Value::Array(items) => {
+            for (idx, item) in items.iter_mut().enumerate() {
+                normalize_bytes(&format!("{path}[{idx}]"), item, errs);
             }
-            if spec.required {
-                if !detail.is_empty() {
-                    detail.push(' ');
+        }
+        _ => {}
+    }
+}
+
+/// Report every value whose JSON type does not match its field's kind.
+fn check_types(
+    root: &Value,
+    fields: &[FieldSpec],
+    prefix: &str,
+    report: &Report,
+    errs: &mut ValidationErrors,
+) {
+    for spec in fields {
+        let Some(value) = value_at(root, spec.path) else {
+            continue;
+        };
+        let path = format!("{prefix}{}", spec.path);
+        if spec.list {
+            match value {
+                Value::Array(items) => {
+                    for (idx, item) in items.iter().enumerate() {
+                        check_type(&format!("{path}[{idx}]"), spec.kind, item, report, errs);
+                    }
                 }
-                detail.push_str("(required)");
+                Value::Null => {}
+                other => push_type_issue(&path, "list", other, report, errs),
             }
-            let detail_trimmed = detail.trim().to_string();
-            if detail_trimmed.is_empty() {
-                lines.push(format!("  {}", flag));
-            } else {
-                lines.push(format!("  {:<24} {}", flag, detail_trimmed));
+        } else {
+            check_type(&path, spec.kind, value, report, errs);
+        }
+    }
+}
+
+fn check_type(path: &str, kind: Kind, value: &Value, report: &Report, errs: &mut ValidationErrors) {
+    let ok = match (kind, value) {
+        (_, Value::Null) | (Kind::Any | Kind::Custom, _) => true,
+        (Kind::Bool, Value::Bool(_)) => true,
+        (Kind::Int | Kind::Bytes, Value::Number(n)) => n.is_i64() || n.is_u64(),
+        (Kind::Float, Value::Number(_)) => true,
+        (
+            Kind::String
+            | Kind::Path
+            | Kind::IpAddr
+            | Kind::SocketAddr
+            | Kind::DateTime
+            | Kind::Date,
+            Value::String(_),
+        ) => true,
+        (Kind::Object, Value::Object(_)) => true,
+        _ => false,
+    };
+    if !ok {
+        push_type_issue(path, kind.name(), value, report, errs);
+    }
+}
+
+fn reject_nulls(paths: &[&str], prefix: &str, report: &Report, errs: &mut ValidationErrors) {
+    for path in paths {
+        let field = format!("{prefix}{path}");
+        let origin = report.provenance.describe(&field);
+        let mut issue = Issue::new(
+            field,
+            IssueKind::Type,
+            "cannot be null: the field is not optional and has no default",
+        )
+        .with_actual("null");
+        if let Some(origin) = origin {
+            issue = issue.with_origin(origin);
+        }
+        errs.push(issue);
+    }
+}
+
+fn push_type_issue(
+    path: &str,
+    expected: &str,
+    found: &Value,
+    report: &Report,
+    errs: &mut ValidationErrors,
+) {
+    let found = match found {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => format!("bool {b}"),
+        Value::Number(n) => format!("number {n}"),
+        Value::String(s) => format!("string {s:?}"),
+        Value::Array(_) => "list".to_string(),
+        Value::Object(_) => "object".to_string(),
+    };
+    let mut issue = Issue::new(
+        path,
+        IssueKind::Type,
+        format!("expected {expected}, found {found}"),
+    )
+    .with_expected(expected)
+    .with_actual(found);
+    if let Some(origin) = report.provenance.describe(path) {
+        issue = issue.with_origin(origin);
+    }
+    errs.push(issue);
+}
+
+fn check_required(
+    meta: &Meta,
+    value: &Value,
+    command: Option<&CommandSpec>,
+    errs: &mut ValidationErrors,
+) {
+    for path in missing_required(meta, value, command) {
+        let message = meta
+            .fields
+            .iter()
+            .find(|spec| spec.path == path)
+            .and_then(|spec| required_reason(spec, value))
+            .unwrap_or_else(|| "required field missing".into());
+        errs.push(Issue::new(path.to_string(), IssueKind::Missing, message));
+    }
+}
+
+/// Required fields unset in `value`, skipping those of unselected commands.
+/// Includes fields whose `required_if` or `required_unless` condition holds.
+fn missing_required(
+    meta: &Meta,
+    value: &Value,
+    command: Option<&CommandSpec>,
+) -> Vec<&'static str> {
+    let inactive = |path: &str| {
+        meta.commands.iter().any(|cmd| {
+            path.strip_prefix(cmd.path)
+                .is_some_and(|rest| rest.starts_with('.'))
+                && command.is_none_or(|selected| selected.path != cmd.path)
+        })
+    };
+    let conditional = meta
+        .fields
+        .iter()
+        .filter(|spec| required_reason(spec, value).is_some())
+        .map(|spec| spec.path);
+    meta.required
+        .iter()
+        .copied()
+        .chain(conditional)
+        .filter(|path| !has_value_at(value, path) && !inactive(path))
+        .collect()
+}
+
+/// Issues for the fields of a list element whose `required_if` or
+/// `required_unless` condition holds but that are unset.
+fn check_conditional(
+    fields: &[FieldSpec],
+    item: &Value,
+    prefix: &str,
+    errs: &mut ValidationErrors,
+) {
+    for spec in fields {
+        if has_value_at(item, spec.path) {
+            continue;
+        }
+        if let Some(message) = required_reason(spec, item) {
+            errs.push(Issue::new(
+                format!("{prefix}{}", spec.path),
+                IssueKind::Missing,
+                message,
+            ));
+        }
+    }
+}
+
+/// Why `spec` is required in `value` by its `required_if` or
+/// `required_unless` condition, if the condition holds.
+fn required_reason(spec: &FieldSpec, value: &Value) -> Option<String> {
+    if spec.required {
+        return None;
+    }
+    let is_set = |path: &str| {
+        value_at(value, path).is_some_and(|v| !matches!(v, Value::Null | Value::Bool(false)))
+    };
+    if let Some(path) = spec.required_if.filter(|path| is_set(path)) {
+        return Some(format!("required when `{path}` is set"));
+    }
+    if let Some(path) = spec.required_unless.filter(|path| !is_set(path)) {
+        return Some(format!("required unless `{path}` is set"));
+    }
+    None
+}
+
+/// How many answers a field gets before it is left missing.
+const PROMPT_ATTEMPTS: usize = 3;
+
+fn prompt_missing(
+    meta: &Meta,
+    acc: &mut Value,
+    command: Option<&CommandSpec>,
+    prompter: &dyn Prompter,
+    report: &mut Report,
+) -> Result<(), CnfgError> {
+    for path in missing_required(meta, acc, command) {
+        let Some(spec) = meta.fields.iter().find(|spec| spec.path == path) else {
+            continue;
+        };
+        let mut error: Option<String> = None;
+        for _ in 0..PROMPT_ATTEMPTS {
+            let answer = prompter
+                .ask(spec, error.as_deref())
+                .map_err(|e| CnfgError::Source(format!("prompt for {path}: {e}")))?;
+            let Some(answer) = answer else {
+                break;
+            };
+            let raw = match (answer.trim(), spec.default) {
+                ("", Some(default)) => default,
+                ("", None) => {
+                    error = Some("a value is required".into());
+                    continue;
+                }
+                (raw, _) => raw,
+            };
+            match parse_for_spec(raw, spec) {
+                Ok(parsed) => {
+                    insert_path(acc, &spec.segments(), parsed)
+                        .map_err(|e| CnfgError::Source(format!("prompt for {path}: {e}")))?;
+                    report.provenance.record(spec.path, Origin::Prompt);
+                    break;
+                }
+                Err(msg) => error = Some(msg),
             }
         }
     }
+    Ok(())
+}
 
-    lines.join("\n").trim_end().to_string()
+/// Serialize `cfg` and render it for `fields` in the requested format.
+fn render_fields<T: Serialize>(
+    fields: &[FieldSpec],
+    cfg: &T,
+    options: SaveOptions,
+) -> Result<String, CnfgError> {
+    let value = serde_json::to_value(cfg).map_err(|e| CnfgError::Serialize(e.to_string()))?;
+    save::render_fields(fields, &value, options)
 }
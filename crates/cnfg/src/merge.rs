@@ -1,24 +1,220 @@
+use crate::error::PathError;
+use crate::types::FieldSpec;
 use serde_json::{Map, Value};
 
+/// How a higher-priority layer combines with a lower one for a field
+/// (from `#[cnfg(merge = "...")]`).
+///
+/// Without a strategy, objects merge recursively and everything else,
+/// arrays included, is overwritten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// The higher layer's value replaces the lower one wholesale, maps included.
+    Replace,
+    /// Arrays are concatenated, lower layer first.
+    Append,
+    /// Arrays are concatenated, skipping elements already present.
+    Union,
+}
+
 /// Deep merge `other` into `base`.
 ///
 /// - Objects are merged recursively.
 /// - Non-objects overwrite.
 /// - `other` always takes precedence over `base`.
+/// - `null` overwrites too; [`resolve_nulls`] later decides what it means.
 pub fn merge(base: &mut Value, override_val: Value) {
-    match (base, override_val) {
-        (Value::Object(base_map), Value::Object(override_map)) => {
+    merge_with(base, override_val, &[]);
+}
+
+/// Deep merge `other` into `base`, honouring each field's
+/// [`MergeStrategy`] from `fields`.
+pub fn merge_with(base: &mut Value, override_val: Value, fields: &[FieldSpec]) {
+    // Most configs declare no strategies; skip tracking paths for them.
+    if fields.iter().all(|spec| spec.merge.is_none()) {
+        merge_at(base, override_val, "", &[]);
+    } else {
+        merge_at(base, override_val, "", fields);
+    }
+}
+
+fn merge_at(base: &mut Value, override_val: Value, path: &str, fields: &[FieldSpec]) {
+    let strategy = fields
+        .iter()
+        .find(|spec| !path.is_empty() && spec.path == path)
+        .and_then(|spec| spec.merge);
+    match (strategy, base, override_val) {
+        (Some(MergeStrategy::Replace), slot, v) => {
+            *slot = v;
+        }
+        (Some(strategy), Value::Array(items), Value::Array(extra)) => {
+            for v in extra {
+                if strategy == MergeStrategy::Union && items.contains(&v) {
+                    continue;
+                }
+                items.push(v);
+            }
+        }
+        (_, Value::Object(base_map), Value::Object(override_map)) => {
             for (k, v) in override_map {
-                merge(base_map.entry(k).or_insert(Value::Null), v);
+                let child = match (fields.is_empty(), path.is_empty()) {
+                    (true, _) => String::new(),
+                    (false, true) => k.clone(),
+                    (false, false) => format!("{path}.{k}"),
+                };
+                merge_at(base_map.entry(k).or_insert(Value::Null), v, &child, fields);
             }
         }
-        (slot, v) => {
+        (_, slot, v) => {
             *slot = v;
         }
     }
 }
 
-/// Insert a nested value into a JSON object given a dotted path.
+/// Apply `null` semantics once every layer is merged.
+///
+/// A `null` from any layer unsets the field: `Option` fields become `None`,
+/// and other fields fall back to their value in `defaults`. Paths of fields
+/// that have no default to fall back to are returned so callers can reject
+/// them. Required fields are left alone; they fail as missing.
+pub fn resolve_nulls(
+    value: &mut Value,
+    defaults: &Value,
+    fields: &[FieldSpec],
+) -> Vec<&'static str> {
+    let mut rejected = Vec::new();
+    // Parents precede their children in `fields`, so a restored section is
+    // checked again field by field.
+    for spec in fields {
+        if spec.optional || spec.required {
+            continue;
+        }
+        let Some(slot) = value_at_mut(value, spec.path).filter(|slot| slot.is_null()) else {
+            continue;
+        };
+        match value_at(defaults, spec.path).filter(|default| !default.is_null()) {
+            Some(default) => *slot = default.clone(),
+            None => rejected.push(spec.path),
+        }
+    }
+    rejected
+}
+
+/// The value at a dotted path such as [`FieldSpec::path`]; a path that
+/// cannot be followed reads as missing.
+pub(crate) fn value_at<'a>(root: &'a Value, path: &str) -> Option<&'a Value> {
+    get_path(root, &dotted(path)).ok().flatten()
+}
+
+pub(crate) fn value_at_mut<'a>(root: &'a mut Value, path: &str) -> Option<&'a mut Value> {
+    get_path_mut(root, &dotted(path)).ok().flatten()
+}
+
+/// Whether a dotted path holds a non-`null` value.
+pub(crate) fn has_value_at(root: &Value, path: &str) -> bool {
+    value_at(root, path).is_some_and(|value| !value.is_null())
+}
+
+/// Remove the value at a dotted path, pruning parents left empty.
+pub(crate) fn take_at(root: &mut Value, path: &str) -> Option<Value> {
+    let segments = dotted(path);
+    let taken = remove_path(root, &segments).ok().flatten()?;
+    for end in (1..segments.len()).rev() {
+        let parent = &segments[..end];
+        let emptied = get_path(root, parent)
+            .ok()
+            .flatten()
+            .and_then(Value::as_object);
+        if !emptied.is_some_and(Map::is_empty) {
+            break;
+        }
+        let _ = remove_path(root, parent);
+    }
+    Some(taken)
+}
+
+fn dotted(path: &str) -> Vec<&str> {
+    path.split('.').collect()
+}
+
+/// One step of a path: an object key or an array index.
+#[derive(Clone, Copy)]
+enum Step<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Split segments like `servers[0]` into their key and index steps.
+fn steps<'a>(path: &[&'a str]) -> Result<Vec<Step<'a>>, PathError> {
+    if path.is_empty() {
+        return Err(PathError::Empty);
+    }
+    let mut steps = Vec::with_capacity(path.len());
+    for segment in path {
+        let invalid = || PathError::InvalidSegment((*segment).to_string());
+        let (key, mut rest) = segment.split_at(segment.find('[').unwrap_or(segment.len()));
+        if !key.is_empty() {
+            steps.push(Step::Key(key));
+        } else if rest.is_empty() {
+            return Err(invalid());
+        }
+        while !rest.is_empty() {
+            let (index, tail) = rest
+                .strip_prefix('[')
+                .and_then(|inner| inner.split_once(']'))
+                .ok_or_else(invalid)?;
+            steps.push(Step::Index(index.parse().map_err(|_| invalid())?));
+            rest = tail;
+        }
+    }
+    Ok(steps)
+}
+
+/// Render steps as `a.b[0]` for error messages.
+fn describe(steps: &[Step<'_>]) -> String {
+    let mut out = String::new();
+    for step in steps {
+        match step {
+            Step::Key(key) if out.is_empty() => out.push_str(key),
+            Step::Key(key) => {
+                out.push('.');
+                out.push_str(key);
+            }
+            Step::Index(index) => out.push_str(&format!("[{index}]")),
+        }
+    }
+    out
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+fn mismatch(steps: &[Step<'_>], at: usize, found: &Value) -> PathError {
+    let expected = match steps[at] {
+        Step::Key(_) => "an object",
+        Step::Index(_) => "an array",
+    };
+    PathError::Mismatch {
+        at: describe(&steps[..at]),
+        expected,
+        found: type_name(found),
+    }
+}
+
+/// Insert a value into nested objects and arrays.
+///
+/// Each segment is a key, optionally followed by indices (`servers[0]`).
+/// Missing or `null` containers are created along the way; an index equal
+/// to an array's length appends. Anything else in the way is an error
+/// rather than being overwritten.
 ///
 /// Example:
 /// ```rust
@@ -26,40 +222,134 @@ pub fn merge(base: &mut Value, override_val: Value) {
 /// use cnfg::merge::insert_path;
 ///
 /// let mut obj = json!({});
-/// insert_path(&mut obj, &["database", "url"], json!("postgres://..."));
+/// insert_path(&mut obj, &["database", "url"], json!("postgres://...")).unwrap();
+/// insert_path(&mut obj, &["servers[0]", "host"], json!("a.internal")).unwrap();
 /// assert_eq!(obj["database"]["url"], "postgres://...");
+/// assert_eq!(obj["servers"][0]["host"], "a.internal");
+///
+/// let err = insert_path(&mut obj, &["database", "url", "scheme"], json!("x"));
+/// assert!(err.is_err());
 /// ```
-pub fn insert_path(root: &mut Value, path: &[&str], value: Value) {
-    // Split off the last segment — that’s where we’ll insert the actual `value`.
-    let (last_key, parents) = path.split_last().expect("path must not be empty");
-
-    // Navigate down to the parent object.
+pub fn insert_path(root: &mut Value, path: &[&str], value: Value) -> Result<(), PathError> {
+    let steps = steps(path)?;
     let mut current = root;
-    for part in parents {
-        // Ensure `current` is an object
-        if !current.get(*part).is_some() {
-            if let Value::Object(map) = current {
-                map.insert((*part).to_string(), Value::Object(Map::new()));
-            } else {
-                let mut map = Map::new();
-                map.insert((*part).to_string(), Value::Object(Map::new()));
-                *current = Value::Object(map);
+    for (at, step) in steps.iter().enumerate() {
+        if current.is_null() {
+            *current = match step {
+                Step::Key(_) => Value::Object(Map::new()),
+                Step::Index(_) => Value::Array(Vec::new()),
+            };
+        }
+        let last = at + 1 == steps.len();
+        current = match (step, current) {
+            (Step::Key(key), Value::Object(map)) => {
+                if last {
+                    map.insert((*key).to_string(), value);
+                    return Ok(());
+                }
+                map.entry(*key).or_insert(Value::Null)
+            }
+            (Step::Index(index), Value::Array(items)) => {
+                let len = items.len();
+                if *index > len {
+                    return Err(PathError::OutOfBounds {
+                        at: describe(&steps[..=at]),
+                        len,
+                    });
+                }
+                if *index == len {
+                    items.push(Value::Null);
+                }
+                if last {
+                    items[*index] = value;
+                    return Ok(());
+                }
+                &mut items[*index]
             }
+            (_, other) => return Err(mismatch(&steps, at, other)),
+        };
+    }
+    unreachable!("the last step returns")
+}
+
+/// Look up the value at a path written as for [`insert_path`].
+///
+/// ```rust
+/// use serde_json::json;
+/// use cnfg::merge::get_path;
+///
+/// let doc = json!({"servers": [{"host": "a"}, {"host": "b"}]});
+/// assert_eq!(get_path(&doc, &["servers[1]", "host"]).unwrap(), Some(&json!("b")));
+/// assert_eq!(get_path(&doc, &["servers[2]", "host"]).unwrap(), None);
+/// ```
+///
+/// Missing keys and indices are `Ok(None)`; a malformed path or a value of
+/// the wrong kind along the way is an error.
+pub fn get_path<'a>(root: &'a Value, path: &[&str]) -> Result<Option<&'a Value>, PathError> {
+    let steps = steps(path)?;
+    let mut current = root;
+    for (at, step) in steps.iter().enumerate() {
+        let next = match (step, current) {
+            (Step::Key(key), Value::Object(map)) => map.get(*key),
+            (Step::Index(index), Value::Array(items)) => items.get(*index),
+            (_, Value::Null) => None,
+            (_, other) => return Err(mismatch(&steps, at, other)),
+        };
+        match next {
+            Some(next) => current = next,
+            None => return Ok(None),
         }
+    }
+    Ok(Some(current))
+}
 
-        // Descend one level
-        current = current
-            .as_object_mut()
-            .and_then(|map| map.get_mut(*part))
-            .unwrap();
+/// Remove and return the value at a path written as for [`insert_path`].
+///
+/// Removing an array element shifts the ones after it. Parents are kept
+/// even when left empty.
+pub fn remove_path(root: &mut Value, path: &[&str]) -> Result<Option<Value>, PathError> {
+    let steps = steps(path)?;
+    let (last, parents) = steps.split_last().expect("steps are never empty");
+    let Some(current) = walk_mut(root, &steps, parents.len())? else {
+        return Ok(None);
+    };
+    match (last, current) {
+        (Step::Key(key), Value::Object(map)) => Ok(map.remove(*key)),
+        (Step::Index(index), Value::Array(items)) => {
+            Ok((*index < items.len()).then(|| items.remove(*index)))
+        }
+        (_, Value::Null) => Ok(None),
+        (_, other) => Err(mismatch(&steps, parents.len(), other)),
     }
+}
 
-    // Now insert the `value` at the last key (only moved once here)
-    if let Value::Object(map) = current {
-        map.insert(last_key.to_string(), value);
-    } else {
-        let mut map = Map::new();
-        map.insert(last_key.to_string(), value);
-        *current = Value::Object(map);
+/// Mutable counterpart of [`get_path`].
+pub(crate) fn get_path_mut<'a>(
+    root: &'a mut Value,
+    path: &[&str],
+) -> Result<Option<&'a mut Value>, PathError> {
+    let steps = steps(path)?;
+    walk_mut(root, &steps, steps.len())
+}
+
+/// Follow the first `count` steps mutably.
+fn walk_mut<'a>(
+    root: &'a mut Value,
+    steps: &[Step<'_>],
+    count: usize,
+) -> Result<Option<&'a mut Value>, PathError> {
+    let mut current = root;
+    for (at, step) in steps[..count].iter().enumerate() {
+        let next = match (step, current) {
+            (Step::Key(key), Value::Object(map)) => map.get_mut(*key),
+            (Step::Index(index), Value::Array(items)) => items.get_mut(*index),
+            (_, Value::Null) => None,
+            (_, other) => return Err(mismatch(steps, at, other)),
+        };
+        match next {
+            Some(next) => current = next,
+            None => return Ok(None),
+        }
     }
+    Ok(Some(current))
 }
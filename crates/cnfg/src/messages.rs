@@ -0,0 +1,95 @@
+//! Rendering validation issues as text, in English or your own language.
+//!
+//! An [`Issue`] keeps its facts apart from its wording: the
+//! [`IssueKind`], `expected` and `actual`, and named `params` such as `min`,
+//! `max`, `pattern`, or `origin` (the layer that set the value). Its
+//! `message` is the English wording, which [`English`] returns and
+//! `Display` uses.
+//!
+//! To localize, implement [`MessageRenderer`] or fill in [`Templates`]:
+//!
+//! ```ignore
+//! let french = Templates::new()
+//!     .set(IssueKind::Missing, "champ obligatoire")
+//!     .set(IssueKind::Range, "doit être dans {expected}, trouvé {actual}");
+//! for line in errs.render(&french) {
+//!     eprintln!("{line}");
+//! }
+//! ```
+
+use crate::error::{Issue, IssueKind};
+use std::collections::HashMap;
+
+/// Turns an issue into the text shown to users.
+pub trait MessageRenderer {
+    fn render(&self, issue: &Issue) -> String;
+}
+
+/// The built-in English wording: the issue's `message`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct English;
+
+impl MessageRenderer for English {
+    fn render(&self, issue: &Issue) -> String {
+        issue.message.clone()
+    }
+}
+
+/// A template per [`IssueKind`], falling back to [`English`] for kinds
+/// without one. Placeholders are filled by [`fill`].
+#[derive(Debug, Clone, Default)]
+pub struct Templates {
+    templates: HashMap<IssueKind, String>,
+}
+
+impl Templates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `template` for issues of `kind`.
+    pub fn set(mut self, kind: IssueKind, template: impl Into<String>) -> Self {
+        self.templates.insert(kind, template.into());
+        self
+    }
+}
+
+impl MessageRenderer for Templates {
+    fn render(&self, issue: &Issue) -> String {
+        match self.templates.get(&issue.kind) {
+            Some(template) => fill(template, issue),
+            None => English.render(issue),
+        }
+    }
+}
+
+/// Replace `{field}`, `{expected}`, `{actual}`, `{message}`, and
+/// `{<param>}` in `template` with the issue's values. Placeholders the
+/// issue has no value for are left as written.
+pub fn fill(template: &str, issue: &Issue) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = &after[..end];
+        let value = match name {
+            "field" => Some(issue.field.as_str()),
+            "expected" => issue.expected.as_deref(),
+            "actual" => issue.actual.as_deref(),
+            "message" => Some(issue.message.as_str()),
+            param => issue.params.get(param).map(String::as_str),
+        };
+        match value {
+            Some(value) => out.push_str(value),
+            None => out.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
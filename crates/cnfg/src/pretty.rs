@@ -0,0 +1,221 @@
+//! Multi-line, optionally colored rendering of [`CnfgError`] and
+//! [`ValidationErrors`] for terminals.
+//!
+//! Each issue gets its own block naming the field, the problem, and the
+//! layer that supplied the value; file errors name the file, and parse
+//! errors show the position reported by the parser. Color is used when
+//! stderr is a terminal and `NO_COLOR` is unset, and can be forced either
+//! way with [`Pretty::color`]. Issues are worded by [`English`] unless
+//! another [`Pretty::renderer`] is given.
+
+use crate::error::{CnfgError, Issue, ValidationErrors};
+use crate::messages::{English, MessageRenderer};
+use std::fmt;
+use std::io::IsTerminal;
+use std::path::Path;
+
+const RED: &str = "\x1b[1;31m";
+const BOLD: &str = "\x1b[1m";
+const CYAN: &str = "\x1b[36m";
+const DIM: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// Display adapter returned by [`CnfgError::pretty`] and
+/// [`ValidationErrors::pretty`].
+pub struct Pretty<'a> {
+    target: Target<'a>,
+    color: bool,
+    renderer: &'a dyn MessageRenderer,
+}
+
+enum Target<'a> {
+    Error(&'a CnfgError),
+    Issues(&'a ValidationErrors),
+}
+
+/// Whether output to stderr should be colored: it is a terminal and
+/// `NO_COLOR` is unset or empty.
+pub fn color_enabled() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    !no_color && std::io::stderr().is_terminal()
+}
+
+impl<'a> Pretty<'a> {
+    fn new(target: Target<'a>) -> Self {
+        Self {
+            target,
+            color: color_enabled(),
+            renderer: &English,
+        }
+    }
+
+    /// Force color on or off instead of detecting it.
+    pub fn color(mut self, enabled: bool) -> Self {
+        self.color = enabled;
+        self
+    }
+
+    /// Word issues with `renderer`, e.g. a localized
+    /// [`Templates`](crate::messages::Templates).
+    pub fn renderer(mut self, renderer: &'a dyn MessageRenderer) -> Self {
+        self.renderer = renderer;
+        self
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("{style}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn header(&self, f: &mut fmt::Formatter<'_>, title: &str) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {}",
+            self.paint(RED, "error"),
+            self.paint(BOLD, title)
+        )
+    }
+
+    fn issues(&self, f: &mut fmt::Formatter<'_>, errs: &ValidationErrors) -> fmt::Result {
+        let title = match errs.len() {
+            1 => "invalid configuration (1 issue)".to_string(),
+            n => format!("invalid configuration ({n} issues)"),
+        };
+        self.header(f, &title)?;
+        for issue in errs {
+            self.issue(f, issue)?;
+        }
+        Ok(())
+    }
+
+    fn issue(&self, f: &mut fmt::Formatter<'_>, issue: &Issue) -> fmt::Result {
+        let text = self.renderer.render(issue);
+        let (message, origin) = split_origin(&text);
+        let origin = origin.or(issue.params.get("origin").map(String::as_str));
+        writeln!(
+            f,
+            "  {} {}: {message}",
+            self.paint(RED, "×"),
+            self.paint(BOLD, &issue.field)
+        )?;
+        if let Some(origin) = origin {
+            writeln!(
+                f,
+                "    {} {}",
+                self.paint(DIM, "╰─ set by"),
+                self.paint(CYAN, origin)
+            )?;
+        }
+        Ok(())
+    }
+
+    fn detail(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        title: &str,
+        file: Option<&Path>,
+        position: Option<(usize, usize)>,
+        message: &str,
+    ) -> fmt::Result {
+        self.header(f, title)?;
+        if let Some(file) = file {
+            writeln!(
+                f,
+                "  {} {}",
+                self.paint(DIM, "in"),
+                self.paint(CYAN, &file.display().to_string())
+            )?;
+        }
+        if let Some((line, column)) = position {
+            writeln!(
+                f,
+                "  {} {}",
+                self.paint(DIM, "at"),
+                self.paint(CYAN, &format!("line {line}, column {column}"))
+            )?;
+        }
+        for line in message.lines() {
+            writeln!(f, "  {line}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Pretty<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.target {
+            Target::Issues(errs) => self.issues(f, errs),
+            Target::Error(CnfgError::Validation(errs)) => self.issues(f, errs),
+            Target::Error(err) => {
+                let (title, position, message) = describe(err.root());
+                self.detail(f, title, err.path(), position, &message)
+            }
+        }
+    }
+}
+
+/// Split a trailing ` (from <origin>)` added by the loader off a message.
+fn split_origin(message: &str) -> (&str, Option<&str>) {
+    message
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once(" (from "))
+        .map_or((message, None), |(message, origin)| (message, Some(origin)))
+}
+
+/// Title, parser-reported position, and detail message for non-validation errors.
+fn describe(err: &CnfgError) -> (&'static str, Option<(usize, usize)>, String) {
+    match err {
+        CnfgError::Io(e) => ("could not read configuration", None, e.to_string()),
+        // The TOML message already quotes the offending line with its position.
+        #[cfg(feature = "toml")]
+        CnfgError::ParseToml(e) => ("invalid TOML", None, e.to_string()),
+        CnfgError::ParseJson(e) => {
+            let position = (e.line() > 0).then(|| (e.line(), e.column()));
+            ("invalid JSON", position, e.to_string())
+        }
+        #[cfg(feature = "yaml")]
+        CnfgError::ParseYaml(e) => {
+            let position = e.location().map(|loc| (loc.line(), loc.column()));
+            ("invalid YAML", position, e.to_string())
+        }
+        #[cfg(feature = "json5")]
+        CnfgError::ParseJson5(e) => {
+            let json5::Error::Message { msg, location } = e;
+            let position = location.as_ref().map(|loc| (loc.line, loc.column));
+            ("invalid JSON5", position, msg.clone())
+        }
+        #[cfg(feature = "ron")]
+        CnfgError::ParseRon(e) => {
+            let position = (e.position.line > 0).then_some((e.position.line, e.position.col));
+            ("invalid RON", position, e.code.to_string())
+        }
+        #[cfg(feature = "hcl")]
+        CnfgError::ParseHcl(e) => ("invalid HCL", None, e.to_string()),
+        CnfgError::ParseIni(msg) => ("invalid INI", None, msg.clone()),
+        CnfgError::ParseProperties(msg) => ("invalid properties file", None, msg.clone()),
+        CnfgError::Cli(msg) => ("invalid command-line arguments", None, msg.clone()),
+        CnfgError::Env(msg) => ("invalid environment variable", None, msg.clone()),
+        CnfgError::Source(msg) => ("configuration source failed", None, msg.clone()),
+        CnfgError::Serialize(msg) => ("could not render configuration", None, msg.clone()),
+        CnfgError::Decrypt(msg) => ("could not decrypt configuration", None, msg.clone()),
+        CnfgError::Include(msg) => ("could not include configuration", None, msg.clone()),
+        other => ("configuration not loaded", None, other.to_string()),
+    }
+}
+
+impl CnfgError {
+    /// Multi-line rendering for terminals; see [`crate::pretty`].
+    pub fn pretty(&self) -> Pretty<'_> {
+        Pretty::new(Target::Error(self))
+    }
+}
+
+impl ValidationErrors {
+    /// Multi-line rendering for terminals; see [`crate::pretty`].
+    pub fn pretty(&self) -> Pretty<'_> {
+        Pretty::new(Target::Issues(self))
+    }
+}
@@ -0,0 +1,74 @@
+//! Asking for missing required fields instead of failing, for
+//! [`Loader::interactive`](crate::Loader::interactive).
+//!
+//! After every layer is merged, each required field that is still unset is
+//! offered to a [`Prompter`]. Answers are parsed like environment variables
+//! (comma-separated for lists) and recorded as [`Origin::Prompt`](crate::Origin::Prompt).
+//! An empty answer keeps the field's default when it has one; otherwise the
+//! field stays missing and is reported as usual.
+
+use crate::types::FieldSpec;
+use crate::util::format_doc;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Asks the user for the value of one field.
+pub trait Prompter: Send + Sync {
+    /// Whether prompting is possible right now; when `false` the loader
+    /// reports missing fields without asking.
+    fn available(&self) -> bool {
+        true
+    }
+
+    /// Ask for `field`. `error` explains why the previous answer was rejected.
+    ///
+    /// Return `Ok(None)` when the user gives up (e.g. end of input).
+    fn ask(&self, field: &FieldSpec, error: Option<&str>) -> io::Result<Option<String>>;
+}
+
+/// Prompts on stderr and reads answers from stdin. Secret fields are read
+/// without echo.
+///
+/// Only available when both stdin and stderr are terminals.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TerminalPrompter;
+
+impl Prompter for TerminalPrompter {
+    fn available(&self) -> bool {
+        io::stdin().is_terminal() && io::stderr().is_terminal()
+    }
+
+    fn ask(&self, field: &FieldSpec, error: Option<&str>) -> io::Result<Option<String>> {
+        let mut stderr = io::stderr();
+        if let Some(error) = error {
+            writeln!(stderr, "  {error}")?;
+        }
+        let question = question(field);
+        if field.secret {
+            return match rpassword::prompt_password(question) {
+                Ok(answer) => Ok(Some(answer)),
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+                Err(e) => Err(e),
+            };
+        }
+        write!(stderr, "{question}")?;
+        stderr.flush()?;
+        let mut line = String::new();
+        if io::stdin().lock().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim_end_matches(['\n', '\r']).to_string()))
+    }
+}
+
+/// `path (doc) [default]: `
+pub fn question(field: &FieldSpec) -> String {
+    let mut question = field.path.to_string();
+    if let Some(doc) = format_doc(field.doc) {
+        question.push_str(&format!(" ({doc})"));
+    }
+    if let Some(default) = field.default {
+        question.push_str(&format!(" [{default}]"));
+    }
+    question.push_str(": ");
+    question
+}
@@ -0,0 +1,146 @@
+//! Tracking which layer supplied each config value.
+
+use crate::spans::Location;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+/// The layer a value came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Origin {
+    /// `#[cnfg(default = ...)]`, `default_fn`, or a nested struct's defaults.
+    Default,
+    /// A config file.
+    File(PathBuf),
+    /// A config document piped to standard input (`--config -`).
+    Stdin,
+    /// A [`Source`](crate::sources::Source), by name.
+    Source(String),
+    /// An environment variable, by name.
+    Env(String),
+    /// A CLI flag, e.g. `--port`.
+    Cli(String),
+    /// An answer typed at an interactive prompt (see [`crate::prompt`]).
+    Prompt,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Origin::Default => write!(f, "default"),
+            Origin::File(path) => write!(f, "file {}", path.display()),
+            Origin::Stdin => write!(f, "stdin"),
+            Origin::Source(name) => write!(f, "source {name}"),
+            Origin::Env(name) => write!(f, "env {name}"),
+            Origin::Cli(flag) => write!(f, "flag {flag}"),
+            Origin::Prompt => write!(f, "prompt"),
+        }
+    }
+}
+
+/// Map from dotted path to the layer that last set it, plus where in the
+/// file each file-sourced key was written.
+#[derive(Debug, Default, Clone)]
+pub struct Provenance {
+    origins: BTreeMap<String, Origin>,
+    locations: BTreeMap<String, Location>,
+}
+
+impl Provenance {
+    /// Origin of the value at `path`, falling back to the nearest recorded
+    /// ancestor (e.g. a list element reports the origin of its list).
+    pub fn get(&self, path: &str) -> Option<&Origin> {
+        let mut current = path;
+        loop {
+            if let Some(origin) = self.origins.get(current) {
+                return Some(origin);
+            }
+            let cut = current.rfind(['.', '['])?;
+            current = &current[..cut];
+        }
+    }
+
+    /// All recorded leaf paths and their origins, sorted by path.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Origin)> {
+        self.origins
+            .iter()
+            .map(|(path, origin)| (path.as_str(), origin))
+    }
+
+    /// Line and column of the value at `path` (or its nearest located
+    /// ancestor) when it came from a file.
+    pub fn location(&self, path: &str) -> Option<Location> {
+        if !matches!(self.get(path), Some(Origin::File(_) | Origin::Stdin)) {
+            return None;
+        }
+        let mut current = path;
+        loop {
+            if let Some(location) = self.locations.get(current) {
+                return Some(*location);
+            }
+            let cut = current.rfind(['.', '['])?;
+            current = &current[..cut];
+        }
+    }
+
+    /// Origin of `path` for messages, e.g. `file config.toml:3:7` or
+    /// `env APP_PORT`.
+    pub fn describe(&self, path: &str) -> Option<String> {
+        let origin = self.get(path)?;
+        Some(match self.location(path) {
+            Some(location) => format!("{origin}:{location}"),
+            None => origin.to_string(),
+        })
+    }
+
+    /// Record `origin` for a single path.
+    pub fn record(&mut self, path: &str, origin: Origin) {
+        // Positions of an overridden value (and anything below it) are stale.
+        let stale: Vec<String> = self
+            .locations
+            .range(path.to_string()..)
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(path))
+            .filter(|key| {
+                key.len() == path.len() || matches!(key.as_bytes()[path.len()], b'.' | b'[')
+            })
+            .cloned()
+            .collect();
+        for key in stale {
+            self.locations.remove(&key);
+        }
+        self.origins.insert(path.to_string(), origin);
+    }
+
+    /// Record where in its file each path was written; see [`crate::spans`].
+    pub fn record_locations(&mut self, locations: BTreeMap<String, Location>) {
+        self.locations.extend(locations);
+    }
+
+    /// Record `origin` for every leaf of `value` (arrays count as leaves).
+    pub fn record_value(&mut self, value: &Value, origin: &Origin) {
+        fn walk(value: &Value, path: &mut String, leaves: &mut Vec<String>) {
+            match value {
+                Value::Object(entries) if !entries.is_empty() => {
+                    for (key, child) in entries {
+                        let len = path.len();
+                        if !path.is_empty() {
+                            path.push('.');
+                        }
+                        path.push_str(key);
+                        walk(child, path, leaves);
+                        path.truncate(len);
+                    }
+                }
+                _ if path.is_empty() => {}
+                _ => leaves.push(path.clone()),
+            }
+        }
+        let mut leaves = Vec::new();
+        walk(value, &mut String::new(), &mut leaves);
+        for path in leaves {
+            self.record(&path, origin.clone());
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Non-fatal diagnostics collected while loading.
+
+use crate::document::Document;
+use crate::provenance::Provenance;
+use std::fmt;
+
+/// A non-fatal issue noticed during loading.
+#[derive(Debug, Clone)]
+pub struct Warning {
+    /// Dotted field path, env var, or file the warning refers to.
+    pub subject: String,
+    pub kind: WarningKind,
+    pub message: String,
+}
+
+/// The type of load warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    /// A config file contained a key that maps to no declared field.
+    UnknownKey,
+    /// An environment variable was set to an empty string, which is used as is.
+    EmptyEnv,
+    /// A decision made while discovering config files.
+    FileDiscovery,
+    /// A deprecated field or renamed key was used.
+    Deprecated,
+    /// An environment variable under the env prefix maps to no field.
+    UnusedEnv,
+    /// A source kept failing and its last known value was used instead
+    /// (see [`RetryPolicy::fail_open`](crate::sources::retry::RetryPolicy::fail_open)).
+    StaleSource,
+    /// A flag was ignored because the field's env var takes precedence
+    /// ([`Precedence::EnvOverCli`](crate::Precedence::EnvOverCli)).
+    Shadowed,
+    /// A validation issue with [`Severity::Warning`](crate::error::Severity::Warning).
+    Validation,
+}
+
+/// Aggregated warnings from a single load.
+#[derive(Debug, Default, Clone)]
+pub struct Warnings {
+    items: Vec<Warning>,
+}
+
+impl Warnings {
+    pub fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    pub fn push(
+        &mut self,
+        kind: WarningKind,
+        subject: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.items.push(Warning {
+            subject: subject.into(),
+            kind,
+            message: message.into(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Warning> {
+        self.items.iter()
+    }
+
+    pub fn into_vec(self) -> Vec<Warning> {
+        self.items
+    }
+}
+
+impl fmt::Display for Warnings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.items.is_empty() {
+            return write!(f, "no warnings");
+        }
+        writeln!(f, "warnings:")?;
+        for warning in &self.items {
+            writeln!(f, "  - {} — {}", warning.subject, warning.message)?;
+        }
+        Ok(())
+    }
+}
+
+/// Everything learned while loading, returned by `load_with_report()`.
+#[derive(Debug, Default, Clone)]
+pub struct Report {
+    pub warnings: Warnings,
+    /// Which layer supplied each value.
+    pub provenance: Provenance,
+    /// The merged values the config was deserialized from.
+    pub document: Document,
+}
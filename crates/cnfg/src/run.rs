@@ -0,0 +1,58 @@
+//! Standard `main` handling.
+//!
+//! [`run`] loads a config and hands it to the rest of the program, exiting
+//! the way CLIs are expected to when loading stops early:
+//!
+//! * `--help`, `--version`, a passing `--check`, `--dump-env`, and `--init`
+//!   exit with 0; their output is already written.
+//! * A failing `--check` exits with the error code.
+//! * Any other error is printed with [`CnfgError::pretty`] to stderr before
+//!   exiting with the error code ([`ERROR_EXIT_CODE`] unless set with
+//!   [`Loader::exit_code`](crate::Loader::exit_code)).
+//!
+//! ```ignore
+//! fn main() {
+//!     cnfg::run::<AppConfig, _>(|cfg| serve(cfg));
+//! }
+//! ```
+
+use crate::error::CnfgError;
+use crate::loader::LoaderExt;
+use serde::de::DeserializeOwned;
+
+/// Exit code for failed loads unless the loader sets another.
+pub const ERROR_EXIT_CODE: i32 = 1;
+
+/// Load `T` and call `f` with it, exiting the process if loading stops
+/// early; see the [module docs](self).
+///
+/// Use [`Loader::run`](crate::Loader::run) to customise the loader first.
+pub fn run<T, R>(f: impl FnOnce(T) -> R) -> R
+where
+    T: LoaderExt + DeserializeOwned,
+{
+    T::loader().run(f)
+}
+
+/// Print `err` if it has not been reported yet and return the exit code
+/// for it: 0 for help, version, passing checks, `--dump-env`, and
+/// `--init`, else `error_code`.
+pub fn handle(err: &CnfgError, error_code: i32) -> i32 {
+    match err {
+        CnfgError::HelpPrinted
+        | CnfgError::VersionPrinted
+        | CnfgError::CheckPassed
+        | CnfgError::EnvPrinted
+        | CnfgError::Initialized(_) => 0,
+        CnfgError::CheckFailed => error_code,
+        err => {
+            eprint!("{}", err.pretty());
+            error_code
+        }
+    }
+}
+
+/// [`handle`] `err`, then exit with its code.
+pub fn exit(err: CnfgError, error_code: i32) -> ! {
+    std::process::exit(handle(&err, error_code))
+}
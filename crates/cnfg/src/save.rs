@@ -0,0 +1,303 @@
+//! Writing a loaded config back to disk.
+//!
+//! Output follows the declaration order of [`ConfigMeta::field_specs`]
+//! rather than serde's alphabetical map order, so a generated file reads
+//! like the struct it came from. Keys without a matching field (maps,
+//! `#[serde(flatten)]` contents) follow in sorted order.
+
+use crate::error::CnfgError;
+use crate::types::{ConfigMeta, FieldSpec};
+use crate::util::format_doc;
+use serde_json::Value;
+use std::path::Path;
+
+/// File formats [`LoaderExt::save_to`](crate::LoaderExt::save_to) can write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl Format {
+    /// Guess the format from a file extension (`.toml`, `.yaml`/`.yml`, `.json`).
+    pub fn from_path(path: &Path) -> Option<Format> {
+        match path.extension()?.to_str()? {
+            "toml" => Some(Format::Toml),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+}
+
+/// How [`LoaderExt::save_to`](crate::LoaderExt::save_to) renders a config.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SaveOptions {
+    pub format: Format,
+    /// Emit field doc comments as `#` comments (TOML and YAML only).
+    pub docs: bool,
+}
+
+impl SaveOptions {
+    pub fn new(format: Format) -> Self {
+        Self {
+            format,
+            docs: false,
+        }
+    }
+
+    /// Toggle doc comment output.
+    pub fn docs(mut self, enabled: bool) -> Self {
+        self.docs = enabled;
+        self
+    }
+}
+
+impl From<Format> for SaveOptions {
+    fn from(format: Format) -> Self {
+        SaveOptions::new(format)
+    }
+}
+
+/// An object whose entries are ordered by field declaration.
+struct Table {
+    entries: Vec<Entry>,
+}
+
+struct Entry {
+    key: String,
+    // Only the comment-capable writers (TOML, YAML) read docs.
+    #[cfg_attr(not(any(feature = "toml", feature = "yaml")), allow(dead_code))]
+    doc: Option<String>,
+    node: Node,
+}
+
+enum Node {
+    Leaf(Value),
+    Table(Table),
+}
+
+/// Render `value` (the serialized config) for `T` in the requested format.
+pub fn render<T: ConfigMeta>(value: &Value, options: SaveOptions) -> Result<String, CnfgError> {
+    render_fields(T::field_specs(), value, options)
+}
+
+/// Like [`render`], ordering and documenting keys by `fields`.
+pub(crate) fn render_fields(
+    fields: &[FieldSpec],
+    value: &Value,
+    options: SaveOptions,
+) -> Result<String, CnfgError> {
+    let Value::Object(_) = value else {
+        return Err(CnfgError::Serialize(
+            "config must serialize to an object".into(),
+        ));
+    };
+    let table = build_table(fields, value, "");
+    let mut out = String::new();
+    match options.format {
+        Format::Json => {
+            write_json(&table, 0, &mut out)?;
+            out.push('\n');
+        }
+        Format::Yaml => write_yaml(&table, 0, options.docs, &mut out)?,
+        Format::Toml => write_toml(&table, "", options.docs, &mut out)?,
+    }
+    Ok(out)
+}
+
+fn build_table(fields: &[FieldSpec], value: &Value, prefix: &str) -> Table {
+    let Value::Object(map) = value else {
+        unreachable!("build_table called on a non-object");
+    };
+    let path_of = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{prefix}.{key}")
+        }
+    };
+
+    let mut keys: Vec<&String> = map.keys().collect();
+    let position = |key: &str| {
+        let path = path_of(key);
+        fields
+            .iter()
+            .position(|spec| spec.path == path)
+            .unwrap_or(usize::MAX)
+    };
+    // Stable sort keeps serde_json's alphabetical order for unknown keys.
+    keys.sort_by_key(|key| position(key));
+
+    let entries = keys
+        .into_iter()
+        .map(|key| {
+            let path = path_of(key);
+            let spec = fields.iter().find(|spec| spec.path == path);
+            let child = &map[key];
+            let has_children = fields
+                .iter()
+                .any(|spec| spec.path.starts_with(&format!("{path}.")));
+            let node = if child.is_object() && has_children {
+                Node::Table(build_table(fields, child, &path))
+            } else {
+                Node::Leaf(child.clone())
+            };
+            Entry {
+                key: key.clone(),
+                doc: spec.and_then(|spec| format_doc(spec.doc)),
+                node,
+            }
+        })
+        .collect();
+    Table { entries }
+}
+
+fn write_json(table: &Table, indent: usize, out: &mut String) -> Result<(), CnfgError> {
+    if table.entries.is_empty() {
+        out.push_str("{}");
+        return Ok(());
+    }
+    let pad = "  ".repeat(indent + 1);
+    out.push_str("{\n");
+    for (idx, entry) in table.entries.iter().enumerate() {
+        out.push_str(&pad);
+        out.push_str(&serde_json::to_string(&entry.key).map_err(serialize_error)?);
+        out.push_str(": ");
+        match &entry.node {
+            Node::Table(child) => write_json(child, indent + 1, out)?,
+            Node::Leaf(value) => {
+                let rendered = serde_json::to_string_pretty(value).map_err(serialize_error)?;
+                out.push_str(&rendered.replace('\n', &format!("\n{pad}")));
+            }
+        }
+        if idx + 1 < table.entries.len() {
+            out.push(',');
+        }
+        out.push('\n');
+    }
+    out.push_str(&"  ".repeat(indent));
+    out.push('}');
+    Ok(())
+}
+
+#[cfg(feature = "yaml")]
+fn write_yaml(table: &Table, indent: usize, docs: bool, out: &mut String) -> Result<(), CnfgError> {
+    let pad = "  ".repeat(indent);
+    for entry in &table.entries {
+        if docs {
+            if let Some(doc) = &entry.doc {
+                out.push_str(&format!("{pad}# {doc}\n"));
+            }
+        }
+        let key = yaml_scalar(&Value::String(entry.key.clone()))?;
+        match &entry.node {
+            Node::Table(child) if !child.entries.is_empty() => {
+                out.push_str(&format!("{pad}{key}:\n"));
+                write_yaml(child, indent + 1, docs, out)?;
+            }
+            Node::Table(_) => out.push_str(&format!("{pad}{key}: {{}}\n")),
+            Node::Leaf(value) if is_block(value) => {
+                out.push_str(&format!("{pad}{key}:\n"));
+                let rendered = serde_yaml::to_string(value).map_err(serialize_error)?;
+                for line in rendered.lines() {
+                    out.push_str(&format!("{pad}  {line}\n"));
+                }
+            }
+            Node::Leaf(value) => out.push_str(&format!("{pad}{key}: {}\n", yaml_scalar(value)?)),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "yaml"))]
+fn write_yaml(_: &Table, _: usize, _: bool, _: &mut String) -> Result<(), CnfgError> {
+    Err(CnfgError::Serialize(
+        "yaml support disabled but attempted to write YAML".into(),
+    ))
+}
+
+#[cfg(feature = "yaml")]
+fn is_block(value: &Value) -> bool {
+    match value {
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(feature = "yaml")]
+fn yaml_scalar(value: &Value) -> Result<String, CnfgError> {
+    Ok(serde_yaml::to_string(value)
+        .map_err(serialize_error)?
+        .trim_end()
+        .to_string())
+}
+
+#[cfg(feature = "toml")]
+fn write_toml(table: &Table, path: &str, docs: bool, out: &mut String) -> Result<(), CnfgError> {
+    // TOML requires a table's plain keys to precede its sub-tables.
+    for entry in &table.entries {
+        let Node::Leaf(value) = &entry.node else {
+            continue;
+        };
+        if value.is_null() {
+            // TOML has no null; absent keys deserialize back to `None`.
+            continue;
+        }
+        if docs {
+            if let Some(doc) = &entry.doc {
+                out.push_str(&format!("# {doc}\n"));
+            }
+        }
+        let rendered = toml::Value::try_from(value)
+            .map_err(|e| CnfgError::Serialize(format!("{}: {e}", entry.key)))?;
+        out.push_str(&format!("{} = {rendered}\n", toml_key(&entry.key)));
+    }
+    for entry in &table.entries {
+        let Node::Table(child) = &entry.node else {
+            continue;
+        };
+        let child_path = if path.is_empty() {
+            toml_key(&entry.key)
+        } else {
+            format!("{path}.{}", toml_key(&entry.key))
+        };
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        if docs {
+            if let Some(doc) = &entry.doc {
+                out.push_str(&format!("# {doc}\n"));
+            }
+        }
+        out.push_str(&format!("[{child_path}]\n"));
+        write_toml(child, &child_path, docs, out)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "toml"))]
+fn write_toml(_: &Table, _: &str, _: bool, _: &mut String) -> Result<(), CnfgError> {
+    Err(CnfgError::Serialize(
+        "toml support disabled but attempted to write TOML".into(),
+    ))
+}
+
+#[cfg(feature = "toml")]
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        toml::Value::String(key.to_string()).to_string()
+    }
+}
+
+fn serialize_error(e: impl std::fmt::Display) -> CnfgError {
+    CnfgError::Serialize(e.to_string())
+}
@@ -0,0 +1,144 @@
+//! Hierarchical view of a config type's metadata.
+//!
+//! [`ConfigMeta::field_specs`](crate::ConfigMeta::field_specs) flattens
+//! nested structs into dotted paths. [`Schema`] keeps the nesting: each
+//! nested section carries the doc comment of its type and its own children,
+//! which is what doc generators, UIs, and schema exporters need.
+
+use crate::types::{CliSpec, ConfigMeta, FieldSpec};
+
+/// The fields and sections of one config type, in declaration order.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    /// Doc comment of the struct.
+    pub doc: Option<&'static str>,
+    pub nodes: Vec<Node>,
+}
+
+/// One entry of a [`Schema`].
+#[derive(Debug, Clone)]
+pub enum Node {
+    Field(Field),
+    Section(Section),
+}
+
+/// A leaf value.
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub spec: FieldSpec,
+    /// The flag bound to the field with `#[cnfg(cli)]`, if any.
+    pub cli: Option<CliSpec>,
+}
+
+/// A field whose value is another config type.
+#[derive(Debug, Clone)]
+pub struct Section {
+    /// Spec of the field holding the section (kind [`Kind::Object`](crate::Kind::Object)).
+    pub spec: FieldSpec,
+    pub kind: SectionKind,
+    /// Schema of the nested type. Paths and flags are absolute for
+    /// [`SectionKind::Nested`], and relative to one element or to the
+    /// command's section otherwise.
+    pub schema: Schema,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SectionKind {
+    /// `#[cnfg(nested)]`
+    Nested,
+    /// `Vec<Nested>` marked `#[cnfg(nested)]`
+    List,
+    /// `#[cnfg(command)]`, selected by the given positional name.
+    Command(&'static str),
+}
+
+/// Callbacks for [`Schema::visit`]. Every method defaults to doing nothing.
+pub trait Visitor {
+    fn visit_field(&mut self, field: &Field) {
+        let _ = field;
+    }
+
+    /// Called before the section's children are visited.
+    fn enter_section(&mut self, section: &Section) {
+        let _ = section;
+    }
+
+    /// Called after the section's children are visited.
+    fn leave_section(&mut self, section: &Section) {
+        let _ = section;
+    }
+}
+
+impl Schema {
+    /// Every field of `T` as a leaf, for [`ConfigMeta`] impls that do not
+    /// describe their sections.
+    pub fn flat<T: ConfigMeta>() -> Self {
+        let cli_specs = T::cli_specs();
+        let nodes = T::field_specs()
+            .iter()
+            .map(|spec| {
+                Node::Field(Field {
+                    spec: spec.clone(),
+                    cli: cli_specs.iter().find(|cli| cli.path == spec.path).cloned(),
+                })
+            })
+            .collect();
+        Self {
+            doc: T::doc(),
+            nodes,
+        }
+    }
+
+    /// Walk the tree depth-first in declaration order.
+    pub fn visit(&self, visitor: &mut impl Visitor) {
+        for node in &self.nodes {
+            match node {
+                Node::Field(field) => visitor.visit_field(field),
+                Node::Section(section) => {
+                    visitor.enter_section(section);
+                    section.schema.visit(visitor);
+                    visitor.leave_section(section);
+                }
+            }
+        }
+    }
+
+    /// Look up a field or section by dotted path. Elements of lists and
+    /// commands are not searched.
+    pub fn find(&self, path: &str) -> Option<&Node> {
+        self.nodes.iter().find_map(|node| match node {
+            Node::Field(field) if field.spec.path == path => Some(node),
+            Node::Section(section) if section.spec.path == path => Some(node),
+            Node::Section(section) if section.kind == SectionKind::Nested => {
+                section.schema.find(path)
+            }
+            _ => None,
+        })
+    }
+
+    /// Produce a copy with `prefix` applied to every absolute path and flag.
+    pub fn with_prefix(&self, prefix: &'static str) -> Self {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| match node {
+                Node::Field(field) => Node::Field(Field {
+                    spec: field.spec.with_prefix(prefix),
+                    cli: field.cli.as_ref().map(|cli| cli.with_prefix(prefix)),
+                }),
+                Node::Section(section) => Node::Section(Section {
+                    spec: section.spec.with_prefix(prefix),
+                    kind: section.kind,
+                    schema: match section.kind {
+                        SectionKind::Nested => section.schema.with_prefix(prefix),
+                        _ => section.schema.clone(),
+                    },
+                }),
+            })
+            .collect();
+        Self {
+            doc: self.doc,
+            nodes,
+        }
+    }
+}
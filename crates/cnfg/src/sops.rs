@@ -0,0 +1,78 @@
+//! sops-encrypted config files.
+//!
+//! A YAML or JSON config file carrying sops metadata (a top-level `sops`
+//! map with `mac` and `version`) is decrypted before it is parsed and
+//! merged, so encrypted secrets can live in git next to plaintext config.
+//! Provenance and source locations point at the encrypted file.
+//!
+//! Decryption goes through a [`SopsDecryptor`]. The default,
+//! [`SopsCommand`], runs `sops --decrypt`, which handles every key backend
+//! sops supports (age, PGP, AWS/GCP KMS, Azure Key Vault, Vault) with its
+//! usual configuration (`SOPS_AGE_KEY_FILE`, cloud credentials, ...). Plug
+//! in a native implementation with [`Loader::sops`](crate::Loader::sops).
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Turns a sops-encrypted file into plaintext.
+pub trait SopsDecryptor: Send + Sync {
+    /// Decrypt the file at `path`, whose contents are `data`. Returns the
+    /// plaintext document in the same format, without the `sops` metadata.
+    fn decrypt(&self, path: &Path, data: &str) -> Result<String, String>;
+}
+
+/// Decrypts by running the `sops` binary.
+#[derive(Debug, Clone)]
+pub struct SopsCommand {
+    program: PathBuf,
+    env: Vec<(String, String)>,
+}
+
+impl Default for SopsCommand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SopsCommand {
+    /// Run `sops` from `PATH`.
+    pub fn new() -> Self {
+        Self {
+            program: PathBuf::from("sops"),
+            env: Vec::new(),
+        }
+    }
+
+    /// Run this binary instead.
+    pub fn program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.program = program.into();
+        self
+    }
+
+    /// Set an environment variable for sops, e.g. `SOPS_AGE_KEY_FILE`.
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+}
+
+impl SopsDecryptor for SopsCommand {
+    fn decrypt(&self, path: &Path, _data: &str) -> Result<String, String> {
+        let output = Command::new(&self.program)
+            .arg("--decrypt")
+            .arg(path)
+            .envs(self.env.iter().map(|(k, v)| (k, v)))
+            .output()
+            .map_err(|e| format!("could not run {}: {e}", self.program.display()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "{} --decrypt failed ({}): {}",
+                self.program.display(),
+                output.status,
+                stderr.trim()
+            ));
+        }
+        String::from_utf8(output.stdout).map_err(|_| "sops output is not UTF-8".to_string())
+    }
+}
@@ -0,0 +1,179 @@
+//! AWS Systems Manager Parameter Store source.
+//!
+//! Parameters under a path prefix map onto dotted config paths:
+//! `/myapp/prod/database/port` with prefix `/myapp/prod` becomes `database.port`.
+//!
+//! The AWS SDK is async and heavyweight, so the source talks to Parameter Store
+//! through the small [`SsmClient`] trait. Implement it over your SDK client:
+//!
+//! ```rust,ignore
+//! struct Sdk(aws_sdk_ssm::Client, tokio::runtime::Handle);
+//!
+//! impl SsmClient for Sdk {
+//!     fn get_parameters_by_path(&self, req: &PageRequest<'_>) -> Result<ParameterPage, String> {
+//!         let out = self.1.block_on(
+//!             self.0
+//!                 .get_parameters_by_path()
+//!                 .path(req.path)
+//!                 .recursive(true)
+//!                 .with_decryption(req.with_decryption)
+//!                 .set_next_token(req.next_token.map(str::to_string))
+//!                 .send(),
+//!         ).map_err(|e| e.to_string())?;
+//!         Ok(ParameterPage {
+//!             parameters: out.parameters().iter().map(|p| Parameter {
+//!                 name: p.name().unwrap_or_default().to_string(),
+//!                 value: p.value().unwrap_or_default().to_string(),
+//!             }).collect(),
+//!             next_token: out.next_token().map(str::to_string),
+//!         })
+//!     }
+//! }
+//! ```
+
+use super::{Source, insert_raw};
+use crate::error::CnfgError;
+use crate::types::FieldSpec;
+use serde_json::Value;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A single Parameter Store entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parameter {
+    /// Full parameter name, e.g. `/myapp/prod/database/port`.
+    pub name: String,
+    /// Parameter value (decrypted for `SecureString` when requested).
+    pub value: String,
+}
+
+/// One page of a `GetParametersByPath` response.
+#[derive(Debug, Clone, Default)]
+pub struct ParameterPage {
+    pub parameters: Vec<Parameter>,
+    pub next_token: Option<String>,
+}
+
+/// Arguments for one `GetParametersByPath` call (always recursive).
+#[derive(Debug, Clone, Copy)]
+pub struct PageRequest<'a> {
+    pub path: &'a str,
+    pub with_decryption: bool,
+    pub next_token: Option<&'a str>,
+}
+
+/// Minimal Parameter Store client used by [`SsmSource`].
+pub trait SsmClient: Send + Sync {
+    fn get_parameters_by_path(&self, request: &PageRequest<'_>) -> Result<ParameterPage, String>;
+}
+
+/// Source reading every parameter under a path prefix.
+pub struct SsmSource<C> {
+    client: C,
+    prefix: String,
+    with_decryption: bool,
+    cache_ttl: Option<Duration>,
+    refresh_interval: Option<Duration>,
+    cache: Mutex<Option<(Instant, Vec<Parameter>)>>,
+}
+
+impl<C: SsmClient> SsmSource<C> {
+    /// Read parameters below `prefix` (e.g. `/myapp/prod`), decrypting `SecureString`s.
+    pub fn new(client: C, prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+        let prefix = format!("/{}", prefix.trim_matches('/'));
+        Self {
+            client,
+            prefix,
+            with_decryption: true,
+            cache_ttl: None,
+            refresh_interval: None,
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// Whether to request decryption of `SecureString` parameters (default: `true`).
+    pub fn with_decryption(mut self, enabled: bool) -> Self {
+        self.with_decryption = enabled;
+        self
+    }
+
+    /// Reuse fetched parameters for `ttl` instead of calling SSM on every load.
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    /// How often the watch subsystem should re-poll Parameter Store.
+    pub fn refresh_every(mut self, interval: Duration) -> Self {
+        self.refresh_interval = Some(interval);
+        self
+    }
+
+    /// Dotted config path for a parameter name, if it lies under the prefix.
+    pub fn path_for(&self, name: &str) -> Option<String> {
+        let rest = name.strip_prefix(&self.prefix)?;
+        if !rest.is_empty() && !rest.starts_with('/') {
+            return None;
+        }
+        let path = rest
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>()
+            .join(".");
+        (!path.is_empty()).then_some(path)
+    }
+
+    fn fetch(&self) -> Result<Vec<Parameter>, CnfgError> {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let (Some(ttl), Some((fetched_at, params))) = (self.cache_ttl, cache.as_ref()) {
+            if fetched_at.elapsed() < ttl {
+                return Ok(params.clone());
+            }
+        }
+
+        let mut params = Vec::new();
+        let mut next_token: Option<String> = None;
+        loop {
+            let request = PageRequest {
+                path: &self.prefix,
+                with_decryption: self.with_decryption,
+                next_token: next_token.as_deref(),
+            };
+            let page = self
+                .client
+                .get_parameters_by_path(&request)
+                .map_err(|msg| CnfgError::Source(format!("{}: {msg}", self.name())))?;
+            params.extend(page.parameters);
+            match page.next_token {
+                Some(token) if !token.is_empty() => next_token = Some(token),
+                _ => break,
+            }
+        }
+
+        *cache = Some((Instant::now(), params.clone()));
+        Ok(params)
+    }
+}
+
+impl<C: SsmClient> Source for SsmSource<C> {
+    fn name(&self) -> String {
+        format!("aws-ssm:{}", self.prefix)
+    }
+
+    fn load(&self, fields: &[FieldSpec]) -> Result<Value, CnfgError> {
+        let mut root = Value::Object(Default::default());
+        for param in self.fetch()? {
+            let Some(path) = self.path_for(&param.name) else {
+                continue;
+            };
+            insert_raw(&mut root, fields, &path, &param.value)
+                .map_err(|msg| CnfgError::Source(format!("{}: {msg}", self.name())))?;
+        }
+        Ok(root)
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        self.refresh_interval
+    }
+}
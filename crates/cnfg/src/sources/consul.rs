@@ -0,0 +1,205 @@
+//! Consul KV source.
+//!
+//! Keys under a prefix map onto dotted config paths, one folder per level:
+//! `myapp/prod/database/port` with prefix `myapp/prod` becomes
+//! `database.port`. Folder entries (keys ending in `/`) are skipped, and
+//! values are coerced with the target field's type.
+//!
+//! The source talks to Consul's HTTP API through the small [`ConsulClient`]
+//! trait, so any HTTP client works. Implement it over yours:
+//!
+//! ```rust,ignore
+//! struct Http(reqwest::blocking::Client, String);
+//!
+//! impl ConsulClient for Http {
+//!     fn list(&self, req: &KvRequest<'_>) -> Result<KvResponse, String> {
+//!         let mut call = self.0.get(format!("{}/v1/kv/{}", self.1, req.prefix))
+//!             .query(&[("recurse", "true")]);
+//!         if let Some(dc) = req.datacenter { call = call.query(&[("dc", dc)]); }
+//!         if let Some(token) = req.token { call = call.header("X-Consul-Token", token); }
+//!         if let Some((index, wait)) = req.blocking {
+//!             call = call.query(&[("index", index.to_string()), ("wait", format!("{}ms", wait.as_millis()))]);
+//!         }
+//!         let resp = call.send().map_err(|e| e.to_string())?;
+//!         let index = resp.headers()["X-Consul-Index"].to_str().unwrap_or("0").parse().unwrap_or(0);
+//!         if resp.status() == 404 {
+//!             return Ok(KvResponse { pairs: Vec::new(), index });
+//!         }
+//!         let entries: Vec<serde_json::Value> = resp.json().map_err(|e| e.to_string())?;
+//!         Ok(KvResponse {
+//!             index,
+//!             pairs: entries.iter().map(|e| KvPair {
+//!                 key: e["Key"].as_str().unwrap_or_default().to_string(),
+//!                 value: e["Value"].as_str().map(decode_base64),
+//!             }).collect(),
+//!         })
+//!     }
+//! }
+//! ```
+//!
+//! With [`Loader::watch`](crate::Loader::watch), the source waits on
+//! blocking queries, so changes are picked up as soon as Consul reports
+//! them.
+
+use super::{Source, insert_raw};
+use crate::error::CnfgError;
+use crate::types::FieldSpec;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// A single KV entry, with its value already base64-decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KvPair {
+    /// Full key, e.g. `myapp/prod/database/port`.
+    pub key: String,
+    /// `None` for folder entries and keys without a value.
+    pub value: Option<String>,
+}
+
+/// Result of a recursive KV read.
+#[derive(Debug, Clone, Default)]
+pub struct KvResponse {
+    pub pairs: Vec<KvPair>,
+    /// The `X-Consul-Index` header.
+    pub index: u64,
+}
+
+/// Arguments for one recursive `GET /v1/kv/<prefix>` call.
+#[derive(Debug, Clone, Copy)]
+pub struct KvRequest<'a> {
+    pub prefix: &'a str,
+    /// `dc` query parameter.
+    pub datacenter: Option<&'a str>,
+    /// `X-Consul-Token` header.
+    pub token: Option<&'a str>,
+    /// `index` and `wait` parameters of a blocking query: return once the
+    /// index moves past the given one, or when the wait passes.
+    pub blocking: Option<(u64, Duration)>,
+}
+
+/// Minimal Consul KV client used by [`ConsulSource`]. A missing prefix
+/// (HTTP 404) should be an empty response, not an error.
+pub trait ConsulClient: Send + Sync {
+    fn list(&self, request: &KvRequest<'_>) -> Result<KvResponse, String>;
+}
+
+/// Source reading every key under a KV prefix.
+pub struct ConsulSource<C> {
+    client: C,
+    prefix: String,
+    datacenter: Option<String>,
+    token: Option<String>,
+    watch: bool,
+    index: AtomicU64,
+}
+
+impl<C: ConsulClient> ConsulSource<C> {
+    /// Read keys below `prefix` (e.g. `myapp/prod`).
+    pub fn new(client: C, prefix: impl Into<String>) -> Self {
+        Self {
+            client,
+            prefix: prefix.into().trim_matches('/').to_string(),
+            datacenter: None,
+            token: None,
+            watch: true,
+            index: AtomicU64::new(0),
+        }
+    }
+
+    /// Read from this datacenter instead of the agent's own.
+    pub fn datacenter(mut self, datacenter: impl Into<String>) -> Self {
+        self.datacenter = Some(datacenter.into());
+        self
+    }
+
+    /// ACL token sent with every request.
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Whether [`Loader::watch`](crate::Loader::watch) waits on blocking
+    /// queries for changes (default: `true`).
+    pub fn watch_changes(mut self, enabled: bool) -> Self {
+        self.watch = enabled;
+        self
+    }
+
+    /// Dotted config path for a key, if it lies under the prefix and is not
+    /// a folder.
+    pub fn path_for(&self, key: &str) -> Option<String> {
+        if key.ends_with('/') {
+            return None;
+        }
+        let rest = match self.prefix.as_str() {
+            "" => key,
+            prefix => key.strip_prefix(prefix)?.strip_prefix('/')?,
+        };
+        let path = rest
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>()
+            .join(".");
+        (!path.is_empty()).then_some(path)
+    }
+
+    /// `X-Consul-Index` of the last response.
+    pub fn index(&self) -> u64 {
+        self.index.load(Ordering::SeqCst)
+    }
+
+    fn list(&self, blocking: Option<(u64, Duration)>) -> Result<KvResponse, CnfgError> {
+        // The trailing slash keeps `app` from matching `application`.
+        let prefix = match self.prefix.as_str() {
+            "" => String::new(),
+            prefix => format!("{prefix}/"),
+        };
+        let request = KvRequest {
+            prefix: &prefix,
+            datacenter: self.datacenter.as_deref(),
+            token: self.token.as_deref(),
+            blocking,
+        };
+        self.client.list(&request).map_err(|msg| self.error(msg))
+    }
+
+    fn error(&self, msg: impl std::fmt::Display) -> CnfgError {
+        CnfgError::Source(format!("{}: {msg}", self.name()))
+    }
+}
+
+impl<C: ConsulClient> Source for ConsulSource<C> {
+    fn name(&self) -> String {
+        match &self.datacenter {
+            Some(dc) => format!("consul:{dc}/{}", self.prefix),
+            None => format!("consul:{}", self.prefix),
+        }
+    }
+
+    fn load(&self, fields: &[FieldSpec]) -> Result<Value, CnfgError> {
+        let response = self.list(None)?;
+        let mut root = Value::Object(Default::default());
+        for pair in &response.pairs {
+            let (Some(path), Some(value)) = (self.path_for(&pair.key), &pair.value) else {
+                continue;
+            };
+            insert_raw(&mut root, fields, &path, value).map_err(|msg| self.error(msg))?;
+        }
+        self.index.store(response.index, Ordering::SeqCst);
+        Ok(root)
+    }
+
+    fn notifies_changes(&self) -> bool {
+        self.watch
+    }
+
+    fn wait_for_change(&self, timeout: Duration) -> Result<bool, CnfgError> {
+        let last = self.index();
+        let response = self.list(Some((last, timeout)))?;
+        // Consul may also reset the index to a lower value; treat any
+        // movement as a change.
+        self.index.store(response.index, Ordering::SeqCst);
+        Ok(response.index != last)
+    }
+}
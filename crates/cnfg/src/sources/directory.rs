@@ -0,0 +1,82 @@
+//! Key-per-file directory source (Kubernetes ConfigMap/Secret volume mounts).
+//!
+//! Every regular file in the directory is one value: the file name is the
+//! dotted config path (`database.port`) and the content is the value, with
+//! trailing newlines stripped. Kubelet's bookkeeping entries (`..data`,
+//! `..2024_01_01_...`) and other dot-files are skipped. Because kubelet swaps
+//! the `..data` symlink atomically, reloading through
+//! [`Loader::watch`](crate::Loader::watch) always sees a consistent set.
+
+use super::{Source, insert_raw};
+use crate::error::CnfgError;
+use crate::types::FieldSpec;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+
+/// Source reading one value per file from a directory.
+#[derive(Debug, Clone)]
+pub struct DirectorySource {
+    dir: PathBuf,
+    optional: bool,
+}
+
+impl DirectorySource {
+    /// Read values from the files in `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            optional: false,
+        }
+    }
+
+    /// Treat a missing directory as empty instead of an error.
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+}
+
+impl Source for DirectorySource {
+    fn name(&self) -> String {
+        format!("dir:{}", self.dir.display())
+    }
+
+    fn load(&self, fields: &[FieldSpec]) -> Result<Value, CnfgError> {
+        let mut root = Value::Object(Default::default());
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if self.optional && e.kind() == std::io::ErrorKind::NotFound => return Ok(root),
+            Err(e) => return Err(CnfgError::Source(format!("{}: {e}", self.name()))),
+        };
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| CnfgError::Source(format!("{}: {e}", self.name())))?;
+            let Ok(key) = entry.file_name().into_string() else {
+                continue;
+            };
+            // `is_file` follows kubelet's symlinks to the real file.
+            if key.starts_with('.') || !entry.path().is_file() {
+                continue;
+            }
+            files.push((key, entry.path()));
+        }
+        files.sort();
+
+        for (key, path) in files {
+            let contents = fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(crate::util::decode_text)
+                .map_err(|e| CnfgError::Source(format!("{}: {key}: {e}", self.name())))?;
+            insert_raw(
+                &mut root,
+                fields,
+                &key,
+                contents.trim_end_matches(['\n', '\r']),
+            )
+            .map_err(|msg| CnfgError::Source(format!("{}: {msg}", self.name())))?;
+        }
+        Ok(root)
+    }
+}
@@ -0,0 +1,192 @@
+//! etcd v3 key-value source.
+//!
+//! Keys under a prefix map onto dotted config paths:
+//! `/myapp/prod/database/port` with prefix `/myapp/prod` becomes
+//! `database.port`. Values are read as UTF-8 strings and coerced with the
+//! target field's type.
+//!
+//! etcd clients are async and pull in gRPC, so the source talks to etcd
+//! through the small [`EtcdClient`] trait. Implement it over your client:
+//!
+//! ```rust,ignore
+//! struct Etcd(etcd_client::KvClient, etcd_client::WatchClient, tokio::runtime::Handle);
+//!
+//! impl EtcdClient for Etcd {
+//!     fn get_prefix(&self, prefix: &str) -> Result<RangeResponse, String> {
+//!         let opts = etcd_client::GetOptions::new().with_prefix();
+//!         let out = self.2.block_on(self.0.clone().get(prefix, Some(opts)))
+//!             .map_err(|e| e.to_string())?;
+//!         Ok(RangeResponse {
+//!             revision: out.header().map_or(0, |h| h.revision()),
+//!             kvs: out.kvs().iter().map(|kv| KeyValue {
+//!                 key: kv.key_str().unwrap_or_default().to_string(),
+//!                 value: kv.value_str().unwrap_or_default().to_string(),
+//!             }).collect(),
+//!         })
+//!     }
+//!
+//!     fn watch_prefix(&self, req: &WatchRequest<'_>) -> Result<Option<i64>, String> {
+//!         let opts = etcd_client::WatchOptions::new()
+//!             .with_prefix()
+//!             .with_start_revision(req.start_revision);
+//!         self.2.block_on(async {
+//!             let (_watcher, mut stream) = self.1.clone().watch(req.prefix, Some(opts)).await
+//!                 .map_err(|e| e.to_string())?;
+//!             match tokio::time::timeout(req.timeout, stream.message()).await {
+//!                 Ok(message) => Ok(message
+//!                     .map_err(|e| e.to_string())?
+//!                     .and_then(|resp| resp.events().iter().filter_map(|e| e.kv()).map(|kv| kv.mod_revision()).max())),
+//!                 Err(_) => Ok(None),
+//!             }
+//!         })
+//!     }
+//! }
+//! ```
+//!
+//! With [`Loader::watch`](crate::Loader::watch), the source listens through
+//! [`EtcdClient::watch_prefix`] and changes are picked up without polling.
+
+use super::{Source, insert_raw};
+use crate::error::CnfgError;
+use crate::types::FieldSpec;
+use serde_json::Value;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+
+/// A single etcd entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyValue {
+    /// Full key, e.g. `/myapp/prod/database/port`.
+    pub key: String,
+    pub value: String,
+}
+
+/// Result of a prefix `Range` request.
+#[derive(Debug, Clone, Default)]
+pub struct RangeResponse {
+    pub kvs: Vec<KeyValue>,
+    /// Store revision the response reflects (from the response header).
+    pub revision: i64,
+}
+
+/// Arguments for one [`EtcdClient::watch_prefix`] call.
+#[derive(Debug, Clone, Copy)]
+pub struct WatchRequest<'a> {
+    pub prefix: &'a str,
+    /// First revision to report events for (one past the last load).
+    pub start_revision: i64,
+    /// How long to wait for the first event.
+    pub timeout: Duration,
+}
+
+/// Minimal etcd v3 client used by [`EtcdSource`].
+pub trait EtcdClient: Send + Sync {
+    fn get_prefix(&self, prefix: &str) -> Result<RangeResponse, String>;
+
+    /// Watch keys under the prefix from `start_revision`, blocking until
+    /// events arrive or the timeout passes. Returns the revision of the
+    /// latest event, or `None` on timeout.
+    fn watch_prefix(&self, request: &WatchRequest<'_>) -> Result<Option<i64>, String>;
+}
+
+/// Source reading every key under a prefix.
+pub struct EtcdSource<C> {
+    client: C,
+    prefix: String,
+    watch: bool,
+    revision: AtomicI64,
+}
+
+impl<C: EtcdClient> EtcdSource<C> {
+    /// Read keys below `prefix` (e.g. `/myapp/prod`).
+    pub fn new(client: C, prefix: impl Into<String>) -> Self {
+        let prefix = prefix.into();
+        let prefix = format!("/{}", prefix.trim_matches('/'));
+        Self {
+            client,
+            prefix,
+            watch: true,
+            revision: AtomicI64::new(0),
+        }
+    }
+
+    /// Whether [`Loader::watch`](crate::Loader::watch) listens for changes
+    /// through etcd's watch API (default: `true`).
+    pub fn watch_changes(mut self, enabled: bool) -> Self {
+        self.watch = enabled;
+        self
+    }
+
+    /// Dotted config path for a key, if it lies under the prefix.
+    pub fn path_for(&self, key: &str) -> Option<String> {
+        let rest = key.strip_prefix(&self.prefix)?;
+        if !rest.is_empty() && !rest.starts_with('/') {
+            return None;
+        }
+        let path = rest
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>()
+            .join(".");
+        (!path.is_empty()).then_some(path)
+    }
+
+    /// Revision of the last load, or of the latest change seen since.
+    pub fn revision(&self) -> i64 {
+        self.revision.load(Ordering::SeqCst)
+    }
+
+    /// Prefix sent to etcd; the trailing slash keeps `/app` from matching
+    /// `/application`.
+    fn key_prefix(&self) -> String {
+        format!("{}/", self.prefix.trim_end_matches('/'))
+    }
+
+    fn error(&self, msg: impl std::fmt::Display) -> CnfgError {
+        CnfgError::Source(format!("{}: {msg}", self.name()))
+    }
+}
+
+impl<C: EtcdClient> Source for EtcdSource<C> {
+    fn name(&self) -> String {
+        format!("etcd:{}", self.prefix)
+    }
+
+    fn load(&self, fields: &[FieldSpec]) -> Result<Value, CnfgError> {
+        let range = self
+            .client
+            .get_prefix(&self.key_prefix())
+            .map_err(|msg| self.error(msg))?;
+        let mut root = Value::Object(Default::default());
+        for kv in &range.kvs {
+            let Some(path) = self.path_for(&kv.key) else {
+                continue;
+            };
+            insert_raw(&mut root, fields, &path, &kv.value).map_err(|msg| self.error(msg))?;
+        }
+        self.revision.store(range.revision, Ordering::SeqCst);
+        Ok(root)
+    }
+
+    fn notifies_changes(&self) -> bool {
+        self.watch
+    }
+
+    fn wait_for_change(&self, timeout: Duration) -> Result<bool, CnfgError> {
+        let prefix = self.key_prefix();
+        let request = WatchRequest {
+            prefix: &prefix,
+            start_revision: self.revision() + 1,
+            timeout,
+        };
+        let latest = self
+            .client
+            .watch_prefix(&request)
+            .map_err(|msg| self.error(msg))?;
+        // Listen past these events while the reload catches up.
+        if let Some(revision) = latest {
+            self.revision.fetch_max(revision, Ordering::SeqCst);
+        }
+        Ok(latest.is_some())
+    }
+}
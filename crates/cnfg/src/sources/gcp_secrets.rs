@@ -0,0 +1,72 @@
+//! Google Cloud Secret Manager resolver.
+//!
+//! Handles `secret://projects/<project>/secrets/<name>` references, reading
+//! the `latest` version unless one is given
+//! (`.../secrets/<name>/versions/3`).
+//!
+//! The Google Cloud client is async and heavyweight, so the resolver talks
+//! to Secret Manager through the small [`SecretManagerClient`] trait.
+//! Implement it over your client:
+//!
+//! ```rust,ignore
+//! struct Gcp(google_cloud_secretmanager_v1::client::SecretManagerService, tokio::runtime::Handle);
+//!
+//! impl SecretManagerClient for Gcp {
+//!     fn access_secret_version(&self, name: &str) -> Result<Vec<u8>, String> {
+//!         let out = self.1.block_on(self.0.access_secret_version().set_name(name).send())
+//!             .map_err(|e| e.to_string())?;
+//!         Ok(out.payload.map(|p| p.data.to_vec()).unwrap_or_default())
+//!     }
+//! }
+//! ```
+
+use super::secrets::SecretResolver;
+
+/// Minimal Secret Manager client used by [`GcpSecretManager`].
+pub trait SecretManagerClient: Send + Sync {
+    /// `AccessSecretVersion` for a full version name
+    /// (`projects/p/secrets/s/versions/v`); returns the payload data.
+    fn access_secret_version(&self, name: &str) -> Result<Vec<u8>, String>;
+}
+
+/// [`SecretResolver`] for Secret Manager resource names.
+pub struct GcpSecretManager<C> {
+    client: C,
+}
+
+impl<C: SecretManagerClient> GcpSecretManager<C> {
+    pub fn new(client: C) -> Self {
+        Self { client }
+    }
+
+    /// Full version name for a reference, if it names a Secret Manager
+    /// secret.
+    pub fn version_name(reference: &str) -> Option<String> {
+        let segments: Vec<&str> = reference.split('/').collect();
+        match segments.as_slice() {
+            ["projects", project, "secrets", secret]
+                if !project.is_empty() && !secret.is_empty() =>
+            {
+                Some(format!("{reference}/versions/latest"))
+            }
+            ["projects", project, "secrets", secret, "versions", version]
+                if !project.is_empty() && !secret.is_empty() && !version.is_empty() =>
+            {
+                Some(reference.to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<C: SecretManagerClient> SecretResolver for GcpSecretManager<C> {
+    fn handles(&self, reference: &str) -> bool {
+        Self::version_name(reference).is_some()
+    }
+
+    fn resolve(&self, reference: &str) -> Result<String, String> {
+        let name = Self::version_name(reference).ok_or("not a Secret Manager secret")?;
+        let data = self.client.access_secret_version(&name)?;
+        String::from_utf8(data).map_err(|_| "secret payload is not UTF-8".to_string())
+    }
+}
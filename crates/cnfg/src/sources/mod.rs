@@ -0,0 +1,96 @@
+//! Additional configuration layers plugged in with [`Loader::source`](crate::Loader::source).
+//!
+//! Sources are merged after config files and before environment variables
+//! and CLI flags.
+
+#[cfg(feature = "aws-secrets")]
+pub mod aws_secrets;
+#[cfg(feature = "aws-ssm")]
+pub mod aws_ssm;
+#[cfg(feature = "consul")]
+pub mod consul;
+pub mod directory;
+#[cfg(feature = "etcd")]
+pub mod etcd;
+#[cfg(feature = "gcp-secrets")]
+pub mod gcp_secrets;
+#[cfg(feature = "redis")]
+pub mod redis;
+#[cfg(feature = "registry")]
+pub mod registry;
+pub mod retry;
+pub mod secrets;
+
+use crate::error::CnfgError;
+use crate::merge::insert_path;
+use crate::types::FieldSpec;
+use serde_json::Value;
+use std::time::Duration;
+
+/// A layer of configuration values.
+pub trait Source: Send + Sync {
+    /// Short label used in errors (e.g. `aws-ssm:/myapp/prod`).
+    fn name(&self) -> String;
+
+    /// Fetch this layer as a JSON object.
+    ///
+    /// `fields` describes the target schema so string-only backends can
+    /// coerce values with [`insert_raw`].
+    fn load(&self, fields: &[FieldSpec]) -> Result<Value, CnfgError>;
+
+    /// How often the [watch](crate::watch) subsystem should re-poll this
+    /// source; `None` means it only changes when files do.
+    fn refresh_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Whether the source implements [`Source::wait_for_change`]. The
+    /// watch subsystem then listens for its changes on a separate thread
+    /// instead of waiting for the next poll.
+    fn notifies_changes(&self) -> bool {
+        false
+    }
+
+    /// Block until the source's data changes or `timeout` passes, and
+    /// report whether it changed. Backends with a native change feed
+    /// (watches, blocking queries, keyspace notifications) implement this.
+    fn wait_for_change(&self, timeout: Duration) -> Result<bool, CnfgError> {
+        std::thread::sleep(timeout);
+        Ok(false)
+    }
+}
+
+/// Boxed future returned by [`AsyncSource::load`].
+#[cfg(feature = "async")]
+pub type BoxFuture<'a, T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send + 'a>>;
+
+/// A layer fetched without blocking, for [`Loader::load_async`](crate::Loader::load_async).
+///
+/// Implementations typically wrap `async fn` bodies with `Box::pin(async move { ... })`.
+#[cfg(feature = "async")]
+pub trait AsyncSource: Send + Sync {
+    /// Short label used in errors and provenance.
+    fn name(&self) -> String;
+
+    /// Fetch this layer as a JSON object; see [`Source::load`].
+    fn load<'a>(&'a self, fields: &'a [FieldSpec]) -> BoxFuture<'a, Result<Value, CnfgError>>;
+}
+
+/// Insert a raw string at `path`, coercing it with the matching field's kind.
+///
+/// Paths without a declared field are inserted as plain strings.
+pub fn insert_raw(
+    root: &mut Value,
+    fields: &[FieldSpec],
+    path: &str,
+    raw: &str,
+) -> Result<(), String> {
+    let value = match fields.iter().find(|spec| spec.path == path) {
+        Some(spec) => {
+            crate::loader::parse_for_spec(raw, spec).map_err(|msg| format!("{path}: {msg}"))?
+        }
+        None => Value::String(raw.to_string()),
+    };
+    let segments: Vec<&str> = path.split('.').collect();
+    insert_path(root, &segments, value).map_err(|err| format!("{path}: {err}"))
+}
@@ -0,0 +1,217 @@
+//! Redis source.
+//!
+//! Reads either one hash, whose fields are config paths, or every string
+//! key under a prefix. Key segments are split on `:` (see
+//! [`RedisSource::separator`]): with prefix `myapp`, the key
+//! `myapp:flags:beta` becomes `flags.beta`, and so does the hash field
+//! `flags:beta` or `flags.beta`. Values are coerced with the target field's
+//! type.
+//!
+//! The source talks to Redis through the small [`RedisClient`] trait, so
+//! any client works. Implement it over yours:
+//!
+//! ```rust,ignore
+//! struct Redis(redis::Client);
+//!
+//! impl RedisClient for Redis {
+//!     fn hash_entries(&self, key: &str) -> Result<Vec<(String, String)>, String> {
+//!         let mut conn = self.0.get_connection().map_err(|e| e.to_string())?;
+//!         redis::cmd("HGETALL").arg(key).query(&mut conn).map_err(|e| e.to_string())
+//!     }
+//!
+//!     fn scan_values(&self, pattern: &str) -> Result<Vec<(String, String)>, String> {
+//!         let mut conn = self.0.get_connection().map_err(|e| e.to_string())?;
+//!         let keys: Vec<String> = redis::cmd("SCAN").cursor_arg(0).arg("MATCH").arg(pattern)
+//!             .arg("TYPE").arg("string")
+//!             .clone().iter(&mut conn).map_err(|e| e.to_string())?.collect();
+//!         if keys.is_empty() {
+//!             return Ok(Vec::new());
+//!         }
+//!         let values: Vec<Option<String>> = redis::cmd("MGET").arg(&keys).query(&mut conn)
+//!             .map_err(|e| e.to_string())?;
+//!         Ok(keys.into_iter().zip(values).filter_map(|(k, v)| Some((k, v?))).collect())
+//!     }
+//!
+//!     fn wait_for_notification(&self, channel: &str, timeout: Duration) -> Result<bool, String> {
+//!         let mut conn = self.0.get_connection().map_err(|e| e.to_string())?;
+//!         let mut pubsub = conn.as_pubsub();
+//!         pubsub.psubscribe(channel).map_err(|e| e.to_string())?;
+//!         pubsub.set_read_timeout(Some(timeout)).map_err(|e| e.to_string())?;
+//!         match pubsub.get_message() {
+//!             Ok(_) => Ok(true),
+//!             Err(e) if e.is_timeout() => Ok(false),
+//!             Err(e) => Err(e.to_string()),
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! With [`RedisSource::reload_on_notifications`], [`Loader::watch`](crate::Loader::watch)
+//! reloads as soon as a keyspace notification arrives. The server must have
+//! them enabled (`notify-keyspace-events K$h` or similar). Pub/sub does not
+//! replay messages missed while reconnecting, so keep a watch interval as a
+//! fallback.
+
+use super::{Source, insert_raw};
+use crate::error::CnfgError;
+use crate::types::FieldSpec;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Minimal Redis client used by [`RedisSource`].
+pub trait RedisClient: Send + Sync {
+    /// `HGETALL key`; an empty list when the key does not exist.
+    fn hash_entries(&self, key: &str) -> Result<Vec<(String, String)>, String>;
+
+    /// String keys matching a glob `pattern` (as for `SCAN MATCH`), with
+    /// their values.
+    fn scan_values(&self, pattern: &str) -> Result<Vec<(String, String)>, String>;
+
+    /// `PSUBSCRIBE` to `channel` and block until a message arrives
+    /// (`Ok(true)`) or the timeout passes (`Ok(false)`).
+    fn wait_for_notification(&self, channel: &str, timeout: Duration) -> Result<bool, String>;
+}
+
+#[derive(Debug, Clone)]
+enum Layout {
+    Hash(String),
+    Prefix(String),
+}
+
+/// Source reading a Redis hash or key prefix.
+pub struct RedisSource<C> {
+    client: C,
+    layout: Layout,
+    separator: String,
+    database: u32,
+    notifications: bool,
+}
+
+impl<C: RedisClient> RedisSource<C> {
+    /// Read the fields of the hash at `key`.
+    pub fn hash(client: C, key: impl Into<String>) -> Self {
+        Self::with_layout(client, Layout::Hash(key.into()))
+    }
+
+    /// Read every string key below `prefix` (e.g. `myapp` for `myapp:*`).
+    pub fn prefix(client: C, prefix: impl Into<String>) -> Self {
+        Self::with_layout(client, Layout::Prefix(prefix.into()))
+    }
+
+    fn with_layout(client: C, layout: Layout) -> Self {
+        Self {
+            client,
+            layout,
+            separator: ":".into(),
+            database: 0,
+            notifications: false,
+        }
+    }
+
+    /// Separator between key segments (default: `:`).
+    pub fn separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = separator.into();
+        self
+    }
+
+    /// Database number, used for keyspace notification channels
+    /// (default: 0). Select the database on the connection itself.
+    pub fn database(mut self, database: u32) -> Self {
+        self.database = database;
+        self
+    }
+
+    /// Whether [`Loader::watch`](crate::Loader::watch) waits for keyspace
+    /// notifications on the hash or prefix (default: `false`).
+    pub fn reload_on_notifications(mut self, enabled: bool) -> Self {
+        self.notifications = enabled;
+        self
+    }
+
+    /// Dotted config path for a key (prefix layout) or field (hash layout).
+    pub fn path_for(&self, key: &str) -> Option<String> {
+        let rest = match &self.layout {
+            Layout::Hash(_) => key,
+            Layout::Prefix(_) => key.strip_prefix(&self.key_prefix())?,
+        };
+        let path = rest
+            .split(self.separator.as_str())
+            .flat_map(|segment| segment.split('.'))
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>()
+            .join(".");
+        (!path.is_empty()).then_some(path)
+    }
+
+    /// Prefix keys must start with; the trailing separator keeps `app`
+    /// from matching `application`.
+    fn key_prefix(&self) -> String {
+        match &self.layout {
+            Layout::Hash(key) => key.clone(),
+            Layout::Prefix(prefix) => {
+                let prefix = prefix.trim_end_matches(self.separator.as_str());
+                format!("{prefix}{}", self.separator)
+            }
+        }
+    }
+
+    /// Glob matching the hash key, or every key under the prefix.
+    fn pattern(&self) -> String {
+        let escaped = escape_glob(&self.key_prefix());
+        match self.layout {
+            Layout::Hash(_) => escaped,
+            Layout::Prefix(_) => format!("{escaped}*"),
+        }
+    }
+
+    fn error(&self, msg: impl std::fmt::Display) -> CnfgError {
+        CnfgError::Source(format!("{}: {msg}", self.name()))
+    }
+}
+
+impl<C: RedisClient> Source for RedisSource<C> {
+    fn name(&self) -> String {
+        match &self.layout {
+            Layout::Hash(key) => format!("redis:{key}"),
+            Layout::Prefix(_) => format!("redis:{}*", self.key_prefix()),
+        }
+    }
+
+    fn load(&self, fields: &[FieldSpec]) -> Result<Value, CnfgError> {
+        let entries = match &self.layout {
+            Layout::Hash(key) => self.client.hash_entries(key),
+            Layout::Prefix(_) => self.client.scan_values(&self.pattern()),
+        }
+        .map_err(|msg| self.error(msg))?;
+        let mut root = Value::Object(Default::default());
+        for (key, value) in &entries {
+            let Some(path) = self.path_for(key) else {
+                continue;
+            };
+            insert_raw(&mut root, fields, &path, value).map_err(|msg| self.error(msg))?;
+        }
+        Ok(root)
+    }
+
+    fn notifies_changes(&self) -> bool {
+        self.notifications
+    }
+
+    fn wait_for_change(&self, timeout: Duration) -> Result<bool, CnfgError> {
+        let channel = format!("__keyspace@{}__:{}", self.database, self.pattern());
+        self.client
+            .wait_for_notification(&channel, timeout)
+            .map_err(|msg| self.error(msg))
+    }
+}
+
+fn escape_glob(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        if matches!(c, '*' | '?' | '[' | ']' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
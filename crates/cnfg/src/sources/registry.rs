@@ -0,0 +1,361 @@
+//! Windows Registry source.
+//!
+//! Maps a registry key subtree onto config paths: subkeys become sections
+//! and values become fields, so with the root
+//! `HKLM\SOFTWARE\Policies\Acme\Agent`, the value `Port` under the subkey
+//! `Server` sets `server.port`. Names are matched to declared fields
+//! ignoring case and `_`/`-`, so `MaxRetries` sets `max_retries`; other
+//! names are lower-cased.
+//!
+//! `REG_SZ` and `REG_EXPAND_SZ` strings are coerced with the field's type,
+//! `REG_DWORD`/`REG_QWORD` become integers (or booleans for `bool` fields),
+//! and `REG_MULTI_SZ` becomes a list. A missing root key is an empty layer,
+//! since policy keys usually only exist once a policy is pushed.
+//!
+//! On Windows, [`RegistrySource::local_machine`] and
+//! [`RegistrySource::current_user`] read the live registry. The mapping
+//! goes through the [`RegistryReader`] trait, so it also runs against other
+//! backends (e.g. an exported `.reg` file, or a test double).
+
+use super::{Source, insert_raw};
+use crate::error::CnfgError;
+use crate::merge::insert_path;
+use crate::types::{FieldSpec, Kind};
+use serde_json::Value;
+
+/// Data of a registry value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegValue {
+    /// `REG_SZ` or `REG_EXPAND_SZ` (not expanded).
+    String(String),
+    /// `REG_DWORD`.
+    Dword(u32),
+    /// `REG_QWORD`.
+    Qword(u64),
+    /// `REG_MULTI_SZ`.
+    MultiString(Vec<String>),
+}
+
+/// Values and subkey names of one key.
+#[derive(Debug, Clone, Default)]
+pub struct RegKey {
+    pub values: Vec<(String, RegValue)>,
+    pub subkeys: Vec<String>,
+}
+
+/// Reads registry keys for [`RegistrySource`].
+pub trait RegistryReader: Send + Sync {
+    /// Read the key at `path` (backslash-separated, below the reader's
+    /// hive); `Ok(None)` when it does not exist.
+    fn read_key(&self, path: &str) -> Result<Option<RegKey>, String>;
+}
+
+/// Source reading a registry key subtree.
+pub struct RegistrySource<R> {
+    reader: R,
+    hive: &'static str,
+    root: String,
+}
+
+impl<R: RegistryReader> RegistrySource<R> {
+    /// Read the subtree at `root` through `reader`. `hive` only names the
+    /// source (e.g. `HKLM`).
+    pub fn new(reader: R, hive: &'static str, root: impl Into<String>) -> Self {
+        Self {
+            reader,
+            hive,
+            root: root.into().trim_matches('\\').to_string(),
+        }
+    }
+
+    fn read_tree(
+        &self,
+        key_path: &str,
+        config_path: &mut Vec<String>,
+        fields: &[FieldSpec],
+        root: &mut Value,
+        depth: usize,
+    ) -> Result<(), CnfgError> {
+        // Registry keys nest at most 512 levels; stop well before a reader
+        // that reports a key as its own subkey overflows the stack.
+        if depth > 64 {
+            return Err(self.error(format!("`{key_path}` is nested too deeply")));
+        }
+        let Some(key) = self
+            .reader
+            .read_key(key_path)
+            .map_err(|msg| self.error(msg))?
+        else {
+            return Ok(());
+        };
+        for (name, data) in &key.values {
+            // The unnamed default value has no config path.
+            if name.is_empty() {
+                continue;
+            }
+            config_path.push(segment_for(fields, config_path, name));
+            let path = config_path.join(".");
+            config_path.pop();
+            if let RegValue::String(text) = data {
+                insert_raw(root, fields, &path, text).map_err(|msg| self.error(msg))?;
+                continue;
+            }
+            let spec = fields.iter().find(|spec| spec.path == path);
+            let value = convert(data, spec).map_err(|msg| self.error(format!("{path}: {msg}")))?;
+            let segments: Vec<&str> = path.split('.').collect();
+            insert_path(root, &segments, value)
+                .map_err(|err| self.error(format!("{path}: {err}")))?;
+        }
+        for name in &key.subkeys {
+            config_path.push(segment_for(fields, config_path, name));
+            self.read_tree(
+                &format!("{key_path}\\{name}"),
+                config_path,
+                fields,
+                root,
+                depth + 1,
+            )?;
+            config_path.pop();
+        }
+        Ok(())
+    }
+
+    fn error(&self, msg: impl std::fmt::Display) -> CnfgError {
+        CnfgError::Source(format!("{}: {msg}", self.name()))
+    }
+}
+
+impl<R: RegistryReader> Source for RegistrySource<R> {
+    fn name(&self) -> String {
+        format!("registry:{}\\{}", self.hive, self.root)
+    }
+
+    fn load(&self, fields: &[FieldSpec]) -> Result<Value, CnfgError> {
+        let mut root = Value::Object(Default::default());
+        self.read_tree(&self.root, &mut Vec::new(), fields, &mut root, 0)?;
+        Ok(root)
+    }
+}
+
+/// Config path segment for a registry name below `parent`.
+fn segment_for(fields: &[FieldSpec], parent: &[String], name: &str) -> String {
+    let normalize = |text: &str| -> String {
+        text.chars()
+            .filter(|c| !matches!(c, '_' | '-' | ' '))
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let wanted = normalize(name);
+    fields
+        .iter()
+        .filter_map(|spec| {
+            let segments: Vec<&str> = spec.path.split('.').collect();
+            let under_parent = segments.len() > parent.len()
+                && segments.iter().zip(parent).all(|(seg, p)| seg == p);
+            under_parent.then(|| segments[parent.len()])
+        })
+        .find(|segment| normalize(segment) == wanted)
+        .map_or_else(|| name.to_lowercase(), str::to_string)
+}
+
+/// Convert non-string registry data; strings go through [`insert_raw`].
+fn convert(data: &RegValue, spec: Option<&FieldSpec>) -> Result<Value, String> {
+    let is_bool = spec.is_some_and(|spec| matches!(spec.kind, Kind::Bool) && !spec.list);
+    match data {
+        RegValue::String(text) => Ok(Value::String(text.clone())),
+        RegValue::Dword(n) if is_bool => Ok(Value::Bool(*n != 0)),
+        RegValue::Qword(n) if is_bool => Ok(Value::Bool(*n != 0)),
+        RegValue::Dword(n) => Ok(Value::from(*n)),
+        RegValue::Qword(n) => Ok(Value::from(*n)),
+        RegValue::MultiString(items) => items
+            .iter()
+            .map(|item| match spec {
+                // Each string is one item, even if it contains commas.
+                Some(spec) => crate::loader::parse_for_spec(
+                    item,
+                    &FieldSpec {
+                        list: false,
+                        ..spec.clone()
+                    },
+                ),
+                None => Ok(Value::String(item.clone())),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+    }
+}
+
+#[cfg(windows)]
+pub use native::WindowsRegistry;
+
+#[cfg(windows)]
+impl RegistrySource<WindowsRegistry> {
+    /// Read `HKEY_LOCAL_MACHINE\<root>`, where machine-wide Group Policy
+    /// lands (e.g. `SOFTWARE\Policies\Acme\Agent`).
+    pub fn local_machine(root: impl Into<String>) -> Self {
+        Self::new(WindowsRegistry::LocalMachine, "HKLM", root)
+    }
+
+    /// Read `HKEY_CURRENT_USER\<root>`.
+    pub fn current_user(root: impl Into<String>) -> Self {
+        Self::new(WindowsRegistry::CurrentUser, "HKCU", root)
+    }
+}
+
+#[cfg(windows)]
+mod native {
+    use super::{RegKey, RegValue, RegistryReader};
+    use std::ptr::null_mut;
+    use windows_sys::Win32::Foundation::{
+        ERROR_FILE_NOT_FOUND, ERROR_NO_MORE_ITEMS, ERROR_SUCCESS,
+    };
+    use windows_sys::Win32::System::Registry::{
+        HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, KEY_READ, REG_DWORD, REG_EXPAND_SZ,
+        REG_MULTI_SZ, REG_QWORD, REG_SZ, RegCloseKey, RegEnumKeyExW, RegEnumValueW, RegOpenKeyExW,
+        RegQueryInfoKeyW,
+    };
+
+    /// The live registry of one hive.
+    #[derive(Debug, Clone, Copy)]
+    pub enum WindowsRegistry {
+        LocalMachine,
+        CurrentUser,
+    }
+
+    /// Closes the key when dropped.
+    struct Key(HKEY);
+
+    impl Drop for Key {
+        fn drop(&mut self) {
+            unsafe { RegCloseKey(self.0) };
+        }
+    }
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(Some(0)).collect()
+    }
+
+    fn from_wide(buf: &[u16]) -> String {
+        let end = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+        String::from_utf16_lossy(&buf[..end])
+    }
+
+    fn check(status: u32, what: &str) -> Result<(), String> {
+        if status == ERROR_SUCCESS {
+            Ok(())
+        } else {
+            Err(format!(
+                "{what} failed: {}",
+                std::io::Error::from_raw_os_error(status as i32)
+            ))
+        }
+    }
+
+    impl RegistryReader for WindowsRegistry {
+        fn read_key(&self, path: &str) -> Result<Option<RegKey>, String> {
+            let hive = match self {
+                WindowsRegistry::LocalMachine => HKEY_LOCAL_MACHINE,
+                WindowsRegistry::CurrentUser => HKEY_CURRENT_USER,
+            };
+            let mut handle: HKEY = null_mut();
+            let status =
+                unsafe { RegOpenKeyExW(hive, wide(path).as_ptr(), 0, KEY_READ, &mut handle) };
+            if status == ERROR_FILE_NOT_FOUND {
+                return Ok(None);
+            }
+            check(status, "RegOpenKeyExW")?;
+            let key = Key(handle);
+
+            let (mut subkeys, mut max_subkey, mut values, mut max_name, mut max_data) =
+                (0u32, 0u32, 0u32, 0u32, 0u32);
+            let status = unsafe {
+                RegQueryInfoKeyW(
+                    key.0,
+                    null_mut(),
+                    null_mut(),
+                    null_mut(),
+                    &mut subkeys,
+                    &mut max_subkey,
+                    null_mut(),
+                    &mut values,
+                    &mut max_name,
+                    &mut max_data,
+                    null_mut(),
+                    null_mut(),
+                )
+            };
+            check(status, "RegQueryInfoKeyW")?;
+
+            let mut out = RegKey::default();
+            let mut name = vec![0u16; max_subkey.max(max_name) as usize + 1];
+            for index in 0..subkeys {
+                let mut len = name.len() as u32;
+                let status = unsafe {
+                    RegEnumKeyExW(
+                        key.0,
+                        index,
+                        name.as_mut_ptr(),
+                        &mut len,
+                        null_mut(),
+                        null_mut(),
+                        null_mut(),
+                        null_mut(),
+                    )
+                };
+                if status == ERROR_NO_MORE_ITEMS {
+                    break;
+                }
+                check(status, "RegEnumKeyExW")?;
+                out.subkeys.push(from_wide(&name[..len as usize]));
+            }
+
+            let mut data = vec![0u8; max_data as usize + 2];
+            for index in 0..values {
+                let mut len = name.len() as u32;
+                let mut size = data.len() as u32;
+                let mut kind = 0u32;
+                let status = unsafe {
+                    RegEnumValueW(
+                        key.0,
+                        index,
+                        name.as_mut_ptr(),
+                        &mut len,
+                        null_mut(),
+                        &mut kind,
+                        data.as_mut_ptr(),
+                        &mut size,
+                    )
+                };
+                if status == ERROR_NO_MORE_ITEMS {
+                    break;
+                }
+                check(status, "RegEnumValueW")?;
+                let bytes = &data[..size as usize];
+                let units: Vec<u16> = bytes
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                    .collect();
+                let value = match kind {
+                    REG_SZ | REG_EXPAND_SZ => RegValue::String(from_wide(&units)),
+                    REG_MULTI_SZ => RegValue::MultiString(
+                        units
+                            .split(|&c| c == 0)
+                            .filter(|item| !item.is_empty())
+                            .map(String::from_utf16_lossy)
+                            .collect(),
+                    ),
+                    REG_DWORD if bytes.len() >= 4 => {
+                        RegValue::Dword(u32::from_le_bytes(bytes[..4].try_into().expect("4 bytes")))
+                    }
+                    REG_QWORD if bytes.len() >= 8 => {
+                        RegValue::Qword(u64::from_le_bytes(bytes[..8].try_into().expect("8 bytes")))
+                    }
+                    // Binary and other types have no config meaning.
+                    _ => continue,
+                };
+                out.values.push((from_wide(&name[..len as usize]), value));
+            }
+            Ok(Some(out))
+        }
+    }
+}
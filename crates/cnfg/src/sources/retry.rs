@@ -0,0 +1,224 @@
+//! Retries for sources backed by remote services.
+//!
+//! A [`RetryPolicy`] set with [`Loader::retry`](crate::Loader::retry)
+//! applies to every [`Source`](super::Source) and
+//! [`AsyncSource`](super::AsyncSource) of the loader. Failed fetches are
+//! retried with exponential backoff until the attempts or the deadline run
+//! out. With [`RetryPolicy::fail_open`], a source that still fails is
+//! replaced by the last value it returned, and the report carries a
+//! [`WarningKind::StaleSource`](crate::WarningKind::StaleSource) warning.
+
+use crate::error::CnfgError;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How failed source fetches are retried.
+///
+/// The default makes a single attempt and fails closed.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    deadline: Option<Duration>,
+    fail_open: bool,
+    cache_dir: Option<PathBuf>,
+    // Shared by clones of the loader, so reloads can fall back to it.
+    last_known: Arc<Mutex<HashMap<String, Value>>>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl RetryPolicy {
+    /// Try each source up to `max_attempts` times (at least once), waiting
+    /// 100ms before the first retry and doubling up to 5s.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            deadline: None,
+            fail_open: false,
+            cache_dir: None,
+            last_known: Arc::default(),
+        }
+    }
+
+    /// Wait `initial` before the first retry, doubling each time up to `max`.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Stop retrying once `deadline` has passed since the first attempt.
+    ///
+    /// Async sources are also cancelled when the deadline passes mid-fetch;
+    /// a blocking source cannot be interrupted, so it is only checked
+    /// between attempts.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// When a source still fails, use the last value it returned instead of
+    /// aborting the load: first from this process (reloads through
+    /// [`Loader::watch`](crate::Loader::watch)), then from the
+    /// [`cache_dir`](Self::cache_dir).
+    pub fn fail_open(mut self, enabled: bool) -> Self {
+        self.fail_open = enabled;
+        self
+    }
+
+    /// Save every successfully fetched layer as JSON in `dir`, so
+    /// [`fail_open`](Self::fail_open) has a value after a restart.
+    ///
+    /// The files hold the fetched values in plain text; put the directory
+    /// somewhere only the service can read.
+    pub fn cache_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Delay before retry number `retry` (1 for the first retry).
+    fn delay(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    /// Whether to retry after `attempts` failures, and how long to wait.
+    fn next_delay(&self, attempts: u32, started: Instant) -> Option<Duration> {
+        let delay = self.delay(attempts);
+        let in_time = self
+            .deadline
+            .is_none_or(|deadline| started.elapsed() + delay < deadline);
+        (attempts < self.max_attempts && in_time).then_some(delay)
+    }
+
+    /// Run a blocking fetch under the policy. The second value is a note
+    /// explaining why a stale value was used.
+    pub(crate) fn fetch(
+        &self,
+        name: &str,
+        mut load: impl FnMut() -> Result<Value, CnfgError>,
+    ) -> Result<(Value, Option<String>), CnfgError> {
+        let started = Instant::now();
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let err = match load() {
+                Ok(value) => return Ok((self.remember(name, value), None)),
+                Err(err) => err,
+            };
+            match self.next_delay(attempts, started) {
+                Some(delay) => std::thread::sleep(delay),
+                None => return self.give_up(name, err, attempts),
+            }
+        }
+    }
+
+    /// Async twin of [`RetryPolicy::fetch`].
+    #[cfg(feature = "async")]
+    pub(crate) async fn fetch_async<'a>(
+        &self,
+        name: &str,
+        load: impl Fn() -> super::BoxFuture<'a, Result<Value, CnfgError>>,
+    ) -> Result<(Value, Option<String>), CnfgError> {
+        let started = Instant::now();
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let result = match self.deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_sub(started.elapsed());
+                    tokio::time::timeout(remaining, load())
+                        .await
+                        .unwrap_or_else(|_| {
+                            Err(CnfgError::Source(format!(
+                                "{name}: timed out after {deadline:?}"
+                            )))
+                        })
+                }
+                None => load().await,
+            };
+            let err = match result {
+                Ok(value) => return Ok((self.remember(name, value), None)),
+                Err(err) => err,
+            };
+            match self.next_delay(attempts, started) {
+                Some(delay) => tokio::time::sleep(delay).await,
+                None => return self.give_up(name, err, attempts),
+            }
+        }
+    }
+
+    fn remember(&self, name: &str, value: Value) -> Value {
+        if self.fail_open || self.cache_dir.is_some() {
+            if let Some(path) = self.cache_path(name) {
+                // A failed write only costs the fallback after a restart.
+                let _ = fs::create_dir_all(path.parent().expect("cache file has a parent"))
+                    .and_then(|()| fs::write(&path, value.to_string()));
+            }
+            let mut last_known = self.last_known.lock().unwrap_or_else(|e| e.into_inner());
+            last_known.insert(name.to_string(), value.clone());
+        }
+        value
+    }
+
+    fn give_up(
+        &self,
+        name: &str,
+        err: CnfgError,
+        attempts: u32,
+    ) -> Result<(Value, Option<String>), CnfgError> {
+        let err = if attempts > 1 {
+            CnfgError::Source(format!("{name}: gave up after {attempts} attempts: {err}"))
+        } else {
+            err
+        };
+        if !self.fail_open {
+            return Err(err);
+        }
+        let remembered = self
+            .last_known
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(name)
+            .cloned();
+        if let Some(value) = remembered {
+            return Ok((value, Some(format!("{err}; using the last value fetched"))));
+        }
+        let cached = self.cache_path(name).and_then(|path| {
+            let value = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+            Some((value, path))
+        });
+        match cached {
+            Some((value, path)) => Ok((
+                value,
+                Some(format!(
+                    "{err}; using the value cached in {}",
+                    path.display()
+                )),
+            )),
+            None => Err(err),
+        }
+    }
+
+    fn cache_path(&self, name: &str) -> Option<PathBuf> {
+        let file: String = name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        Some(self.cache_dir.as_ref()?.join(format!("{file}.json")))
+    }
+}
@@ -0,0 +1,188 @@
+//! Secret manager references.
+//!
+//! A string value of the form `secret://<reference>`, from any layer, is
+//! replaced by the secret it names once every layer has been merged. A
+//! field marked `#[cnfg(secret_ref = "<reference>")]` resolves its
+//! reference whenever no layer sets it. The plaintext is then coerced to
+//! the field's type. Env vars and flags are only read as references while
+//! a resolver is added or the field has a `secret_ref`; otherwise they are
+//! type-checked as written.
+//!
+//! References are resolved by the [`SecretResolver`]s added with
+//! [`Loader::secret_resolver`](crate::Loader::secret_resolver); the first
+//! one that [handles](SecretResolver::handles) a reference is used:
+//!
+//! - `sources::gcp_secrets` (feature `gcp-secrets`) for
+//!   `secret://projects/<project>/secrets/<name>[/versions/<version>]`;
+//! - `sources::aws_secrets` (feature `aws-secrets`) for
+//!   `secret://aws/<name>` or `secret://arn:aws:secretsmanager:...`, with an
+//!   optional `#<key>` picking one key of a JSON secret.
+//!
+//! Resolved values are cached per reference for the loader and its clones
+//! (five minutes by default, see
+//! [`Loader::secret_cache_ttl`](crate::Loader::secret_cache_ttl)). They are
+//! redacted from [`Report::document`](crate::Report::document), which keeps
+//! the reference, and the provenance of each resolved field names the
+//! reference rather than the layer that held it. Failures are reported as
+//! [`IssueKind::Secret`](crate::error::IssueKind::Secret) issues on the
+//! field.
+
+use crate::error::{Issue, IssueKind, ValidationErrors};
+use crate::provenance::{Origin, Provenance};
+use crate::types::FieldSpec;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Scheme marking a secret reference in a config value.
+pub const SCHEME: &str = "secret://";
+
+/// Resolves references to a secret store.
+pub trait SecretResolver: Send + Sync {
+    /// Whether `reference` (without the `secret://` scheme) belongs to
+    /// this store.
+    fn handles(&self, reference: &str) -> bool;
+
+    /// Fetch the secret's value.
+    fn resolve(&self, reference: &str) -> Result<String, String>;
+}
+
+/// The resolvers of a loader, with the cache they share.
+#[derive(Clone)]
+pub(crate) struct Resolvers {
+    resolvers: Vec<Arc<dyn SecretResolver>>,
+    pub(crate) ttl: Duration,
+    // Shared by clones of the loader, so reloads reuse it.
+    cache: Arc<Mutex<HashMap<String, (Instant, String)>>>,
+}
+
+impl Default for Resolvers {
+    fn default() -> Self {
+        Self {
+            resolvers: Vec::new(),
+            ttl: Duration::from_secs(300),
+            cache: Arc::default(),
+        }
+    }
+}
+
+impl Resolvers {
+    pub(crate) fn push(&mut self, resolver: Arc<dyn SecretResolver>) {
+        self.resolvers.push(resolver);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.resolvers.is_empty()
+    }
+
+    fn resolve(&self, reference: &str) -> Result<String, String> {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((fetched_at, value)) = cache.get(reference) {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(value.clone());
+            }
+        }
+        let resolver = self
+            .resolvers
+            .iter()
+            .find(|resolver| resolver.handles(reference))
+            .ok_or_else(|| {
+                format!("no secret resolver handles `{SCHEME}{reference}`; add one with Loader::secret_resolver")
+            })?;
+        let value = resolver
+            .resolve(reference)
+            .map_err(|msg| format!("could not resolve `{SCHEME}{reference}`: {msg}"))?;
+        if !self.ttl.is_zero() {
+            cache.insert(reference.to_string(), (Instant::now(), value.clone()));
+        }
+        Ok(value)
+    }
+
+    /// Replace every reference in `doc` by its secret. Returns the
+    /// references by path, for redacting the document afterwards.
+    pub(crate) fn resolve_all(
+        &self,
+        doc: &mut Value,
+        fields: &[FieldSpec],
+        provenance: &mut Provenance,
+        errs: &mut ValidationErrors,
+    ) -> Vec<(String, String)> {
+        let mut references = Vec::new();
+        collect(doc, &mut String::new(), &mut references);
+        for spec in fields {
+            let Some(reference) = spec.secret_ref else {
+                continue;
+            };
+            let unset = matches!(provenance.get(spec.path), None | Some(Origin::Default));
+            if unset && !references.iter().any(|(path, _)| path == spec.path) {
+                let reference = reference.strip_prefix(SCHEME).unwrap_or(reference);
+                references.push((spec.path.to_string(), reference.to_string()));
+            }
+        }
+
+        for (path, reference) in &references {
+            let resolved = self.resolve(reference).and_then(|plain| {
+                crate::loader::coerce_raw(fields, path, &plain)
+                    .map_err(|msg| format!("secret `{SCHEME}{reference}`: {msg}"))
+            });
+            let segments: Vec<&str> = path.split('.').collect();
+            // The paths were just read from `doc` or declared by a field.
+            match resolved {
+                Ok(value) => {
+                    let _ = crate::merge::insert_path(doc, &segments, value);
+                    provenance.record(path, Origin::Source(format!("{SCHEME}{reference}")));
+                }
+                Err(message) => {
+                    // Keep the reference out of the type checks; this issue
+                    // already covers the field.
+                    let _ = crate::merge::remove_path(doc, &segments);
+                    errs.push(Issue::new(path.clone(), IssueKind::Secret, message));
+                }
+            }
+        }
+        references
+    }
+}
+
+/// Put the references back in place of resolved secrets.
+pub(crate) fn redact(doc: &mut Value, references: &[(String, String)]) {
+    for (path, reference) in references {
+        let segments: Vec<&str> = path.split('.').collect();
+        let _ = crate::merge::insert_path(
+            doc,
+            &segments,
+            Value::String(format!("{SCHEME}{reference}")),
+        );
+    }
+}
+
+fn collect(value: &Value, path: &mut String, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let len = path.len();
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+                collect(child, path, out);
+                path.truncate(len);
+            }
+        }
+        Value::Array(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                let len = path.len();
+                path.push_str(&format!("[{idx}]"));
+                collect(item, path, out);
+                path.truncate(len);
+            }
+        }
+        Value::String(text) => {
+            if let Some(reference) = text.strip_prefix(SCHEME) {
+                out.push((path.clone(), reference.to_string()));
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,265 @@
+//! Where each key of a config file was written.
+//!
+//! Files are parsed into `serde_json::Value`, which forgets positions, so the
+//! source text is scanned a second time for the line and column of every
+//! key. TOML positions come from `toml_edit`; YAML block mappings and
+//! sequences are tracked by indentation (flow-style `{...}` collections are
+//! not descended into). Paths use the same `a.b` / `list[0].c` form as
+//! [`Provenance`](crate::Provenance).
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// 1-based line and column of a key in a config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Locate every key in `text`, choosing the scanner by `path`'s extension.
+/// Formats without a scanner yield no locations.
+pub fn locate(path: &Path, text: &str) -> BTreeMap<String, Location> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    locate_format(ext, text)
+}
+
+/// Like [`locate`], for a format given as its file extension.
+#[cfg_attr(
+    not(any(feature = "toml", feature = "yaml")),
+    allow(unused_mut, unused_variables)
+)]
+pub(crate) fn locate_format(ext: &str, text: &str) -> BTreeMap<String, Location> {
+    let mut out = BTreeMap::new();
+    match ext {
+        #[cfg(feature = "toml")]
+        "toml" => toml_keys(text, &mut out),
+        #[cfg(feature = "yaml")]
+        "yaml" | "yml" => yaml_keys(text, &mut out),
+        _ => {}
+    }
+    out
+}
+
+#[cfg(feature = "toml")]
+fn toml_keys(text: &str, out: &mut BTreeMap<String, Location>) {
+    use toml_edit::{ImDocument, Item, Table, Value};
+
+    fn walk_table(
+        table: &Table,
+        prefix: &str,
+        index: &LineIndex,
+        out: &mut BTreeMap<String, Location>,
+    ) {
+        for (name, _) in table.iter() {
+            let Some((key, item)) = table.get_key_value(name) else {
+                continue;
+            };
+            let path = join(prefix, name);
+            if let Some(span) = key.span().or_else(|| item.span()) {
+                out.insert(path.clone(), index.locate(span.start));
+            }
+            walk_item(item, &path, index, out);
+        }
+    }
+
+    fn walk_item(item: &Item, path: &str, index: &LineIndex, out: &mut BTreeMap<String, Location>) {
+        match item {
+            Item::Table(table) => walk_table(table, path, index, out),
+            Item::ArrayOfTables(tables) => {
+                for (idx, table) in tables.iter().enumerate() {
+                    let item_path = format!("{path}[{idx}]");
+                    if let Some(span) = table.span() {
+                        out.insert(item_path.clone(), index.locate(span.start));
+                    }
+                    walk_table(table, &item_path, index, out);
+                }
+            }
+            Item::Value(value) => walk_value(value, path, index, out),
+            Item::None => {}
+        }
+    }
+
+    fn walk_value(
+        value: &Value,
+        path: &str,
+        index: &LineIndex,
+        out: &mut BTreeMap<String, Location>,
+    ) {
+        match value {
+            Value::InlineTable(table) => {
+                for (name, _) in table.iter() {
+                    let Some((key, item)) = table.get_key_value(name) else {
+                        continue;
+                    };
+                    let child = join(path, name);
+                    if let Some(span) = key.span().or_else(|| item.span()) {
+                        out.insert(child.clone(), index.locate(span.start));
+                    }
+                    walk_item(item, &child, index, out);
+                }
+            }
+            Value::Array(items) => {
+                for (idx, item) in items.iter().enumerate() {
+                    let child = format!("{path}[{idx}]");
+                    if let Some(span) = item.span() {
+                        out.insert(child.clone(), index.locate(span.start));
+                    }
+                    walk_value(item, &child, index, out);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Parse errors are reported by the real parse; no spans then.
+    let Ok(doc) = ImDocument::parse(text) else {
+        return;
+    };
+    let index = LineIndex::new(text);
+    walk_table(doc.as_table(), "", &index, out);
+}
+
+#[cfg(feature = "yaml")]
+fn yaml_keys(text: &str, out: &mut BTreeMap<String, Location>) {
+    struct Frame {
+        indent: usize,
+        segment: String,
+        is_item: bool,
+        next_index: usize,
+    }
+
+    fn path_of(stack: &[Frame]) -> String {
+        let mut path = String::new();
+        for frame in stack {
+            if !frame.is_item && !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(&frame.segment);
+        }
+        path
+    }
+
+    let mut stack: Vec<Frame> = Vec::new();
+    for (idx, raw) in text.lines().enumerate() {
+        let line = idx + 1;
+        let trimmed = raw.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("---") {
+            continue;
+        }
+        let mut indent = raw.len() - trimmed.len();
+        let mut rest = trimmed;
+
+        // `- ` starts a sequence item; a mapping may follow on the same line.
+        while rest == "-" || rest.starts_with("- ") {
+            while stack
+                .last()
+                .is_some_and(|top| top.indent > indent || (top.indent == indent && top.is_item))
+            {
+                stack.pop();
+            }
+            let position = stack.last_mut().map_or(0, |owner| {
+                owner.next_index += 1;
+                owner.next_index - 1
+            });
+            stack.push(Frame {
+                indent,
+                segment: format!("[{position}]"),
+                is_item: true,
+                next_index: 0,
+            });
+            let path = path_of(&stack);
+            out.insert(
+                path,
+                Location {
+                    line,
+                    column: indent + 1,
+                },
+            );
+            let after = rest[1..].trim_start();
+            indent += rest.len() - after.len();
+            rest = after;
+        }
+
+        let Some(key) = yaml_key(rest) else {
+            continue;
+        };
+        while stack.last().is_some_and(|top| top.indent >= indent) {
+            stack.pop();
+        }
+        stack.push(Frame {
+            indent,
+            segment: key,
+            is_item: false,
+            next_index: 0,
+        });
+        out.insert(
+            path_of(&stack),
+            Location {
+                line,
+                column: raw[..indent].chars().count() + 1,
+            },
+        );
+    }
+}
+
+/// The key of a `key: value` (or `key:`) line, unquoted.
+#[cfg(feature = "yaml")]
+fn yaml_key(line: &str) -> Option<String> {
+    let (key, rest) = if let Some(quote @ ('"' | '\'')) = line.chars().next() {
+        let close = line[1..].find(quote)? + 1;
+        (&line[1..close], &line[close + 1..])
+    } else {
+        let split = line
+            .find(": ")
+            .or_else(|| line.strip_suffix(':').map(str::len))?;
+        (line[..split].trim_end(), &line[split..])
+    };
+    let valid = rest.starts_with(':') && (rest.len() == 1 || rest[1..].starts_with([' ', '\t']));
+    (valid && !key.is_empty() && !key.starts_with(['{', '['])).then(|| key.to_string())
+}
+
+#[cfg(any(feature = "toml", feature = "yaml"))]
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+/// Byte offset → line/column lookup.
+#[cfg(feature = "toml")]
+struct LineIndex<'a> {
+    text: &'a str,
+    starts: Vec<usize>,
+}
+
+#[cfg(feature = "toml")]
+impl<'a> LineIndex<'a> {
+    fn new(text: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        Self { text, starts }
+    }
+
+    fn locate(&self, offset: usize) -> Location {
+        let line = self.starts.partition_point(|start| *start <= offset);
+        let start = self.starts[line - 1];
+        Location {
+            line,
+            column: self.text[start..offset].chars().count() + 1,
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Isolation for tests that load configs.
+//!
+//! Environment variables and the working directory are process-wide, so
+//! tests that change them race with each other. A [`Sandbox`] takes a
+//! process-wide lock, snapshots the environment, and puts everything back
+//! when dropped, including variables set by `.env` files during the test:
+//!
+//! ```ignore
+//! let sandbox = Sandbox::new()
+//!     .env("APP_PORT", "9000")
+//!     .args(["app", "--debug"]);
+//! sandbox.config_file("app", "config.toml", "name = \"test\"");
+//! let cfg = sandbox.loader::<AppConfig>().load()?;
+//! ```
+//!
+//! Each sandbox owns a fresh temporary directory with `XDG_CONFIG_HOME`
+//! pointing inside it, and clears `CONFIG_FILE`, `CONFIG_FORMAT`, and
+//! `CONFIG_PROFILE` so the host's settings do not leak in. Code outside a
+//! sandbox that reads the environment at the same time is not protected.
+
+use crate::dotenv::PROFILE_ENV;
+use crate::loader::{Loader, LoaderExt};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+/// Held by the live sandbox; only one exists at a time.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Variables cleared in every sandbox.
+const CLEARED: &[&str] = &["CONFIG_FILE", "CONFIG_FORMAT", PROFILE_ENV];
+
+/// Temporary env vars, argv, config directory, and optionally working
+/// directory for one test; see the [module docs](self).
+pub struct Sandbox {
+    root: PathBuf,
+    args: Vec<String>,
+    /// The environment when the sandbox was created, restored on drop.
+    saved: HashMap<OsString, OsString>,
+    previous_dir: Option<PathBuf>,
+    // Dropped last, after everything is restored.
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sandbox {
+    /// Wait for any other sandbox to finish, then create a fresh one.
+    ///
+    /// # Panics
+    ///
+    /// If the temporary directory cannot be created.
+    pub fn new() -> Self {
+        // A test that panicked inside its sandbox still restored everything.
+        let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let root = std::env::temp_dir().join(format!(
+            "cnfg-sandbox-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::SeqCst)
+        ));
+        fs::create_dir_all(root.join("config")).expect("create sandbox directory");
+
+        let mut sandbox = Self {
+            root,
+            args: vec!["app".to_string()],
+            saved: std::env::vars_os().collect(),
+            previous_dir: None,
+            _guard: guard,
+        };
+        let config_home = sandbox.root.join("config");
+        sandbox = sandbox.env("XDG_CONFIG_HOME", config_home);
+        for name in CLEARED {
+            sandbox = sandbox.remove_env(name);
+        }
+        sandbox
+    }
+
+    /// Set an environment variable until the sandbox is dropped.
+    pub fn env(self, name: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+        // SAFETY: the sandbox lock serializes environment changes between
+        // sandboxes; see the module docs.
+        unsafe { std::env::set_var(name.into(), value.into()) };
+        self
+    }
+
+    /// Unset an environment variable until the sandbox is dropped.
+    pub fn remove_env(self, name: impl Into<OsString>) -> Self {
+        // SAFETY: as in `env`.
+        unsafe { std::env::remove_var(name.into()) };
+        self
+    }
+
+    /// Command line used by [`Sandbox::loader`]; the first item is the
+    /// program name. Defaults to `["app"]`.
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Run with the sandbox directory as the working directory, so `.env`
+    /// files and `config.*` files written with [`Sandbox::file`] are found.
+    ///
+    /// # Panics
+    ///
+    /// If the working directory cannot be changed.
+    pub fn current_dir(mut self) -> Self {
+        if self.previous_dir.is_none() {
+            self.previous_dir = Some(std::env::current_dir().expect("read working directory"));
+        }
+        std::env::set_current_dir(&self.root).expect("enter sandbox directory");
+        self
+    }
+
+    /// The sandbox's temporary directory.
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// The per-user config directory of `app`, as found by
+    /// [`dirs::user_config_dir`](crate::dirs::user_config_dir).
+    pub fn config_dir(&self, app: &str) -> PathBuf {
+        self.root.join("config").join(app)
+    }
+
+    /// Write `contents` to `name` inside the sandbox directory, creating
+    /// parent directories, and return its path.
+    ///
+    /// # Panics
+    ///
+    /// If the file cannot be written.
+    pub fn file(&self, name: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> PathBuf {
+        write(self.root.join(name), contents)
+    }
+
+    /// Write `contents` to `name` inside the config directory of `app`
+    /// and return its path.
+    ///
+    /// # Panics
+    ///
+    /// If the file cannot be written.
+    pub fn config_file(
+        &self,
+        app: &str,
+        name: impl AsRef<Path>,
+        contents: impl AsRef<[u8]>,
+    ) -> PathBuf {
+        write(self.config_dir(app).join(name), contents)
+    }
+
+    /// A loader for `T` that parses the sandbox's [`args`](Sandbox::args).
+    pub fn loader<T: LoaderExt>(&self) -> Loader<T> {
+        T::loader().args(self.args.clone())
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        // SAFETY: the sandbox lock is still held.
+        for (name, _) in std::env::vars_os() {
+            if !self.saved.contains_key(&name) {
+                unsafe { std::env::remove_var(&name) };
+            }
+        }
+        for (name, value) in self.saved.drain() {
+            if std::env::var_os(&name).as_ref() != Some(&value) {
+                unsafe { std::env::set_var(&name, value) };
+            }
+        }
+        if let Some(dir) = self.previous_dir.take() {
+            let _ = std::env::set_current_dir(dir);
+        }
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+fn write(path: PathBuf, contents: impl AsRef<[u8]>) -> PathBuf {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("create sandbox directory");
+    }
+    fs::write(&path, contents).expect("write sandbox file");
+    path
+}
@@ -0,0 +1,44 @@
+//! `tracing` spans and events for the load pipeline (feature `tracing`).
+//!
+//! Everything is emitted under the `cnfg` target. Events name files, env
+//! vars, flags, and paths, and count values, but never include the values
+//! themselves. Without the feature the macros expand to nothing.
+
+/// Enter a debug span that lasts until the end of the enclosing block.
+macro_rules! stage {
+    ($name:literal) => {
+        #[cfg(feature = "tracing")]
+        let _stage = tracing::debug_span!(target: "cnfg", $name).entered();
+    };
+}
+
+/// Emit an event at the given level (`DEBUG`, `TRACE`, ...).
+macro_rules! event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::event!(target: "cnfg", tracing::Level::$level, $($arg)+);
+    };
+}
+
+pub(crate) use {event, stage};
+
+/// Number of leaf values in a layer.
+#[cfg(feature = "tracing")]
+pub(crate) fn leaf_count(value: &serde_json::Value) -> usize {
+    match value {
+        serde_json::Value::Object(map) if !map.is_empty() => map.values().map(leaf_count).sum(),
+        _ => 1,
+    }
+}
+
+/// Number of fields whose winning value came from a matching origin.
+#[cfg(feature = "tracing")]
+pub(crate) fn overrides(
+    provenance: &crate::Provenance,
+    matches: impl Fn(&crate::Origin) -> bool,
+) -> usize {
+    provenance
+        .iter()
+        .filter(|(_, origin)| matches(origin))
+        .count()
+}
@@ -1,5 +1,7 @@
 use crate::error::ValidationErrors;
+use crate::merge::MergeStrategy;
 use serde::Deserialize;
+use std::borrow::Cow;
 
 /// Kind of configuration value.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -8,9 +10,92 @@ pub enum Kind {
     Int,
     Float,
     String,
+    /// `std::net::IpAddr`
+    IpAddr,
+    /// `std::net::SocketAddr`
+    SocketAddr,
+    /// `std::path::PathBuf`; `~` and `$VAR` are expanded after merging.
+    Path,
+    /// Integer byte count from `#[cnfg(bytes)]`; accepts `512MB`, `2GiB`, ...
+    Bytes,
+    /// RFC 3339 date-time (`chrono::DateTime`, `time::OffsetDateTime`).
+    DateTime,
+    /// RFC 3339 full date (`chrono::NaiveDate`, `time::Date`).
+    Date,
     Object,
+    /// A type cnfg does not know the shape of (enums, maps, custom types).
+    /// Env/CLI input is passed to serde as a string; no type checking.
+    Any,
+    /// A field decoded by its own `#[cnfg(parse_with = "...")]` function.
+    /// Env/CLI input goes through that function; no type checking.
+    Custom,
 }
 
+impl Kind {
+    /// Human-readable name used in help and generated docs.
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Bool => "bool",
+            Kind::Int => "integer",
+            Kind::Float => "float",
+            Kind::String => "string",
+            Kind::IpAddr => "ip address",
+            Kind::SocketAddr => "socket address",
+            Kind::Path => "path",
+            Kind::Bytes => "byte size",
+            Kind::DateTime => "date-time",
+            Kind::Date => "date",
+            Kind::Object => "object",
+            Kind::Any => "value",
+            Kind::Custom => "custom",
+        }
+    }
+
+    /// JSON Schema `format` of values of this kind, if it has one.
+    pub fn format(self) -> Option<&'static str> {
+        match self {
+            Kind::DateTime => Some("date-time"),
+            Kind::Date => Some("date"),
+            _ => None,
+        }
+    }
+
+    /// Placeholder shown after a flag taking this kind of value in help.
+    pub fn placeholder(self) -> &'static str {
+        match self {
+            Kind::Bool => "BOOL",
+            Kind::Int => "INT",
+            Kind::Float => "FLOAT",
+            Kind::String => "STRING",
+            Kind::IpAddr => "IP",
+            Kind::SocketAddr => "ADDR",
+            Kind::Path => "PATH",
+            Kind::Bytes => "SIZE",
+            Kind::DateTime => "DATETIME",
+            Kind::Date => "DATE",
+            Kind::Object | Kind::Any | Kind::Custom => "VALUE",
+        }
+    }
+}
+
+/// Whether a field's env var or flag wins when both are set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Precedence {
+    /// Flags override env vars (defaults < files < env < flags).
+    #[default]
+    CliOverEnv,
+    /// A set env var overrides the flag, e.g. a `PORT` injected by the
+    /// platform; the flag is ignored with a [`WarningKind::Shadowed`]
+    /// warning. `--set` overrides still win.
+    ///
+    /// [`WarningKind::Shadowed`]: crate::WarningKind::Shadowed
+    EnvOverCli,
+}
+
+/// Decoder for env and CLI strings set with `#[cnfg(parse_with = "...")]`,
+/// returning the value to merge or a message for the parse error.
+pub type ParseFn = fn(&str) -> Result<serde_json::Value, String>;
+
 /// Specification of a config field (for env + defaults).
 #[derive(Debug, Clone)]
 pub struct FieldSpec {
@@ -18,6 +103,8 @@ pub struct FieldSpec {
     pub name: &'static str,
     /// Optional env var to read from
     pub env: Option<&'static str>,
+    /// Env vars read, in order, when `env` is unset (`#[cnfg(env_alias)]`).
+    pub env_aliases: &'static [&'static str],
     /// Fully-qualified dotted path (e.g. `database.url`).
     pub path: &'static str,
     /// Combined doc comments extracted from the field.
@@ -28,6 +115,42 @@ pub struct FieldSpec {
     pub default: Option<&'static str>,
     /// Whether this field was declared as required.
     pub required: bool,
+    /// Dotted path whose value, when set and not `false`, makes this field
+    /// required (`#[cnfg(required_if = "...")]`).
+    pub required_if: Option<&'static str>,
+    /// Dotted path whose value, unless set and not `false`, makes this field
+    /// required (`#[cnfg(required_unless = "...")]`).
+    pub required_unless: Option<&'static str>,
+    /// Whether the field is an `Option<T>`, so `null` means `None`.
+    pub optional: bool,
+    /// Whether `<ENV>_FILE` may name a file holding the value.
+    pub allow_file: bool,
+    /// Whether the field holds a list (`Vec<T>`) of `kind` values.
+    pub list: bool,
+    /// Deprecation note shown when the field is set explicitly.
+    pub deprecated: Option<&'static str>,
+    /// Previous dotted path whose values migrate to this field.
+    pub renamed_from: Option<&'static str>,
+    /// How layers combine for this field; `None` keeps the default deep merge.
+    pub merge: Option<MergeStrategy>,
+    /// Whether this map collects the undeclared keys of its struct
+    /// (`#[cnfg(extra)]`).
+    pub extra: bool,
+    /// Whether the value is sensitive (`#[cnfg(secret)]`) and kept out of
+    /// fingerprints.
+    pub secret: bool,
+    /// Secret manager reference resolved when no layer sets the field
+    /// (`#[cnfg(secret_ref = "...")]`).
+    pub secret_ref: Option<&'static str>,
+    /// Whether a relative path set by a config file is resolved against that
+    /// file's directory (`#[cnfg(relative_to_config)]`).
+    pub relative_to_config: bool,
+    /// Custom decoder for env input (`#[cnfg(parse_with = "...")]`); it
+    /// receives the whole string, commas included.
+    pub parse: Option<ParseFn>,
+    /// Whether the env var or flag wins (`#[cnfg(precedence = "...")]`);
+    /// `None` uses the loader's order.
+    pub precedence: Option<Precedence>,
 }
 
 /// Specification of a CLI argument.
@@ -49,6 +172,57 @@ pub struct CliSpec {
     pub default: Option<&'static str>,
     /// Whether this flag is required (mirrors field requirement).
     pub required: bool,
+    /// Whether the flag may repeat, collecting values into a list.
+    pub multiple: bool,
+    /// Accepted values (from `#[cnfg(values(...))]`); empty means any.
+    pub values: &'static [&'static str],
+    /// Placeholder shown in help, e.g. `PORT` for `--port <PORT>`
+    /// (`#[cnfg(cli(value_name = "..."))]`); `None` derives one from `kind`.
+    pub value_name: Option<&'static str>,
+    /// Whether the flag is left out of `--help` and completions but still
+    /// parsed (`#[cnfg(cli(hidden))]`); `--help-all` lists it.
+    pub hidden: bool,
+    /// Custom decoder for the flag's value (`#[cnfg(parse_with = "...")]`).
+    pub parse: Option<ParseFn>,
+}
+
+/// A subcommand backed by an `Option<Nested>` field marked `#[cnfg(command)]`.
+///
+/// The command is selected by the first positional argument; its flags are
+/// accepted unprefixed after it and its section is only populated while it
+/// is selected.
+#[derive(Debug, Clone)]
+pub struct CommandSpec {
+    /// Positional name (e.g. `serve`).
+    pub name: &'static str,
+    /// Dotted path of the backing field.
+    pub path: &'static str,
+    /// Documentation extracted from the field.
+    pub doc: Option<&'static str>,
+    /// Flags of the command's config, relative to `path`.
+    pub cli_specs: fn() -> &'static [CliSpec],
+    /// Defaults of the command's config, relative to `path`.
+    pub defaults_json: fn() -> serde_json::Value,
+}
+
+/// A list of nested configs backed by a `Vec<Nested>` (or
+/// `Option<Vec<Nested>>`) field marked `#[cnfg(nested)]`.
+///
+/// Element fields are not part of the parent's [`ConfigMeta::field_specs`];
+/// each element is defaulted, type checked, and validated against the
+/// element type's metadata, with issues reported as `path[index].field`.
+#[derive(Debug, Clone)]
+pub struct ListSpec {
+    /// Dotted path of the backing field.
+    pub path: &'static str,
+    /// Metadata of the element type, relative to one element.
+    pub field_specs: fn() -> &'static [FieldSpec],
+    /// Required fields of the element type, relative to one element.
+    pub required_fields: fn() -> &'static [&'static str],
+    /// Defaults merged beneath every element.
+    pub defaults_json: fn() -> serde_json::Value,
+    /// Lists nested inside the element type.
+    pub lists: fn() -> &'static [ListSpec],
 }
 
 /// Trait that all derived config structs will implement
@@ -59,6 +233,19 @@ pub trait ConfigMeta: Sized + for<'de> Deserialize<'de> {
     /// JSON object containing defaults for each field.
     fn defaults_json() -> serde_json::Value;
 
+    /// Same as [`defaults_json`](Self::defaults_json) without the copy. The
+    /// derive builds the object once and borrows it from then on.
+    fn defaults() -> Cow<'static, serde_json::Value> {
+        Cow::Owned(Self::defaults_json())
+    }
+
+    /// Why the defaults could not be built, e.g. a
+    /// `#[cnfg(default_fn = "...")]` value that failed to serialize. Loading
+    /// reports it as [`CnfgError::Serialize`](crate::CnfgError::Serialize).
+    fn defaults_error() -> Option<&'static str> {
+        None
+    }
+
     /// Metadata about all fields in the struct.
     fn field_specs() -> &'static [FieldSpec];
 
@@ -72,6 +259,177 @@ pub trait ConfigMeta: Sized + for<'de> Deserialize<'de> {
     fn doc() -> Option<&'static str> {
         None
     }
+
+    /// Text printed above the usage line of `--help` instead of the doc
+    /// comment (from `#[cnfg(about = "...")]`).
+    fn about() -> Option<&'static str> {
+        None
+    }
+
+    /// Text printed at the end of `--help` (from
+    /// `#[cnfg(after_help = "...")]`).
+    fn after_help() -> Option<&'static str> {
+        None
+    }
+
+    /// Application name used for platform config directory discovery
+    /// (from `#[cnfg(app_name = "...")]`).
+    fn app_name() -> Option<&'static str> {
+        None
+    }
+
+    /// Prefix shared by the application's environment variables (from
+    /// `#[cnfg(env_prefix = "...")]`); unbound variables under it are
+    /// reported as warnings.
+    fn env_prefix() -> Option<&'static str> {
+        None
+    }
+
+    /// Whether fields without an `env` name read the env prefix followed by
+    /// their upper-cased path, e.g. `DATABASE_PORT` (from
+    /// `#[cnfg(auto_env)]`).
+    fn auto_env() -> bool {
+        false
+    }
+
+    /// Version reported by `--version` (from `#[cnfg(version)]`); `None`
+    /// leaves the flag unhandled.
+    fn version() -> Option<&'static str> {
+        None
+    }
+
+    /// Which `.env` files are read (from `#[cnfg(dotenv = ...)]`).
+    fn dotenv() -> crate::Dotenv {
+        crate::Dotenv::Search
+    }
+
+    /// Subcommands declared with `#[cnfg(command)]`.
+    fn commands() -> &'static [CommandSpec] {
+        &[]
+    }
+
+    /// Lists of nested configs declared with `Vec<Nested>` fields.
+    fn lists() -> &'static [ListSpec] {
+        &[]
+    }
+
+    /// The fields of this type as a tree, keeping nested sections and their
+    /// docs. The default lists every field as a leaf.
+    fn schema() -> crate::Schema {
+        crate::Schema::flat::<Self>()
+    }
+
+    /// Flags and env vars bound by this type, checked for collisions when
+    /// the derive embeds it as a nested field.
+    #[doc(hidden)]
+    const BINDINGS: Bindings = Bindings::EMPTY;
+
+    /// Paths read through `#[cnfg(accessors)]` methods of this type and its
+    /// nested sections, joined to `prefix`; see [`crate::audit`].
+    #[doc(hidden)]
+    fn audit_reads(_prefix: &str, _out: &mut Vec<String>) {}
+}
+
+/// Flag and env var names of a config type as constants, so the derive can
+/// reject collisions with nested types while the crate compiles.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy)]
+pub struct Bindings {
+    /// Flags of direct fields, without `--`.
+    pub flags: &'static [&'static str],
+    /// Env vars of direct fields.
+    pub envs: &'static [&'static str],
+    /// Nested sections as (flag prefix, bindings of the nested type).
+    pub nested: &'static [(&'static str, &'static Bindings)],
+}
+
+impl Bindings {
+    pub const EMPTY: Bindings = Bindings {
+        flags: &[],
+        envs: &[],
+        nested: &[],
+    };
+
+    /// Whether `flag` (without `--`) is bound here, counting nested flags
+    /// with their prefixes.
+    pub const fn has_flag(&self, flag: &str) -> bool {
+        self.has_flag_bytes(flag.as_bytes())
+    }
+
+    const fn has_flag_bytes(&self, flag: &[u8]) -> bool {
+        let mut i = 0;
+        while i < self.flags.len() {
+            if bytes_eq(self.flags[i].as_bytes(), flag) {
+                return true;
+            }
+            i += 1;
+        }
+        let mut i = 0;
+        while i < self.nested.len() {
+            let (prefix, nested) = self.nested[i];
+            let prefix = prefix.as_bytes();
+            if flag.len() > prefix.len() && flag[prefix.len()] == b'-' {
+                let (head, rest) = flag.split_at(prefix.len());
+                if bytes_eq(head, prefix) && nested.has_flag_bytes(rest.split_at(1).1) {
+                    return true;
+                }
+            }
+            i += 1;
+        }
+        false
+    }
+
+    /// Whether `env` is bound here or in a nested section.
+    pub const fn has_env(&self, env: &str) -> bool {
+        let mut i = 0;
+        while i < self.envs.len() {
+            if bytes_eq(self.envs[i].as_bytes(), env.as_bytes()) {
+                return true;
+            }
+            i += 1;
+        }
+        let mut i = 0;
+        while i < self.nested.len() {
+            if self.nested[i].1.has_env(env) {
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+
+    /// Whether any env var bound here is also bound by `other`.
+    pub const fn shares_env(&self, other: &Bindings) -> bool {
+        let mut i = 0;
+        while i < self.envs.len() {
+            if other.has_env(self.envs[i]) {
+                return true;
+            }
+            i += 1;
+        }
+        let mut i = 0;
+        while i < self.nested.len() {
+            if self.nested[i].1.shares_env(other) {
+                return true;
+            }
+            i += 1;
+        }
+        false
+    }
+}
+
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
 }
 
 /// Trait implemented by config structs that support runtime validation.
@@ -89,11 +447,32 @@ impl FieldSpec {
         Self {
             name: self.name,
             env: self.env,
+            env_aliases: self.env_aliases,
             path: combined_path,
             doc: self.doc,
             kind: self.kind,
             default: self.default,
             required: self.required,
+            required_if: self
+                .required_if
+                .map(|path| crate::util::leak_string(format!("{prefix}.{path}"))),
+            required_unless: self
+                .required_unless
+                .map(|path| crate::util::leak_string(format!("{prefix}.{path}"))),
+            optional: self.optional,
+            allow_file: self.allow_file,
+            list: self.list,
+            deprecated: self.deprecated,
+            renamed_from: self
+                .renamed_from
+                .map(|old| crate::util::leak_string(format!("{prefix}.{old}"))),
+            merge: self.merge,
+            extra: self.extra,
+            secret: self.secret,
+            secret_ref: self.secret_ref,
+            relative_to_config: self.relative_to_config,
+            parse: self.parse,
+            precedence: self.precedence,
         }
     }
 
@@ -101,6 +480,21 @@ impl FieldSpec {
     pub fn segments(&self) -> Vec<&'static str> {
         self.path.split('.').collect()
     }
+
+    /// Dotted path of the struct holding this field (empty at the top level).
+    pub fn parent_path(&self) -> &'static str {
+        self.path.rsplit_once('.').map_or("", |(parent, _)| parent)
+    }
+}
+
+impl ListSpec {
+    /// Produce a copy of this spec with `prefix.` applied to the path.
+    pub fn with_prefix(&self, prefix: &'static str) -> Self {
+        Self {
+            path: crate::util::leak_string(format!("{prefix}.{}", self.path)),
+            ..self.clone()
+        }
+    }
 }
 
 impl CliSpec {
@@ -121,9 +515,20 @@ impl CliSpec {
             takes_value: self.takes_value,
             default: self.default,
             required: self.required,
+            multiple: self.multiple,
+            values: self.values,
+            value_name: self.value_name,
+            hidden: self.hidden,
+            parse: self.parse,
         }
     }
 
+    /// Placeholder for the flag's value: `value_name`, else one derived
+    /// from the kind.
+    pub fn placeholder(&self) -> &'static str {
+        self.value_name.unwrap_or_else(|| self.kind.placeholder())
+    }
+
     /// Return dotted path segments for this CLI flag.
     pub fn segments(&self) -> Vec<&'static str> {
         self.path.split('.').collect()
@@ -14,11 +14,237 @@ pub fn format_doc(doc: Option<&str>) -> Option<String> {
     })
 }
 
-/// Render a CLI flag with padding suitable for help output.
-pub fn format_flag(flag: &str, takes_value: bool) -> String {
-    if takes_value {
-        format!("--{} <value>", flag)
+/// Render a CLI flag for help output, followed by `<value_name>` when it
+/// takes a value.
+pub fn format_flag(flag: &str, value_name: Option<&str>) -> String {
+    match value_name {
+        Some(name) => format!("--{} <{}>", flag, name),
+        None => format!("--{}", flag),
+    }
+}
+
+/// Expand a leading `~` and `$VAR` / `${VAR}` references in a path string.
+/// `$${` produces a literal `${`.
+pub fn expand_path(raw: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    if rest == "~" || rest.starts_with("~/") || rest.starts_with("~\\") {
+        let home = crate::dirs::home_dir().ok_or("cannot expand `~`: home directory unknown")?;
+        out.push_str(&home.to_string_lossy());
+        rest = &rest[1..];
+    }
+
+    while let Some(idx) = rest.find('$') {
+        out.push_str(&rest[..idx]);
+        let after = &rest[idx + 1..];
+        if let Some(escaped) = after.strip_prefix("${") {
+            out.push_str("${");
+            rest = escaped;
+            continue;
+        }
+        let (name, consumed) = if let Some(braced) = after.strip_prefix('{') {
+            let end = braced
+                .find('}')
+                .ok_or_else(|| format!("unterminated `${{` in `{raw}`"))?;
+            if end == 0 {
+                return Err(format!("empty `${{}}` in `{raw}`"));
+            }
+            (&braced[..end], end + 2)
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        };
+        if name.is_empty() {
+            out.push('$');
+        } else {
+            let value = std::env::var(name)
+                .map_err(|_| format!("environment variable `{name}` is not set"))?;
+            out.push_str(&value);
+        }
+        rest = &after[consumed..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Parse a human-readable byte size such as `512MB`, `2GiB`, or `1024`.
+///
+/// Decimal units (`KB`, `MB`, `GB`, `TB`, `PB`) are powers of 1000 and binary
+/// units (`KiB` ... `PiB`) powers of 1024. Units are case-insensitive; a bare
+/// `K`/`M`/`G`/`T`/`P` is decimal.
+pub fn parse_byte_size(raw: &str) -> Result<u64, String> {
+    let text = raw.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '_'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number = number.replace('_', "");
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1000,
+        "m" | "mb" => 1000u64.pow(2),
+        "g" | "gb" => 1000u64.pow(3),
+        "t" | "tb" => 1000u64.pow(4),
+        "p" | "pb" => 1000u64.pow(5),
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        "pib" => 1 << 50,
+        _ => {
+            return Err(format!(
+                "expected a byte size like `512MB` or `2GiB`, found `{raw}`"
+            ));
+        }
+    };
+    let invalid = || format!("expected a byte size like `512MB` or `2GiB`, found `{raw}`");
+
+    if let Ok(whole) = number.parse::<u64>() {
+        return whole
+            .checked_mul(multiplier)
+            .ok_or_else(|| format!("byte size `{raw}` is too large"));
+    }
+    let value = number.parse::<f64>().map_err(|_| invalid())? * multiplier as f64;
+    if value.fract() != 0.0 {
+        return Err(format!("byte size `{raw}` is not a whole number of bytes"));
+    }
+    if !value.is_finite() || value >= u64::MAX as f64 {
+        return Err(format!("byte size `{raw}` is too large"));
+    }
+    Ok(value as u64)
+}
+
+/// Parse an integer as written in Rust source: an optional sign, a `0x`,
+/// `0o`, or `0b` prefix, and `_` between digits (`1_048_576`, `0o755`).
+///
+/// Values beyond `i64` are accepted up to `u64::MAX`.
+pub(crate) fn parse_int(raw: &str) -> Option<serde_json::Number> {
+    let (negative, unsigned) = match raw.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, raw.strip_prefix('+').unwrap_or(raw)),
+    };
+    let prefix = unsigned.get(..2).map(str::to_ascii_lowercase);
+    let (radix, digits) = match prefix.as_deref() {
+        Some("0x") => (16, &unsigned[2..]),
+        Some("0o") => (8, &unsigned[2..]),
+        Some("0b") => (2, &unsigned[2..]),
+        _ => (10, unsigned),
+    };
+    if digits.starts_with('_') || digits.ends_with('_') || digits.starts_with(['+', '-']) {
+        return None;
+    }
+    let magnitude = u64::from_str_radix(&digits.replace('_', ""), radix).ok()?;
+    if negative {
+        let value = 0i64.checked_sub_unsigned(magnitude)?;
+        Some(value.into())
     } else {
-        format!("--{}", flag)
+        Some(magnitude.into())
+    }
+}
+
+/// Base of `defaults_json()` for `#[cnfg(use_default)]`: `T::default()` as
+/// a JSON object, minus `null`s and the `required` dotted paths, which must
+/// still be set explicitly.
+pub fn default_base<T: Default + serde::Serialize>(
+    required: &[&str],
+) -> serde_json::Map<String, serde_json::Value> {
+    let value = serde_json::to_value(T::default()).expect("Default value must serialize");
+    let serde_json::Value::Object(mut map) = value else {
+        panic!("#[cnfg(use_default)] needs a struct that serializes to an object");
+    };
+    drop_nulls(&mut map);
+    for path in required {
+        let mut segments: Vec<&str> = path.split('.').collect();
+        let Some(last) = segments.pop() else {
+            continue;
+        };
+        let parent = segments.into_iter().try_fold(&mut map, |current, segment| {
+            current.get_mut(segment)?.as_object_mut()
+        });
+        if let Some(parent) = parent {
+            parent.remove(last);
+        }
+    }
+    map
+}
+
+fn drop_nulls(map: &mut serde_json::Map<String, serde_json::Value>) {
+    map.retain(|_, value| !value.is_null());
+    for value in map.values_mut() {
+        if let serde_json::Value::Object(inner) = value {
+            drop_nulls(inner);
+        }
+    }
+}
+
+/// Read a text file; see [`decode_text`].
+pub fn read_text(path: &std::path::Path) -> std::io::Result<String> {
+    decode_text(std::fs::read(path)?)
+        .map_err(|msg| std::io::Error::new(std::io::ErrorKind::InvalidData, msg))
+}
+
+/// Decode file contents as text, dropping a byte-order mark. UTF-16 with a
+/// BOM is transcoded. With the `encoding` feature, UTF-16 without a BOM is
+/// detected and anything else that is not UTF-8 is read as Windows-1252
+/// (a superset of Latin-1); without it, such contents are rejected.
+pub fn decode_text(mut bytes: Vec<u8>) -> Result<String, String> {
+    match bytes.as_slice() {
+        [0xEF, 0xBB, 0xBF, ..] => {
+            bytes.drain(..3);
+        }
+        [0xFF, 0xFE, rest @ ..] => return decode_utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => return decode_utf16(rest, u16::from_be_bytes),
+        #[cfg(feature = "encoding")]
+        [first, 0, ..] if *first != 0 && bytes.len() % 2 == 0 => {
+            return decode_utf16(&bytes, u16::from_le_bytes);
+        }
+        #[cfg(feature = "encoding")]
+        [0, second, ..] if *second != 0 && bytes.len() % 2 == 0 => {
+            return decode_utf16(&bytes, u16::from_be_bytes);
+        }
+        _ => {}
+    }
+    match String::from_utf8(bytes) {
+        Ok(text) => Ok(text),
+        #[cfg(feature = "encoding")]
+        Err(e) => Ok(e.as_bytes().iter().map(|&b| windows_1252(b)).collect()),
+        #[cfg(not(feature = "encoding"))]
+        Err(e) => Err(format!(
+            "not valid UTF-8 (at byte {}); save it as UTF-8 or enable the `encoding` feature",
+            e.utf8_error().valid_up_to()
+        )),
+    }
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> Result<String, String> {
+    if bytes.len() % 2 != 0 {
+        return Err("truncated UTF-16: odd number of bytes".into());
+    }
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|e| {
+            format!(
+                "invalid UTF-16: unpaired surrogate {:#06x}",
+                e.unpaired_surrogate()
+            )
+        })
+}
+
+/// The character for a Windows-1252 byte. Its five unassigned bytes map to
+/// the Latin-1 control characters, as browsers do.
+#[cfg(feature = "encoding")]
+fn windows_1252(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8d}', 'Ž',
+        '\u{8f}', '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9d}',
+        'ž', 'Ÿ',
+    ];
+    match byte {
+        0x80..=0x9F => HIGH[usize::from(byte - 0x80)],
+        _ => char::from(byte),
     }
 }
@@ -0,0 +1,261 @@
+//! Checks behind the built-in `#[cnfg(validate(...))]` attributes.
+//!
+//! Derive-generated `validate()` bodies call these with the field's value
+//! and, on failure, record the returned message as an [`Issue`](crate::error::Issue).
+//!
+//! The filesystem checks touch the disk when `validate()` runs, so they only
+//! apply to fields that opt in. Relative paths resolve against the working
+//! directory, and messages name the absolute path that was checked.
+//!
+//! `forbid_in_profile` and `when_profile` depend on [`active_profile`]: the
+//! loader's profile while it validates, else `CONFIG_PROFILE`.
+
+use crate::datetime::{self, Timestamp};
+use serde_json::Value;
+use std::cell::RefCell;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+/// `validate(url)`: `value` parses as an absolute URL. A non-empty
+/// `schemes` restricts the scheme, and `require_host` rejects URLs such as
+/// `unix:/run/app.sock` that have no host.
+pub fn url(value: &str, schemes: &[&str], require_host: bool) -> Result<(), String> {
+    let parsed = url::Url::parse(value).map_err(|e| format!("invalid URL: {e}"))?;
+    if !schemes.is_empty() && !schemes.contains(&parsed.scheme()) {
+        return Err(format!(
+            "URL scheme `{}` is not allowed (expected {})",
+            parsed.scheme(),
+            one_of(schemes)
+        ));
+    }
+    if require_host && parsed.host_str().is_none_or(str::is_empty) {
+        return Err("URL must include a host".into());
+    }
+    Ok(())
+}
+
+/// `validate(file_exists)`: `path` names an existing file (not a directory).
+pub fn file_exists(path: &Path) -> Result<(), String> {
+    let full = absolute(path);
+    match fs::metadata(&full) {
+        Ok(meta) if meta.is_file() => Ok(()),
+        Ok(_) => Err(format!("not a file: {}", full.display())),
+        Err(_) => Err(format!("file not found: {}", full.display())),
+    }
+}
+
+/// `validate(dir_exists)`: `path` names an existing directory.
+pub fn dir_exists(path: &Path) -> Result<(), String> {
+    let full = absolute(path);
+    match fs::metadata(&full) {
+        Ok(meta) if meta.is_dir() => Ok(()),
+        Ok(_) => Err(format!("not a directory: {}", full.display())),
+        Err(_) => Err(format!("directory not found: {}", full.display())),
+    }
+}
+
+/// `validate(readable)`: the file at `path` can be opened, or the directory
+/// listed, by this process.
+pub fn readable(path: &Path) -> Result<(), String> {
+    let full = absolute(path);
+    let result = match fs::metadata(&full) {
+        Ok(meta) if meta.is_dir() => fs::read_dir(&full).map(drop),
+        Ok(_) => fs::File::open(&full).map(drop),
+        Err(e) => Err(e),
+    };
+    result.map_err(|e| format!("cannot read {}: {e}", full.display()))
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// `validate(email)`: a plain `local@domain` address. Display names,
+/// quoted local parts, and IP-literal domains are rejected.
+#[cfg(feature = "email")]
+pub fn email(value: &str) -> Result<(), String> {
+    let invalid = |reason: &str| Err(format!("invalid email address: {reason}"));
+    let Some((local, domain)) = value.rsplit_once('@') else {
+        return invalid("missing `@`");
+    };
+    if local.is_empty() || local.len() > 64 {
+        return invalid("the part before `@` must be 1 to 64 characters");
+    }
+    let atext = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+/=?^_`{|}~-".contains(c);
+    if local
+        .split('.')
+        .any(|part| part.is_empty() || !part.chars().all(atext))
+    {
+        return invalid("the part before `@` has an unexpected character or dot");
+    }
+    if !domain.contains('.') {
+        return invalid("the domain needs at least one dot");
+    }
+    check_hostname(domain).map_err(|reason| format!("invalid email address: {reason}"))
+}
+
+/// `validate(hostname)`: an RFC 1123 host name such as `db-1.internal`.
+/// A single trailing dot is allowed.
+#[cfg(feature = "hostname")]
+pub fn hostname(value: &str) -> Result<(), String> {
+    check_hostname(value).map_err(|reason| format!("invalid hostname: {reason}"))
+}
+
+#[cfg(any(feature = "email", feature = "hostname"))]
+fn check_hostname(value: &str) -> Result<(), String> {
+    let name = value.strip_suffix('.').unwrap_or(value);
+    if name.is_empty() {
+        return Err("empty name".into());
+    }
+    if name.len() > 253 {
+        return Err("longer than 253 characters".into());
+    }
+    for label in name.split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("label `{label}` must be 1 to 63 characters"));
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(format!("label `{label}` starts or ends with `-`"));
+        }
+        if let Some(c) = label
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || *c == '-'))
+        {
+            return Err(format!("unexpected character `{c}`"));
+        }
+    }
+    Ok(())
+}
+
+/// `validate(uuid)`: a UUID in hyphenated, simple, braced, or URN form.
+#[cfg(feature = "uuid")]
+pub fn uuid(value: &str) -> Result<(), String> {
+    uuid::Uuid::try_parse(value)
+        .map(drop)
+        .map_err(|e| format!("invalid UUID: {e}"))
+}
+
+/// `validate(semver)`: a semantic version such as `1.4.0-rc.1`.
+#[cfg(feature = "semver")]
+pub fn semver(value: &str) -> Result<(), String> {
+    semver::Version::parse(value)
+        .map(drop)
+        .map_err(|e| format!("invalid semantic version: {e}"))
+}
+
+/// `validate(port)`: a TCP/UDP port from 1 to 65535, or from 1024 with
+/// `unprivileged`.
+pub fn port<N>(value: N, unprivileged: bool) -> Result<(), String>
+where
+    N: TryInto<u16> + Copy + fmt::Display,
+{
+    let min = if unprivileged { 1024 } else { 1 };
+    match value.try_into() {
+        Ok(port) if port >= min => Ok(()),
+        _ if unprivileged => Err(format!(
+            "must be an unprivileged port (1024-65535), found {value}"
+        )),
+        _ => Err(format!("must be a port (1-65535), found {value}")),
+    }
+}
+
+/// `validate(range(...))` on date and date-time fields: `value` is within
+/// `min` and `max`, each a date (midnight UTC) or RFC 3339 date-time.
+pub fn date_range<T: Timestamp + ?Sized>(
+    value: &T,
+    min: Option<&str>,
+    max: Option<&str>,
+) -> Result<(), String> {
+    let at = value.timestamp();
+    let bound = |raw: &str| datetime::parse_bound(raw).expect("range bound checked by the derive");
+    if let Some(min) = min.filter(|min| at < bound(min)) {
+        return Err(format!("must be >= {min}"));
+    }
+    if let Some(max) = max.filter(|max| at > bound(max)) {
+        return Err(format!("must be <= {max}"));
+    }
+    Ok(())
+}
+
+/// `validate(cidr)`: an IPv4 or IPv6 network in `address/prefix` form, such
+/// as `10.0.0.0/8` or `fd00::/64`.
+pub fn cidr(value: &str) -> Result<(), String> {
+    let Some((address, prefix)) = value.split_once('/') else {
+        return Err(format!("invalid CIDR `{value}`: expected `address/prefix`"));
+    };
+    let address: IpAddr = address
+        .parse()
+        .map_err(|_| format!("invalid CIDR `{value}`: `{address}` is not an IP address"))?;
+    let max = if address.is_ipv4() { 32 } else { 128 };
+    match prefix.parse::<u8>() {
+        Ok(bits) if bits <= max => Ok(()),
+        _ => Err(format!(
+            "invalid CIDR `{value}`: prefix length must be 0-{max}, found `{prefix}`"
+        )),
+    }
+}
+
+thread_local! {
+    /// Profile of the load whose checks are running on this thread.
+    static PROFILE: RefCell<Option<Option<String>>> = const { RefCell::new(None) };
+}
+
+/// Run `check` with `profile` as the [`active_profile`].
+pub(crate) fn with_profile<R>(profile: Option<String>, check: impl FnOnce() -> R) -> R {
+    let previous = PROFILE.with(|cell| cell.replace(Some(profile)));
+    let result = check();
+    PROFILE.with(|cell| *cell.borrow_mut() = previous);
+    result
+}
+
+/// The profile profile-conditional checks compare against: the loader's
+/// while it validates, else a non-empty `CONFIG_PROFILE`.
+pub fn active_profile() -> Option<String> {
+    PROFILE
+        .with(|cell| cell.borrow().clone())
+        .unwrap_or_else(|| {
+            env::var(crate::dotenv::PROFILE_ENV)
+                .ok()
+                .filter(|name| !name.is_empty())
+        })
+}
+
+/// Whether `profile` is the [`active_profile`].
+pub fn in_profile(profile: &str) -> bool {
+    active_profile().as_deref() == Some(profile)
+}
+
+/// `validate(forbid_in_profile(profile = "...", value = ...))`: `value`,
+/// or an element of it when it is a list, is not `forbidden`.
+pub fn forbid_value(value: &Value, forbidden: &Value, profile: &str) -> Result<(), String> {
+    let found = match value {
+        Value::Array(items) => items.contains(forbidden),
+        other => other == forbidden,
+    };
+    if !found {
+        return Ok(());
+    }
+    let shown = match forbidden {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    Err(format!(
+        "`{shown}` is not allowed in the `{profile}` profile"
+    ))
+}
+
+/// `a`, `a or b`, `a, b, or c`.
+fn one_of(options: &[&str]) -> String {
+    match options {
+        [] => String::new(),
+        [only] => format!("`{only}`"),
+        [first, second] => format!("`{first}` or `{second}`"),
+        [rest @ .., last] => {
+            let rest: Vec<String> = rest.iter().map(|s| format!("`{s}`")).collect();
+            format!("{}, or `{last}`", rest.join(", "))
+        }
+    }
+}
@@ -0,0 +1,442 @@
+//! Polling reload of the effective configuration.
+//!
+//! [`Loader::watch`] runs the full pipeline on a background thread at a fixed
+//! interval (or faster, if a source asks for it via
+//! [`Source::refresh_interval`](crate::sources::Source::refresh_interval)).
+//! Sources that [notify changes](crate::sources::Source::notifies_changes)
+//! also trigger a reload as soon as they report one. When the effective
+//! config changes it is swapped in atomically and listeners are notified;
+//! failed reloads keep the last good config.
+//!
+//! [`Loader::refresh`] does the same under a [`RefreshPolicy`], which adds a
+//! random delay to each wait, so replicas started together do not poll
+//! remote stores in step, and decides what happens after a failure.
+//! [`Watcher::on_refresh`] and [`Watcher::stats`] report every reload for
+//! logging or metrics.
+//!
+//! Components interested in a single key can [`Watcher::subscribe`] to its
+//! path and receive the new value, typed, only when that key changes:
+//!
+//! ```ignore
+//! watcher.subscribe("database.pool", |size: u32| pool.resize(size))?;
+//! ```
+
+use crate::error::{CnfgError, Issue, IssueKind, ValidationErrors};
+use crate::global;
+use crate::loader::{Loader, LoaderExt};
+use crate::merge::get_path;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Longest a change listener blocks before checking whether to stop.
+const MAX_CHANGE_WAIT: Duration = Duration::from_secs(1);
+
+enum Signal {
+    Reload,
+    Stop,
+}
+
+type Listener<T> = Arc<dyn Fn(&Arc<T>) + Send + Sync>;
+
+/// Called with the previous and new config as JSON after every change.
+type Subscriber = Arc<dyn Fn(&Value, &Value) + Send + Sync>;
+
+type RefreshListener = Arc<dyn Fn(&RefreshEvent) + Send + Sync>;
+
+struct Shared<T> {
+    current: RwLock<Arc<T>>,
+    last_error: Mutex<Option<String>>,
+    listeners: Mutex<Vec<Listener<T>>>,
+    subscribers: Mutex<Vec<Subscriber>>,
+    refresh_listeners: Mutex<Vec<RefreshListener>>,
+    stats: Mutex<RefreshStats>,
+}
+
+/// What a [`Watcher`] does after a failed reload. The last good config
+/// stays active in every case.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnRefreshError {
+    /// Try again after the usual interval.
+    #[default]
+    Retry,
+    /// Double the wait after each failure in a row, up to this long.
+    Backoff(Duration),
+    /// Stop reloading.
+    Stop,
+}
+
+/// How [`Loader::refresh`] rebuilds the config in the background.
+#[derive(Debug, Clone)]
+pub struct RefreshPolicy {
+    interval: Duration,
+    jitter: Duration,
+    on_error: OnRefreshError,
+}
+
+impl RefreshPolicy {
+    /// Reload every `interval`, without jitter, retrying failures at the
+    /// same pace.
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            jitter: Duration::ZERO,
+            on_error: OnRefreshError::Retry,
+        }
+    }
+
+    /// Add a random delay of up to `jitter` to every wait.
+    pub fn jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// What to do after a failed reload.
+    pub fn on_error(mut self, on_error: OnRefreshError) -> Self {
+        self.on_error = on_error;
+        self
+    }
+
+    /// The wait before the next reload, given the failures in a row so far.
+    fn next_wait(&self, tick: Duration, failures: u32) -> Duration {
+        let base = match self.on_error {
+            OnRefreshError::Backoff(max) if failures > 0 => {
+                let factor = 1u32.checked_shl(failures).unwrap_or(u32::MAX);
+                tick.saturating_mul(factor).min(max.max(tick))
+            }
+            _ => tick,
+        };
+        base + random_up_to(self.jitter)
+    }
+}
+
+/// A uniformly random duration in `0..=max`.
+fn random_up_to(max: Duration) -> Duration {
+    let nanos = u64::try_from(max.as_nanos()).unwrap_or(u64::MAX);
+    if nanos == 0 {
+        return Duration::ZERO;
+    }
+    // Each `RandomState` is freshly keyed, so hashing nothing is random.
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_nanos(random % nanos.saturating_add(1))
+}
+
+/// One background reload, passed to [`Watcher::on_refresh`].
+#[derive(Debug, Clone)]
+pub struct RefreshEvent {
+    /// Whether the config changed, or why the reload failed.
+    pub result: Result<bool, String>,
+    /// How long the reload took.
+    pub elapsed: Duration,
+    /// Failed reloads in a row, including this one.
+    pub consecutive_failures: u32,
+}
+
+/// Counts of a watcher's background reloads, from [`Watcher::stats`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefreshStats {
+    /// Reloads that succeeded, whether or not the config changed.
+    pub successes: u64,
+    /// Successful reloads that changed the config.
+    pub changes: u64,
+    /// Reloads that failed, keeping the previous config.
+    pub failures: u64,
+    /// Failed reloads since the last success.
+    pub consecutive_failures: u32,
+    /// When the last successful reload finished; `None` until one has.
+    pub last_success: Option<SystemTime>,
+}
+
+/// Handle to a running config watcher. Dropping it stops the background thread.
+pub struct Watcher<T> {
+    shared: Arc<Shared<T>>,
+    signals: mpsc::Sender<Signal>,
+    stopped: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl<T> Watcher<T> {
+    /// The most recent successfully loaded config.
+    pub fn current(&self) -> Arc<T> {
+        self.shared
+            .current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Call `listener` with the new config after every change.
+    pub fn on_change(&self, listener: impl Fn(&Arc<T>) + Send + Sync + 'static) {
+        self.shared
+            .listeners
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Arc::new(listener));
+    }
+
+    /// Call `listener` with the value at `path` (dotted, as in
+    /// `database.pool` or `servers[0].port`) whenever a reload changes it.
+    ///
+    /// Fails with a [`CnfgError::Validation`] issue for `path` if it does
+    /// not name a value of the current config, or that value does not
+    /// deserialize as `V`.
+    pub fn subscribe<V>(
+        &self,
+        path: &str,
+        listener: impl Fn(V) + Send + Sync + 'static,
+    ) -> Result<(), CnfgError>
+    where
+        T: Serialize,
+        V: DeserializeOwned,
+    {
+        let issue = |kind, message: String| {
+            let mut errs = ValidationErrors::new();
+            errs.push(Issue::new(path, kind, message));
+            CnfgError::Validation(errs)
+        };
+        let current = serde_json::to_value(&*self.current())
+            .map_err(|err| CnfgError::Serialize(err.to_string()))?;
+        let found = get_path(&current, &path.split('.').collect::<Vec<_>>())
+            .map_err(|err| issue(IssueKind::Custom, err.to_string()))?
+            .ok_or_else(|| {
+                issue(
+                    IssueKind::Missing,
+                    "not a path of the watched config".into(),
+                )
+            })?;
+        V::deserialize(found).map_err(|err| issue(IssueKind::Type, err.to_string()))?;
+
+        let segments: Vec<String> = path.split('.').map(str::to_string).collect();
+        let at = move |root: &Value| {
+            let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+            get_path(root, &segments).ok().flatten().cloned()
+        };
+        let subscriber = move |old: &Value, new: &Value| {
+            let value = at(new);
+            if value == at(old) {
+                return;
+            }
+            // A value that no longer reads as `V` is skipped.
+            if let Some(Ok(value)) = value.map(serde_json::from_value::<V>) {
+                listener(value);
+            }
+        };
+        self.shared
+            .subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Arc::new(subscriber));
+        Ok(())
+    }
+
+    /// Publish the current config as the global `T` and replace it after
+    /// every change; see [`crate::global`].
+    pub fn publish_global(&self)
+    where
+        T: Send + Sync + 'static,
+    {
+        global::set(self.current());
+        self.on_change(|cfg| global::set(Arc::clone(cfg)));
+    }
+
+    /// Call `listener` after every background reload, successful or not,
+    /// e.g. to log failures or feed metrics.
+    pub fn on_refresh(&self, listener: impl Fn(&RefreshEvent) + Send + Sync + 'static) {
+        self.shared
+            .refresh_listeners
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Arc::new(listener));
+    }
+
+    /// Counts of the background reloads so far.
+    pub fn stats(&self) -> RefreshStats {
+        self.shared
+            .stats
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Message of the last failed reload, cleared by the next successful one.
+    pub fn last_error(&self) -> Option<String> {
+        self.shared
+            .last_error
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Stop watching and wait for the background thread to exit.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        // Change listeners notice the flag within `MAX_CHANGE_WAIT` and are
+        // not waited for.
+        self.stopped.store(true, Ordering::SeqCst);
+        let _ = self.signals.send(Signal::Stop);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl<T> Drop for Watcher<T> {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl<T> Shared<T> {
+    /// Make `cfg` current and notify listeners and subscribers.
+    fn swap(&self, cfg: Arc<T>, previous: &Value, value: &Value) {
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = Arc::clone(&cfg);
+        for listener in snapshot(&self.listeners) {
+            listener(&cfg);
+        }
+        for subscriber in snapshot(&self.subscribers) {
+            subscriber(previous, value);
+        }
+    }
+
+    /// Count a reload and pass it to the refresh listeners.
+    fn record(&self, event: RefreshEvent) {
+        {
+            let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+            stats.consecutive_failures = event.consecutive_failures;
+            match event.result {
+                Ok(changed) => {
+                    stats.successes += 1;
+                    stats.changes += u64::from(changed);
+                    stats.last_success = Some(SystemTime::now());
+                }
+                Err(_) => stats.failures += 1,
+            }
+        }
+        for listener in snapshot(&self.refresh_listeners) {
+            listener(&event);
+        }
+    }
+}
+
+/// Copy out the callbacks so they run without the lock held, free to
+/// register more.
+fn snapshot<F: ?Sized>(callbacks: &Mutex<Vec<Arc<F>>>) -> Vec<Arc<F>> {
+    callbacks.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+impl<T> Loader<T>
+where
+    T: LoaderExt + Send + Sync + 'static,
+{
+    /// Load once, then keep reloading every `interval` on a background thread.
+    ///
+    /// The initial load must succeed; later failures are recorded in
+    /// [`Watcher::last_error`] and the previous config stays active.
+    pub fn watch(self, interval: Duration) -> Result<Watcher<T>, CnfgError> {
+        self.refresh(RefreshPolicy::new(interval))
+    }
+
+    /// Like [`Loader::watch`], with the jitter and failure handling of
+    /// `policy`. Sources asking for a shorter
+    /// [`refresh_interval`](crate::sources::Source::refresh_interval) still
+    /// get it.
+    pub fn refresh(self, policy: RefreshPolicy) -> Result<Watcher<T>, CnfgError> {
+        let tick = self
+            .sources
+            .iter()
+            .filter_map(|source| source.refresh_interval())
+            .fold(policy.interval, Duration::min);
+
+        let initial = self.clone().load()?;
+        let mut last_value = serde_json::to_value(&initial)?;
+        let shared = Arc::new(Shared {
+            current: RwLock::new(Arc::new(initial)),
+            last_error: Mutex::new(None),
+            listeners: Mutex::new(Vec::new()),
+            subscribers: Mutex::new(Vec::new()),
+            refresh_listeners: Mutex::new(Vec::new()),
+            stats: Mutex::new(RefreshStats::default()),
+        });
+
+        let (signals, received) = mpsc::channel::<Signal>();
+        let stopped = Arc::new(AtomicBool::new(false));
+        for source in self
+            .sources
+            .iter()
+            .filter(|source| source.notifies_changes())
+        {
+            let source = Arc::clone(source);
+            let signals = signals.clone();
+            let stopped = Arc::clone(&stopped);
+            let wait = tick.min(MAX_CHANGE_WAIT);
+            std::thread::spawn(move || {
+                while !stopped.load(Ordering::SeqCst) {
+                    match source.wait_for_change(wait) {
+                        Ok(true) => {
+                            if signals.send(Signal::Reload).is_err() {
+                                break;
+                            }
+                        }
+                        Ok(false) => {}
+                        // The next poll reports the failure if it persists.
+                        Err(_) => std::thread::sleep(wait),
+                    }
+                }
+            });
+        }
+
+        let worker = Arc::clone(&shared);
+        let handle = std::thread::spawn(move || {
+            let mut failures = 0;
+            while let Ok(Signal::Reload) | Err(RecvTimeoutError::Timeout) =
+                received.recv_timeout(policy.next_wait(tick, failures))
+            {
+                let started = Instant::now();
+                let result = match self.clone().load() {
+                    Ok(cfg) => {
+                        *worker.last_error.lock().unwrap_or_else(|e| e.into_inner()) = None;
+                        let value = serde_json::to_value(&cfg).unwrap_or(Value::Null);
+                        let changed = value != last_value;
+                        if changed {
+                            let previous = std::mem::replace(&mut last_value, value);
+                            worker.swap(Arc::new(cfg), &previous, &last_value);
+                        }
+                        Ok(changed)
+                    }
+                    Err(err) => {
+                        let message = err.to_string();
+                        *worker.last_error.lock().unwrap_or_else(|e| e.into_inner()) =
+                            Some(message.clone());
+                        Err(message)
+                    }
+                };
+                failures = if result.is_ok() { 0 } else { failures + 1 };
+                worker.record(RefreshEvent {
+                    result,
+                    elapsed: started.elapsed(),
+                    consecutive_failures: failures,
+                });
+                if failures > 0 && policy.on_error == OnRefreshError::Stop {
+                    break;
+                }
+            }
+        });
+
+        Ok(Watcher {
+            shared,
+            signals,
+            stopped,
+            handle: Some(handle),
+        })
+    }
+}
@@ -0,0 +1,157 @@
+#![cfg(feature = "age")]
+
+use cnfg::age::{AgeCommand, AgeDecryptor};
+use cnfg::error::IssueKind;
+use cnfg::{Cnfg, CnfgError, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "")]
+    password: String,
+
+    #[cnfg(default = 5432)]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct SecretConfig {
+    #[cnfg(default = "app")]
+    name: String,
+
+    #[serde(default)]
+    tokens: Vec<String>,
+
+    #[cnfg(nested)]
+    database: Database,
+}
+
+/// "Decrypts" by reversing the armored payload line.
+struct Reverse;
+
+impl AgeDecryptor for Reverse {
+    fn decrypt(&self, armored: &str) -> Result<String, String> {
+        let lines: Vec<&str> = armored.lines().collect();
+        assert_eq!(lines[0], "-----BEGIN AGE ENCRYPTED FILE-----");
+        assert_eq!(lines[lines.len() - 1], "-----END AGE ENCRYPTED FILE-----");
+        match lines[1] {
+            "YmFk" => Err("no identity matched any of the recipients".into()),
+            payload => Ok(payload.chars().rev().collect()),
+        }
+    }
+}
+
+fn write_config(body: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, body).expect("write config");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    dir
+}
+
+#[test]
+fn decrypts_enc_values_and_coerces_them() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let _dir = write_config(
+        r#"{"name": "billing", "tokens": ["plain", "enc:Y2Jh"], "database": {"password": "enc:MnJldG51aA==", "port": "enc:2346"}}"#,
+    );
+    let cfg = SecretConfig::loader().args(["app"]).age(Reverse).load();
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+
+    let cfg = cfg.expect("decrypted");
+    assert_eq!(cfg.name, "billing");
+    assert_eq!(cfg.database.password, "==Aa15GdlJnM");
+    assert_eq!(cfg.database.port, 6432);
+    assert_eq!(cfg.tokens, ["plain", "hJ2Y"]);
+}
+
+#[test]
+fn reports_failures_on_the_field() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let _dir =
+        write_config(r#"{"tokens": ["enc:not base64!"], "database": {"password": "enc:YmFk"}}"#);
+    let failed = SecretConfig::loader().args(["app"]).age(Reverse).load();
+    let missing = SecretConfig::loader().args(["app"]).load();
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+
+    let Err(CnfgError::Validation(errs)) = failed else {
+        panic!("expected validation errors, got {failed:?}");
+    };
+    let issues = errs.into_vec();
+    assert_eq!(issues.len(), 2);
+    assert!(issues.iter().all(|i| matches!(i.kind, IssueKind::Decrypt)));
+    let by_field = |field: &str| {
+        issues
+            .iter()
+            .find(|i| i.field == field)
+            .map(|i| i.message.as_str())
+    };
+    assert_eq!(
+        by_field("database.password"),
+        Some("no identity matched any of the recipients")
+    );
+    assert_eq!(
+        by_field("tokens[0]"),
+        Some("encrypted value is not padded base64")
+    );
+
+    let Err(CnfgError::Validation(errs)) = missing else {
+        panic!("expected validation errors, got {missing:?}");
+    };
+    assert!(
+        errs.iter()
+            .all(|i| i.message.contains("set CONFIG_AGE_IDENTITY")),
+        "{errs:?}"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn runs_the_age_binary_with_the_identity_from_env() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = write_config(r#"{"database": {"password": "enc:aHVudGVyMgo="}}"#);
+    // Expects `--decrypt -i <identity>` and decodes the armored base64.
+    let script = dir.path().join("age");
+    std::fs::write(
+        &script,
+        "#!/bin/sh\n[ \"$1 $2\" = '--decrypt -i' ] && [ -f \"$3\" ] || { echo \"bad identity $3\" >&2; exit 1; }\ngrep -v -- ----- | base64 -d\n",
+    )
+    .expect("write script");
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+    let identity = dir.path().join("key.txt");
+    std::fs::write(&identity, "AGE-SECRET-KEY-1").expect("write identity");
+
+    let command = AgeCommand::new(&identity).program(&script);
+    assert_eq!(command.identity(), identity);
+    let cfg = SecretConfig::loader().args(["app"]).age(command).load();
+
+    // Without a decryptor on the loader, `age` from PATH runs with the
+    // identity named by CONFIG_AGE_IDENTITY.
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let mut dirs = vec![dir.path().to_path_buf()];
+    dirs.extend(std::env::split_paths(&path));
+    unsafe { std::env::set_var("PATH", std::env::join_paths(dirs).expect("join PATH")) };
+    unsafe { std::env::set_var("CONFIG_AGE_IDENTITY", &identity) };
+    let from_env = SecretConfig::loader().args(["app"]).load();
+    unsafe { std::env::set_var("CONFIG_AGE_IDENTITY", "/missing/key.txt") };
+    let missing = SecretConfig::loader().args(["app"]).load();
+    unsafe { std::env::remove_var("CONFIG_AGE_IDENTITY") };
+    unsafe { std::env::set_var("PATH", path) };
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+
+    assert_eq!(cfg.expect("decrypted").database.password, "hunter2");
+    assert_eq!(from_env.expect("decrypted").database.password, "hunter2");
+    let Err(CnfgError::Validation(errs)) = missing else {
+        panic!("expected validation errors, got {missing:?}");
+    };
+    assert!(
+        errs.iter()
+            .all(|i| i.message.ends_with("bad identity /missing/key.txt")),
+        "{errs:?}"
+    );
+}
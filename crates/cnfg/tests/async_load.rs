@@ -0,0 +1,100 @@
+#![cfg(feature = "async")]
+
+use cnfg::sources::BoxFuture;
+use cnfg::{AsyncSource, Cnfg, CnfgError, FieldSpec, LoaderExt, Origin, Source};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::time::Duration;
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct RemoteConfig {
+    #[cnfg(default = "local")]
+    region: String,
+
+    #[cnfg(default = 1)]
+    replicas: u32,
+
+    #[cnfg(default = "none")]
+    token: String,
+}
+
+struct SlowRemote {
+    name: &'static str,
+    value: Value,
+    delay: Duration,
+}
+
+impl AsyncSource for SlowRemote {
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    fn load<'a>(&'a self, _fields: &'a [FieldSpec]) -> BoxFuture<'a, Result<Value, CnfgError>> {
+        Box::pin(async move {
+            tokio::time::sleep(self.delay).await;
+            Ok(self.value.clone())
+        })
+    }
+}
+
+struct Blocking(Value);
+
+impl Source for Blocking {
+    fn name(&self) -> String {
+        "blocking".into()
+    }
+
+    fn load(&self, _fields: &[FieldSpec]) -> Result<Value, CnfgError> {
+        Ok(self.0.clone())
+    }
+}
+
+#[tokio::test]
+async fn merges_async_sources_in_order() {
+    let (cfg, report) = RemoteConfig::loader()
+        .args(["app"])
+        .source(Blocking(json!({ "region": "blocking", "token": "abc" })))
+        .async_source(SlowRemote {
+            name: "vault",
+            value: json!({ "region": "eu-west-1", "replicas": 3 }),
+            delay: Duration::from_millis(30),
+        })
+        .async_source(SlowRemote {
+            name: "ssm",
+            value: json!({ "replicas": 5 }),
+            delay: Duration::from_millis(1),
+        })
+        .load_async_with_report()
+        .await
+        .expect("load async");
+
+    assert_eq!(
+        cfg.region, "eu-west-1",
+        "async sources merge after blocking ones"
+    );
+    assert_eq!(
+        cfg.replicas, 5,
+        "later sources win regardless of finish order"
+    );
+    assert_eq!(cfg.token, "abc");
+    assert_eq!(
+        report.provenance.get("replicas"),
+        Some(&Origin::Source("ssm".into()))
+    );
+}
+
+#[test]
+fn sync_load_rejects_async_sources() {
+    let result = RemoteConfig::loader()
+        .args(["app"])
+        .async_source(SlowRemote {
+            name: "vault",
+            value: json!({}),
+            delay: Duration::ZERO,
+        })
+        .load();
+    match result {
+        Err(CnfgError::Source(msg)) => assert!(msg.contains("load_async"), "{msg}"),
+        other => panic!("expected source error, got {other:?}"),
+    }
+}
@@ -0,0 +1,89 @@
+use cnfg::test::Sandbox;
+use cnfg::{Audited, Cnfg};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(accessors)]
+struct Database {
+    #[cnfg(default = "localhost")]
+    host: String,
+
+    #[cnfg(default = 5432)]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(accessors)]
+struct ServiceConfig {
+    /// Port to listen on.
+    #[cnfg(default = 8080)]
+    port: u16,
+
+    #[cnfg(default = "info")]
+    log_level: String,
+
+    #[serde(default)]
+    legacy_mode: bool,
+
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Cache {
+    #[cnfg(default = 60)]
+    ttl: u32,
+
+    #[cnfg(default = 128)]
+    size: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(accessors)]
+struct WorkerConfig {
+    #[cnfg(default = 4)]
+    threads: u32,
+
+    #[cnfg(nested)]
+    cache: Cache,
+}
+
+#[test]
+fn reports_fields_never_read() {
+    let cfg = Sandbox::new()
+        .loader::<ServiceConfig>()
+        .load()
+        .expect("config");
+    let cfg = Audited::new(cfg);
+    assert_eq!(*cfg.port(), 8080);
+    assert_eq!(cfg.database().host(), "localhost");
+    // Direct field access is not tracked.
+    assert_eq!(cfg.log_level, "info");
+
+    let report = cfg.report();
+    assert_eq!(report.read, ["port", "database.host"]);
+    assert_eq!(report.unused, ["log_level", "legacy_mode", "database.port"]);
+    assert_eq!(
+        report.to_string(),
+        "unused config keys (3):\n  log_level\n  legacy_mode\n  database.port\n"
+    );
+}
+
+#[test]
+fn untracked_sections_count_as_read_whole() {
+    let cfg = Sandbox::new()
+        .loader::<WorkerConfig>()
+        .load()
+        .expect("config");
+    let cfg = Audited::new(cfg);
+    let clone = cfg.clone();
+    assert_eq!(clone.cache().ttl, 60);
+
+    let report = cfg.report();
+    assert_eq!(report.read, ["cache.ttl", "cache.size"]);
+    assert_eq!(report.unused, ["threads"]);
+
+    assert_eq!(*clone.threads(), 4);
+    assert!(cfg.report().unused.is_empty());
+    assert_eq!(cfg.report().to_string(), "every config key was read\n");
+}
@@ -0,0 +1,113 @@
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, ConfigMeta, LoaderExt, Origin, WarningKind};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "localhost")]
+    host: String,
+
+    #[cnfg(default = 5432)]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(auto_env)]
+struct AutoConfig {
+    #[cnfg(default = false)]
+    debug: bool,
+
+    #[cnfg(default = "svc", env = "AUTO_SERVICE")]
+    name: String,
+
+    #[serde(default)]
+    tags: Vec<String>,
+
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(auto_env, env_prefix = "AUTOAPP_")]
+struct PrefixedAuto {
+    #[cnfg(default = 1)]
+    workers: u32,
+
+    #[cnfg(default = "x")]
+    name: String,
+}
+
+#[test]
+fn names_come_from_paths() {
+    assert!(AutoConfig::auto_env());
+    let sandbox = Sandbox::new()
+        .env("DEBUG", "yes")
+        .env("NAME", "ignored: the field has its own env name")
+        .env("AUTO_SERVICE", "api")
+        .env("TAGS", "a,b")
+        .env("DATABASE_PORT", "6543");
+    let (cfg, report) = sandbox
+        .loader::<AutoConfig>()
+        .load_with_report()
+        .expect("config");
+    assert!(cfg.debug);
+    assert_eq!(cfg.name, "api");
+    assert_eq!(cfg.tags, ["a", "b"]);
+    assert_eq!(cfg.database.host, "localhost");
+    assert_eq!(cfg.database.port, 6543);
+    assert_eq!(
+        report.provenance.get("database.port"),
+        Some(&Origin::Env("DATABASE_PORT".into()))
+    );
+}
+
+#[test]
+fn off_by_default_and_per_loader() {
+    #[derive(Debug, Serialize, Deserialize, Cnfg)]
+    struct Plain {
+        #[cnfg(default = 1)]
+        workers: u32,
+    }
+
+    assert!(!Plain::auto_env());
+    let sandbox = Sandbox::new().env("WORKERS", "8");
+    let cfg = sandbox.loader::<Plain>().load().expect("config");
+    assert_eq!(cfg.workers, 1);
+    let cfg = sandbox
+        .loader::<Plain>()
+        .auto_env(true)
+        .load()
+        .expect("config");
+    assert_eq!(cfg.workers, 8);
+
+    let sandbox = sandbox.env("DEBUG", "true");
+    let cfg = sandbox
+        .loader::<AutoConfig>()
+        .auto_env(false)
+        .load()
+        .expect("config");
+    assert!(!cfg.debug);
+}
+
+#[test]
+fn prefix_applies_and_counts_as_used() {
+    let sandbox = Sandbox::new()
+        .env("AUTOAPP_WORKERS", "4")
+        .env("AUTOAPP_NAMES", "typo")
+        .env("WORKERS", "99");
+    let (cfg, report) = sandbox
+        .loader::<PrefixedAuto>()
+        .load_with_report()
+        .expect("config");
+    assert_eq!(cfg.workers, 4);
+    let unused: Vec<&str> = report
+        .warnings
+        .iter()
+        .filter(|w| w.kind == WarningKind::UnusedEnv)
+        .map(|w| w.message.as_str())
+        .collect();
+    assert_eq!(unused, ["set but unused; did you mean `AUTOAPP_NAME`?"]);
+
+    let vars = cfg.to_env_vars().expect("env vars");
+    assert!(vars.contains(&("AUTOAPP_WORKERS".into(), "4".into())));
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "aws-ssm")]
+
+use cnfg::sources::aws_ssm::{PageRequest, Parameter, ParameterPage, SsmClient, SsmSource};
+use cnfg::{Cnfg, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+#[derive(Default)]
+struct FakeSsm {
+    calls: AtomicUsize,
+}
+
+impl SsmClient for &FakeSsm {
+    fn get_parameters_by_path(&self, request: &PageRequest<'_>) -> Result<ParameterPage, String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        assert_eq!(request.path, "/myapp/prod");
+        assert!(request.with_decryption);
+        let param = |name: &str, value: &str| Parameter {
+            name: name.into(),
+            value: value.into(),
+        };
+        Ok(match request.next_token {
+            None => ParameterPage {
+                parameters: vec![param("/myapp/prod/database/port", "5433")],
+                next_token: Some("page-2".into()),
+            },
+            Some(_) => ParameterPage {
+                parameters: vec![param("/myapp/prod/database/password", "s3cret")],
+                next_token: None,
+            },
+        })
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = 5432)]
+    port: u16,
+    #[cnfg(default = "")]
+    password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct AppConfig {
+    #[serde(default)]
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[test]
+fn loads_parameters_under_prefix_with_caching() {
+    let client = Box::leak(Box::new(FakeSsm::default()));
+    let source = SsmSource::new(&*client, "myapp/prod/").cache_ttl(Duration::from_secs(60));
+    assert_eq!(source.path_for("/myapp/prod/a/b").as_deref(), Some("a.b"));
+    assert_eq!(source.path_for("/myapp/production/a"), None);
+
+    let loader = AppConfig::loader().args(["app"]).source(source);
+    let cfg = loader.clone().load().expect("load from ssm");
+    assert_eq!(cfg.database.port, 5433);
+    assert_eq!(cfg.database.password, "s3cret");
+    assert_eq!(client.calls.load(Ordering::SeqCst), 2, "both pages fetched");
+
+    loader.load().expect("second load");
+    assert_eq!(client.calls.load(Ordering::SeqCst), 2, "served from cache");
+}
@@ -0,0 +1,50 @@
+use cnfg::{Cnfg, ConfigMeta};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Pool {
+    #[cnfg(cli, env = "BINDINGS_POOL_SIZE")]
+    max_size: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(cli = "--dsn", env = "BINDINGS_DATABASE_URL")]
+    url: String,
+
+    #[cnfg(nested)]
+    pool: Pool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct AppConfig {
+    #[cnfg(cli, env = "BINDINGS_PORT")]
+    port: u16,
+
+    #[serde(rename = "primary_db")]
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[test]
+fn bindings_cover_nested_prefixes() {
+    let bindings = AppConfig::BINDINGS;
+    for flag in ["port", "primary-db-dsn", "primary-db-pool-max-size"] {
+        assert!(bindings.has_flag(flag), "{flag}");
+    }
+    for flag in ["dsn", "database-dsn", "primary-db", "pool-max-size"] {
+        assert!(!bindings.has_flag(flag), "{flag}");
+    }
+
+    assert!(bindings.has_env("BINDINGS_POOL_SIZE"));
+    assert!(!bindings.has_env("BINDINGS_POOL"));
+    assert!(bindings.shares_env(&Pool::BINDINGS));
+    assert!(!Database::BINDINGS.shares_env(&cnfg::types::Bindings::EMPTY));
+}
+
+#[test]
+fn cli_specs_match_bindings() {
+    for spec in AppConfig::cli_specs() {
+        assert!(AppConfig::BINDINGS.has_flag(spec.flag), "{}", spec.flag);
+    }
+}
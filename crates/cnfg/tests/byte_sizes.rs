@@ -0,0 +1,109 @@
+use cnfg::error::IssueKind;
+use cnfg::util::parse_byte_size;
+use cnfg::{Cnfg, CnfgError, ConfigMeta, Kind, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct CacheConfig {
+    /// In-memory cache budget.
+    #[cnfg(
+        bytes,
+        default = "64MiB",
+        env = "BYTES_CACHE_SIZE",
+        cli,
+        validate(range(min = "1MiB", max = "1GiB"))
+    )]
+    cache_size: u64,
+
+    #[cnfg(bytes)]
+    buffer: Option<usize>,
+
+    #[serde(default)]
+    #[cnfg(bytes, cli = "--chunk")]
+    chunks: Vec<u32>,
+}
+
+#[test]
+fn parses_units() {
+    assert_eq!(parse_byte_size("1024"), Ok(1024));
+    assert_eq!(parse_byte_size("512MB"), Ok(512_000_000));
+    assert_eq!(parse_byte_size("2GiB"), Ok(2 << 30));
+    assert_eq!(parse_byte_size("1.5 kib"), Ok(1536));
+    assert_eq!(parse_byte_size("4k"), Ok(4000));
+    assert!(parse_byte_size("1.5B").is_err());
+    assert!(parse_byte_size("12 parsecs").is_err());
+    assert!(parse_byte_size("99999999PiB").is_err());
+}
+
+#[test]
+fn sizes_from_defaults_files_env_and_cli() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    assert_eq!(CacheConfig::field_specs()[0].kind, Kind::Bytes);
+
+    let cfg = CacheConfig::loader()
+        .args(["app"])
+        .load()
+        .expect("defaults");
+    assert_eq!(cfg.cache_size, 64 << 20);
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.yaml");
+    std::fs::write(&path, "buffer: 8KiB\nchunks: [1KB, 2048]\n").expect("write yaml");
+    unsafe {
+        std::env::set_var("CONFIG_FILE", &path);
+        std::env::set_var("BYTES_CACHE_SIZE", "128MiB");
+    }
+    let cfg = CacheConfig::loader()
+        .args(["app", "--chunk", "4KiB"])
+        .load()
+        .expect("load sizes");
+    assert_eq!(cfg.buffer, Some(8192));
+    assert_eq!(cfg.cache_size, 128 << 20);
+    assert_eq!(cfg.chunks, [4096]);
+
+    let cfg = CacheConfig::loader()
+        .args(["app"])
+        .load()
+        .expect("file list");
+    assert_eq!(cfg.chunks, [1000, 2048]);
+    unsafe {
+        std::env::remove_var("CONFIG_FILE");
+        std::env::remove_var("BYTES_CACHE_SIZE");
+    }
+}
+
+#[test]
+fn range_bounds_are_in_bytes() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    match CacheConfig::loader()
+        .args(["app", "--cache-size", "2GiB"])
+        .load()
+    {
+        Err(CnfgError::Validation(errs)) => {
+            let issue = errs.iter().next().expect("one issue");
+            assert_eq!(issue.field, "cache_size");
+            assert!(matches!(issue.kind, IssueKind::Range));
+            assert!(
+                issue.message.contains(&(1u64 << 30).to_string()),
+                "{}",
+                issue.message
+            );
+        }
+        other => panic!("expected range error, got {other:?}"),
+    }
+
+    match CacheConfig::loader()
+        .args(["app", "--cache-size", "lots"])
+        .load()
+    {
+        Err(CnfgError::Validation(errs)) => {
+            let issue = errs.into_iter().next().expect("one issue");
+            assert_eq!(issue.field, "--cache-size");
+            assert!(issue.message.contains("byte size"), "{}", issue.message);
+        }
+        other => panic!("expected validation error, got {other:?}"),
+    }
+}
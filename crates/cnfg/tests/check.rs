@@ -0,0 +1,86 @@
+use cnfg::{Cnfg, CnfgError, LoaderExt, Origin};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct CheckConfig {
+    #[cnfg(default = 4, env = "CHECK_WORKERS", validate(range(max = "16")))]
+    workers: u32,
+
+    #[cnfg(cli, default = "info")]
+    level: String,
+}
+
+#[test]
+fn passing_check_lists_layers() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe { std::env::set_var("CHECK_WORKERS", "8") };
+    let summary = CheckConfig::loader().args(["app"]).check();
+    unsafe { std::env::remove_var("CHECK_WORKERS") };
+
+    assert!(summary.passed());
+    assert_eq!(
+        summary.layers(),
+        [&Origin::Default, &Origin::Env("CHECK_WORKERS".into())]
+    );
+    let text = summary.to_string();
+    assert!(
+        text.starts_with("config check passed\nlayers:\n  - default\n"),
+        "{text}"
+    );
+}
+
+#[test]
+fn failing_check_names_the_offending_layer() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe { std::env::set_var("CHECK_WORKERS", "64") };
+    let summary = CheckConfig::loader().args(["app"]).check();
+    unsafe { std::env::remove_var("CHECK_WORKERS") };
+
+    assert!(!summary.passed());
+    let text = summary.to_string();
+    assert!(
+        text.contains("  - workers — must be <= 16 (from env CHECK_WORKERS)"),
+        "{text}"
+    );
+}
+
+#[test]
+fn check_flag_returns_sentinels() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    match CheckConfig::loader().args(["app", "--check"]).load() {
+        Err(CnfgError::CheckPassed) => {}
+        other => panic!("expected passing check, got {other:?}"),
+    }
+
+    unsafe { std::env::set_var("CHECK_WORKERS", "many") };
+    match CheckConfig::loader().args(["app", "--check"]).load() {
+        Err(CnfgError::CheckFailed) => {}
+        other => panic!("expected failing check, got {other:?}"),
+    }
+    unsafe { std::env::remove_var("CHECK_WORKERS") };
+
+    assert!(CheckConfig::help().contains("--check"));
+}
+
+#[test]
+fn check_reads_the_short_config_flag() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("prod.yaml");
+    std::fs::write(&path, "workers: 64\n").expect("write yaml");
+    let path = path.to_str().expect("utf-8 path");
+
+    match CheckConfig::loader()
+        .args(["app", "--check", "-c", path])
+        .load()
+    {
+        Err(CnfgError::CheckFailed) => {}
+        other => panic!("expected failing check, got {other:?}"),
+    }
+    let summary = CheckConfig::loader().args(["app", "-c", path]).check();
+    assert_eq!(summary.layers()[1], &Origin::File(path.into()));
+    assert!(CheckConfig::help().contains("-c, --config <path>"));
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "clap")]
+
+use cnfg::{Cnfg, LoaderExt, Origin};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Serve {
+    /// Port to listen on.
+    #[cnfg(cli, default = 8080)]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(app_name = "tool")]
+struct Tool {
+    /// Verbose logging.
+    #[cnfg(cli, default = false)]
+    verbose: bool,
+
+    #[cnfg(cli, default = "info", values("debug", "info", "warn"))]
+    level: String,
+
+    #[serde(default)]
+    #[cnfg(cli)]
+    tags: Vec<String>,
+
+    /// Run the HTTP server.
+    #[cnfg(command)]
+    serve: Option<Serve>,
+}
+
+fn app() -> clap::Command {
+    cnfg::clap::augment::<Tool>(clap::Command::new("tool")).arg(
+        clap::Arg::new("dry-run")
+            .long("dry-run")
+            .action(clap::ArgAction::SetTrue),
+    )
+}
+
+#[test]
+fn command_mirrors_the_flags() {
+    let cmd = cnfg::clap::command::<Tool>();
+    assert_eq!(cmd.get_name(), "tool");
+    let longs: Vec<_> = cmd
+        .get_arguments()
+        .filter_map(|arg| arg.get_long())
+        .collect();
+    assert_eq!(longs, ["verbose", "level", "tags"]);
+    assert!(cmd.find_subcommand("serve").is_some());
+    cmd.debug_assert();
+
+    let err = app()
+        .try_get_matches_from(["tool", "--level", "loud"])
+        .expect_err("clap rejects unknown values");
+    assert_eq!(err.kind(), clap::error::ErrorKind::InvalidValue);
+}
+
+#[test]
+fn matches_become_the_cli_layer() {
+    let matches = app()
+        .try_get_matches_from([
+            "tool",
+            "--dry-run",
+            "--verbose",
+            "--tags",
+            "a",
+            "--tags",
+            "b",
+            "serve",
+            "--port",
+            "9000",
+        ])
+        .expect("matches");
+    assert!(matches.get_flag("dry-run"));
+
+    let (cfg, report) = Tool::loader()
+        .clap_matches(&matches)
+        .load_with_report()
+        .expect("config");
+    assert!(cfg.verbose);
+    assert_eq!(cfg.level, "info", "clap defaults never apply");
+    assert_eq!(cfg.tags, ["a", "b"]);
+    assert_eq!(cfg.serve.map(|serve| serve.port), Some(9000));
+    assert_eq!(
+        report.provenance.get("verbose"),
+        Some(&Origin::Cli("--verbose".into()))
+    );
+    assert_eq!(report.provenance.get("level"), Some(&Origin::Default));
+}
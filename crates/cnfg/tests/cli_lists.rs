@@ -0,0 +1,53 @@
+use cnfg::{Cnfg, CnfgError, LoaderExt};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct ListConfig {
+    /// Tags attached to every request.
+    #[serde(default)]
+    #[cnfg(cli = "--tag", env = "CLI_LISTS_TAGS")]
+    tags: Vec<String>,
+
+    #[serde(default)]
+    #[cnfg(cli)]
+    ports: Option<Vec<u16>>,
+}
+
+#[test]
+fn repeated_flags_collect_into_lists() {
+    let cfg = ListConfig::loader()
+        .args([
+            "app", "--tag", "a", "--ports", "80", "--tag", "b", "--ports", "443",
+        ])
+        .load()
+        .expect("load repeated flags");
+    assert_eq!(cfg.tags, ["a", "b"]);
+    assert_eq!(cfg.ports, Some(vec![80, 443]));
+}
+
+#[test]
+fn list_elements_are_type_checked() {
+    match ListConfig::loader().args(["app", "--ports", "http"]).load() {
+        Err(CnfgError::Validation(errs)) => {
+            let fields: Vec<_> = errs.into_iter().map(|issue| issue.field).collect();
+            assert_eq!(fields, ["--ports"]);
+        }
+        other => panic!("expected validation error, got {other:?}"),
+    }
+}
+
+#[test]
+fn help_marks_repeatable_flags() {
+    assert!(ListConfig::help().contains("--tag <STRING>..."));
+}
+
+#[test]
+fn env_lists_are_comma_separated() {
+    unsafe { std::env::set_var("CLI_LISTS_TAGS", "x, y,z") };
+    let cfg = ListConfig::loader()
+        .args(["app"])
+        .load()
+        .expect("load env list");
+    assert_eq!(cfg.tags, ["x", "y", "z"]);
+    unsafe { std::env::remove_var("CLI_LISTS_TAGS") };
+}
@@ -0,0 +1,103 @@
+use cnfg::{Cnfg, CnfgError, LoaderExt, Shell};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Level {
+    #[serde(rename = "debug")]
+    Debug,
+    #[serde(rename = "info")]
+    Info,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct CompletionConfig {
+    /// Listening port.
+    #[cnfg(cli, default = 8080)]
+    port: u16,
+
+    /// Where state is kept: [local] disk.
+    #[cnfg(cli, default = "/var/lib/app")]
+    data_dir: PathBuf,
+
+    /// Log level.
+    #[cnfg(cli, values("debug", "info"), default = "info")]
+    level: Level,
+
+    /// Toggle verbose output.
+    #[cnfg(cli, default = false)]
+    verbose: bool,
+}
+
+fn render(shell: Shell) -> String {
+    cnfg::completions::render::<CompletionConfig>(shell, "myapp")
+}
+
+#[test]
+fn bash_completes_flags_and_values() {
+    let script = render(Shell::Bash);
+    assert!(script.contains("complete -F _myapp myapp"), "{script}");
+    assert!(
+        script.contains("--port --data-dir --level --verbose --help"),
+        "{script}"
+    );
+    assert!(script.contains("compgen -W \"debug info\""), "{script}");
+    assert!(script.contains("--data-dir)\n            COMPREPLY=($(compgen -f"));
+    assert!(!script.contains("--verbose)"), "switches take no value");
+}
+
+#[test]
+fn zsh_describes_flags() {
+    let script = render(Shell::Zsh);
+    assert!(script.starts_with("#compdef myapp\n"), "{script}");
+    assert!(
+        script.contains("'--port[Listening port.]:integer: '"),
+        "{script}"
+    );
+    assert!(
+        script.contains("'--data-dir[Where state is kept\\: \\[local\\] disk.]:path:_files'"),
+        "{script}"
+    );
+    assert!(script.contains(":value:(debug info)'"), "{script}");
+    assert!(
+        script.contains("'--verbose[Toggle verbose output.]' \\"),
+        "{script}"
+    );
+}
+
+#[test]
+fn fish_emits_one_line_per_flag() {
+    let script = render(Shell::Fish);
+    assert!(script.contains("complete -c myapp -l port -x -d 'Listening port.'"));
+    assert!(script.contains("complete -c myapp -l data-dir -r -F"));
+    assert!(script.contains("complete -c myapp -l level -x -a 'debug info'"));
+    assert!(script.contains("complete -c myapp -l verbose -d 'Toggle verbose output.'"));
+    assert_eq!("FISH".parse::<Shell>(), Ok(Shell::Fish));
+    assert!("powershell".parse::<Shell>().is_err());
+}
+
+#[test]
+fn declared_values_are_enforced_on_the_cli() {
+    let cfg = CompletionConfig::loader()
+        .args(["app", "--level", "debug"])
+        .load()
+        .expect("load with listed value");
+    assert!(matches!(cfg.level, Level::Debug));
+
+    match CompletionConfig::loader()
+        .args(["app", "--level", "trace"])
+        .load()
+    {
+        Err(CnfgError::Validation(errs)) => {
+            let issue = errs.into_iter().next().expect("one issue");
+            assert_eq!(issue.field, "--level");
+            assert!(
+                issue.message.contains("expected one of debug, info"),
+                "{}",
+                issue.message
+            );
+        }
+        other => panic!("expected validation error, got {other:?}"),
+    }
+    assert!(CompletionConfig::help().contains("[possible values: debug, info]"));
+}
@@ -0,0 +1,88 @@
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, Origin};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "localhost")]
+    host: String,
+
+    #[cnfg(default = 5432)]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct PackagedConfig {
+    #[cnfg(default = "app")]
+    name: String,
+
+    #[cnfg(default = "info")]
+    log_level: String,
+
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[test]
+fn drop_ins_merge_above_the_config_file_in_name_order() {
+    let sandbox = Sandbox::new();
+    let main = sandbox.file("etc/app.toml", "name = \"main\"\nlog_level = \"warn\"\n");
+    let db = sandbox.file(
+        "etc/conf.d/10-db.toml",
+        "[database]\nhost = \"db\"\nport = 1\n",
+    );
+    let local = sandbox.file("etc/conf.d/20-local.toml", "[database]\nport = 2\n");
+    sandbox.file("etc/conf.d/30-notes.txt", "log_level = \"trace\"\n");
+    sandbox.file("etc/conf.d/.99-hidden.toml", "log_level = \"trace\"\n");
+    let dir = main.parent().expect("parent").display().to_string();
+    let sandbox = sandbox.env("CONFIG_FILE", main.display().to_string());
+
+    let (cfg, report) = sandbox
+        .loader::<PackagedConfig>()
+        .config_glob(format!("{dir}/conf.d/*.toml"))
+        .load_with_report()
+        .expect("config");
+    assert_eq!(cfg.name, "main");
+    assert_eq!(cfg.log_level, "warn");
+    assert_eq!((cfg.database.host.as_str(), cfg.database.port), ("db", 2));
+    assert_eq!(report.provenance.get("name"), Some(&Origin::File(main)));
+    assert_eq!(
+        report.provenance.get("database.host"),
+        Some(&Origin::File(db))
+    );
+    assert_eq!(
+        report.provenance.get("database.port"),
+        Some(&Origin::File(local))
+    );
+}
+
+#[test]
+fn later_patterns_win_and_empty_ones_are_skipped() {
+    let sandbox = Sandbox::new();
+    let yaml = sandbox.file("conf.d/a.yaml", "name: yaml\nlog_level: debug\n");
+    sandbox.file("conf.d/b.json", "{\"name\": \"json\"}");
+    let dir = yaml.parent().expect("parent").display().to_string();
+
+    let cfg = sandbox
+        .loader::<PackagedConfig>()
+        .config_glob(format!("{dir}/*.json"))
+        .config_glob(format!("{dir}/*.yaml"))
+        .config_glob(format!("{dir}/missing/*.toml"))
+        .load()
+        .expect("config");
+    assert_eq!(cfg.name, "yaml");
+    assert_eq!(cfg.log_level, "debug");
+}
+
+#[test]
+fn config_file_entries_expand_wildcards() {
+    let sandbox = Sandbox::new();
+    let first = sandbox.file("conf.d/01-base.yaml", "name: base\nlog_level: debug\n");
+    sandbox.file("conf.d/02-name.yaml", "name: override\n");
+    let dir = first.parent().expect("parent").display().to_string();
+    let sandbox = sandbox.env("CONFIG_FILE", format!("{dir}/*.yaml"));
+
+    let cfg = sandbox.loader::<PackagedConfig>().load().expect("config");
+    assert_eq!(cfg.name, "override");
+    assert_eq!(cfg.log_level, "debug");
+}
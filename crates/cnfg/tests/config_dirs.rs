@@ -0,0 +1,63 @@
+use cnfg::{Cnfg, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(app_name = "cnfg-dirs-test")]
+struct AppConfig {
+    #[cnfg(default = "from-default")]
+    name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct UnnamedConfig {
+    #[cnfg(default = "from-default")]
+    name: String,
+}
+
+#[test]
+fn discovers_user_config_dir_from_struct_attribute() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let app_dir = dir.path().join("cnfg-dirs-test");
+    std::fs::create_dir_all(&app_dir).expect("create app dir");
+    std::fs::write(app_dir.join("config.yaml"), "name: from-xdg\n").expect("write yaml");
+
+    unsafe {
+        std::env::remove_var("CONFIG_FILE");
+        std::env::set_var("XDG_CONFIG_HOME", dir.path());
+    }
+    let cfg = AppConfig::load().expect("load from xdg dir");
+    assert_eq!(cfg.name, "from-xdg");
+
+    let unnamed = UnnamedConfig::load().expect("load without app name");
+    assert_eq!(unnamed.name, "from-default");
+    unsafe { std::env::remove_var("XDG_CONFIG_HOME") };
+}
+
+#[test]
+fn loader_builder_supplies_app_name() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let app_dir = dir.path().join("builder-app");
+    std::fs::create_dir_all(&app_dir).expect("create app dir");
+    std::fs::write(app_dir.join("config.json"), r#"{ "name": "from-builder" }"#)
+        .expect("write json");
+
+    unsafe {
+        std::env::remove_var("CONFIG_FILE");
+        std::env::set_var("XDG_CONFIG_HOME", dir.path());
+    }
+    let cfg = UnnamedConfig::loader()
+        .app_name("builder-app")
+        .load()
+        .expect("load with builder app name");
+    assert_eq!(cfg.name, "from-builder");
+    assert_eq!(
+        cnfg::dirs::user_config_dir("builder-app"),
+        Some(app_dir.clone())
+    );
+    unsafe { std::env::remove_var("XDG_CONFIG_HOME") };
+}
@@ -0,0 +1,91 @@
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, CnfgError};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct MountedConfig {
+    #[cnfg(default = "app")]
+    name: String,
+
+    #[cnfg(default = 3000)]
+    port: u16,
+}
+
+/// A sandbox whose `CONFIG_FILE` is `contents` in a file named `config`.
+fn mounted(contents: &str) -> Sandbox {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file("mnt/config", contents);
+    sandbox.env("CONFIG_FILE", path.display().to_string())
+}
+
+#[test]
+#[cfg(feature = "yaml")]
+fn env_names_the_format_of_extensionless_files() {
+    let sandbox = mounted("name: mounted\nport: 8443\n").env("CONFIG_FORMAT", "YAML");
+    let cfg = sandbox.loader::<MountedConfig>().load().expect("config");
+    assert_eq!((cfg.name.as_str(), cfg.port), ("mounted", 8443));
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn builder_overrides_env_and_flag_overrides_builder() {
+    let sandbox = mounted("{\"port\": 8081}").env("CONFIG_FORMAT", "yaml");
+    let cfg = sandbox
+        .loader::<MountedConfig>()
+        .config_format("json")
+        .load()
+        .expect("config");
+    assert_eq!(cfg.port, 8081);
+
+    let err = sandbox
+        .loader::<MountedConfig>()
+        .args(["app", "--config-format", "toml"])
+        .config_format("json")
+        .load()
+        .unwrap_err();
+    assert!(matches!(err.root(), CnfgError::ParseToml(_)), "{err:?}");
+}
+
+#[test]
+#[cfg(feature = "toml")]
+fn known_extensions_win() {
+    let sandbox = Sandbox::new().env("CONFIG_FORMAT", "json");
+    let path = sandbox.file("app.toml", "port = 9000\n");
+    let cfg = sandbox
+        .env("CONFIG_FILE", path.display().to_string())
+        .loader::<MountedConfig>()
+        .load()
+        .expect("config");
+    assert_eq!(cfg.port, 9000);
+}
+
+#[test]
+fn unknown_formats_are_rejected() {
+    let sandbox = mounted("port = 1\n").env("CONFIG_FORMAT", "xml");
+    let err = sandbox.loader::<MountedConfig>().load().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Env error: CONFIG_FORMAT: unknown format `xml`; use toml, yaml, json, json5, ron, hcl, ini, or properties"
+    );
+
+    let err = sandbox
+        .loader::<MountedConfig>()
+        .config_format("conf")
+        .load()
+        .unwrap_err();
+    assert!(
+        matches!(&err, CnfgError::Cli(msg) if msg.starts_with("config_format: unknown format `conf`")),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn missing_format_suggests_the_override() {
+    let sandbox = mounted("port = 1\n");
+    let err = sandbox.loader::<MountedConfig>().load().unwrap_err();
+    let message = err.to_string();
+    assert!(
+        message.ends_with("or name the format with --config-format or CONFIG_FORMAT"),
+        "{message}"
+    );
+}
@@ -0,0 +1,168 @@
+#![cfg(feature = "consul")]
+
+use cnfg::sources::consul::{ConsulClient, ConsulSource, KvPair, KvRequest, KvResponse};
+use cnfg::{Cnfg, LoaderExt, Origin};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Current index, and each key's value with the index it was last
+/// modified at.
+type Store = (u64, BTreeMap<String, (Option<String>, u64)>);
+
+/// Datacenter and token of each request.
+type Requests = Vec<(Option<String>, Option<String>)>;
+
+#[derive(Clone, Default)]
+struct FakeConsul {
+    state: Arc<(Mutex<Store>, Condvar)>,
+    requests: Arc<Mutex<Requests>>,
+}
+
+impl FakeConsul {
+    fn put(&self, key: &str, value: Option<&str>) {
+        let (lock, changed) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.0 += 1;
+        let index = state.0;
+        state.1.insert(key.into(), (value.map(Into::into), index));
+        changed.notify_all();
+    }
+}
+
+impl ConsulClient for FakeConsul {
+    fn list(&self, request: &KvRequest<'_>) -> Result<KvResponse, String> {
+        self.requests.lock().unwrap().push((
+            request.datacenter.map(Into::into),
+            request.token.map(Into::into),
+        ));
+        if request.token == Some("revoked") {
+            return Err("403 Permission denied".into());
+        }
+        let (lock, changed) = &*self.state;
+        // Index of the latest change under the prefix, like X-Consul-Index.
+        let modified = |state: &Store| {
+            state
+                .1
+                .iter()
+                .filter(|(key, _)| key.starts_with(request.prefix))
+                .map(|(_, (_, index))| *index)
+                .max()
+                .unwrap_or(0)
+        };
+        let mut state = lock.lock().unwrap();
+        if let Some((index, wait)) = request.blocking {
+            state = changed
+                .wait_timeout_while(state, wait, |state| modified(state) <= index)
+                .unwrap()
+                .0;
+        }
+        Ok(KvResponse {
+            index: modified(&state),
+            pairs: state
+                .1
+                .iter()
+                .filter(|(key, _)| key.starts_with(request.prefix))
+                .map(|(key, (value, _))| KvPair {
+                    key: key.clone(),
+                    value: value.clone(),
+                })
+                .collect(),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "localhost")]
+    host: String,
+
+    #[cnfg(default = 5432)]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct ConsulConfig {
+    #[cnfg(default = false)]
+    maintenance: bool,
+
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[test]
+fn reads_folders_as_nested_paths() {
+    let consul = FakeConsul::default();
+    consul.put("myapp/prod/", None);
+    consul.put("myapp/prod/database/", None);
+    consul.put("myapp/prod/database/port", Some("6432"));
+    consul.put("myapp/prod/maintenance", Some("true"));
+    consul.put("myapp/production/database/port", Some("1"));
+
+    let source = ConsulSource::new(consul.clone(), "/myapp/prod/")
+        .datacenter("eu1")
+        .token("secret");
+    assert_eq!(
+        source.path_for("myapp/prod/database/port").as_deref(),
+        Some("database.port")
+    );
+    assert_eq!(source.path_for("myapp/prod/database/"), None);
+    assert_eq!(source.path_for("myapp/production/x"), None);
+
+    let (cfg, report) = ConsulConfig::loader()
+        .args(["app"])
+        .source(source)
+        .load_with_report()
+        .expect("load from consul");
+    assert_eq!(cfg.database.port, 6432);
+    assert_eq!(cfg.database.host, "localhost");
+    assert!(cfg.maintenance);
+    assert_eq!(
+        report.provenance.get("database.port"),
+        Some(&Origin::Source("consul:eu1/myapp/prod".into()))
+    );
+    assert_eq!(
+        consul.requests.lock().unwrap()[0],
+        (Some("eu1".into()), Some("secret".into()))
+    );
+
+    let err = ConsulConfig::loader()
+        .args(["app"])
+        .source(ConsulSource::new(consul, "myapp/prod").token("revoked"))
+        .load()
+        .expect_err("denied");
+    assert_eq!(
+        err.to_string(),
+        "Source error: consul:myapp/prod: 403 Permission denied"
+    );
+}
+
+#[test]
+fn watch_reloads_through_blocking_queries() {
+    let consul = FakeConsul::default();
+    consul.put("svc/database/port", Some("6432"));
+
+    let watcher = ConsulConfig::loader()
+        .args(["app"])
+        .source(ConsulSource::new(consul.clone(), "svc"))
+        .watch(Duration::from_secs(3600))
+        .expect("start watcher");
+    assert_eq!(watcher.current().database.port, 6432);
+
+    consul.put("svc/database/port", Some("7432"));
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while watcher.current().database.port != 7432 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(watcher.current().database.port, 7432);
+
+    consul.put("elsewhere/database/port", Some("1"));
+    consul.put("svc/maintenance", Some("on"));
+    while !watcher.current().maintenance && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert!(watcher.current().maintenance);
+    assert_eq!(watcher.current().database.port, 7432);
+    watcher.stop();
+}
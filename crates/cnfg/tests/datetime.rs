@@ -0,0 +1,159 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use cnfg::datetime::{parse_date, parse_date_time};
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, CnfgError, ConfigMeta, Kind, LoaderExt};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct ReleaseConfig {
+    /// When the release goes live.
+    #[cnfg(env = "DT_LAUNCH", cli)]
+    launch: Option<DateTime<Utc>>,
+
+    #[cnfg(env = "DT_FREEZE", cli)]
+    freeze: Option<NaiveDate>,
+
+    #[cnfg(default = "2024-01-01T00:00:00Z")]
+    epoch: DateTime<Utc>,
+
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    #[cnfg(env = "DT_AUDIT")]
+    audit: Option<time::OffsetDateTime>,
+}
+
+#[test]
+fn parses_rfc3339() {
+    assert_eq!(parse_date_time("1970-01-01T00:00:00Z"), Ok((0, 0)));
+    assert_eq!(
+        parse_date_time("1970-01-01t01:00:00.25+01:00"),
+        Ok((0, 250_000_000))
+    );
+    assert_eq!(
+        parse_date_time("2024-02-29 12:00:00-00:30"),
+        Ok((1_709_209_800, 0))
+    );
+    assert_eq!(parse_date("2000-03-01"), Ok((951_868_800, 0)));
+    assert!(parse_date_time("2024-05-01").is_err());
+    assert!(parse_date_time("2024-05-01T25:00:00Z").is_err());
+    assert!(parse_date_time("2024-05-01T12:00:00").is_err());
+    assert!(parse_date("2023-02-29").is_err());
+}
+
+#[test]
+fn kinds_and_schema_format() {
+    let kinds: Vec<Kind> = ReleaseConfig::field_specs()
+        .iter()
+        .map(|spec| spec.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [Kind::DateTime, Kind::Date, Kind::DateTime, Kind::DateTime]
+    );
+    let spec: serde_json::Value = serde_json::from_str(&ReleaseConfig::help_json()).expect("json");
+    assert_eq!(spec["fields"][0]["format"], "date-time");
+    assert_eq!(spec["fields"][1]["format"], "date");
+    assert_eq!(spec["flags"][0]["value_name"], "DATETIME");
+}
+
+#[test]
+fn env_cli_and_defaults() {
+    let sandbox = Sandbox::new()
+        .env("DT_LAUNCH", "2024-05-01T12:00:00+02:00")
+        .env("DT_AUDIT", "2024-05-02T00:00:00Z")
+        .args(["app", "--freeze", "2024-04-15"]);
+    let cfg = sandbox.loader::<ReleaseConfig>().load().expect("load");
+    assert_eq!(cfg.launch.expect("launch").timestamp(), 1_714_557_600);
+    assert_eq!(cfg.freeze, NaiveDate::from_ymd_opt(2024, 4, 15));
+    assert_eq!(cfg.epoch.timestamp(), 1_704_067_200);
+    assert_eq!(cfg.audit.expect("audit").unix_timestamp(), 1_714_608_000);
+}
+
+#[test]
+fn toml_datetimes_pass_through() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file(
+        "release.toml",
+        "launch = 2024-05-01T12:00:00Z\nfreeze = 2024-04-15\n",
+    );
+    let sandbox = sandbox.args(["app".into(), "--config".into(), path.display().to_string()]);
+    let cfg = sandbox.loader::<ReleaseConfig>().load().expect("load");
+    assert_eq!(cfg.launch.expect("launch").timestamp(), 1_714_564_800);
+    assert_eq!(cfg.freeze, NaiveDate::from_ymd_opt(2024, 4, 15));
+}
+
+#[test]
+fn bad_values_name_the_format() {
+    let sandbox =
+        Sandbox::new()
+            .env("DT_LAUNCH", "next tuesday")
+            .args(["app", "--freeze", "2024-13-01"]);
+    let err = sandbox.loader::<ReleaseConfig>().load().unwrap_err();
+    let CnfgError::Validation(errs) = err else {
+        panic!("expected validation error, got {err:?}");
+    };
+    let messages: Vec<String> = errs
+        .iter()
+        .map(|issue| format!("{}: {}", issue.field, issue.message))
+        .collect();
+    assert!(
+        messages
+            .iter()
+            .any(|m| m.starts_with("DT_LAUNCH") && m.contains("RFC 3339 date-time")),
+        "{messages:?}"
+    );
+    assert!(
+        messages
+            .iter()
+            .any(|m| m.starts_with("--freeze") && m.contains("a date like `2024-05-01`")),
+        "{messages:?}"
+    );
+}
+
+#[cfg(all(feature = "chrono", feature = "time"))]
+mod ranges {
+    use super::*;
+    use cnfg::Validate;
+
+    #[derive(Debug, Serialize, Deserialize, Cnfg)]
+    struct Window {
+        #[cnfg(validate(range(min = "2024-01-01", max = "2024-12-31T23:59:59Z")))]
+        starts: DateTime<Utc>,
+
+        #[cnfg(validate(range(max = "2030-01-01")))]
+        ends: Option<NaiveDate>,
+
+        #[serde(with = "time::serde::rfc3339")]
+        #[cnfg(validate(range(min = "2024-06-01T00:00:00+02:00")))]
+        reviewed: time::OffsetDateTime,
+    }
+
+    fn window(starts: &str, ends: &str, reviewed: &str) -> Window {
+        serde_json::from_value(serde_json::json!({
+            "starts": starts,
+            "ends": ends,
+            "reviewed": reviewed,
+        }))
+        .expect("window")
+    }
+
+    #[test]
+    fn range_by_date() {
+        let ok = window("2024-03-01T00:00:00Z", "2029-12-31", "2024-05-31T22:00:00Z");
+        assert!(Validate::validate(&ok).is_ok());
+
+        let bad = window("2023-12-31T23:59:59Z", "2030-01-02", "2024-05-31T21:59:59Z");
+        let errs = Validate::validate(&bad).unwrap_err();
+        let messages: Vec<String> = errs
+            .iter()
+            .map(|issue| format!("{}: {}", issue.field, issue.message))
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "starts: must be >= 2024-01-01",
+                "ends: must be <= 2030-01-01",
+                "reviewed: must be >= 2024-06-01T00:00:00+02:00",
+            ]
+        );
+    }
+}
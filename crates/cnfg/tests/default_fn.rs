@@ -0,0 +1,111 @@
+use cnfg::{Cnfg, ConfigMeta, LoaderExt};
+use serde::{Deserialize, Serialize};
+
+fn default_workers() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+mod defaults {
+    pub fn labels() -> serde_json::Value {
+        serde_json::json!(["primary", "eu"])
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct ComputedConfig {
+    #[cnfg(default_fn = "default_workers", cli)]
+    workers: usize,
+
+    #[cnfg(default_fn = "defaults::labels")]
+    labels: Vec<String>,
+}
+
+#[test]
+fn computes_defaults_from_functions() {
+    let defaults = ComputedConfig::defaults_json();
+    assert_eq!(defaults["workers"], default_workers());
+    assert_eq!(defaults["labels"], serde_json::json!(["primary", "eu"]));
+
+    let cfg = ComputedConfig::loader().args(["app"]).load().expect("load");
+    assert_eq!(cfg.workers, default_workers());
+    assert_eq!(cfg.labels, ["primary", "eu"]);
+
+    let cfg = ComputedConfig::loader()
+        .args(["app", "--workers", "3"])
+        .load()
+        .expect("override computed default");
+    assert_eq!(cfg.workers, 3);
+}
+
+static TIMEOUT_CALLS: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+fn counted_timeout() -> u64 {
+    TIMEOUT_CALLS.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    30
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct CachedConfig {
+    #[cnfg(default_fn = "counted_timeout")]
+    timeout: u64,
+
+    #[cnfg(nested)]
+    inner: ComputedConfig,
+}
+
+#[test]
+fn builds_defaults_once_per_type() {
+    let first = CachedConfig::defaults();
+    let second = CachedConfig::defaults();
+    assert!(matches!(first, std::borrow::Cow::Borrowed(_)));
+    assert!(std::ptr::eq(&*first, &*second));
+    assert_eq!(CachedConfig::defaults_json(), *first);
+    assert_eq!(
+        first["inner"]["labels"],
+        serde_json::json!(["primary", "eu"])
+    );
+
+    for _ in 0..3 {
+        let cfg = CachedConfig::loader().args(["app"]).load().expect("load");
+        assert_eq!(cfg.timeout, 30);
+    }
+    assert_eq!(TIMEOUT_CALLS.load(std::sync::atomic::Ordering::SeqCst), 1);
+}
+
+#[derive(Debug, Deserialize)]
+struct Opaque;
+
+impl Serialize for Opaque {
+    fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+        Err(serde::ser::Error::custom("not representable"))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct OpaqueConfig {
+    #[cnfg(default_fn = "opaque")]
+    handle: Opaque,
+}
+
+fn opaque() -> Opaque {
+    Opaque
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct WrapsOpaque {
+    #[cnfg(nested)]
+    inner: OpaqueConfig,
+}
+
+#[test]
+fn unserializable_defaults_fail_the_load() {
+    assert_eq!(
+        OpaqueConfig::defaults_error(),
+        Some("default_fn for `handle`: not representable")
+    );
+    let err = OpaqueConfig::loader().args(["app"]).load().unwrap_err();
+    assert!(matches!(err, cnfg::CnfgError::Serialize(_)), "{err:?}");
+
+    let err = WrapsOpaque::loader().args(["app"]).load().unwrap_err();
+    assert!(err.to_string().contains("default_fn for `handle`"), "{err}");
+}
@@ -0,0 +1,78 @@
+use cnfg::{Cnfg, LoaderExt, WarningKind};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Server {
+    #[cnfg(default = "127.0.0.1", renamed_from = "address")]
+    host: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct EvolvingConfig {
+    #[cnfg(default = 8080, renamed_from = "legacy.port")]
+    port: u16,
+
+    #[cnfg(default = 1, cli, deprecated = "use `workers` instead")]
+    threads: u32,
+
+    #[serde(default)]
+    #[cnfg(nested)]
+    server: Server,
+}
+
+#[test]
+fn migrates_renamed_keys_and_warns_on_deprecated_fields() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.json");
+    std::fs::write(
+        &path,
+        r#"{ "legacy": { "port": 9000 }, "threads": 4, "server": { "address": "0.0.0.0" } }"#,
+    )
+    .expect("write json");
+
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let (cfg, report) = EvolvingConfig::loader()
+        .args(["app", "--threads", "8"])
+        .load_with_report()
+        .expect("load");
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+
+    assert_eq!(cfg.port, 9000);
+    assert_eq!(cfg.server.host, "0.0.0.0");
+    assert_eq!(cfg.threads, 8);
+
+    let deprecated: Vec<&str> = report
+        .warnings
+        .iter()
+        .filter(|w| w.kind == WarningKind::Deprecated)
+        .map(|w| w.subject.as_str())
+        .collect();
+    assert_eq!(
+        deprecated,
+        ["legacy.port", "server.address", "threads", "--threads"]
+    );
+    assert!(
+        !report
+            .warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::UnknownKey && w.subject == "legacy"),
+        "migrated keys are not reported as unknown"
+    );
+}
+
+#[test]
+fn current_key_wins_over_renamed_key() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, r#"{ "legacy": { "port": 1 }, "port": 2 }"#).expect("write json");
+
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let cfg = EvolvingConfig::loader().args(["app"]).load().expect("load");
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+    assert_eq!(cfg.port, 2);
+}
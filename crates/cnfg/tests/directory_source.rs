@@ -0,0 +1,59 @@
+use cnfg::sources::directory::DirectorySource;
+use cnfg::{Cnfg, CnfgError, LoaderExt};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = 5432)]
+    port: u16,
+    #[cnfg(default = "localhost")]
+    host: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct MountedConfig {
+    #[cnfg(default = false)]
+    debug: bool,
+
+    #[serde(default)]
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[test]
+fn reads_one_value_per_file() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    std::fs::write(dir.path().join("database.port"), "6543\n").expect("write");
+    std::fs::write(dir.path().join("debug"), "true").expect("write");
+    std::fs::create_dir(dir.path().join("..2024_01_01_00_00_00.000")).expect("mkdir");
+    std::fs::write(dir.path().join("..data"), "ignored").expect("write");
+
+    let cfg = MountedConfig::loader()
+        .args(["app"])
+        .source(DirectorySource::new(dir.path()))
+        .load()
+        .expect("load mounted dir");
+    assert_eq!(cfg.database.port, 6543);
+    assert_eq!(cfg.database.host, "localhost");
+    assert!(cfg.debug);
+}
+
+#[test]
+fn missing_directories_are_optional_on_request() {
+    let missing = std::env::temp_dir().join("cnfg-missing-mount-dir");
+    let cfg = MountedConfig::loader()
+        .args(["app"])
+        .source(DirectorySource::new(&missing).optional())
+        .load()
+        .expect("optional dir");
+    assert_eq!(cfg.database.port, 5432);
+
+    match MountedConfig::loader()
+        .args(["app"])
+        .source(DirectorySource::new(&missing))
+        .load()
+    {
+        Err(CnfgError::Source(msg)) => assert!(msg.starts_with("dir:"), "{msg}"),
+        other => panic!("expected source error, got {other:?}"),
+    }
+}
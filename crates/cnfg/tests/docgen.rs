@@ -0,0 +1,76 @@
+use cnfg::Cnfg;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Database {
+    /// Connection string for the primary database.
+    #[cnfg(env = "DOCGEN_DB_URL", required)]
+    url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct AppConfig {
+    /// Listening port | public.
+    #[cnfg(default = 8080, cli)]
+    port: u16,
+
+    #[serde(default)]
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[test]
+fn renders_markdown_table_for_nested_schema() {
+    let table = cnfg::docgen::markdown::<AppConfig>();
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(
+        lines[0],
+        "| Field | Type | Default | Env | CLI | Required | Description |"
+    );
+    assert_eq!(
+        lines[2],
+        "| `port` | integer | `8080` |  | `--port` | no | Listening port \\| public. |"
+    );
+    assert_eq!(
+        lines[3],
+        "| `database.url` | string |  | `DOCGEN_DB_URL` |  | yes | Connection string for the primary database. |"
+    );
+    assert_eq!(lines.len(), 4, "object rows are omitted");
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(env_prefix = "SHOP_")]
+struct ShopConfig {
+    #[cnfg(default = 8080)]
+    port: u16,
+
+    #[cnfg(default = true, env = "SHOP_CACHE")]
+    cache: bool,
+
+    #[cnfg(secret, env = "SHOP_DB_PASSWORD")]
+    db_password: Option<String>,
+}
+
+#[test]
+fn renders_configmap_with_secrets_split_out() {
+    let mut cfg = ShopConfig {
+        port: 9000,
+        cache: true,
+        db_password: None,
+    };
+    assert_eq!(
+        cnfg::docgen::to_configmap(&cfg, "shop", "prod").expect("manifest"),
+        "apiVersion: v1\nkind: ConfigMap\nmetadata:\n  name: \"shop\"\n  namespace: \"prod\"\n\
+         data:\n  SHOP_PORT: \"9000\"\n  SHOP_CACHE: \"true\"\n"
+    );
+
+    cfg.db_password = Some("s3cr\"t".into());
+    let manifest = cnfg::docgen::to_configmap(&cfg, "shop", "prod").expect("manifest");
+    let (configmap, secret) = manifest.split_once("---\n").expect("two documents");
+    assert!(!configmap.contains("s3cr"), "{configmap}");
+    assert_eq!(
+        secret,
+        "apiVersion: v1\nkind: Secret\nmetadata:\n  name: \"shop-secrets\"\n  namespace: \"prod\"\n\
+         type: Opaque\nstringData:\n  SHOP_DB_PASSWORD: \"s3cr\\\"t\"\n"
+    );
+}
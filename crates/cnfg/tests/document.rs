@@ -0,0 +1,102 @@
+use cnfg::{Cnfg, CnfgError, LoaderExt, Origin};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = 5432, env = "DOCUMENT_DB_PORT")]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct PluginHost {
+    #[serde(default)]
+    #[cnfg(nested)]
+    database: Database,
+
+    #[serde(default)]
+    plugins: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct MetricsPlugin {
+    interval: u32,
+    sinks: Vec<String>,
+}
+
+#[test]
+fn exposes_merged_values_with_provenance() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.yaml");
+    std::fs::write(
+        &path,
+        "plugins:\n  metrics:\n    interval: 15\n    sinks: [statsd, stdout]\n  audit:\n    target: syslog\n",
+    )
+    .expect("write yaml");
+    unsafe {
+        std::env::set_var("CONFIG_FILE", &path);
+        std::env::set_var("DOCUMENT_DB_PORT", "6543");
+    }
+    let (_, report) = PluginHost::loader()
+        .args(["app"])
+        .load_with_report()
+        .expect("load");
+    unsafe {
+        std::env::remove_var("CONFIG_FILE");
+        std::env::remove_var("DOCUMENT_DB_PORT");
+    }
+    let doc = &report.document;
+
+    assert_eq!(doc.get::<u16>("database.port").expect("port"), Some(6543));
+    assert_eq!(
+        doc.get::<MetricsPlugin>("plugins.metrics")
+            .expect("metrics"),
+        Some(MetricsPlugin {
+            interval: 15,
+            sinks: vec!["statsd".into(), "stdout".into()],
+        })
+    );
+    assert_eq!(doc.get::<u32>("plugins.missing").expect("absent"), None);
+    assert_eq!(doc.get_str("plugins.audit.target"), Some("syslog"));
+    assert_eq!(doc.get_str("database.port"), None);
+
+    assert_eq!(
+        doc.origin("database.port"),
+        Some(&Origin::Env("DOCUMENT_DB_PORT".into()))
+    );
+    assert_eq!(
+        doc.origin("plugins.metrics.interval"),
+        Some(&Origin::File(path))
+    );
+
+    let paths: Vec<String> = doc.iter_paths().map(|(path, _)| path).collect();
+    assert_eq!(
+        paths,
+        [
+            "database.port",
+            "plugins.audit.target",
+            "plugins.metrics.interval",
+            "plugins.metrics.sinks",
+        ]
+    );
+}
+
+#[test]
+fn typed_access_reports_the_path() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let (_, report) = PluginHost::loader()
+        .args(["app"])
+        .load_with_report()
+        .expect("load");
+    match report.document.get::<String>("database.port") {
+        Err(CnfgError::Validation(errs)) => {
+            assert_eq!(errs.iter().next().expect("issue").field, "database.port");
+        }
+        other => panic!("expected type error, got {other:?}"),
+    }
+}
@@ -0,0 +1,105 @@
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, CnfgError, Dotenv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct SearchConfig {
+    #[cnfg(env = "DOTENV_SEARCH_NAME", default = "unset")]
+    name: String,
+
+    #[cnfg(env = "DOTENV_SEARCH_PORT", default = 0)]
+    port: u16,
+
+    #[cnfg(env = "DOTENV_SEARCH_HOST", default = "unset")]
+    host: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(dotenv = false)]
+struct OptOutConfig {
+    #[cnfg(env = "DOTENV_OPTOUT_NAME", default = "unset")]
+    name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(dotenv(".env.base", ".env.test"))]
+struct ExplicitConfig {
+    #[cnfg(env = "DOTENV_EXPLICIT_NAME", default = "unset")]
+    name: String,
+
+    #[cnfg(env = "DOTENV_EXPLICIT_PORT", default = 0)]
+    port: u16,
+}
+
+/// A sandbox working in a directory holding `files`.
+fn in_dir(files: &[(&str, &str)]) -> Sandbox {
+    let sandbox = Sandbox::new().current_dir();
+    for (name, contents) in files {
+        sandbox.file(name, contents);
+    }
+    sandbox
+}
+
+#[test]
+fn searches_env_local_and_profile_files() {
+    let sandbox = in_dir(&[
+        (
+            ".env",
+            "DOTENV_SEARCH_NAME=base\nDOTENV_SEARCH_PORT=1\nDOTENV_SEARCH_HOST=file\n",
+        ),
+        (".env.local", "DOTENV_SEARCH_PORT=2\n"),
+        (".env.staging", "DOTENV_SEARCH_NAME=staging\n"),
+    ])
+    .env("DOTENV_SEARCH_HOST", "real");
+    let cfg = sandbox
+        .loader::<SearchConfig>()
+        .profile("staging")
+        .load()
+        .expect("config");
+    assert_eq!(cfg.name, "staging");
+    assert_eq!(cfg.port, 2);
+    assert_eq!(cfg.host, "real", "set variables are never replaced");
+}
+
+#[test]
+fn opting_out_reads_no_files() {
+    let files = [(".env", "DOTENV_OPTOUT_NAME=file\n")];
+    let sandbox = in_dir(&files);
+    let cfg = sandbox.loader::<OptOutConfig>().load();
+    assert_eq!(cfg.expect("config").name, "unset");
+
+    let cfg = sandbox
+        .loader::<SearchConfig>()
+        .dotenv(Dotenv::Disabled)
+        .load();
+    assert_eq!(cfg.expect("config").name, "unset");
+    assert!(std::env::var("DOTENV_OPTOUT_NAME").is_err());
+}
+
+#[test]
+fn explicit_files_are_required_and_later_ones_win() {
+    let sandbox = in_dir(&[
+        (".env", "DOTENV_EXPLICIT_NAME=ignored\n"),
+        (
+            ".env.base",
+            "DOTENV_EXPLICIT_NAME=base\nDOTENV_EXPLICIT_PORT=1\n",
+        ),
+        (".env.test", "DOTENV_EXPLICIT_PORT=2\n"),
+    ]);
+    let cfg = sandbox.loader::<ExplicitConfig>().load().expect("config");
+    assert_eq!(cfg.name, "base");
+    assert_eq!(cfg.port, 2);
+    drop(sandbox);
+    assert!(
+        std::env::var("DOTENV_EXPLICIT_NAME").is_err(),
+        "variables read from .env files are removed with the sandbox"
+    );
+
+    let sandbox = in_dir(&[]);
+    let err = sandbox
+        .loader::<SearchConfig>()
+        .dotenv_file("missing.env")
+        .load()
+        .expect_err("missing file");
+    assert!(matches!(&err, CnfgError::Env(msg) if msg.starts_with("missing.env")));
+}
@@ -0,0 +1,87 @@
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, CnfgError};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct AppConfig {
+    #[cnfg(default = "app")]
+    name: String,
+
+    #[cnfg(default = 8080)]
+    port: u16,
+}
+
+fn load(name: &str, contents: &[u8]) -> Result<AppConfig, CnfgError> {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file(name, contents);
+    sandbox
+        .args(["app".into(), "--config".into(), path.display().to_string()])
+        .loader::<AppConfig>()
+        .load()
+}
+
+fn utf16(text: &str, bom: bool, unit: fn(u16) -> [u8; 2]) -> Vec<u8> {
+    let mut bytes = if bom {
+        unit(0xFEFF).to_vec()
+    } else {
+        Vec::new()
+    };
+    bytes.extend(text.encode_utf16().flat_map(unit));
+    bytes
+}
+
+#[test]
+fn strips_utf8_bom() {
+    let cfg = load("app.yaml", b"\xEF\xBB\xBFname: caf\xC3\xA9\nport: 9000\n").expect("config");
+    assert_eq!(cfg.name, "café");
+    assert_eq!(cfg.port, 9000);
+}
+
+#[test]
+fn transcodes_utf16_with_bom() {
+    let toml = "name = \"día\"\nport = 9001\n";
+    let cfg = load("le.toml", &utf16(toml, true, u16::to_le_bytes)).expect("config");
+    assert_eq!((cfg.name.as_str(), cfg.port), ("día", 9001));
+}
+
+#[test]
+fn transcodes_big_endian_utf16() {
+    let json = r#"{"name": "über", "port": 9002}"#;
+    let cfg = load("be.json", &utf16(json, true, u16::to_be_bytes)).expect("config");
+    assert_eq!((cfg.name.as_str(), cfg.port), ("über", 9002));
+}
+
+#[cfg(not(feature = "encoding"))]
+#[test]
+fn latin1_fails_naming_the_file() {
+    let err = load("legacy.toml", b"name = \"caf\xE9\"\n").unwrap_err();
+    assert!(err.path().is_some_and(|path| path.ends_with("legacy.toml")));
+    let CnfgError::Io(io) = err.root() else {
+        panic!("expected I/O error, got {err:?}");
+    };
+    assert_eq!(io.kind(), std::io::ErrorKind::InvalidData);
+    let message = err.to_string();
+    assert!(
+        message.contains("legacy.toml: I/O error: not valid UTF-8 (at byte 11)"),
+        "{message}"
+    );
+    assert!(message.contains("`encoding` feature"), "{message}");
+}
+
+#[cfg(feature = "encoding")]
+#[test]
+fn reads_windows_1252() {
+    let cfg = load("legacy.toml", b"name = \"\x80 caf\xE9\"\n").expect("config");
+    assert_eq!(cfg.name, "€ café");
+}
+
+#[cfg(feature = "encoding")]
+#[test]
+fn detects_utf16_without_bom() {
+    let cfg = load(
+        "plain.toml",
+        &utf16("port = 9003\n", false, u16::to_le_bytes),
+    )
+    .expect("config");
+    assert_eq!(cfg.port, 9003);
+}
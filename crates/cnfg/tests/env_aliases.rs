@@ -0,0 +1,63 @@
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, Origin};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct AliasConfig {
+    #[cnfg(
+        default = "localhost",
+        env = "ALIAS_DB_HOST",
+        env_alias = "ALIAS_DATABASE_HOST",
+        env_alias = "ALIAS_PGHOST"
+    )]
+    host: String,
+
+    #[cnfg(default = 5432, env = "ALIAS_DB_PORT")]
+    port: u16,
+}
+
+#[test]
+fn aliases_apply_when_the_primary_name_is_unset() {
+    let sandbox = Sandbox::new()
+        .env("ALIAS_PGHOST", "pg")
+        .env("ALIAS_DATABASE_HOST", "old");
+    let (cfg, report) = sandbox
+        .loader::<AliasConfig>()
+        .load_with_report()
+        .expect("config");
+    assert_eq!(cfg.host, "old", "aliases are tried in order");
+    assert_eq!(
+        report.provenance.get("host"),
+        Some(&Origin::Env("ALIAS_DATABASE_HOST".into()))
+    );
+
+    let sandbox = sandbox.env("ALIAS_DB_HOST", "new");
+    let cfg = sandbox.loader::<AliasConfig>().load().expect("config");
+    assert_eq!(cfg.host, "new");
+}
+
+#[test]
+fn case_insensitive_lookup_is_opt_in() {
+    let sandbox = Sandbox::new()
+        .env("alias_db_port", "6543")
+        .env("Alias_PgHost", "pg");
+    let exact = sandbox.loader::<AliasConfig>().load().expect("config");
+    assert_eq!(exact.port, 5432);
+    assert_eq!(exact.host, "localhost");
+
+    let (cfg, report) = sandbox
+        .loader::<AliasConfig>()
+        .env_case_insensitive(true)
+        .load_with_report()
+        .expect("config");
+    assert_eq!(cfg.port, 6543);
+    assert_eq!(cfg.host, "pg");
+    assert_eq!(
+        report.provenance.get("port"),
+        Some(&Origin::Env("alias_db_port".into()))
+    );
+    assert_eq!(
+        report.provenance.get("host"),
+        Some(&Origin::Env("Alias_PgHost".into()))
+    );
+}
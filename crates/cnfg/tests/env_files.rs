@@ -0,0 +1,60 @@
+use cnfg::{Cnfg, CnfgError, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct SecretConfig {
+    #[cnfg(env = "ENVFILE_DB_PASSWORD", allow_file, default = "")]
+    db_password: String,
+
+    #[cnfg(env = "ENVFILE_API_TOKEN", default = "")]
+    api_token: String,
+}
+
+#[test]
+fn reads_values_from_file_suffix() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let pw = dir.path().join("db_pw");
+    let token = dir.path().join("token");
+    std::fs::write(&pw, "hunter2\n").expect("write secret");
+    std::fs::write(&token, "tok-123").expect("write secret");
+
+    unsafe {
+        std::env::set_var("ENVFILE_DB_PASSWORD_FILE", &pw);
+        std::env::set_var("ENVFILE_API_TOKEN_FILE", &token);
+    }
+
+    let cfg = SecretConfig::load().expect("load with secret file");
+    assert_eq!(cfg.db_password, "hunter2");
+    assert_eq!(cfg.api_token, "", "opt-in is per field by default");
+
+    let cfg = SecretConfig::loader()
+        .env_files(true)
+        .load()
+        .expect("load with global switch");
+    assert_eq!(cfg.api_token, "tok-123");
+
+    unsafe { std::env::set_var("ENVFILE_DB_PASSWORD", "direct") };
+    let cfg = SecretConfig::load().expect("direct value wins");
+    assert_eq!(cfg.db_password, "direct");
+
+    unsafe {
+        std::env::remove_var("ENVFILE_DB_PASSWORD");
+        std::env::remove_var("ENVFILE_DB_PASSWORD_FILE");
+        std::env::remove_var("ENVFILE_API_TOKEN_FILE");
+    }
+}
+
+#[test]
+fn reports_unreadable_secret_files() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe { std::env::set_var("ENVFILE_DB_PASSWORD_FILE", "/nonexistent/cnfg/secret") };
+    match SecretConfig::load() {
+        Err(CnfgError::Env(msg)) => assert!(msg.contains("ENVFILE_DB_PASSWORD_FILE"), "{msg}"),
+        other => panic!("expected env error, got {other:?}"),
+    }
+    unsafe { std::env::remove_var("ENVFILE_DB_PASSWORD_FILE") };
+}
@@ -0,0 +1,141 @@
+#![cfg(feature = "etcd")]
+
+use cnfg::sources::etcd::{EtcdClient, EtcdSource, KeyValue, RangeResponse, WatchRequest};
+use cnfg::{Cnfg, LoaderExt, Origin};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Current revision, and each key's value with the revision it was last
+/// modified at.
+type Store = (i64, BTreeMap<String, (String, i64)>);
+
+#[derive(Clone, Default)]
+struct FakeEtcd {
+    state: Arc<(Mutex<Store>, Condvar)>,
+}
+
+impl FakeEtcd {
+    fn put(&self, key: &str, value: &str) {
+        let (lock, changed) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.0 += 1;
+        let revision = state.0;
+        state.1.insert(key.into(), (value.into(), revision));
+        changed.notify_all();
+    }
+}
+
+impl EtcdClient for FakeEtcd {
+    fn get_prefix(&self, prefix: &str) -> Result<RangeResponse, String> {
+        let state = self.state.0.lock().unwrap();
+        Ok(RangeResponse {
+            kvs: state
+                .1
+                .iter()
+                .filter(|(key, _)| key.starts_with(prefix))
+                .map(|(key, (value, _))| KeyValue {
+                    key: key.clone(),
+                    value: value.clone(),
+                })
+                .collect(),
+            revision: state.0,
+        })
+    }
+
+    fn watch_prefix(&self, request: &WatchRequest<'_>) -> Result<Option<i64>, String> {
+        let (lock, changed) = &*self.state;
+        let latest = |state: &Store| {
+            state
+                .1
+                .iter()
+                .filter(|(key, (_, rev))| {
+                    key.starts_with(request.prefix) && *rev >= request.start_revision
+                })
+                .map(|(_, (_, rev))| *rev)
+                .max()
+        };
+        let state = lock.lock().unwrap();
+        let (state, _) = changed
+            .wait_timeout_while(state, request.timeout, |state| latest(state).is_none())
+            .unwrap();
+        Ok(latest(&state))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "localhost")]
+    host: String,
+
+    #[cnfg(default = 5432)]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct EtcdConfig {
+    #[cnfg(default = false)]
+    maintenance: bool,
+
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[test]
+fn reads_keys_under_the_prefix() {
+    let etcd = FakeEtcd::default();
+    etcd.put("/myapp/prod/database/port", "6432");
+    etcd.put("/myapp/prod/maintenance", "true");
+    etcd.put("/myapp/production/database/port", "1");
+    etcd.put("/other/database/host", "elsewhere");
+
+    let source = EtcdSource::new(etcd.clone(), "myapp/prod/");
+    assert_eq!(
+        source.path_for("/myapp/prod/database/port").as_deref(),
+        Some("database.port")
+    );
+    assert_eq!(source.path_for("/myapp/production/x"), None);
+
+    let (cfg, report) = EtcdConfig::loader()
+        .args(["app"])
+        .source(source)
+        .load_with_report()
+        .expect("load from etcd");
+    assert_eq!(cfg.database.port, 6432);
+    assert_eq!(cfg.database.host, "localhost");
+    assert!(cfg.maintenance);
+    assert_eq!(
+        report.provenance.get("database.port"),
+        Some(&Origin::Source("etcd:/myapp/prod".into()))
+    );
+}
+
+#[test]
+fn watch_reloads_on_etcd_events_without_polling() {
+    let etcd = FakeEtcd::default();
+    etcd.put("/svc/database/port", "6432");
+
+    let watcher = EtcdConfig::loader()
+        .args(["app"])
+        .source(EtcdSource::new(etcd.clone(), "/svc"))
+        .watch(Duration::from_secs(3600))
+        .expect("start watcher");
+    assert_eq!(watcher.current().database.port, 6432);
+
+    etcd.put("/svc/database/port", "7432");
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while watcher.current().database.port != 7432 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(watcher.current().database.port, 7432);
+
+    etcd.put("/elsewhere/database/port", "1");
+    etcd.put("/svc/maintenance", "on");
+    while !watcher.current().maintenance && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert!(watcher.current().maintenance);
+    assert_eq!(watcher.current().database.port, 7432);
+    watcher.stop();
+}
@@ -0,0 +1,92 @@
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, CnfgError, LoaderExt};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "localhost", env = "EXPORT_DB_HOST")]
+    host: String,
+
+    #[cnfg(default = 5432)]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(env_prefix = "EXPORT_")]
+struct ExportConfig {
+    #[cnfg(default = "hello world $USER", cli)]
+    greeting: String,
+
+    #[serde(default)]
+    #[cnfg(cli = "--tag")]
+    tags: Vec<String>,
+
+    api_key: Option<String>,
+
+    #[serde(default)]
+    #[cnfg(nested)]
+    database: Database,
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Self {
+            host: "localhost".into(),
+            port: 5432,
+        }
+    }
+}
+
+#[test]
+fn flattens_fields_into_env_names() {
+    let cfg = ExportConfig::loader()
+        .args(["app", "--tag", "a", "--tag", "b"])
+        .load()
+        .expect("config");
+    let vars = cfg.to_env_vars().expect("vars");
+    assert_eq!(
+        vars,
+        [
+            (
+                "EXPORT_GREETING".to_string(),
+                "hello world $USER".to_string()
+            ),
+            ("EXPORT_TAGS".to_string(), "a,b".to_string()),
+            ("EXPORT_DB_HOST".to_string(), "localhost".to_string()),
+            ("EXPORT_DATABASE_PORT".to_string(), "5432".to_string()),
+        ]
+    );
+    assert_eq!(
+        cnfg::export::render(&vars),
+        "EXPORT_GREETING=\"hello world \\$USER\"\nEXPORT_TAGS=a,b\n\
+         EXPORT_DB_HOST=localhost\nEXPORT_DATABASE_PORT=5432\n"
+    );
+}
+
+#[test]
+fn rendered_lines_read_back_through_dotenv() {
+    let cfg = ExportConfig::loader()
+        .args(["app", "--greeting", "say \"hi\"\nback\\slash"])
+        .load()
+        .expect("config");
+    let rendered = cnfg::export::render(&cfg.to_env_vars().expect("vars"));
+
+    let sandbox = Sandbox::new().current_dir();
+    sandbox.file(".env", &rendered);
+    // Loading copies the `.env` file into the environment.
+    sandbox.loader::<ExportConfig>().load().expect("config");
+    assert_eq!(
+        std::env::var("EXPORT_GREETING").as_deref(),
+        Ok(cfg.greeting.as_str())
+    );
+    assert_eq!(std::env::var("EXPORT_TAGS").as_deref(), Ok(""));
+}
+
+#[test]
+fn dump_env_flag_prints_and_stops() {
+    match ExportConfig::loader().args(["app", "--dump-env"]).load() {
+        Err(CnfgError::EnvPrinted) => {}
+        other => panic!("expected env sentinel, got {other:?}"),
+    }
+    assert!(ExportConfig::help().contains("--dump-env"));
+}
@@ -0,0 +1,87 @@
+use cnfg::{Cnfg, ConfigMeta, LoaderExt, WarningKind};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Scripting {
+    #[cnfg(default = "lua")]
+    engine: String,
+
+    #[cnfg(extra)]
+    options: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct HostConfig {
+    #[cnfg(default = 8080)]
+    port: u16,
+
+    #[serde(default)]
+    #[cnfg(nested)]
+    scripting: Scripting,
+
+    #[cnfg(extra)]
+    extras: HashMap<String, Value>,
+}
+
+#[test]
+fn collects_undeclared_keys_per_struct() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    assert!(
+        HostConfig::field_specs()
+            .iter()
+            .any(|spec| spec.extra && spec.path == "scripting.options")
+    );
+
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        r#"
+port = 9000
+greeting = "hello"
+
+[theme]
+accent = "teal"
+
+[scripting]
+engine = "rhai"
+max_ops = 10000
+"#,
+    )
+    .expect("write toml");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let (cfg, report) = HostConfig::loader()
+        .args(["app"])
+        .load_with_report()
+        .expect("load");
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+
+    assert_eq!(cfg.port, 9000);
+    assert_eq!(cfg.extras.len(), 2);
+    assert_eq!(cfg.extras["greeting"], json!("hello"));
+    assert_eq!(cfg.extras["theme"], json!({ "accent": "teal" }));
+    assert_eq!(cfg.scripting.engine, "rhai");
+    assert_eq!(cfg.scripting.options["max_ops"], json!(10000));
+    assert!(
+        !report
+            .warnings
+            .iter()
+            .any(|warning| warning.kind == WarningKind::UnknownKey),
+        "{}",
+        report.warnings
+    );
+}
+
+#[test]
+fn extras_default_to_empty() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+    let cfg = HostConfig::loader().args(["app"]).load().expect("load");
+    assert!(cfg.extras.is_empty());
+    assert!(cfg.scripting.options.is_empty());
+}
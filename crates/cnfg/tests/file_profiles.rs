@@ -0,0 +1,130 @@
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, Origin, WarningKind};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "localhost")]
+    host: String,
+
+    #[cnfg(default = 5)]
+    pool: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct SiteConfig {
+    #[cnfg(default = "info")]
+    log_level: String,
+
+    #[cnfg(default = false)]
+    debug: bool,
+
+    #[cnfg(nested)]
+    database: Database,
+}
+
+const TOML: &str = "\
+log_level = \"debug\"
+debug = true
+
+[database]
+host = \"db.local\"
+
+[profile.production]
+log_level = \"warn\"
+debug = false
+
+[profile.production.database]
+pool = 50
+";
+
+fn sandbox_with(name: &str, contents: &str) -> (Sandbox, std::path::PathBuf) {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file(name, contents);
+    let sandbox = sandbox.args(["app".into(), "--config".into(), path.display().to_string()]);
+    (sandbox, path)
+}
+
+#[test]
+fn selected_profile_overrides_the_top_level() {
+    let (sandbox, path) = sandbox_with("app.toml", TOML);
+    let (cfg, report) = sandbox
+        .loader::<SiteConfig>()
+        .profile("production")
+        .load_with_report()
+        .expect("config");
+    assert_eq!(cfg.log_level, "warn");
+    assert!(!cfg.debug);
+    assert_eq!(cfg.database.host, "db.local");
+    assert_eq!(cfg.database.pool, 50);
+    assert_eq!(
+        report.provenance.get("database.pool"),
+        Some(&Origin::File(path))
+    );
+    assert_eq!(
+        report.provenance.location("log_level").map(|at| at.line),
+        Some(8)
+    );
+    assert!(
+        !report
+            .warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::UnknownKey),
+        "{:?}",
+        report.warnings
+    );
+}
+
+#[test]
+fn sections_are_dropped_without_a_profile() {
+    let (sandbox, _) = sandbox_with("app.toml", TOML);
+    let (cfg, report) = sandbox
+        .loader::<SiteConfig>()
+        .load_with_report()
+        .expect("config");
+    assert_eq!(cfg.log_level, "debug");
+    assert!(cfg.debug);
+    assert_eq!(cfg.database.pool, 5);
+    assert_eq!(
+        report.provenance.location("log_level").map(|at| at.line),
+        Some(1)
+    );
+    assert!(
+        report
+            .warnings
+            .iter()
+            .all(|w| w.kind != WarningKind::UnknownKey)
+    );
+}
+
+#[test]
+fn yaml_profiles_follow_config_profile() {
+    let (sandbox, _) = sandbox_with(
+        "app.yaml",
+        "log_level: debug\nprofiles:\n  staging:\n    database:\n      host: staging-db\n  production:\n    log_level: error\n",
+    );
+    let cfg = sandbox
+        .env("CONFIG_PROFILE", "staging")
+        .loader::<SiteConfig>()
+        .load()
+        .expect("config");
+    assert_eq!(cfg.log_level, "debug");
+    assert_eq!(cfg.database.host, "staging-db");
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct AwsConfig {
+    #[cnfg(default = "default")]
+    profile: String,
+}
+
+#[test]
+fn fields_named_profile_keep_their_key() {
+    let (sandbox, _) = sandbox_with("aws.toml", "profile = \"billing\"\n");
+    let cfg = sandbox
+        .loader::<AwsConfig>()
+        .profile("production")
+        .load()
+        .expect("config");
+    assert_eq!(cfg.profile, "billing");
+}
@@ -1,5 +1,8 @@
-use cnfg::Cnfg;
+use cnfg::{Cnfg, LoaderExt, Origin};
 use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
 
 #[derive(Debug, Serialize, Deserialize, Cnfg)]
 struct FileConfig {
@@ -12,6 +15,7 @@ struct FileConfig {
 
 #[test]
 fn loads_from_yaml_and_json() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
     let dir = tempfile::tempdir().expect("tempdir");
 
     let yaml_path = dir.path().join("config.yaml");
@@ -34,3 +38,73 @@ fn loads_from_yaml_and_json() {
 
     unsafe { std::env::remove_var("CONFIG_FILE") };
 }
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "localhost")]
+    host: String,
+
+    #[cnfg(default = "")]
+    password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct LayeredConfig {
+    #[cnfg(default = "from-default")]
+    name: String,
+
+    #[cnfg(default = 3000)]
+    port: u16,
+
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[test]
+fn merges_every_listed_file_in_order() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let base = dir.path().join("base.toml");
+    std::fs::write(
+        &base,
+        "name = \"base\"\nport = 1\n[database]\nhost = \"db\"\n",
+    )
+    .expect("write base");
+    let prod = dir.path().join("prod.toml");
+    std::fs::write(&prod, "port = 2\n").expect("write prod");
+    let secrets = dir.path().join("secrets.yaml");
+    std::fs::write(&secrets, "database:\n  password: hunter2\n").expect("write secrets");
+
+    let list = format!(
+        "{}:{},{}",
+        base.display(),
+        prod.display(),
+        secrets.display()
+    );
+    unsafe { std::env::set_var("CONFIG_FILE", &list) };
+    let from_env = LayeredConfig::loader().args(["app"]).load_with_report();
+    let flags = [
+        "app".to_string(),
+        "--config".into(),
+        secrets.display().to_string(),
+        format!("--config={}", base.display()),
+    ];
+    let from_flags = LayeredConfig::loader().args(flags).load();
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+
+    let (cfg, report) = from_env.expect("layered files");
+    assert_eq!(cfg.name, "base");
+    assert_eq!(cfg.port, 2);
+    assert_eq!(cfg.database.host, "db");
+    assert_eq!(cfg.database.password, "hunter2");
+    assert_eq!(report.provenance.get("port"), Some(&Origin::File(prod)));
+    assert_eq!(
+        report.provenance.get("database.host"),
+        Some(&Origin::File(base))
+    );
+
+    // `--config` replaces CONFIG_FILE; later flags win.
+    let cfg = from_flags.expect("flag files");
+    assert_eq!(cfg.port, 1);
+    assert_eq!(cfg.database.password, "hunter2");
+}
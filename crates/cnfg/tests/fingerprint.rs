@@ -0,0 +1,99 @@
+use cnfg::{Cnfg, ConfigMeta, LoaderExt};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Cnfg)]
+struct Replica {
+    #[cnfg(required)]
+    host: String,
+
+    #[cnfg(default = "", secret)]
+    auth_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Cnfg)]
+struct ServiceConfig {
+    #[cnfg(default = 8080, cli)]
+    port: u16,
+
+    #[cnfg(default = "hunter2", secret, cli)]
+    password: String,
+
+    #[cnfg(nested)]
+    replicas: Vec<Replica>,
+}
+
+fn load(args: &[&str]) -> ServiceConfig {
+    let mut full = vec!["app"];
+    full.extend_from_slice(args);
+    ServiceConfig::loader().args(full).load().expect("load")
+}
+
+#[test]
+fn fingerprint_is_stable_and_hex_encoded() {
+    assert!(
+        ServiceConfig::field_specs()
+            .iter()
+            .any(|spec| spec.path == "password" && spec.secret)
+    );
+    let first = load(&[]).fingerprint().expect("fingerprint");
+    let second = load(&[]).fingerprint().expect("fingerprint");
+    assert_eq!(first, second);
+    let hex = first.strip_prefix("sha256:").expect("prefixed");
+    assert_eq!(hex.len(), 64);
+    assert!(
+        hex.chars()
+            .all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase())
+    );
+}
+
+#[test]
+fn detects_changes_but_ignores_secrets() {
+    let baseline = load(&[]);
+    let recorded = baseline.fingerprint().expect("fingerprint");
+    assert!(!baseline.has_changed_since(&recorded).expect("compare"));
+
+    let rotated = load(&["--password", "correct-horse"]);
+    assert!(!rotated.has_changed_since(&recorded).expect("compare"));
+
+    let moved = load(&["--port", "9090"]);
+    assert!(moved.has_changed_since(&recorded).expect("compare"));
+
+    let mut with_replica = baseline.clone();
+    with_replica.replicas.push(Replica {
+        host: "a".into(),
+        auth_token: "one".into(),
+    });
+    let replica_print = with_replica.fingerprint().expect("fingerprint");
+    assert_ne!(replica_print, recorded);
+    with_replica.replicas[0].auth_token = "two".into();
+    assert!(
+        !with_replica
+            .has_changed_since(&replica_print)
+            .expect("compare")
+    );
+}
+
+#[test]
+fn canonical_string_is_sorted_and_redacted() {
+    let mut cfg = load(&["--port", "9000"]);
+    cfg.replicas = vec![Replica {
+        host: "db-1".into(),
+        auth_token: "t0ken".into(),
+    }];
+    let rendered = cfg.to_canonical_string().expect("render");
+    assert_eq!(
+        rendered,
+        r#"{
+  "password": "<redacted>",
+  "port": 9000,
+  "replicas": [
+    {
+      "auth_token": "<redacted>",
+      "host": "db-1"
+    }
+  ]
+}"#
+    );
+    cfg.password = "rotated".into();
+    assert_eq!(cfg.to_canonical_string().expect("render"), rendered);
+}
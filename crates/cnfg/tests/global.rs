@@ -0,0 +1,75 @@
+use cnfg::{Cnfg, LoaderExt, global};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct GlobalConfig {
+    #[cnfg(default = 1, cli)]
+    workers: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct WatchedGlobal {
+    #[cnfg(default = 1)]
+    workers: u32,
+}
+
+#[test]
+fn init_then_read_anywhere_with_scoped_overrides() {
+    let cfg = GlobalConfig::loader()
+        .args(["app", "--workers", "4"])
+        .init_global()
+        .expect("init");
+    assert_eq!(cfg.workers, 4);
+    assert_eq!(GlobalConfig::global().workers, 4);
+
+    let seen = global::scoped(Arc::new(GlobalConfig { workers: 9 }), || {
+        let other_thread = std::thread::spawn(|| GlobalConfig::global().workers)
+            .join()
+            .expect("thread");
+        (GlobalConfig::global().workers, other_thread)
+    });
+    assert_eq!(seen, (9, 4), "overrides only apply to the current thread");
+    assert_eq!(GlobalConfig::global().workers, 4);
+
+    assert_eq!(
+        global::clear::<GlobalConfig>().map(|cfg| cfg.workers),
+        Some(4)
+    );
+    assert!(GlobalConfig::try_global().is_none());
+}
+
+#[test]
+#[should_panic(expected = "read before init_global()")]
+fn reading_before_init_panics() {
+    #[derive(Debug, Serialize, Deserialize, Cnfg)]
+    struct NeverInitialized {
+        #[cnfg(default = 1)]
+        workers: u32,
+    }
+    NeverInitialized::global();
+}
+
+#[test]
+fn watcher_keeps_the_global_current() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, r#"{ "workers": 2 }"#).expect("write json");
+    let path_arg = path.display().to_string();
+
+    let watcher = WatchedGlobal::loader()
+        .args(["app", "--config", &path_arg])
+        .watch(Duration::from_millis(10))
+        .expect("start watcher");
+    watcher.publish_global();
+    assert_eq!(WatchedGlobal::global().workers, 2);
+
+    std::fs::write(&path, r#"{ "workers": 8 }"#).expect("write json");
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while WatchedGlobal::global().workers != 8 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(WatchedGlobal::global().workers, 8);
+    watcher.stop();
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "hcl")]
+
+use cnfg::{Cnfg, CnfgError, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Listener {
+    #[cnfg(default = "0.0.0.0")]
+    host: String,
+
+    #[cnfg(default = 8080)]
+    port: u16,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct Service {
+    image: String,
+    replicas: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct PlatformConfig {
+    #[cnfg(default = "platform")]
+    name: String,
+
+    #[cnfg(nested)]
+    listener: Listener,
+
+    #[serde(default)]
+    service: BTreeMap<String, Service>,
+}
+
+fn load_file(name: &str, body: &str) -> Result<PlatformConfig, CnfgError> {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join(name);
+    std::fs::write(&path, body).expect("write config");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let result = PlatformConfig::loader().args(["app"]).load();
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+    result
+}
+
+#[test]
+fn loads_blocks_and_labelled_blocks() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let cfg = load_file(
+        "platform.hcl",
+        r#"
+# service definitions
+name = "edge"
+
+listener {
+  port = 9000
+}
+
+service "api" {
+  image    = "api:1.2"
+  replicas = 3
+}
+
+service "worker" {
+  image    = "worker:1.2"
+  replicas = 1
+}
+"#,
+    )
+    .expect("load hcl");
+    assert_eq!(cfg.name, "edge");
+    assert_eq!(cfg.listener.port, 9000);
+    assert_eq!(cfg.listener.host, "0.0.0.0");
+    assert_eq!(cfg.service["api"].replicas, 3);
+    assert_eq!(cfg.service["worker"].image, "worker:1.2");
+}
+
+#[test]
+fn reports_hcl_syntax_errors() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    assert!(matches!(
+        load_file("broken.hcl", "listener {\n  port = \n")
+            .as_ref()
+            .map_err(CnfgError::root),
+        Err(CnfgError::ParseHcl(_))
+    ));
+}
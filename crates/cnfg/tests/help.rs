@@ -1,4 +1,4 @@
-use cnfg::{Cnfg, LoaderExt};
+use cnfg::{Cnfg, HelpOptions, LoaderExt};
 use serde::{Deserialize, Serialize};
 
 /// Demonstrates CLI help output extraction.
@@ -17,7 +17,214 @@ struct HelpConfig {
 fn renders_cli_help() {
     let help = HelpConfig::help();
     assert!(help.contains("Usage:"));
-    assert!(help.contains("--port <value>"));
+    assert!(help.contains("--port <INT>"));
     assert!(help.contains("Listening port"));
     assert!(help.contains("--verbose"));
 }
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct PlaceholderConfig {
+    #[cnfg(default = 8080, cli(value_name = "PORT"))]
+    port: u16,
+
+    #[cnfg(default = "https://api.example.com", cli, validate(url))]
+    endpoint: String,
+
+    #[cnfg(default = "/tmp", cli(flag = "--dir"))]
+    data_dir: std::path::PathBuf,
+}
+
+#[test]
+fn renders_value_placeholders() {
+    let help = PlaceholderConfig::help();
+    assert!(help.contains("--port <PORT>"), "{help}");
+    assert!(help.contains("--endpoint <URL>"), "{help}");
+    assert!(help.contains("--dir <PATH>"), "{help}");
+
+    let cfg = PlaceholderConfig::loader()
+        .args(["app", "--port", "9000", "--dir", "/srv"])
+        .load()
+        .expect("config");
+    assert_eq!(cfg.port, 9000);
+    assert_eq!(cfg.data_dir, std::path::PathBuf::from("/srv"));
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct HiddenConfig {
+    /// Listening port.
+    #[cnfg(default = 8080, cli)]
+    port: u16,
+
+    /// Delay every response, for debugging.
+    #[cnfg(default = 0, cli(hidden))]
+    debug_delay_ms: u64,
+}
+
+#[test]
+fn hidden_flags_are_parsed_but_not_listed() {
+    let help = HiddenConfig::help();
+    assert!(help.contains("--port <INT>"), "{help}");
+    assert!(!help.contains("--debug-delay-ms"), "{help}");
+    assert!(help.contains("--help-all"), "{help}");
+
+    let all = HiddenConfig::help_all();
+    assert!(all.contains("--debug-delay-ms <INT>"), "{all}");
+    assert!(all.contains("Delay every response"), "{all}");
+
+    let completions = HiddenConfig::completions(cnfg::Shell::Bash);
+    assert!(!completions.contains("--debug-delay-ms"), "{completions}");
+
+    let cfg = HiddenConfig::loader()
+        .args(["app", "--debug-delay-ms", "250"])
+        .load()
+        .expect("config");
+    assert_eq!(cfg.debug_delay_ms, 250);
+    assert!(!HelpConfig::help().contains("--help-all"));
+}
+
+/// Ignored in favour of `about`.
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(
+    about = "Serve the example API.",
+    after_help = "Docs: https://example.com/docs"
+)]
+struct AboutConfig {
+    /// Address the server listens on; accepts an IP address or a host name
+    /// that resolves to one.
+    #[cnfg(default = "127.0.0.1", cli)]
+    listen: String,
+}
+
+#[test]
+fn about_after_help_and_options() {
+    let help = AboutConfig::help();
+    assert!(help.starts_with("Serve the example API.\n"), "{help}");
+    assert!(!help.contains("Ignored"), "{help}");
+    assert!(
+        help.ends_with("\n\nDocs: https://example.com/docs"),
+        "{help}"
+    );
+
+    let options = HelpOptions::new()
+        .about("Example server")
+        .example("app --listen 0.0.0.0", "Listen on every interface")
+        .column(20)
+        .width(60);
+    let help = AboutConfig::help_with(&options);
+    assert!(help.starts_with("Example server\n"), "{help}");
+    assert!(
+        help.contains("Examples:\n  app --listen 0.0.0.0 Listen on every interface"),
+        "{help}"
+    );
+    assert!(help.ends_with("Docs: https://example.com/docs"), "{help}");
+    let rows: Vec<&str> = help.lines().skip_while(|l| *l != "Options:").collect();
+    assert_eq!(
+        rows[1],
+        "  --listen <STRING>    Address the server listens on;"
+    );
+    assert_eq!(
+        rows[2],
+        "                       accepts an IP address or a host name"
+    );
+    assert!(help.lines().all(|line| line.len() <= 60), "{help}");
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct VerboseConfig {
+    /// How long an idle connection is kept open before the server closes it.
+    /// Clients that reconnect often benefit from a longer timeout, while
+    /// servers with many short-lived clients should keep it low to free
+    /// sockets sooner.
+    #[cnfg(default = 30, cli)]
+    idle_timeout: u64,
+}
+
+#[test]
+fn wraps_descriptions_to_the_terminal_width() {
+    let width = cnfg::help::terminal_width().unwrap_or(cnfg::help::DEFAULT_WIDTH);
+    let help = VerboseConfig::help();
+    let rows: Vec<&str> = help
+        .lines()
+        .skip_while(|line| *line != "Options:")
+        .skip(1)
+        .take_while(|line| !line.trim_start().starts_with("--set"))
+        .collect();
+    assert!(rows.len() > 1, "{help}");
+    assert!(
+        rows[0].starts_with("  --idle-timeout <INT>     How long"),
+        "{help}"
+    );
+    for row in &rows {
+        assert!(row.len() <= width.max(47), "{help}");
+    }
+    for row in &rows[1..] {
+        assert!(row.starts_with(&" ".repeat(27)), "{help}");
+        assert!(!row[27..].starts_with(' '), "{help}");
+    }
+    assert!(help.contains("[default: 30]"), "{help}");
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct UsageConfig {
+    /// Key for the upstream API.
+    #[cnfg(required, cli(value_name = "KEY"))]
+    api_key: String,
+
+    #[cnfg(default = false, cli)]
+    verbose: bool,
+}
+
+#[test]
+fn usage_names_the_program_and_required_flags() {
+    let help = UsageConfig::help_with(&HelpOptions::new().bin_name("myapp"));
+    assert!(
+        help.contains("Usage:\n  myapp --api-key <KEY> [OPTIONS]\n"),
+        "{help}"
+    );
+
+    let help = UsageConfig::help();
+    assert!(help.contains(" --api-key <KEY> [OPTIONS]"), "{help}");
+    assert!(!help.contains("<binary>"), "{help}");
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(app_name = "json-demo", version = "1.2.3")]
+struct JsonConfig {
+    /// Listening port.
+    #[cnfg(default = 8080, env = "JSON_DEMO_PORT", cli)]
+    port: u16,
+
+    #[cnfg(required, secret, cli(value_name = "KEY"))]
+    api_key: String,
+
+    #[cnfg(default = 0, cli(hidden))]
+    debug_delay_ms: u64,
+}
+
+#[test]
+fn help_json_describes_fields_and_flags() {
+    let spec: serde_json::Value = serde_json::from_str(&JsonConfig::help_json()).expect("json");
+    assert_eq!(spec["name"], "json-demo");
+    assert_eq!(spec["version"], "1.2.3");
+
+    let port = &spec["fields"][0];
+    assert_eq!(port["path"], "port");
+    assert_eq!(port["kind"], "int");
+    assert_eq!(port["default"], 8080);
+    assert_eq!(port["env"], "JSON_DEMO_PORT");
+    assert_eq!(port["doc"], "Listening port.");
+    assert_eq!(spec["fields"][1]["required"], true);
+    assert_eq!(spec["fields"][1]["secret"], true);
+
+    let flags = spec["flags"].as_array().expect("flags");
+    assert_eq!(flags.len(), 3);
+    assert_eq!(flags[1]["flag"], "--api-key");
+    assert_eq!(flags[1]["value_name"], "KEY");
+    assert_eq!(flags[2]["hidden"], true);
+
+    match JsonConfig::loader().args(["app", "--help-json"]).load() {
+        Err(cnfg::CnfgError::HelpPrinted) => {}
+        other => panic!("expected help sentinel, got {other:?}"),
+    }
+    assert!(JsonConfig::help().contains("--help-json"));
+}
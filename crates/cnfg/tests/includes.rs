@@ -0,0 +1,159 @@
+use cnfg::{Cnfg, CnfgError, LoaderExt, Origin, WarningKind};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "localhost")]
+    host: String,
+
+    #[cnfg(default = 5432)]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct IncludeConfig {
+    #[cnfg(default = "app")]
+    name: String,
+
+    #[cnfg(default = "none")]
+    region: String,
+
+    #[cnfg(nested)]
+    database: Database,
+}
+
+fn write(dir: &Path, name: &str, contents: &str) -> std::path::PathBuf {
+    let path = dir.join(name);
+    std::fs::create_dir_all(path.parent().expect("parent")).expect("mkdir");
+    std::fs::write(&path, contents).expect("write file");
+    path
+}
+
+fn load(path: &Path) -> cnfg::Loader<IncludeConfig> {
+    IncludeConfig::loader().args(["app".to_string(), format!("--config={}", path.display())])
+}
+
+#[test]
+fn merges_included_files_beneath_the_including_file() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let common = write(
+        dir.path(),
+        "common.toml",
+        "name = \"common\"\n[database]\nhost = \"db\"\nport = 1\n",
+    );
+    write(dir.path(), "region/a.yaml", "region: a\n");
+    let b = write(
+        dir.path(),
+        "region/b.yaml",
+        "region: b\ndatabase:\n  port: 2\n",
+    );
+    write(dir.path(), "region/notes.txt", "region = ignored\n");
+    let main = write(
+        dir.path(),
+        "conf/main.toml",
+        "include = [\"../common.toml\", \"../region/*.yaml\"]\nname = \"main\"\n",
+    );
+
+    let (cfg, report) = load(&main).load_with_report().expect("config");
+    assert_eq!(cfg.name, "main");
+    assert_eq!(cfg.region, "b");
+    assert_eq!(cfg.database.host, "db");
+    assert_eq!(cfg.database.port, 2);
+    assert_eq!(
+        report.provenance.get("database.host"),
+        Some(&Origin::File(common))
+    );
+    assert_eq!(report.provenance.get("region"), Some(&Origin::File(b)));
+    assert_eq!(report.provenance.get("name"), Some(&Origin::File(main)));
+    // The directive itself is not an unknown key.
+    assert!(
+        !report
+            .warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::UnknownKey)
+    );
+}
+
+#[test]
+fn nested_includes_resolve_relative_to_their_own_file() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    write(
+        dir.path(),
+        "shared/db.toml",
+        "[database]\nhost = \"nested\"\n",
+    );
+    write(dir.path(), "shared/base.toml", "include = \"db.toml\"\n");
+    let main = write(dir.path(), "main.toml", "include = \"shared/base.toml\"\n");
+
+    let cfg = load(&main).load().expect("config");
+    assert_eq!(cfg.database.host, "nested");
+}
+
+#[test]
+fn include_key_is_configurable() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    write(dir.path(), "extra.toml", "region = \"eu\"\n");
+    let main = write(dir.path(), "main.toml", "imports = [\"extra.toml\"]\n");
+
+    let cfg = load(&main).include_key("imports").load().expect("config");
+    assert_eq!(cfg.region, "eu");
+}
+
+#[test]
+fn detects_include_cycles() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    write(dir.path(), "a.toml", "include = \"b.toml\"\n");
+    let main = write(dir.path(), "b.toml", "include = \"a.toml\"\n");
+
+    let err = load(&main).load().expect_err("cycle");
+    assert!(matches!(&err, CnfgError::Include(msg) if msg.contains("include cycle")));
+}
+
+#[test]
+fn limits_include_depth() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    write(dir.path(), "three.toml", "region = \"deep\"\n");
+    write(dir.path(), "two.toml", "include = \"three.toml\"\n");
+    let one = write(dir.path(), "one.toml", "include = \"two.toml\"\n");
+
+    let cfg = load(&one).include_depth(2).load().expect("within limit");
+    assert_eq!(cfg.region, "deep");
+    let err = load(&one).include_depth(1).load().expect_err("too deep");
+    assert!(matches!(&err, CnfgError::Include(msg) if msg.contains("deeper than 1")));
+}
+
+#[test]
+fn missing_literal_include_is_an_error() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let main = write(
+        dir.path(),
+        "main.toml",
+        "include = [\"missing.toml\", \"none/*.toml\"]\n",
+    );
+
+    let err = load(&main).load().expect_err("missing include");
+    assert!(matches!(&err, CnfgError::Include(msg) if msg.contains("missing.toml")));
+}
+
+#[test]
+#[cfg(all(feature = "toml", feature = "yaml"))]
+fn parse_errors_name_the_included_file() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let broken = write(dir.path(), "broken.yaml", "region: [a\n");
+    let main = write(
+        dir.path(),
+        "main.toml",
+        "include = [\"broken.yaml\"]\nname = \"main\"\n",
+    );
+    let err = load(&main).load().expect_err("broken include");
+    assert_eq!(err.path(), Some(broken.as_path()));
+    assert!(matches!(err.root(), CnfgError::ParseYaml(_)));
+    assert!(
+        err.to_string()
+            .starts_with(&format!("{}: YAML parse error: ", broken.display())),
+        "{err}"
+    );
+    let source = std::error::Error::source(&err).expect("source");
+    assert!(source.to_string().starts_with("YAML parse error: "));
+}
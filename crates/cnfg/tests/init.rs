@@ -0,0 +1,138 @@
+use cnfg::{Cnfg, CnfgError, FieldSpec, Format, LoaderExt, Prompter, SaveOptions};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Database {
+    /// Connection string
+    #[cnfg(default = "postgres://localhost/app", cli)]
+    url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct InitConfig {
+    /// Port to listen on
+    #[cnfg(default = 8080, cli)]
+    port: u16,
+
+    #[cnfg(required, cli)]
+    name: String,
+
+    #[serde(default)]
+    #[cnfg(nested)]
+    database: Database,
+}
+
+struct Answer(&'static str);
+
+impl Prompter for Answer {
+    fn ask(&self, _: &FieldSpec, _: Option<&str>) -> io::Result<Option<String>> {
+        Ok(Some(self.0.to_string()))
+    }
+}
+
+#[test]
+fn writes_defaults_and_docs() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("app.toml");
+    let cfg = InitConfig::loader()
+        .args(["app", "--name", "svc"])
+        .init(&path, SaveOptions::new(Format::Toml).docs(true))
+        .expect("init");
+    assert_eq!(cfg.port, 8080);
+
+    let written = std::fs::read_to_string(&path).expect("read");
+    assert_eq!(
+        written,
+        "# Port to listen on\nport = 8080\nname = \"svc\"\n\n[database]\n\
+         # Connection string\nurl = \"postgres://localhost/app\"\n"
+    );
+}
+
+#[test]
+fn prompts_for_required_fields_when_interactive() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("app.yaml");
+    InitConfig::loader()
+        .args(["app"])
+        .prompter(Answer("prompted"))
+        .init(&path, Format::Yaml)
+        .expect("init");
+    let written = std::fs::read_to_string(&path).expect("read");
+    assert!(written.contains("name: prompted"), "{written}");
+}
+
+#[test]
+fn validates_before_writing() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("app.toml");
+    let err = InitConfig::loader()
+        .args(["app"])
+        .init(&path, Format::Toml)
+        .expect_err("name is required");
+    assert!(matches!(err, CnfgError::Validation(_)));
+    assert!(!path.exists());
+}
+
+#[test]
+fn refuses_to_overwrite_without_force() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("app.toml");
+    std::fs::write(&path, "port = 1\nname = \"old\"\n").expect("write");
+
+    let err = InitConfig::loader()
+        .args(["app", "--name", "new"])
+        .init(&path, Format::Toml)
+        .expect_err("existing file");
+    let CnfgError::Io(io_err) = &err else {
+        panic!("expected an I/O error, got {err:?}");
+    };
+    assert_eq!(io_err.kind(), io::ErrorKind::AlreadyExists);
+    assert!(err.to_string().contains("--force"), "{err}");
+
+    let cfg = InitConfig::loader()
+        .args(["app", "--name", "new"])
+        .force(true)
+        .init(&path, Format::Toml)
+        .expect("forced init");
+    assert_eq!(cfg.port, 8080);
+    let written = std::fs::read_to_string(&path).expect("read");
+    assert!(written.contains("name = \"new\""), "{written}");
+}
+
+#[test]
+fn init_flag_writes_the_named_file() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("starter.json");
+    let path_arg = path.display().to_string();
+
+    let err = InitConfig::loader()
+        .args(["app", "--init", &path_arg, "--name", "svc"])
+        .load()
+        .expect_err("init exits");
+    assert!(matches!(&err, CnfgError::Initialized(written) if *written == path));
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).expect("read")).expect("json");
+    assert_eq!(written["name"], "svc");
+    assert_eq!(written["database"]["url"], "postgres://localhost/app");
+
+    let err = InitConfig::loader()
+        .args(["app", "--name", "other", "--init", &path_arg])
+        .load()
+        .expect_err("refuses to overwrite");
+    assert!(matches!(err, CnfgError::Io(_)));
+
+    InitConfig::loader()
+        .args(["app", "--name", "other", "--init", &path_arg, "--force"])
+        .load()
+        .expect_err("init exits");
+    let written = std::fs::read_to_string(&path).expect("read");
+    assert!(written.contains("\"other\""), "{written}");
+}
+
+#[test]
+fn help_lists_init() {
+    let help = InitConfig::help();
+    assert!(help.contains("--init [path]"), "{help}");
+    assert!(help.contains("--force"), "{help}");
+}
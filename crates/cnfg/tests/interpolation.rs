@@ -0,0 +1,156 @@
+use cnfg::error::IssueKind;
+use cnfg::{Cnfg, CnfgError, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Server {
+    #[cnfg(default = "localhost")]
+    host: String,
+    #[cnfg(default = 8080, env = "INTERP_PORT")]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct InterpConfig {
+    #[serde(default)]
+    #[cnfg(nested)]
+    server: Server,
+
+    #[cnfg(default = "")]
+    base_url: String,
+
+    #[cnfg(default = 0)]
+    health_port: u16,
+
+    #[cnfg(default = "")]
+    literal: String,
+
+    #[cnfg(default = "", env = "INTERP_PASSWORD", cli)]
+    password: String,
+
+    #[cnfg(default = "/srv")]
+    data_dir: PathBuf,
+}
+
+fn load_json(json: &str) -> Result<InterpConfig, CnfgError> {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, json).expect("write json");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let result = InterpConfig::loader().args(["app"]).load();
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+    result
+}
+
+#[test]
+fn resolves_references_after_merging() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe { std::env::set_var("INTERP_PORT", "9443") };
+    let cfg = load_json(
+        r#"{
+            "server": { "host": "api.internal" },
+            "base_url": "http://${server.host}:${server.port}/v1",
+            "health_port": "${server.port}",
+            "literal": "$${server.host} costs $5"
+        }"#,
+    )
+    .expect("load with references");
+    unsafe { std::env::remove_var("INTERP_PORT") };
+
+    assert_eq!(cfg.base_url, "http://api.internal:9443/v1");
+    assert_eq!(cfg.health_port, 9443, "whole references keep their type");
+    assert_eq!(cfg.literal, "${server.host} costs $5");
+}
+
+#[test]
+fn reports_cycles_and_unresolved_references() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let err = load_json(
+        r#"{
+            "base_url": "${literal}",
+            "literal": "x${base_url}",
+            "server": { "host": "${server.nope}" }
+        }"#,
+    )
+    .expect_err("invalid references");
+    let CnfgError::Validation(errs) = err else {
+        panic!("expected validation error, got {err:?}");
+    };
+    let messages: Vec<(&str, &str)> = errs
+        .iter()
+        .filter(|i| matches!(i.kind, IssueKind::Interpolation))
+        .map(|i| (i.field.as_str(), i.message.as_str()))
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            (
+                "base_url",
+                "reference cycle: base_url -> literal -> base_url"
+            ),
+            ("literal", "reference cycle: literal -> base_url -> literal"),
+            ("server.host", "unresolved reference `${server.nope}`"),
+        ]
+    );
+}
+
+#[test]
+fn substitutes_environment_variables_in_files() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe {
+        std::env::set_var("INTERP_HOST", "db.prod");
+        std::env::remove_var("INTERP_UNSET_PORT");
+    }
+    let cfg = load_json(
+        r#"{
+            "server": { "host": "${INTERP_HOST}", "port": "${INTERP_UNSET_PORT:-7070}" },
+            "base_url": "postgres://${INTERP_HOST}/app",
+            "literal": "$${INTERP_HOST}"
+        }"#,
+    )
+    .expect("load with env substitution");
+    assert_eq!(cfg.server.host, "db.prod");
+    assert_eq!(cfg.server.port, 7070, "coerced after substitution");
+    assert_eq!(cfg.base_url, "postgres://db.prod/app");
+    assert_eq!(cfg.literal, "${INTERP_HOST}");
+
+    let err = load_json(r#"{ "base_url": "${INTERP_UNSET_PORT}" }"#).expect_err("unset var");
+    let CnfgError::Validation(errs) = err else {
+        panic!("expected validation error, got {err:?}");
+    };
+    let issue = errs.iter().next().expect("one issue");
+    assert_eq!(issue.field, "base_url");
+    assert_eq!(
+        issue.message,
+        "environment variable `INTERP_UNSET_PORT` is not set"
+    );
+    unsafe { std::env::remove_var("INTERP_HOST") };
+}
+
+#[test]
+fn env_and_flag_values_are_taken_as_written() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe { std::env::set_var("INTERP_PASSWORD", "ab${cd}ef$${x}") };
+    let from_env = InterpConfig::loader().args(["app"]).load();
+    unsafe { std::env::remove_var("INTERP_PASSWORD") };
+    let from_flag = InterpConfig::loader()
+        .args(["app", "--password", "q${zz}"])
+        .load();
+
+    assert_eq!(from_env.expect("env value").password, "ab${cd}ef$${x}");
+    assert_eq!(from_flag.expect("flag value").password, "q${zz}");
+}
+
+#[test]
+fn escapes_survive_path_expansion() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let cfg =
+        load_json(r#"{ "server": { "host": "db" }, "data_dir": "/a/$${HOME}/${server.host}" }"#)
+            .expect("load escaped path");
+
+    assert_eq!(cfg.data_dir, PathBuf::from("/a/${HOME}/db"));
+}
@@ -0,0 +1,82 @@
+#![cfg(any(feature = "json5", feature = "ron"))]
+
+use cnfg::{Cnfg, CnfgError, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Server {
+    #[cnfg(default = "127.0.0.1")]
+    host: String,
+
+    #[cnfg(default = 8080)]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct HumanConfig {
+    #[cnfg(default = "app")]
+    name: String,
+
+    #[serde(default)]
+    tags: Vec<String>,
+
+    #[cnfg(nested)]
+    server: Server,
+}
+
+fn load_file(name: &str, body: &str) -> Result<HumanConfig, CnfgError> {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join(name);
+    std::fs::write(&path, body).expect("write config");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let result = HumanConfig::loader().args(["app"]).load();
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+    result
+}
+
+#[test]
+#[cfg(feature = "json5")]
+fn loads_json5_with_comments_and_trailing_commas() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let cfg = load_file(
+        "config.json5",
+        "{\n  // service name\n  name: 'billing',\n  tags: ['a', 'b',],\n  server: { port: 9000, },\n}\n",
+    )
+    .expect("load json5");
+    assert_eq!(cfg.name, "billing");
+    assert_eq!(cfg.tags, ["a", "b"]);
+    assert_eq!(cfg.server.port, 9000);
+    assert_eq!(cfg.server.host, "127.0.0.1");
+
+    assert!(matches!(
+        load_file("broken.json5", "{ name: }")
+            .as_ref()
+            .map_err(CnfgError::root),
+        Err(CnfgError::ParseJson5(_))
+    ));
+}
+
+#[test]
+#[cfg(feature = "ron")]
+fn loads_ron_structs_and_maps() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let cfg = load_file(
+        "config.ron",
+        "(\n  name: \"billing\",\n  tags: [\"a\"],\n  server: (host: \"0.0.0.0\", port: 9000),\n)\n",
+    )
+    .expect("load ron");
+    assert_eq!(cfg.name, "billing");
+    assert_eq!(cfg.tags, ["a"]);
+    assert_eq!(cfg.server.host, "0.0.0.0");
+    assert_eq!(cfg.server.port, 9000);
+
+    assert!(matches!(
+        load_file("broken.ron", "(name: ")
+            .as_ref()
+            .map_err(CnfgError::root),
+        Err(CnfgError::ParseRon(_))
+    ));
+}
@@ -0,0 +1,104 @@
+#![cfg(any(feature = "ini", feature = "properties"))]
+
+use cnfg::{Cnfg, CnfgError, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "localhost")]
+    host: String,
+
+    #[cnfg(default = 5432)]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct LegacyConfig {
+    #[cnfg(default = "legacy")]
+    name: String,
+
+    #[cnfg(default = false)]
+    debug: bool,
+
+    #[serde(default)]
+    tags: Vec<String>,
+
+    #[cnfg(nested)]
+    database: Database,
+}
+
+fn load_file(name: &str, body: &str) -> Result<LegacyConfig, CnfgError> {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join(name);
+    std::fs::write(&path, body).expect("write config");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let result = LegacyConfig::loader().args(["app"]).load();
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+    result
+}
+
+#[test]
+#[cfg(feature = "ini")]
+fn ini_sections_become_nested_paths() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let cfg = load_file(
+        "legacy.ini",
+        "; top-level keys\nname = \"billing\"\ndebug: true\ntags = a, b\n\n[database]\nhost = db.internal\nport = 6432\n",
+    )
+    .expect("load ini");
+    assert_eq!(cfg.name, "billing");
+    assert!(cfg.debug);
+    assert_eq!(cfg.tags, ["a", "b"]);
+    assert_eq!(cfg.database.host, "db.internal");
+    assert_eq!(cfg.database.port, 6432);
+}
+
+#[test]
+#[cfg(feature = "ini")]
+fn ini_errors_name_file_and_line() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let err = load_file("bad.ini", "[database\nport = 1\n").unwrap_err();
+    assert!(err.path().is_some_and(|path| path.ends_with("bad.ini")));
+    match err.root() {
+        CnfgError::ParseIni(msg) => assert!(msg.contains("line 1"), "{msg}"),
+        other => panic!("expected ini error, got {other:?}"),
+    }
+    match load_file("typed.ini", "[database]\nport = many\n")
+        .as_ref()
+        .map_err(CnfgError::root)
+    {
+        Err(CnfgError::ParseIni(msg)) => {
+            assert!(msg.contains("line 2: database.port"), "{msg}")
+        }
+        other => panic!("expected ini error, got {other:?}"),
+    }
+}
+
+#[test]
+#[cfg(feature = "properties")]
+fn properties_dotted_keys_become_nested_paths() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let cfg = load_file(
+        "app.properties",
+        "# legacy service\n! another comment\nname billing\\u0020svc\ndatabase.host=db.\\\n    internal\ndatabase.port : 6432\ndebug=true\n",
+    )
+    .expect("load properties");
+    assert_eq!(cfg.name, "billing svc");
+    assert!(cfg.debug);
+    assert_eq!(cfg.database.host, "db.internal");
+    assert_eq!(cfg.database.port, 6432);
+}
+
+#[test]
+#[cfg(feature = "properties")]
+fn properties_parser_handles_escapes() {
+    let entries = cnfg::formats::parse_properties("a\\=b = x\\ty\nempty\n").expect("parse");
+    assert_eq!(entries[0].path, "a=b");
+    assert_eq!(entries[0].raw, "x\ty");
+    assert_eq!(entries[1].path, "empty");
+    assert_eq!(entries[1].raw, "");
+    assert!(cnfg::formats::parse_properties("k=\\u12").is_err());
+}
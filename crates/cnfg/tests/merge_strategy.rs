@@ -0,0 +1,129 @@
+use cnfg::merge::merge_with;
+use cnfg::{Cnfg, ConfigMeta, LoaderExt, MergeStrategy};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct LayeredConfig {
+    #[cnfg(
+        default_fn = "default_hosts",
+        merge = "append",
+        env = "MERGE_HOSTS",
+        cli = "--host"
+    )]
+    hosts: Vec<String>,
+
+    #[cnfg(default_fn = "default_scopes", merge = "union")]
+    scopes: Vec<String>,
+
+    #[cnfg(default_fn = "default_plain")]
+    plain: Vec<String>,
+
+    #[serde(default)]
+    #[cnfg(merge = "replace")]
+    labels: BTreeMap<String, String>,
+
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+}
+
+fn default_hosts() -> Vec<String> {
+    vec!["localhost".into()]
+}
+
+fn default_scopes() -> Vec<String> {
+    vec!["read".into()]
+}
+
+fn default_plain() -> Vec<String> {
+    vec!["a".into(), "b".into()]
+}
+
+#[test]
+fn strategies_are_exposed_on_field_specs() {
+    let strategy = |path: &str| {
+        LayeredConfig::field_specs()
+            .iter()
+            .find(|spec| spec.path == path)
+            .and_then(|spec| spec.merge)
+    };
+    assert_eq!(strategy("hosts"), Some(MergeStrategy::Append));
+    assert_eq!(strategy("scopes"), Some(MergeStrategy::Union));
+    assert_eq!(strategy("labels"), Some(MergeStrategy::Replace));
+    assert_eq!(strategy("plain"), None);
+}
+
+#[test]
+fn merge_with_honours_strategies() {
+    let mut base = json!({
+        "hosts": ["a"],
+        "scopes": ["read", "write"],
+        "plain": ["a"],
+        "labels": { "team": "core", "tier": "1" },
+        "headers": { "x-a": "1" },
+    });
+    merge_with(
+        &mut base,
+        json!({
+            "hosts": ["b", "a"],
+            "scopes": ["write", "admin"],
+            "plain": ["b"],
+            "labels": { "team": "edge" },
+            "headers": { "x-b": "2" },
+        }),
+        LayeredConfig::field_specs(),
+    );
+    assert_eq!(base["hosts"], json!(["a", "b", "a"]));
+    assert_eq!(base["scopes"], json!(["read", "write", "admin"]));
+    assert_eq!(base["plain"], json!(["b"]));
+    assert_eq!(base["labels"], json!({ "team": "edge" }));
+    assert_eq!(base["headers"], json!({ "x-a": "1", "x-b": "2" }));
+}
+
+#[test]
+fn layers_extend_appended_lists() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        r#"
+hosts = ["db.internal"]
+scopes = ["read", "write"]
+plain = ["c"]
+
+[labels]
+team = "core"
+"#,
+    )
+    .expect("write toml");
+    unsafe {
+        std::env::set_var("CONFIG_FILE", &path);
+        std::env::set_var("MERGE_HOSTS", "cache.internal");
+    }
+    let cfg = LayeredConfig::loader()
+        .args(["app", "--host", "edge.internal"])
+        .load()
+        .expect("load");
+    unsafe {
+        std::env::remove_var("CONFIG_FILE");
+        std::env::remove_var("MERGE_HOSTS");
+    }
+
+    assert_eq!(
+        cfg.hosts,
+        [
+            "localhost",
+            "db.internal",
+            "cache.internal",
+            "edge.internal"
+        ]
+    );
+    assert_eq!(cfg.scopes, ["read", "write"]);
+    assert_eq!(cfg.plain, ["c"]);
+    assert_eq!(cfg.labels.len(), 1);
+}
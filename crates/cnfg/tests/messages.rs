@@ -0,0 +1,91 @@
+use cnfg::error::{Issue, IssueKind, ValidationErrors};
+use cnfg::messages::{English, MessageRenderer, Templates, fill};
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, CnfgError};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct WorkerConfig {
+    #[cnfg(required, env = "MSG_NAME")]
+    name: String,
+
+    #[cnfg(default = 4, env = "MSG_WORKERS", validate(range(min = 1, max = 32)))]
+    workers: u32,
+}
+
+fn french() -> Templates {
+    Templates::new()
+        .set(IssueKind::Missing, "champ obligatoire")
+        .set(
+            IssueKind::Range,
+            "doit être entre {min} et {max}, trouvé {actual} (source : {origin})",
+        )
+}
+
+#[test]
+fn templates_use_issue_data() {
+    let sandbox = Sandbox::new()
+        .env("MSG_NAME", "ingest")
+        .env("MSG_WORKERS", "64");
+    let err = sandbox.loader::<WorkerConfig>().load().unwrap_err();
+    let CnfgError::Validation(errs) = &err else {
+        panic!("expected validation error, got {err:?}");
+    };
+    assert_eq!(
+        errs.render(&french()),
+        ["workers: doit être entre 1 et 32, trouvé 64 (source : env MSG_WORKERS)",]
+    );
+    assert_eq!(
+        errs.render(&English),
+        ["workers: must be <= 32 (from env MSG_WORKERS)",]
+    );
+    let missing = Issue::missing("name");
+    assert_eq!(french().render(&missing), "champ obligatoire");
+
+    let range = errs.iter().find(|issue| issue.field == "workers").unwrap();
+    assert_eq!(range.params["origin"], "env MSG_WORKERS");
+    assert_eq!(range.to_string(), errs.render(&English)[0]);
+}
+
+#[test]
+fn display_and_pretty_render() {
+    let mut errs = ValidationErrors::new();
+    errs.push(Issue::custom("mode", "unknown mode").with_origin("env MODE"));
+    errs.push(Issue::range("port", Some(1), Some(1024), 8080));
+    assert_eq!(
+        errs.to_string(),
+        "validation failed:\n  - mode — unknown mode (from env MODE)\n  - port — must be <= 1024\n"
+    );
+
+    let templates = Templates::new().set(IssueKind::Custom, "mode inconnu");
+    assert_eq!(
+        errs.pretty().color(false).renderer(&templates).to_string(),
+        "error: invalid configuration (2 issues)\n  × mode: mode inconnu\n    ╰─ set by env MODE\n  × port: must be <= 1024\n"
+    );
+}
+
+#[test]
+fn fill_leaves_unknown_placeholders() {
+    let issue = Issue::regex("host", "^[a-z]+$").with_actual("Web01");
+    assert_eq!(
+        fill(
+            "{field} {actual} ne correspond pas à {pattern} {nope} {",
+            &issue
+        ),
+        "host Web01 ne correspond pas à ^[a-z]+$ {nope} {"
+    );
+    assert_eq!(IssueKind::Regex.name(), "regex");
+
+    struct Shouting;
+    impl MessageRenderer for Shouting {
+        fn render(&self, issue: &Issue) -> String {
+            issue.message.to_uppercase()
+        }
+    }
+    let mut errs = ValidationErrors::new();
+    errs.push(issue);
+    assert_eq!(
+        errs.render(&Shouting),
+        ["host: REGEX NOT MATCHED: ^[A-Z]+$"]
+    );
+}
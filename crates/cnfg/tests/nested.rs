@@ -35,9 +35,7 @@ fn propagates_nested_environment_values() {
 fn surfaces_nested_required_errors() {
     let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
     unsafe { std::env::remove_var("NESTED_URL") };
-    assert!(NestedParent::required_fields()
-        .iter()
-        .any(|path| *path == "child.url"));
+    assert!(NestedParent::required_fields().contains(&"child.url"));
     match NestedParent::load() {
         Err(CnfgError::Validation(errors)) => {
             assert!(errors.iter().any(|issue| issue.field == "child.url"));
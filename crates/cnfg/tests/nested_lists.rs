@@ -0,0 +1,171 @@
+use cnfg::error::IssueKind;
+use cnfg::{Cnfg, CnfgError, ConfigMeta, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Upstream {
+    /// Host name of the upstream.
+    #[cnfg(required)]
+    host: String,
+
+    #[cnfg(default = 80, validate(range(min = 1, max = 65535)))]
+    port: u32,
+
+    #[cnfg(default = 1)]
+    weight: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Pool {
+    #[cnfg(default = "default")]
+    name: String,
+
+    #[cnfg(nested)]
+    members: Vec<Upstream>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct ProxyConfig {
+    #[cnfg(nested)]
+    upstreams: Vec<Upstream>,
+
+    #[cnfg(nested)]
+    backups: Option<Vec<Upstream>>,
+
+    #[serde(default)]
+    #[cnfg(nested)]
+    pools: Vec<Pool>,
+}
+
+fn load_toml(contents: &str) -> Result<ProxyConfig, CnfgError> {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, contents).expect("write toml");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let result = ProxyConfig::loader().args(["app"]).load();
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+    result
+}
+
+#[test]
+fn loads_arrays_of_tables_with_element_defaults() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let cfg = load_toml(
+        r#"
+[[upstreams]]
+host = "a.internal"
+
+[[upstreams]]
+host = "b.internal"
+port = 8080
+weight = 3
+
+[[pools]]
+name = "edge"
+members = [{ host = "c.internal" }]
+"#,
+    )
+    .expect("load upstreams");
+
+    assert_eq!(cfg.upstreams.len(), 2);
+    assert_eq!(cfg.upstreams[0].port, 80);
+    assert_eq!(cfg.upstreams[0].weight, 1);
+    assert_eq!(cfg.upstreams[1].port, 8080);
+    assert_eq!(cfg.upstreams[1].weight, 3);
+    assert!(cfg.backups.is_none());
+    assert_eq!(cfg.pools[0].name, "edge");
+    assert_eq!(cfg.pools[0].members[0].port, 80);
+}
+
+#[test]
+fn defaults_to_an_empty_list() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+    let cfg = ProxyConfig::loader().args(["app"]).load().expect("load");
+    assert!(cfg.upstreams.is_empty());
+    assert!(cfg.backups.is_none());
+}
+
+#[test]
+fn reports_element_errors_with_indexed_paths() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let result = load_toml(
+        r#"
+[[upstreams]]
+host = "a.internal"
+
+[[upstreams]]
+port = 9000
+
+[[upstreams]]
+host = "c.internal"
+port = 70000
+
+[[backups]]
+host = "d.internal"
+weight = "heavy"
+
+[[pools]]
+members = [{ host = "e.internal", port = 0 }]
+"#,
+    );
+    let Err(CnfgError::Validation(errs)) = result else {
+        panic!("expected validation errors, got {result:?}");
+    };
+    let kind_of = |field: &str| {
+        errs.iter()
+            .find(|issue| issue.field == field)
+            .map(|issue| &issue.kind)
+    };
+    assert!(
+        matches!(kind_of("upstreams[1].host"), Some(IssueKind::Missing)),
+        "{errs:?}"
+    );
+    assert!(
+        matches!(kind_of("backups[0].weight"), Some(IssueKind::Type)),
+        "{errs:?}"
+    );
+
+    let result = load_toml(
+        r#"
+[[upstreams]]
+host = "c.internal"
+port = 70000
+
+[[pools]]
+members = [{ host = "e.internal", port = 0 }]
+"#,
+    );
+    let Err(CnfgError::Validation(errs)) = result else {
+        panic!("expected validation errors, got {result:?}");
+    };
+    let fields: Vec<&str> = errs.iter().map(|issue| issue.field.as_str()).collect();
+    assert_eq!(fields, ["upstreams[0].port", "pools[0].members[0].port"]);
+}
+
+#[test]
+fn exposes_list_metadata_and_docs() {
+    let paths: Vec<&str> = ProxyConfig::lists().iter().map(|list| list.path).collect();
+    assert_eq!(paths, ["upstreams", "backups", "pools"]);
+    assert!(
+        !ProxyConfig::field_specs()
+            .iter()
+            .any(|spec| spec.path.starts_with("upstreams."))
+    );
+    assert!(ProxyConfig::required_fields().is_empty());
+
+    let table = cnfg::docgen::markdown::<ProxyConfig>();
+    assert!(
+        table.contains(
+            "| `upstreams[].host` | string |  |  |  | yes | Host name of the upstream. |"
+        ),
+        "{table}"
+    );
+    assert!(
+        table.contains("| `pools[].members[].port` | integer | `80` |"),
+        "{table}"
+    );
+}
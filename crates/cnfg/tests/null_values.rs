@@ -0,0 +1,112 @@
+use cnfg::merge::resolve_nulls;
+use cnfg::{Cnfg, CnfgError, ConfigMeta, FieldSpec, LoaderExt, Source};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Tls {
+    #[cnfg(default = "cert.pem")]
+    cert: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct ServiceConfig {
+    #[cnfg(default = 8080)]
+    port: u16,
+
+    #[cnfg(default = "info")]
+    log_level: Option<String>,
+
+    #[serde(default)]
+    #[cnfg(nested)]
+    tls: Tls,
+
+    name: Option<String>,
+
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+struct Layer(Value);
+
+impl Source for Layer {
+    fn name(&self) -> String {
+        "layer".into()
+    }
+
+    fn load(&self, _fields: &[FieldSpec]) -> Result<Value, CnfgError> {
+        Ok(self.0.clone())
+    }
+}
+
+#[test]
+fn null_unsets_to_default_or_none() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.yaml");
+    std::fs::write(
+        &path,
+        "port: 9000\nlog_level: debug\nname: api\ntls:\n  cert: custom.pem\n",
+    )
+    .expect("write yaml");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let cfg = ServiceConfig::loader()
+        .args(["app"])
+        .source(Layer(json!({
+            "port": null,
+            "log_level": null,
+            "name": null,
+            "tls": { "cert": null },
+        })))
+        .load()
+        .expect("nulls resolve");
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+
+    assert_eq!(cfg.port, 8080, "falls back to the default");
+    assert_eq!(cfg.log_level, None, "optional fields become None");
+    assert_eq!(cfg.name, None);
+    assert_eq!(cfg.tls.cert, "cert.pem");
+}
+
+#[test]
+fn null_without_default_is_rejected_by_field() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let result = ServiceConfig::loader()
+        .args(["app"])
+        .source(Layer(json!({ "tags": null })))
+        .load();
+    match result {
+        Err(CnfgError::Validation(errs)) => {
+            let issue = errs.iter().next().expect("one issue");
+            assert_eq!(issue.field, "tags");
+            assert!(
+                issue.message.contains("cannot be null"),
+                "{}",
+                issue.message
+            );
+            assert!(
+                issue.message.contains("(from source layer)"),
+                "{}",
+                issue.message
+            );
+        }
+        other => panic!("expected validation error, got {other:?}"),
+    }
+}
+
+#[test]
+fn resolve_nulls_reports_unresolvable_paths() {
+    let mut value = json!({ "port": null, "tls": null, "tags": null, "name": null });
+    let rejected = resolve_nulls(
+        &mut value,
+        &ServiceConfig::defaults_json(),
+        ServiceConfig::field_specs(),
+    );
+    assert_eq!(rejected, ["tags"]);
+    assert_eq!(value["port"], 8080);
+    assert_eq!(value["tls"], json!({ "cert": "cert.pem" }));
+    assert!(value["name"].is_null());
+}
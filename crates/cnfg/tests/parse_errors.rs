@@ -0,0 +1,138 @@
+use cnfg::error::IssueKind;
+use cnfg::{Cnfg, CnfgError, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct ServiceConfig {
+    #[cnfg(default = 8080, env = "PARSE_ERRORS_PORT")]
+    port: u16,
+
+    #[cnfg(default = false, env = "PARSE_ERRORS_DEBUG")]
+    debug: bool,
+
+    #[cnfg(default = 4, cli)]
+    workers: u32,
+
+    #[cnfg(default = 1.5, cli)]
+    ratio: f64,
+
+    #[cnfg(required)]
+    name: String,
+}
+
+#[test]
+fn reports_every_bad_env_and_flag_value_together() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe {
+        std::env::set_var("PARSE_ERRORS_PORT", "eighty");
+        std::env::set_var("PARSE_ERRORS_DEBUG", "maybe");
+    }
+    let result = ServiceConfig::loader()
+        .args([
+            "app",
+            "--workers",
+            "many",
+            "--ratio",
+            "half",
+            "--set",
+            "port=x",
+        ])
+        .load();
+    unsafe {
+        std::env::remove_var("PARSE_ERRORS_PORT");
+        std::env::remove_var("PARSE_ERRORS_DEBUG");
+    }
+
+    let Err(CnfgError::Validation(errs)) = result else {
+        panic!("expected validation errors, got {result:?}");
+    };
+    let found: Vec<(String, String)> = errs
+        .into_iter()
+        .map(|issue| {
+            if issue.field != "name" {
+                assert!(matches!(issue.kind, IssueKind::Type), "{issue:?}");
+            }
+            (issue.field, issue.message)
+        })
+        .collect();
+    let expected = [
+        ("PARSE_ERRORS_PORT", "expected an integer"),
+        (
+            "PARSE_ERRORS_DEBUG",
+            "expected a boolean (true/false, yes/no, on/off, or 1/0)",
+        ),
+        ("--workers", "expected an integer"),
+        ("--ratio", "expected a float"),
+        ("--set port", "expected an integer"),
+        ("name", "required field missing"),
+    ];
+    assert_eq!(
+        found,
+        expected.map(|(field, message)| (field.to_string(), message.to_string()))
+    );
+}
+
+#[test]
+fn structural_cli_errors_still_stop_early() {
+    let err = ServiceConfig::loader()
+        .args(["app", "--workers"])
+        .load()
+        .expect_err("missing value");
+    assert!(matches!(err, CnfgError::Cli(msg) if msg.contains("missing value for --workers")));
+}
+
+#[test]
+fn booleans_accept_common_spellings_in_any_case() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    for (raw, expected) in [
+        ("ON", true),
+        ("Yes", true),
+        ("tRuE", true),
+        ("1", true),
+        ("off", false),
+        ("NO", false),
+        ("False", false),
+        ("0", false),
+    ] {
+        unsafe { std::env::set_var("PARSE_ERRORS_DEBUG", raw) };
+        let cfg = ServiceConfig::loader()
+            .args(["app", "--set", "name=svc"])
+            .load()
+            .unwrap_or_else(|err| panic!("{raw}: {err}"));
+        assert_eq!(cfg.debug, expected, "{raw}");
+    }
+    unsafe { std::env::remove_var("PARSE_ERRORS_DEBUG") };
+}
+
+#[test]
+fn integers_accept_underscores_and_radix_prefixes() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    for (raw, expected) in [("8_080", 8080), ("0x1F90", 8080), ("0o17620", 8080)] {
+        unsafe { std::env::set_var("PARSE_ERRORS_PORT", raw) };
+        let cfg = ServiceConfig::loader()
+            .args(["app", "--workers", "0b1_000", "--set", "name=svc"])
+            .load()
+            .unwrap_or_else(|err| panic!("{raw}: {err}"));
+        assert_eq!(cfg.port, expected, "{raw}");
+        assert_eq!(cfg.workers, 8);
+    }
+    unsafe { std::env::remove_var("PARSE_ERRORS_PORT") };
+
+    for bad in ["0x", "_1", "1_", "0xG", "1.0"] {
+        let result = ServiceConfig::loader()
+            .args(["app", "--workers", bad, "--set", "name=svc"])
+            .load();
+        let Err(CnfgError::Validation(errs)) = result else {
+            panic!("{bad}: expected validation errors, got {result:?}");
+        };
+        let issue = errs.iter().next().expect("one issue");
+        assert_eq!(
+            (issue.field.as_str(), issue.message.as_str()),
+            ("--workers", "expected an integer"),
+            "{bad}"
+        );
+    }
+}
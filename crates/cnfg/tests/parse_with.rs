@@ -0,0 +1,116 @@
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, CnfgError, ConfigMeta, Kind, LoaderExt};
+use serde::{Deserialize, Serialize};
+
+fn parse_duration_secs(raw: &str) -> Result<u64, String> {
+    let (digits, unit) = raw.split_at(raw.trim_end_matches(char::is_alphabetic).len());
+    let n: u64 = digits
+        .parse()
+        .map_err(|_| format!("expected a duration like `90s` or `1h`, found `{raw}`"))?;
+    match unit {
+        "" | "s" => Ok(n),
+        "m" => Ok(n * 60),
+        "h" => Ok(n * 3600),
+        other => Err(format!("unknown duration unit `{other}`")),
+    }
+}
+
+fn parse_zones(raw: &str) -> Result<Vec<String>, String> {
+    let zones: Vec<String> = raw.split(',').map(|z| z.trim().to_string()).collect();
+    match zones
+        .iter()
+        .find(|zone| !zone.ends_with(|c: char| c.is_ascii_lowercase()))
+    {
+        Some(bad) => Err(format!("`{bad}` is not an availability zone")),
+        None => Ok(zones),
+    }
+}
+
+fn parse_tenant(raw: &str) -> Result<u32, String> {
+    raw.strip_prefix("tn-")
+        .and_then(|id| id.parse().ok())
+        .ok_or_else(|| format!("expected a tenant id like `tn-42`, found `{raw}`"))
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct DeployConfig {
+    /// Session lifetime in seconds.
+    #[cnfg(parse_with = "parse_duration_secs", env = "PW_TTL", cli, default = 60)]
+    ttl: u64,
+
+    #[serde(default)]
+    #[cnfg(parse_with = "parse_zones", env = "PW_ZONES", cli)]
+    zones: Vec<String>,
+
+    #[cnfg(parse_with = "parse_tenant", cli)]
+    tenant: Option<u32>,
+}
+
+#[test]
+fn decodes_env_and_cli_strings() {
+    let sandbox = Sandbox::new()
+        .env("PW_TTL", "1h")
+        .env("PW_ZONES", "us-east-1a, us-east-1b")
+        .args(["app", "--tenant", "tn-7"]);
+    let cfg = sandbox.loader::<DeployConfig>().load().expect("load");
+    assert_eq!(cfg.ttl, 3600);
+    assert_eq!(cfg.zones, ["us-east-1a", "us-east-1b"]);
+    assert_eq!(cfg.tenant, Some(7));
+
+    let sandbox = sandbox.args(["app", "--ttl", "5m", "--zones", "eu-west-1c"]);
+    let cfg = sandbox.loader::<DeployConfig>().load().expect("load");
+    assert_eq!(cfg.ttl, 300);
+    assert_eq!(cfg.zones, ["eu-west-1c"]);
+}
+
+#[test]
+fn files_and_defaults_skip_the_decoder() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file("deploy.toml", "ttl = 90\nzones = [\"a\"]\n");
+    let sandbox = sandbox.args(["app".into(), "--config".into(), path.display().to_string()]);
+    let cfg = sandbox.loader::<DeployConfig>().load().expect("load");
+    assert_eq!(cfg.ttl, 90);
+    assert_eq!(cfg.zones, ["a"]);
+}
+
+#[test]
+fn decoder_errors_name_the_source() {
+    let sandbox = Sandbox::new()
+        .env("PW_TTL", "3 fortnights")
+        .args(["app", "--tenant", "42"]);
+    let err = sandbox.loader::<DeployConfig>().load().unwrap_err();
+    let CnfgError::Validation(errs) = err else {
+        panic!("expected validation error, got {err:?}");
+    };
+    let messages: Vec<String> = errs
+        .iter()
+        .map(|issue| format!("{}: {}", issue.field, issue.message))
+        .collect();
+    assert!(
+        messages
+            .iter()
+            .any(|m| m.starts_with("PW_TTL") && m.contains("expected a duration")),
+        "{messages:?}"
+    );
+    assert!(
+        messages
+            .iter()
+            .any(|m| m.starts_with("--tenant") && m.contains("tn-42")),
+        "{messages:?}"
+    );
+}
+
+#[test]
+fn custom_kind_in_metadata() {
+    let specs = DeployConfig::field_specs();
+    assert!(specs.iter().all(|spec| spec.kind == Kind::Custom));
+    assert!(specs.iter().all(|spec| spec.parse.is_some()));
+    let zones = DeployConfig::cli_specs()
+        .iter()
+        .find(|spec| spec.flag == "zones")
+        .expect("zones flag");
+    assert!(!zones.multiple);
+
+    let json = DeployConfig::help_json();
+    assert!(json.contains("\"kind\": \"custom\""), "{json}");
+}
@@ -0,0 +1,134 @@
+use cnfg::error::{IssueKind, PathError};
+use cnfg::merge::{get_path, insert_path, remove_path};
+use cnfg::{Cnfg, CnfgError, LoaderExt};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::io::Write;
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[test]
+fn inserts_through_array_indices() {
+    let mut doc = json!({});
+    insert_path(&mut doc, &["servers[0]", "host"], json!("a")).expect("create list");
+    insert_path(&mut doc, &["servers[1]", "host"], json!("b")).expect("append");
+    insert_path(&mut doc, &["servers[0]", "port"], json!(80)).expect("fill element");
+    insert_path(&mut doc, &["grid[0][0]"], json!(1)).expect("nested indices");
+    insert_path(&mut doc, &["limits", "cpu"], json!(2)).expect("plain keys");
+    assert_eq!(
+        doc,
+        json!({
+            "servers": [{"host": "a", "port": 80}, {"host": "b"}],
+            "grid": [[1]],
+            "limits": {"cpu": 2},
+        })
+    );
+
+    assert_eq!(
+        get_path(&doc, &["servers[1]", "host"]),
+        Ok(Some(&json!("b")))
+    );
+    assert_eq!(get_path(&doc, &["servers[5]"]), Ok(None));
+    assert_eq!(get_path(&doc, &["limits", "memory"]), Ok(None));
+}
+
+#[test]
+fn rejects_bad_paths_instead_of_overwriting() {
+    let mut doc = json!({"port": 80, "servers": [{"host": "a"}]});
+    assert_eq!(insert_path(&mut doc, &[], json!(1)), Err(PathError::Empty));
+    assert_eq!(
+        insert_path(&mut doc, &["port", "number"], json!(1)),
+        Err(PathError::Mismatch {
+            at: "port".into(),
+            expected: "an object",
+            found: "a number",
+        })
+    );
+    assert_eq!(
+        insert_path(&mut doc, &["servers", "host"], json!("b")),
+        Err(PathError::Mismatch {
+            at: "servers".into(),
+            expected: "an object",
+            found: "an array",
+        })
+    );
+    let err = insert_path(&mut doc, &["servers[3]"], json!({})).expect_err("gap");
+    assert_eq!(
+        err,
+        PathError::OutOfBounds {
+            at: "servers[3]".into(),
+            len: 1,
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "index `servers[3]` is out of bounds (length 1)"
+    );
+    for bad in ["servers[x]", "servers[0", "", "[0]x"] {
+        assert_eq!(
+            insert_path(&mut doc, &[bad], json!(1)),
+            Err(PathError::InvalidSegment(bad.into())),
+            "{bad}"
+        );
+    }
+    assert_eq!(doc, json!({"port": 80, "servers": [{"host": "a"}]}));
+}
+
+#[test]
+fn removes_keys_and_elements() {
+    let mut doc = json!({"servers": [{"host": "a"}, {"host": "b"}], "db": {"url": "x"}});
+    assert_eq!(
+        remove_path(&mut doc, &["servers[0]"]),
+        Ok(Some(json!({"host": "a"})))
+    );
+    assert_eq!(remove_path(&mut doc, &["db", "url"]), Ok(Some(json!("x"))));
+    assert_eq!(remove_path(&mut doc, &["db", "url"]), Ok(None));
+    assert_eq!(remove_path(&mut doc, &["servers[4]", "host"]), Ok(None));
+    assert!(remove_path(&mut doc, &["servers", "host"]).is_err());
+    assert_eq!(doc, json!({"servers": [{"host": "b"}], "db": {}}));
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "localhost", env = "PATHS_DB_HOST")]
+    host: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct PathsConfig {
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[test]
+fn env_value_under_a_scalar_is_reported() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let mut file = tempfile::Builder::new()
+        .suffix(".json")
+        .tempfile()
+        .expect("temp file");
+    write!(file, r#"{{"database": "postgres://db"}}"#).expect("write config");
+    unsafe {
+        std::env::set_var("CONFIG_FILE", file.path());
+        std::env::set_var("PATHS_DB_HOST", "db.internal");
+    }
+    let result = PathsConfig::loader().args(["app"]).load();
+    unsafe {
+        std::env::remove_var("CONFIG_FILE");
+        std::env::remove_var("PATHS_DB_HOST");
+    }
+
+    let Err(CnfgError::Validation(errs)) = result else {
+        panic!("expected validation errors, got {result:?}");
+    };
+    let issue = errs
+        .iter()
+        .find(|issue| issue.field == "PATHS_DB_HOST")
+        .expect("env issue");
+    assert!(matches!(issue.kind, IssueKind::Type));
+    assert_eq!(
+        issue.message,
+        "expected an object at `database`, found a string"
+    );
+}
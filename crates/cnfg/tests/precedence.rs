@@ -0,0 +1,92 @@
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, Origin, Precedence, WarningKind};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct ServerConfig {
+    /// Injected by the platform; wins over `--port`.
+    #[cnfg(default = 8080, env = "PREC_PORT", cli, precedence = "env_over_cli")]
+    port: u16,
+
+    #[cnfg(default = "info", env = "PREC_LOG", cli)]
+    log: String,
+}
+
+#[test]
+fn env_wins_for_marked_fields() {
+    let sandbox = Sandbox::new()
+        .env("PREC_PORT", "3000")
+        .env("PREC_LOG", "warn")
+        .args(["app", "--port", "9000", "--log", "debug"]);
+    let (cfg, report) = sandbox
+        .loader::<ServerConfig>()
+        .load_with_report()
+        .expect("config");
+    assert_eq!(cfg.port, 3000);
+    assert_eq!(cfg.log, "debug");
+    assert_eq!(
+        report.provenance.get("port"),
+        Some(&Origin::Env("PREC_PORT".into()))
+    );
+    let shadowed: Vec<(&str, &str)> = report
+        .warnings
+        .iter()
+        .filter(|w| w.kind == WarningKind::Shadowed)
+        .map(|w| (w.subject.as_str(), w.message.as_str()))
+        .collect();
+    assert_eq!(
+        shadowed,
+        [("--port", "ignored; PREC_PORT takes precedence")]
+    );
+}
+
+#[test]
+fn set_overrides_still_win() {
+    let sandbox = Sandbox::new()
+        .env("PREC_PORT", "3000")
+        .args(["app", "--set", "port=9000"]);
+    let (cfg, report) = sandbox
+        .loader::<ServerConfig>()
+        .load_with_report()
+        .expect("config");
+    assert_eq!(cfg.port, 9000);
+    assert_eq!(
+        report.provenance.get("port"),
+        Some(&Origin::Cli("--set port".into()))
+    );
+    assert!(
+        !report
+            .warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::Shadowed)
+    );
+}
+
+#[test]
+fn flag_applies_when_env_is_unset() {
+    let sandbox = Sandbox::new().args(["app", "--port", "9000"]);
+    let (cfg, report) = sandbox
+        .loader::<ServerConfig>()
+        .load_with_report()
+        .expect("config");
+    assert_eq!(cfg.port, 9000);
+    assert!(
+        !report
+            .warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::Shadowed)
+    );
+}
+
+#[test]
+fn loader_order_applies_to_every_field() {
+    let sandbox = Sandbox::new()
+        .env("PREC_LOG", "warn")
+        .args(["app", "--log", "debug"]);
+    let cfg = sandbox
+        .loader::<ServerConfig>()
+        .precedence(Precedence::EnvOverCli)
+        .load()
+        .expect("config");
+    assert_eq!(cfg.log, "warn");
+}
@@ -0,0 +1,81 @@
+use cnfg::{Cnfg, CnfgError, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct ServerConfig {
+    #[cnfg(default = 8080)]
+    port: u16,
+
+    #[cnfg(required)]
+    name: String,
+}
+
+fn load_yaml(contents: &str) -> (CnfgError, std::path::PathBuf, tempfile::TempDir) {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.yaml");
+    std::fs::write(&path, contents).expect("write yaml");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let err = ServerConfig::loader()
+        .args(["app"])
+        .load()
+        .expect_err("load fails");
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+    (err, path, dir)
+}
+
+#[test]
+fn renders_issues_with_their_source() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let (err, path, _dir) = load_yaml("port: eighty\n");
+    let rendered = err.pretty().color(false).to_string();
+    assert_eq!(
+        rendered,
+        format!(
+            "error: invalid configuration (2 issues)\n  \
+             × port: expected integer, found string \"eighty\"\n    \
+             ╰─ set by file {}:1:1\n  \
+             × name: required field missing\n",
+            path.display()
+        )
+    );
+
+    let CnfgError::Validation(errs) = &err else {
+        panic!("expected validation error");
+    };
+    assert_eq!(errs.pretty().color(false).to_string(), rendered);
+}
+
+#[test]
+fn colors_only_when_asked_or_on_a_terminal() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let (err, _, _dir) = load_yaml("name: api\nport: eighty\n");
+    let colored = err.pretty().color(true).to_string();
+    assert!(
+        colored.starts_with("\x1b[1;31merror\x1b[0m: "),
+        "{colored:?}"
+    );
+    assert!(colored.contains("\x1b[1mport\x1b[0m"), "{colored:?}");
+
+    unsafe { std::env::set_var("NO_COLOR", "1") };
+    assert!(!cnfg::pretty::color_enabled());
+    assert!(!err.pretty().to_string().contains('\x1b'));
+    unsafe { std::env::remove_var("NO_COLOR") };
+}
+
+#[test]
+fn renders_parse_errors_with_position() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let (err, path, _dir) = load_yaml("name: api\nport: [1, 2\n");
+    let rendered = err.pretty().color(false).to_string();
+    let mut lines = rendered.lines();
+    assert_eq!(lines.next(), Some("error: invalid YAML"));
+    assert_eq!(
+        lines.next(),
+        Some(format!("  in {}", path.display()).as_str())
+    );
+    let position = lines.next().expect("position line");
+    assert!(position.starts_with("  at line 3, column "), "{rendered}");
+}
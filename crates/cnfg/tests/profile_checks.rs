@@ -0,0 +1,111 @@
+use cnfg::error::IssueKind;
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, CnfgError};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct ServerConfig {
+    #[cnfg(
+        default = "0.0.0.0",
+        env = "PC_BIND",
+        validate(forbid_in_profile(profile = "prod", value = "0.0.0.0"))
+    )]
+    bind: String,
+
+    #[cnfg(
+        default = true,
+        env = "PC_DEBUG",
+        validate(forbid_in_profile(profile = "prod", value = true))
+    )]
+    debug: bool,
+
+    #[serde(default)]
+    #[cnfg(validate(forbid_in_profile(profile = "prod", value = "*")))]
+    origins: Vec<String>,
+
+    #[cnfg(
+        default = "http://localhost",
+        env = "PC_PUBLIC_URL",
+        validate(when_profile(profile = "prod", url(schemes = ["https"]), regex = "\\.example\\.com$"))
+    )]
+    public_url: String,
+}
+
+fn messages(err: CnfgError) -> Vec<String> {
+    let CnfgError::Validation(errs) = err else {
+        panic!("expected validation error, got {err:?}");
+    };
+    errs.iter()
+        .map(|issue| format!("{}: {}", issue.field, issue.message))
+        .collect()
+}
+
+#[test]
+fn dev_defaults_pass_outside_the_profile() {
+    let sandbox = Sandbox::new();
+    let cfg = sandbox.loader::<ServerConfig>().load().expect("config");
+    assert_eq!(cfg.bind, "0.0.0.0");
+    assert!(cfg.debug);
+
+    sandbox
+        .loader::<ServerConfig>()
+        .profile("staging")
+        .load()
+        .expect("other profiles are unchecked");
+}
+
+#[test]
+fn forbidden_values_fail_in_the_profile() {
+    let sandbox = Sandbox::new();
+    let err = sandbox
+        .loader::<ServerConfig>()
+        .profile("prod")
+        .load()
+        .unwrap_err();
+    let CnfgError::Validation(errs) = &err else {
+        panic!("expected validation error, got {err:?}");
+    };
+    assert!(errs.iter().all(|issue| {
+        matches!(issue.kind, IssueKind::Forbidden) || issue.field == "public_url"
+    }));
+    assert_eq!(
+        messages(err),
+        [
+            "bind: `0.0.0.0` is not allowed in the `prod` profile (from default)",
+            "debug: `true` is not allowed in the `prod` profile (from default)",
+            "public_url: URL scheme `http` is not allowed (expected `https`) (from default)",
+            "public_url: regex not matched: \\.example\\.com$ (from default)",
+        ]
+    );
+}
+
+#[test]
+fn list_elements_and_env_profile() {
+    let sandbox = Sandbox::new()
+        .env("CONFIG_PROFILE", "prod")
+        .env("PC_BIND", "10.0.0.5")
+        .env("PC_DEBUG", "false")
+        .env("PC_PUBLIC_URL", "https://app.example.com");
+    let path = sandbox.file("app.toml", "origins = [\"https://a.io\", \"*\"]\n");
+    let sandbox = sandbox.args(["app".into(), "--config".into(), path.display().to_string()]);
+    let err = sandbox.loader::<ServerConfig>().load().unwrap_err();
+    let found = messages(err);
+    assert_eq!(found.len(), 1, "{found:?}");
+    assert!(
+        found[0].starts_with("origins: `*` is not allowed in the `prod` profile (from file "),
+        "{found:?}"
+    );
+}
+
+#[test]
+fn safe_values_pass_in_the_profile() {
+    let sandbox = Sandbox::new()
+        .env("CONFIG_PROFILE", "prod")
+        .env("PC_BIND", "10.0.0.5")
+        .env("PC_DEBUG", "false")
+        .env("PC_PUBLIC_URL", "https://app.example.com");
+    sandbox
+        .loader::<ServerConfig>()
+        .load()
+        .expect("prod config");
+}
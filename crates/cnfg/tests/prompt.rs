@@ -0,0 +1,143 @@
+use cnfg::{Cnfg, CnfgError, FieldSpec, LoaderExt, Origin, Prompter};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+/// Answers from a script, remembering what was asked.
+#[derive(Clone, Default)]
+struct Scripted {
+    answers: Arc<Mutex<VecDeque<&'static str>>>,
+    asked: Arc<Mutex<Vec<Asked>>>,
+}
+
+#[derive(Clone)]
+struct Asked {
+    question: String,
+    secret: bool,
+    error: Option<String>,
+}
+
+impl Scripted {
+    fn new(answers: &[&'static str]) -> Self {
+        let scripted = Self::default();
+        scripted.answers.lock().unwrap().extend(answers);
+        scripted
+    }
+
+    fn asked(&self) -> Vec<Asked> {
+        self.asked.lock().unwrap().clone()
+    }
+}
+
+impl Prompter for Scripted {
+    fn ask(&self, field: &FieldSpec, error: Option<&str>) -> io::Result<Option<String>> {
+        self.asked.lock().unwrap().push(Asked {
+            question: cnfg::prompt::question(field),
+            secret: field.secret,
+            error: error.map(str::to_string),
+        });
+        Ok(self.answers.lock().unwrap().pop_front().map(str::to_string))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct SetupConfig {
+    /// Public host name
+    #[cnfg(required, cli)]
+    host: String,
+
+    #[cnfg(required, cli)]
+    port: u16,
+
+    #[cnfg(required, secret, cli)]
+    token: String,
+
+    #[cnfg(default = 4, cli)]
+    workers: u32,
+}
+
+#[test]
+fn prompts_for_missing_required_fields() {
+    let prompter = Scripted::new(&["example.com", "8443", "s3cret"]);
+    let (cfg, report) = SetupConfig::loader()
+        .args(["app", "--workers", "8"])
+        .prompter(prompter.clone())
+        .load_with_report()
+        .expect("load");
+
+    assert_eq!(cfg.host, "example.com");
+    assert_eq!(cfg.port, 8443);
+    assert_eq!(cfg.token, "s3cret");
+    assert_eq!(cfg.workers, 8);
+    assert_eq!(report.provenance.get("port"), Some(&Origin::Prompt));
+
+    let asked = prompter.asked();
+    assert_eq!(asked.len(), 3);
+    assert_eq!(asked[0].question, "host (Public host name): ");
+    assert!(!asked[0].secret);
+    assert!(asked[2].secret, "token is masked");
+}
+
+#[test]
+fn skips_fields_already_set() {
+    let prompter = Scripted::new(&["s3cret"]);
+    let cfg = SetupConfig::loader()
+        .args(["app", "--host", "h", "--port", "1"])
+        .prompter(prompter.clone())
+        .load()
+        .expect("load");
+    assert_eq!(cfg.token, "s3cret");
+    assert_eq!(prompter.asked().len(), 1);
+}
+
+#[test]
+fn asks_again_after_an_invalid_answer() {
+    let prompter = Scripted::new(&["not-a-port", "", "80"]);
+    let cfg = SetupConfig::loader()
+        .args(["app", "--host", "h", "--token", "t"])
+        .prompter(prompter.clone())
+        .load()
+        .expect("load");
+    assert_eq!(cfg.port, 80);
+
+    let errors: Vec<_> = prompter
+        .asked()
+        .into_iter()
+        .map(|asked| asked.error)
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            None,
+            Some("expected an integer".to_string()),
+            Some("a value is required".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn unanswered_fields_are_still_reported() {
+    let prompter = Scripted::new(&["h"]);
+    let err = SetupConfig::loader()
+        .args(["app", "--token", "t"])
+        .prompter(prompter)
+        .load()
+        .expect_err("port stays missing");
+    let CnfgError::Validation(errs) = err else {
+        panic!("expected validation errors, got {err:?}");
+    };
+    let fields: Vec<_> = errs.into_iter().map(|issue| issue.field).collect();
+    assert_eq!(fields, vec!["port"]);
+}
+
+#[test]
+fn disabled_without_a_terminal() {
+    // Test harness stdin is not a terminal, so nothing is asked.
+    let err = SetupConfig::loader()
+        .args(["app"])
+        .interactive(true)
+        .load()
+        .expect_err("missing fields");
+    assert!(matches!(err, CnfgError::Validation(_)));
+}
@@ -0,0 +1,168 @@
+#![cfg(feature = "redis")]
+
+use cnfg::sources::redis::{RedisClient, RedisSource};
+use cnfg::{Cnfg, LoaderExt, Origin};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Default)]
+struct Store {
+    strings: BTreeMap<String, String>,
+    hashes: BTreeMap<String, BTreeMap<String, String>>,
+    /// Keys touched, in order, and how many a subscriber has seen.
+    events: Vec<String>,
+    seen: usize,
+    channels: Vec<String>,
+}
+
+#[derive(Clone, Default)]
+struct FakeRedis {
+    state: Arc<(Mutex<Store>, Condvar)>,
+}
+
+impl FakeRedis {
+    fn set(&self, key: &str, value: &str) {
+        let (lock, changed) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.strings.insert(key.into(), value.into());
+        state.events.push(key.into());
+        changed.notify_all();
+    }
+
+    fn hset(&self, key: &str, field: &str, value: &str) {
+        let (lock, changed) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        let hash = state.hashes.entry(key.into()).or_default();
+        hash.insert(field.into(), value.into());
+        state.events.push(key.into());
+        changed.notify_all();
+    }
+}
+
+impl RedisClient for FakeRedis {
+    fn hash_entries(&self, key: &str) -> Result<Vec<(String, String)>, String> {
+        let state = self.state.0.lock().unwrap();
+        let hash = state.hashes.get(key).cloned().unwrap_or_default();
+        Ok(hash.into_iter().collect())
+    }
+
+    fn scan_values(&self, pattern: &str) -> Result<Vec<(String, String)>, String> {
+        // Test prefixes contain no glob characters.
+        let prefix = pattern.strip_suffix('*').expect("prefix pattern");
+        let state = self.state.0.lock().unwrap();
+        Ok(state
+            .strings
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+
+    fn wait_for_notification(&self, channel: &str, timeout: Duration) -> Result<bool, String> {
+        let pattern = channel.strip_prefix("__keyspace@0__:").expect("db 0");
+        let matches = |key: &String| match pattern.strip_suffix('*') {
+            Some(prefix) => key.starts_with(prefix),
+            None => key == pattern,
+        };
+        let (lock, changed) = &*self.state;
+        let mut state = lock.lock().unwrap();
+        state.channels.push(channel.into());
+        let pending = |state: &Store| state.events[state.seen..].iter().any(matches);
+        let (mut state, _) = changed
+            .wait_timeout_while(state, timeout, |state| !pending(state))
+            .unwrap();
+        let notified = pending(&state);
+        state.seen = state.events.len();
+        Ok(notified)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Flags {
+    #[cnfg(default = false)]
+    beta: bool,
+
+    #[cnfg(default = 0)]
+    rollout: u8,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct RedisConfig {
+    #[cnfg(default = "app")]
+    name: String,
+
+    #[cnfg(nested)]
+    flags: Flags,
+}
+
+#[test]
+fn reads_a_key_prefix() {
+    let redis = FakeRedis::default();
+    redis.set("myapp:flags:beta", "yes");
+    redis.set("myapp:flags:rollout", "25");
+    redis.set("myapplication:name", "other");
+
+    let source = RedisSource::prefix(redis.clone(), "myapp:");
+    assert_eq!(
+        source.path_for("myapp:flags:beta").as_deref(),
+        Some("flags.beta")
+    );
+    assert_eq!(source.path_for("myapplication:name"), None);
+
+    let (cfg, report) = RedisConfig::loader()
+        .args(["app"])
+        .source(source)
+        .load_with_report()
+        .expect("load from redis");
+    assert!(cfg.flags.beta);
+    assert_eq!(cfg.flags.rollout, 25);
+    assert_eq!(cfg.name, "app");
+    assert_eq!(
+        report.provenance.get("flags.rollout"),
+        Some(&Origin::Source("redis:myapp:*".into()))
+    );
+}
+
+#[test]
+fn reads_a_hash() {
+    let redis = FakeRedis::default();
+    redis.hset("toggles", "flags.beta", "on");
+    redis.hset("toggles", "flags:rollout", "50");
+    redis.hset("toggles", "name", "svc");
+
+    let cfg = RedisConfig::loader()
+        .args(["app"])
+        .source(RedisSource::hash(redis, "toggles"))
+        .load()
+        .expect("load hash");
+    assert!(cfg.flags.beta);
+    assert_eq!(cfg.flags.rollout, 50);
+    assert_eq!(cfg.name, "svc");
+}
+
+#[test]
+fn watch_reloads_on_keyspace_notifications() {
+    let redis = FakeRedis::default();
+    redis.hset("toggles", "flags.rollout", "10");
+
+    let watcher = RedisConfig::loader()
+        .args(["app"])
+        .source(RedisSource::hash(redis.clone(), "toggles").reload_on_notifications(true))
+        .watch(Duration::from_secs(3600))
+        .expect("start watcher");
+    assert_eq!(watcher.current().flags.rollout, 10);
+
+    redis.set("unrelated", "1");
+    redis.hset("toggles", "flags.rollout", "90");
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while watcher.current().flags.rollout != 90 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(watcher.current().flags.rollout, 90);
+    watcher.stop();
+
+    let channels = redis.state.0.lock().unwrap().channels.clone();
+    assert_eq!(channels[0], "__keyspace@0__:toggles");
+}
@@ -0,0 +1,156 @@
+use cnfg::{
+    Cnfg, CnfgError, FieldSpec, LoaderExt, OnRefreshError, RefreshEvent, RefreshPolicy, Source,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct RemoteConfig {
+    #[cnfg(default = 0)]
+    version: usize,
+}
+
+/// Serves `version`, or fails while `down` is set.
+#[derive(Clone, Default)]
+struct Remote {
+    version: Arc<AtomicUsize>,
+    down: Arc<AtomicBool>,
+    calls: Arc<AtomicUsize>,
+}
+
+impl Source for Remote {
+    fn name(&self) -> String {
+        "config-service".into()
+    }
+
+    fn load(&self, _: &[FieldSpec]) -> Result<Value, CnfgError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.down.load(Ordering::SeqCst) {
+            return Err(CnfgError::Source("connection reset".into()));
+        }
+        Ok(json!({ "version": self.version.load(Ordering::SeqCst) }))
+    }
+}
+
+fn wait_until(what: &str, done: impl Fn() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !done() {
+        assert!(Instant::now() < deadline, "timed out waiting for {what}");
+        std::thread::sleep(Duration::from_millis(2));
+    }
+}
+
+#[test]
+fn reports_every_reload() {
+    let remote = Remote::default();
+    let watcher = RemoteConfig::loader()
+        .args(["app"])
+        .source(remote.clone())
+        .refresh(RefreshPolicy::new(Duration::from_millis(5)).jitter(Duration::from_millis(3)))
+        .expect("start refresh");
+    let events: Arc<Mutex<Vec<RefreshEvent>>> = Arc::default();
+    let seen = Arc::clone(&events);
+    watcher.on_refresh(move |event| seen.lock().expect("events").push(event.clone()));
+
+    remote.version.store(1, Ordering::SeqCst);
+    wait_until("the new version", || watcher.current().version == 1);
+    let stats = watcher.stats();
+    assert_eq!(stats.changes, 1);
+    assert!(stats.successes >= 1 && stats.last_success.is_some());
+
+    remote.down.store(true, Ordering::SeqCst);
+    wait_until("two failures", || watcher.stats().consecutive_failures >= 2);
+    assert_eq!(watcher.current().version, 1, "last good config kept");
+    assert!(
+        watcher
+            .last_error()
+            .is_some_and(|e| e.contains("connection reset"))
+    );
+
+    remote.down.store(false, Ordering::SeqCst);
+    wait_until("recovery", || watcher.stats().consecutive_failures == 0);
+    watcher.stop();
+
+    let events = events.lock().expect("events");
+    assert!(events.iter().any(|e| e.result == Ok(true)));
+    let failed: Vec<u32> = events
+        .iter()
+        .filter(|e| e.result.is_err())
+        .map(|e| e.consecutive_failures)
+        .collect();
+    assert_eq!(failed[..2], [1, 2]);
+}
+
+#[test]
+fn stops_after_a_failure_when_asked() {
+    let remote = Remote::default();
+    let watcher = RemoteConfig::loader()
+        .args(["app"])
+        .source(remote.clone())
+        .refresh(RefreshPolicy::new(Duration::from_millis(2)).on_error(OnRefreshError::Stop))
+        .expect("start refresh");
+    remote.down.store(true, Ordering::SeqCst);
+    wait_until("the failure", || watcher.stats().failures == 1);
+    let calls = remote.calls.load(Ordering::SeqCst);
+    remote.down.store(false, Ordering::SeqCst);
+    std::thread::sleep(Duration::from_millis(30));
+    assert_eq!(remote.calls.load(Ordering::SeqCst), calls);
+    assert_eq!(watcher.stats().failures, 1);
+}
+
+#[test]
+fn backs_off_while_failing() {
+    let remote = Remote::default();
+    let watcher = RemoteConfig::loader()
+        .args(["app"])
+        .source(remote.clone())
+        .refresh(
+            RefreshPolicy::new(Duration::from_millis(5))
+                .on_error(OnRefreshError::Backoff(Duration::from_secs(1))),
+        )
+        .expect("start refresh");
+    remote.down.store(true, Ordering::SeqCst);
+    wait_until("the first failure", || watcher.stats().failures >= 1);
+    // Waits of 10, 20, 40, 80, and 160ms fit at most five more failures.
+    std::thread::sleep(Duration::from_millis(200));
+    let failures = watcher.stats().failures;
+    assert!((1..=6).contains(&failures), "{failures} failures");
+}
+
+#[test]
+fn listeners_can_register_listeners() {
+    let remote = Remote::default();
+    let watcher = Arc::new(
+        RemoteConfig::loader()
+            .args(["app"])
+            .source(remote.clone())
+            .refresh(RefreshPolicy::new(Duration::from_millis(5)))
+            .expect("start refresh"),
+    );
+    let done = Arc::new(AtomicBool::new(false));
+    let added = Arc::new(AtomicUsize::new(0));
+    let (weak, stop, count) = (
+        Arc::downgrade(&watcher),
+        Arc::clone(&done),
+        Arc::clone(&added),
+    );
+    watcher.on_refresh(move |_| {
+        if stop.load(Ordering::SeqCst) {
+            return;
+        }
+        if let Some(watcher) = weak.upgrade() {
+            watcher.on_refresh(|_| {});
+            count.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+    wait_until("a listener added from a listener", || {
+        added.load(Ordering::SeqCst) >= 2
+    });
+
+    done.store(true, Ordering::SeqCst);
+    wait_until("listeners to let go", || Arc::strong_count(&watcher) == 1);
+    Arc::into_inner(watcher).expect("sole owner").stop();
+}
@@ -0,0 +1,179 @@
+#![cfg(feature = "registry")]
+
+use cnfg::sources::registry::{RegKey, RegValue, RegistryReader, RegistrySource};
+use cnfg::{Cnfg, CnfgError, LoaderExt, Origin};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+/// Keys by full path (case-sensitive, unlike the real registry).
+#[derive(Default)]
+struct FakeRegistry {
+    keys: BTreeMap<String, RegKey>,
+}
+
+impl FakeRegistry {
+    fn key(mut self, path: &str, values: &[(&str, RegValue)]) -> Self {
+        if let Some((parent, name)) = path.rsplit_once('\\') {
+            let parent = self.keys.entry(parent.into()).or_default();
+            if !parent.subkeys.iter().any(|key| key == name) {
+                parent.subkeys.push(name.into());
+            }
+        }
+        let key = self.keys.entry(path.into()).or_default();
+        key.values.extend(
+            values
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone())),
+        );
+        self
+    }
+}
+
+impl RegistryReader for FakeRegistry {
+    fn read_key(&self, path: &str) -> Result<Option<RegKey>, String> {
+        if path.ends_with("Denied") {
+            return Err("Access is denied. (os error 5)".into());
+        }
+        Ok(self.keys.get(path).cloned())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Server {
+    #[cnfg(default = "127.0.0.1")]
+    host: String,
+
+    #[cnfg(default = 8080)]
+    port: u16,
+
+    #[cnfg(default = false)]
+    tls: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct AgentConfig {
+    #[cnfg(default = 3, env = "REGISTRY_TEST_MAX_RETRIES")]
+    max_retries: u32,
+
+    #[cnfg(default = "info")]
+    log_level: String,
+
+    #[serde(default)]
+    allowed_hosts: Vec<String>,
+
+    #[cnfg(nested)]
+    server: Server,
+}
+
+const ROOT: &str = r"SOFTWARE\Policies\Acme\Agent";
+
+fn policy() -> FakeRegistry {
+    FakeRegistry::default()
+        .key(
+            ROOT,
+            &[
+                ("", RegValue::String("ignored default value".into())),
+                ("MaxRetries", RegValue::Dword(7)),
+                (
+                    "AllowedHosts",
+                    RegValue::MultiString(vec!["a.acme".into(), "b,c.acme".into()]),
+                ),
+            ],
+        )
+        .key(
+            &format!(r"{ROOT}\Server"),
+            &[
+                ("Port", RegValue::String("9443".into())),
+                ("TLS", RegValue::Dword(1)),
+                ("Banner", RegValue::String("managed".into())),
+            ],
+        )
+}
+
+#[test]
+fn maps_subkeys_and_values_onto_fields() {
+    let (cfg, report) = AgentConfig::loader()
+        .args(["app"])
+        .source(RegistrySource::new(policy(), "HKLM", ROOT))
+        .load_with_report()
+        .expect("load from registry");
+    assert_eq!(cfg.max_retries, 7);
+    assert_eq!(cfg.allowed_hosts, ["a.acme", "b,c.acme"]);
+    assert_eq!(cfg.server.port, 9443);
+    assert!(cfg.server.tls);
+    assert_eq!(cfg.server.host, "127.0.0.1");
+    assert_eq!(cfg.log_level, "info");
+    assert_eq!(
+        report.provenance.get("server.port"),
+        Some(&Origin::Source(format!("registry:HKLM\\{ROOT}")))
+    );
+    // Undeclared values keep a lower-cased name.
+    assert_eq!(report.document.get_str("server.banner"), Some("managed"));
+}
+
+#[test]
+fn sits_between_files_and_env() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.json");
+    std::fs::write(
+        &path,
+        r#"{"max_retries": 1, "log_level": "debug", "server": {"port": 1}}"#,
+    )
+    .expect("write config");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    unsafe { std::env::set_var("REGISTRY_TEST_MAX_RETRIES", "9") };
+    let cfg = AgentConfig::loader()
+        .args(["app"])
+        .source(RegistrySource::new(policy(), "HKLM", ROOT))
+        .load();
+    unsafe { std::env::remove_var("REGISTRY_TEST_MAX_RETRIES") };
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+
+    let cfg = cfg.expect("layered load");
+    assert_eq!(cfg.max_retries, 9);
+    assert_eq!(cfg.server.port, 9443);
+    assert_eq!(cfg.log_level, "debug");
+}
+
+#[test]
+fn missing_keys_are_empty_and_read_errors_fail() {
+    let cfg = AgentConfig::loader()
+        .args(["app"])
+        .source(RegistrySource::new(
+            policy(),
+            "HKLM",
+            r"SOFTWARE\Policies\Other",
+        ))
+        .load()
+        .expect("no policy pushed");
+    assert_eq!(cfg.max_retries, 3);
+
+    let denied = policy().key(&format!(r"{ROOT}\Denied"), &[]);
+    let err = AgentConfig::loader()
+        .args(["app"])
+        .source(RegistrySource::new(denied, "HKLM", format!(r"\{ROOT}\")))
+        .load()
+        .expect_err("unreadable subkey");
+    let CnfgError::Source(message) = err else {
+        panic!("expected a source error, got {err:?}");
+    };
+    assert_eq!(
+        message,
+        format!("registry:HKLM\\{ROOT}: Access is denied. (os error 5)")
+    );
+
+    let bad = policy().key(
+        &format!(r"{ROOT}\Server"),
+        &[("Port", RegValue::Qword(1 << 40))],
+    );
+    let err = AgentConfig::loader()
+        .args(["app"])
+        .source(RegistrySource::new(bad, "HKLM", ROOT))
+        .load()
+        .expect_err("port out of range");
+    assert!(err.to_string().contains("server.port"), "{err}");
+}
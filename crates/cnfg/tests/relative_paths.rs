@@ -0,0 +1,75 @@
+use cnfg::{Cnfg, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Tls {
+    #[cnfg(relative_to_config)]
+    cert: PathBuf,
+
+    #[cnfg(relative_to_config)]
+    key: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct AnchoredConfig {
+    #[cnfg(nested)]
+    tls: Tls,
+
+    #[cnfg(default = "data", env = "ANCHORED_DATA_DIR", relative_to_config)]
+    data_dir: PathBuf,
+
+    #[cnfg(default = "logs")]
+    log_dir: PathBuf,
+}
+
+#[test]
+fn file_values_resolve_against_their_file() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let conf = dir.path().join("conf");
+    std::fs::create_dir(&conf).expect("mkdir");
+    let base = conf.join("base.toml");
+    std::fs::write(
+        &base,
+        "log_dir = \"var/log\"\n[tls]\ncert = \"tls/cert.pem\"\n",
+    )
+    .expect("write base");
+    let overlay = dir.path().join("overlay.toml");
+    std::fs::write(&overlay, "[tls]\nkey = \"/etc/tls/key.pem\"\n").expect("write overlay");
+
+    let cfg = AnchoredConfig::loader()
+        .args([
+            "app".to_string(),
+            format!("--config={}", base.display()),
+            format!("--config={}", overlay.display()),
+        ])
+        .load()
+        .expect("config");
+    assert_eq!(cfg.tls.cert, conf.join("tls/cert.pem"));
+    // Absolute paths, defaults, and unmarked fields are left alone.
+    assert_eq!(cfg.tls.key.as_deref(), Some("/etc/tls/key.pem"));
+    assert_eq!(cfg.data_dir, PathBuf::from("data"));
+    assert_eq!(cfg.log_dir, PathBuf::from("var/log"));
+}
+
+#[test]
+fn env_values_stay_relative_to_the_working_directory() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let file = dir.path().join("config.toml");
+    std::fs::write(&file, "data_dir = \"state\"\n[tls]\ncert = \"c.pem\"\n").expect("write");
+
+    unsafe { std::env::set_var("ANCHORED_DATA_DIR", "env-state") };
+    let result = AnchoredConfig::loader()
+        .args(["app".to_string(), format!("--config={}", file.display())])
+        .load();
+    unsafe { std::env::remove_var("ANCHORED_DATA_DIR") };
+
+    let cfg = result.expect("config");
+    assert_eq!(cfg.data_dir, PathBuf::from("env-state"));
+    assert_eq!(cfg.tls.cert, dir.path().join("c.pem"));
+}
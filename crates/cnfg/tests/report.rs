@@ -0,0 +1,131 @@
+use cnfg::{Cnfg, LoaderExt, WarningKind};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "localhost")]
+    host: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct ReportConfig {
+    #[cnfg(default = 8080, env = "REPORT_PORT")]
+    port: u16,
+
+    #[cnfg(default = "app", env = "REPORT_NAME")]
+    name: String,
+
+    #[serde(default)]
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[test]
+fn reports_unknown_keys_and_empty_env() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.json");
+    std::fs::write(
+        &path,
+        r#"{ "port": 9000, "prot": 1, "database": { "host": "db", "hots": "x" } }"#,
+    )
+    .expect("write json");
+
+    unsafe {
+        std::env::set_var("CONFIG_FILE", &path);
+        std::env::set_var("REPORT_NAME", "");
+    }
+    let (cfg, report) = ReportConfig::loader()
+        .args(["app"])
+        .load_with_report()
+        .expect("load with report");
+    assert_eq!(cfg.port, 9000);
+    assert_eq!(cfg.name, "", "empty env var still applies");
+
+    let unknown: Vec<&str> = report
+        .warnings
+        .iter()
+        .filter(|w| w.kind == WarningKind::UnknownKey)
+        .map(|w| w.subject.as_str())
+        .collect();
+    assert_eq!(unknown, ["database.hots", "prot"]);
+    assert!(
+        report
+            .warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::EmptyEnv && w.subject == "REPORT_NAME")
+    );
+    assert!(
+        report
+            .warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::FileDiscovery)
+    );
+
+    unsafe {
+        std::env::remove_var("CONFIG_FILE");
+        std::env::remove_var("REPORT_NAME");
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(env_prefix = "UNUSEDENV_")]
+struct PrefixedConfig {
+    #[cnfg(default = 8080, env = "UNUSEDENV_DATABASE_PORT")]
+    database_port: u16,
+
+    #[cnfg(default = "", env = "UNUSEDENV_TOKEN", allow_file)]
+    token: String,
+}
+
+#[test]
+fn reports_unused_env_vars_under_the_prefix() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let vars = [
+        ("UNUSEDENV_DATABSE_PORT", "5432"),
+        ("UNUSEDENV_TOKEN_FILE", "/dev/null"),
+        ("UNUSEDENV_UNRELATED", "1"),
+        ("UNUSEDENVX", "1"),
+    ];
+    unsafe {
+        for (name, value) in vars {
+            std::env::set_var(name, value);
+        }
+    }
+    let result = PrefixedConfig::loader().args(["app"]).load_with_report();
+    let unprefixed = ReportConfig::loader().args(["app"]).load_with_report();
+    unsafe {
+        for (name, _) in vars {
+            std::env::remove_var(name);
+        }
+    }
+
+    let (_, report) = result.expect("load with report");
+    let unused: Vec<(&str, &str)> = report
+        .warnings
+        .iter()
+        .filter(|w| w.kind == WarningKind::UnusedEnv)
+        .map(|w| (w.subject.as_str(), w.message.as_str()))
+        .collect();
+    assert_eq!(
+        unused,
+        [
+            (
+                "UNUSEDENV_DATABSE_PORT",
+                "set but unused; did you mean `UNUSEDENV_DATABASE_PORT`?"
+            ),
+            ("UNUSEDENV_UNRELATED", "set but unused"),
+        ]
+    );
+
+    let (_, report) = unprefixed.expect("load with report");
+    assert!(
+        !report
+            .warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::UnusedEnv)
+    );
+}
@@ -0,0 +1,90 @@
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, CnfgError, ConfigMeta, FieldDef, Kind, SchemaBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Tls {
+    #[cnfg(default = false, env = "RI_TLS")]
+    enabled: bool,
+
+    #[cnfg(env = "RI_CERT", required_if = "enabled")]
+    cert_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct ServerConfig {
+    #[cnfg(nested)]
+    tls: Tls,
+
+    #[cnfg(env = "RI_SOCKET")]
+    socket: Option<String>,
+
+    /// Either a socket or a port.
+    #[cnfg(env = "RI_PORT", required_unless = "socket")]
+    port: Option<u16>,
+}
+
+fn messages(err: CnfgError) -> Vec<String> {
+    let CnfgError::Validation(errs) = err else {
+        panic!("expected validation error, got {err:?}");
+    };
+    errs.iter()
+        .map(|issue| format!("{}: {}", issue.field, issue.message))
+        .collect()
+}
+
+#[test]
+fn conditions_are_prefixed_for_nested_fields() {
+    let spec = ServerConfig::field_specs()
+        .iter()
+        .find(|spec| spec.path == "tls.cert_path")
+        .expect("cert_path");
+    assert_eq!(spec.required_if, Some("tls.enabled"));
+    assert!(!spec.required);
+}
+
+#[test]
+fn required_if_follows_the_flag() {
+    let sandbox = Sandbox::new().env("RI_PORT", "8443");
+    let cfg = sandbox.loader::<ServerConfig>().load().expect("tls off");
+    assert_eq!(cfg.tls.cert_path, None);
+
+    let sandbox = sandbox.env("RI_TLS", "true");
+    let err = sandbox.loader::<ServerConfig>().load().unwrap_err();
+    assert_eq!(
+        messages(err),
+        ["tls.cert_path: required when `tls.enabled` is set"]
+    );
+
+    let sandbox = sandbox.env("RI_CERT", "/etc/tls/cert.pem");
+    let cfg = sandbox.loader::<ServerConfig>().load().expect("tls on");
+    assert_eq!(cfg.tls.cert_path, Some(PathBuf::from("/etc/tls/cert.pem")));
+}
+
+#[test]
+fn required_unless_another_field_is_set() {
+    let sandbox = Sandbox::new();
+    let err = sandbox.loader::<ServerConfig>().load().unwrap_err();
+    assert_eq!(messages(err), ["port: required unless `socket` is set"]);
+
+    let sandbox = sandbox.env("RI_SOCKET", "/run/app.sock");
+    let cfg = sandbox.loader::<ServerConfig>().load().expect("socket");
+    assert_eq!(cfg.port, None);
+}
+
+#[test]
+fn dynamic_schemas_take_conditions() {
+    let schema = SchemaBuilder::new()
+        .field(FieldDef::new("auth.mode", Kind::String).env("RI_AUTH_MODE"))
+        .field(FieldDef::new("auth.token", Kind::String).required_if("auth.mode"))
+        .build()
+        .expect("valid schema");
+    let _sandbox = Sandbox::new().env("RI_AUTH_MODE", "bearer");
+    let err = schema.loader::<Value>().args(["app"]).load().unwrap_err();
+    assert_eq!(
+        messages(err),
+        ["auth.token: required when `auth.mode` is set"]
+    );
+}
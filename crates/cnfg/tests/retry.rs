@@ -0,0 +1,212 @@
+use cnfg::{Cnfg, CnfgError, FieldSpec, LoaderExt, RetryPolicy, Source, WarningKind};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct RemoteConfig {
+    #[cnfg(default = "local")]
+    region: String,
+}
+
+/// Fails its first `failures` fetches, or every fetch while `down` is set.
+#[derive(Clone, Default)]
+struct Flaky {
+    failures: usize,
+    calls: Arc<AtomicUsize>,
+    down: Arc<AtomicBool>,
+}
+
+impl Source for Flaky {
+    fn name(&self) -> String {
+        "config-service".into()
+    }
+
+    fn load(&self, _: &[FieldSpec]) -> Result<Value, CnfgError> {
+        let call = self.calls.fetch_add(1, Ordering::SeqCst);
+        if call < self.failures || self.down.load(Ordering::SeqCst) {
+            return Err(CnfgError::Source("connection reset".into()));
+        }
+        Ok(json!({"region": "eu-west-1"}))
+    }
+}
+
+fn quick(attempts: u32) -> RetryPolicy {
+    RetryPolicy::new(attempts).backoff(Duration::from_millis(1), Duration::from_millis(4))
+}
+
+#[test]
+fn retries_until_the_source_answers() {
+    let source = Flaky {
+        failures: 2,
+        ..Flaky::default()
+    };
+    let cfg = RemoteConfig::loader()
+        .args(["app"])
+        .source(source.clone())
+        .retry(quick(3))
+        .load()
+        .expect("third attempt succeeds");
+    assert_eq!(cfg.region, "eu-west-1");
+    assert_eq!(source.calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn gives_up_after_max_attempts_or_deadline() {
+    let source = Flaky {
+        failures: usize::MAX,
+        ..Flaky::default()
+    };
+    let err = RemoteConfig::loader()
+        .args(["app"])
+        .source(source.clone())
+        .retry(quick(3))
+        .load()
+        .expect_err("source stays down");
+    assert_eq!(
+        err.to_string(),
+        "Source error: config-service: gave up after 3 attempts: Source error: connection reset"
+    );
+    assert_eq!(source.calls.load(Ordering::SeqCst), 3);
+
+    let source = Flaky {
+        failures: usize::MAX,
+        ..Flaky::default()
+    };
+    let policy = RetryPolicy::new(100)
+        .backoff(Duration::from_millis(20), Duration::from_millis(20))
+        .deadline(Duration::from_millis(50));
+    RemoteConfig::loader()
+        .args(["app"])
+        .source(source.clone())
+        .retry(policy)
+        .load()
+        .expect_err("deadline passes");
+    assert!(source.calls.load(Ordering::SeqCst) <= 3);
+
+    let source = Flaky {
+        failures: 1,
+        ..Flaky::default()
+    };
+    let err = RemoteConfig::loader()
+        .args(["app"])
+        .source(source)
+        .load()
+        .expect_err("no retries by default");
+    assert_eq!(err.to_string(), "Source error: connection reset");
+}
+
+#[test]
+fn fails_open_with_the_last_value_fetched() {
+    let source = Flaky::default();
+    let loader = RemoteConfig::loader()
+        .args(["app"])
+        .source(source.clone())
+        .retry(quick(2).fail_open(true));
+    assert_eq!(loader.clone().load().expect("healthy").region, "eu-west-1");
+
+    source.down.store(true, Ordering::SeqCst);
+    let (cfg, report) = loader.load_with_report().expect("stale value");
+    assert_eq!(cfg.region, "eu-west-1");
+    let warning = report
+        .warnings
+        .iter()
+        .find(|w| w.kind == WarningKind::StaleSource)
+        .expect("stale warning");
+    assert_eq!(warning.subject, "config-service");
+    assert!(
+        warning.message.ends_with("; using the last value fetched"),
+        "{}",
+        warning.message
+    );
+}
+
+#[test]
+fn fails_open_from_the_cache_dir_after_a_restart() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let policy = || quick(1).fail_open(true).cache_dir(dir.path());
+    RemoteConfig::loader()
+        .args(["app"])
+        .source(Flaky::default())
+        .retry(policy())
+        .load()
+        .expect("healthy");
+    assert!(dir.path().join("config_service.json").is_file());
+
+    let down = Flaky::default();
+    down.down.store(true, Ordering::SeqCst);
+    let (cfg, report) = RemoteConfig::loader()
+        .args(["app"])
+        .source(down.clone())
+        .retry(policy())
+        .load_with_report()
+        .expect("cached value");
+    assert_eq!(cfg.region, "eu-west-1");
+    assert!(
+        report
+            .warnings
+            .iter()
+            .any(|w| w.kind == WarningKind::StaleSource && w.message.contains("cached in"))
+    );
+
+    let empty = tempfile::tempdir().expect("tempdir");
+    RemoteConfig::loader()
+        .args(["app"])
+        .source(down)
+        .retry(quick(1).fail_open(true).cache_dir(empty.path()))
+        .load()
+        .expect_err("nothing to fall back to");
+}
+
+#[cfg(feature = "async")]
+mod async_sources {
+    use super::*;
+    use cnfg::AsyncSource;
+    use cnfg::sources::BoxFuture;
+
+    struct Slow {
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl AsyncSource for Slow {
+        fn name(&self) -> String {
+            "slow".into()
+        }
+
+        fn load<'a>(&'a self, _: &'a [FieldSpec]) -> BoxFuture<'a, Result<Value, CnfgError>> {
+            Box::pin(async move {
+                if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    tokio::time::sleep(Duration::from_secs(10)).await;
+                }
+                Ok(json!({"region": "us-east-2"}))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn deadline_cancels_a_hanging_fetch() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let err = RemoteConfig::loader()
+            .args(["app"])
+            .async_source(Slow {
+                calls: Arc::clone(&calls),
+            })
+            .retry(quick(3).deadline(Duration::from_millis(50)))
+            .load_async()
+            .await
+            .expect_err("times out");
+        assert_eq!(err.to_string(), "Source error: slow: timed out after 50ms");
+
+        calls.store(1, Ordering::SeqCst);
+        let cfg = RemoteConfig::loader()
+            .args(["app"])
+            .async_source(Slow { calls })
+            .retry(quick(3).deadline(Duration::from_millis(50)))
+            .load_async()
+            .await
+            .expect("answers in time");
+        assert_eq!(cfg.region, "us-east-2");
+    }
+}
@@ -0,0 +1,34 @@
+use cnfg::run::{ERROR_EXIT_CODE, handle};
+use cnfg::{Cnfg, CnfgError, LoaderExt};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct RunConfig {
+    #[cnfg(default = 8080, cli)]
+    port: u16,
+}
+
+#[test]
+fn run_hands_the_config_to_the_closure() {
+    let port = RunConfig::loader()
+        .args(["app", "--port", "9000"])
+        .exit_code(2)
+        .run(|cfg| cfg.port);
+    assert_eq!(port, 9000);
+}
+
+#[test]
+fn early_exits_map_to_exit_codes() {
+    assert_eq!(handle(&CnfgError::HelpPrinted, 2), 0);
+    assert_eq!(handle(&CnfgError::VersionPrinted, 2), 0);
+    assert_eq!(handle(&CnfgError::CheckPassed, 2), 0);
+    assert_eq!(handle(&CnfgError::Initialized("app.toml".into()), 2), 0);
+    assert_eq!(handle(&CnfgError::CheckFailed, 2), 2);
+
+    let err = RunConfig::loader()
+        .args(["app", "--port", "high"])
+        .load()
+        .expect_err("bad port");
+    assert_eq!(handle(&err, ERROR_EXIT_CODE), 1);
+    assert_eq!(handle(&err, 78), 78);
+}
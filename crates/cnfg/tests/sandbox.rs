@@ -0,0 +1,46 @@
+use cnfg::test::Sandbox;
+use cnfg::{Cnfg, Origin};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(app_name = "sandboxed")]
+struct SandboxConfig {
+    #[cnfg(default = "unset", env = "SANDBOX_NAME")]
+    name: String,
+
+    #[cnfg(default = 1, cli)]
+    workers: u32,
+}
+
+#[test]
+fn env_args_and_config_dir_are_scoped() {
+    let before = std::env::var_os("XDG_CONFIG_HOME");
+    {
+        let sandbox =
+            Sandbox::new()
+                .env("SANDBOX_NAME", "from-env")
+                .args(["app", "--workers", "3"]);
+        let file = sandbox.config_file("sandboxed", "config.toml", "workers = 2\n");
+        assert!(file.starts_with(sandbox.path()));
+
+        let (cfg, report) = sandbox
+            .loader::<SandboxConfig>()
+            .load_with_report()
+            .expect("config");
+        assert_eq!(cfg.name, "from-env");
+        assert_eq!(cfg.workers, 3);
+        assert_eq!(
+            report.provenance.get("workers"),
+            Some(&Origin::Cli("--workers".into()))
+        );
+
+        let cfg = sandbox
+            .loader::<SandboxConfig>()
+            .args(["app"])
+            .load()
+            .expect("config");
+        assert_eq!(cfg.workers, 2, "the user config dir is inside the sandbox");
+    }
+    assert!(std::env::var_os("SANDBOX_NAME").is_none());
+    assert_eq!(std::env::var_os("XDG_CONFIG_HOME"), before);
+}
@@ -0,0 +1,132 @@
+use cnfg::{Cnfg, CnfgError, Format, LoaderExt, SaveOptions};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    /// Connection string.
+    #[cnfg(default = "postgres://localhost/app")]
+    url: String,
+
+    #[cnfg(default = 5)]
+    pool: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct SaveConfig {
+    /// Service name.
+    #[cnfg(default = "svc")]
+    name: String,
+
+    /// Listening port.
+    #[cnfg(default = 8080, validate(range(min = "1")))]
+    port: u16,
+
+    #[serde(default)]
+    tags: Vec<String>,
+
+    api_key: Option<String>,
+
+    /// Database settings.
+    #[cnfg(nested)]
+    database: Database,
+}
+
+fn sample() -> SaveConfig {
+    SaveConfig {
+        name: "billing".into(),
+        port: 9000,
+        tags: vec!["a".into(), "b".into()],
+        api_key: None,
+        database: Database {
+            url: "postgres://db/billing".into(),
+            pool: 10,
+        },
+    }
+}
+
+#[test]
+fn toml_follows_declaration_order() {
+    let rendered = sample().render_as(Format::Toml).expect("render toml");
+    assert_eq!(
+        rendered,
+        "name = \"billing\"\nport = 9000\ntags = [\"a\", \"b\"]\n\n[database]\nurl = \"postgres://db/billing\"\npool = 10\n"
+    );
+}
+
+#[test]
+fn docs_become_comments() {
+    let options = SaveOptions::new(Format::Toml).docs(true);
+    let rendered = sample().render_as(options).expect("render toml");
+    assert!(
+        rendered.starts_with("# Service name.\nname = "),
+        "{rendered}"
+    );
+    assert!(rendered.contains("# Database settings.\n[database]\n# Connection string.\nurl"));
+
+    let yaml = sample()
+        .render_as(SaveOptions::new(Format::Yaml).docs(true))
+        .expect("render yaml");
+    assert!(
+        yaml.starts_with("# Service name.\nname: billing\n# Listening port.\nport: 9000\ntags:\n  - a\n  - b\napi_key: null\n"),
+        "{yaml}"
+    );
+    assert!(
+        yaml.contains(
+            "database:\n  # Connection string.\n  url: postgres://db/billing\n  pool: 10\n"
+        )
+    );
+}
+
+#[test]
+fn json_is_ordered_and_parseable() {
+    let rendered = sample().render_as(Format::Json).expect("render json");
+    assert!(rendered.starts_with("{\n  \"name\": \"billing\",\n  \"port\": 9000,\n"));
+    let parsed: serde_json::Value = serde_json::from_str(&rendered).expect("valid json");
+    assert_eq!(parsed["database"]["pool"], 10);
+}
+
+#[test]
+fn saved_files_load_back() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    for name in ["config.toml", "config.yaml", "config.json"] {
+        let path = dir.path().join(name);
+        let format = Format::from_path(&path).expect("known extension");
+        sample().save_to(&path, format).expect("save");
+
+        unsafe { std::env::set_var("CONFIG_FILE", &path) };
+        let loaded = SaveConfig::loader()
+            .args(["app"])
+            .load()
+            .expect("load back");
+        unsafe { std::env::remove_var("CONFIG_FILE") };
+        assert_eq!(loaded.name, "billing", "{name}");
+        assert_eq!(loaded.tags, ["a", "b"], "{name}");
+        assert_eq!(loaded.database.pool, 10, "{name}");
+        assert_eq!(loaded.api_key, None, "{name}");
+    }
+}
+
+#[test]
+fn invalid_configs_are_not_written() {
+    let mut cfg = sample();
+    cfg.port = 0;
+    assert!(matches!(
+        cfg.render_as(Format::Toml),
+        Err(CnfgError::Validation(_))
+    ));
+}
+
+#[test]
+fn write_errors_name_the_file() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("missing/config.toml");
+    let err = sample()
+        .save_to(&path, Format::Toml)
+        .expect_err("no such directory");
+    assert_eq!(err.path(), Some(path.as_path()));
+    assert!(matches!(err.root(), CnfgError::Io(_)));
+}
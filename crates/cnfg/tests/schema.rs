@@ -0,0 +1,164 @@
+use cnfg::schema::{Field, Node, Section, SectionKind, Visitor};
+use cnfg::{Cnfg, ConfigMeta, Schema};
+use serde::{Deserialize, Serialize};
+
+/// Connection pool limits.
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Pool {
+    #[cnfg(default = 10, cli)]
+    max_size: u32,
+}
+
+/// Primary database.
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Database {
+    /// Connection string
+    #[cnfg(default = "postgres://localhost/app", env = "SCHEMA_DATABASE_URL")]
+    url: String,
+
+    #[serde(default)]
+    #[cnfg(nested)]
+    pool: Pool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Upstream {
+    #[cnfg(required)]
+    host: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Migrate {
+    #[cnfg(cli)]
+    dry_run: bool,
+}
+
+/// Service settings.
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct AppConfig {
+    #[cnfg(default = 8080, cli)]
+    port: u16,
+
+    /// Where data lives
+    #[serde(default)]
+    #[cnfg(nested)]
+    database: Database,
+
+    #[cnfg(nested)]
+    upstreams: Vec<Upstream>,
+
+    #[cnfg(command)]
+    migrate: Option<Migrate>,
+}
+
+fn section<'a>(schema: &'a Schema, path: &str) -> &'a Section {
+    match schema.find(path) {
+        Some(Node::Section(section)) => section,
+        other => panic!("expected a section at {path}, got {other:?}"),
+    }
+}
+
+#[test]
+fn keeps_nesting_and_docs() {
+    let schema = AppConfig::schema();
+    assert_eq!(schema.doc, Some("Service settings."));
+    assert_eq!(schema.nodes.len(), 4);
+
+    let database = section(&schema, "database");
+    assert_eq!(database.kind, SectionKind::Nested);
+    assert_eq!(database.spec.doc, Some("Where data lives"));
+    assert_eq!(database.schema.doc, Some("Primary database."));
+
+    let pool = section(&schema, "database.pool");
+    assert_eq!(pool.schema.doc, Some("Connection pool limits."));
+    let Some(Node::Field(max_size)) = schema.find("database.pool.max_size") else {
+        panic!("missing database.pool.max_size");
+    };
+    assert_eq!(max_size.spec.default, Some("10"));
+    assert_eq!(
+        max_size.cli.as_ref().map(|cli| cli.flag),
+        Some("database-pool-max-size")
+    );
+
+    let Some(Node::Field(url)) = schema.find("database.url") else {
+        panic!("missing database.url");
+    };
+    assert_eq!(url.spec.env, Some("SCHEMA_DATABASE_URL"));
+    assert!(url.cli.is_none());
+}
+
+#[test]
+fn lists_and_commands_are_relative() {
+    let schema = AppConfig::schema();
+
+    let upstreams = section(&schema, "upstreams");
+    assert_eq!(upstreams.kind, SectionKind::List);
+    let Node::Field(host) = &upstreams.schema.nodes[0] else {
+        panic!("expected a field");
+    };
+    assert_eq!(host.spec.path, "host");
+    assert!(host.spec.required);
+
+    let migrate = section(&schema, "migrate");
+    assert_eq!(migrate.kind, SectionKind::Command("migrate"));
+    let Node::Field(dry_run) = &migrate.schema.nodes[0] else {
+        panic!("expected a field");
+    };
+    assert_eq!(dry_run.cli.as_ref().map(|cli| cli.flag), Some("dry-run"));
+    assert!(schema.find("migrate.dry_run").is_none());
+}
+
+#[derive(Default)]
+struct Outline {
+    depth: usize,
+    lines: Vec<String>,
+}
+
+impl Visitor for Outline {
+    fn visit_field(&mut self, field: &Field) {
+        let indent = "  ".repeat(self.depth);
+        self.lines.push(format!("{indent}{}", field.spec.name));
+    }
+
+    fn enter_section(&mut self, section: &Section) {
+        let indent = "  ".repeat(self.depth);
+        self.lines.push(format!("{indent}{}:", section.spec.name));
+        self.depth += 1;
+    }
+
+    fn leave_section(&mut self, _: &Section) {
+        self.depth -= 1;
+    }
+}
+
+#[test]
+fn visitor_walks_in_declaration_order() {
+    let mut outline = Outline::default();
+    AppConfig::schema().visit(&mut outline);
+    assert_eq!(
+        outline.lines,
+        [
+            "port",
+            "database:",
+            "  url",
+            "  pool:",
+            "    max_size",
+            "upstreams:",
+            "  host",
+            "migrate:",
+            "  dry_run",
+        ]
+    );
+}
+
+#[test]
+fn flat_schema_lists_every_field() {
+    let schema = Schema::flat::<AppConfig>();
+    assert!(
+        schema
+            .nodes
+            .iter()
+            .all(|node| matches!(node, Node::Field(_)))
+    );
+    assert_eq!(schema.nodes.len(), AppConfig::field_specs().len());
+}
@@ -0,0 +1,172 @@
+use cnfg::error::IssueKind;
+use cnfg::{CnfgError, DynamicSchema, FieldDef, Kind, SchemaBuilder};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+fn plugin_schema() -> DynamicSchema {
+    SchemaBuilder::new()
+        .doc("Plugin host")
+        .field(
+            FieldDef::new("server.port", Kind::Int)
+                .doc("Port to listen on")
+                .default(8080)
+                .env("SCHEMA_BUILDER_PORT")
+                .cli()
+                .validate(|value| match value.as_u64() {
+                    Some(port) if port >= 1024 => Ok(()),
+                    _ => Err("must be an unprivileged port".into()),
+                }),
+        )
+        .field(FieldDef::new("server.host", Kind::String).default("127.0.0.1"))
+        .field(
+            FieldDef::new("name", Kind::String)
+                .required()
+                .flag("--plugin-name"),
+        )
+        .field(FieldDef::new("tags", Kind::String).list().cli())
+        .build()
+        .expect("valid schema")
+}
+
+#[test]
+fn loads_defaults_and_flags_into_a_value() {
+    let cfg: Value = plugin_schema()
+        .loader()
+        .args([
+            "host",
+            "--plugin-name",
+            "resize",
+            "--tags",
+            "a",
+            "--tags",
+            "b",
+        ])
+        .load()
+        .expect("load");
+    assert_eq!(
+        cfg,
+        json!({
+            "server": { "port": 8080, "host": "127.0.0.1" },
+            "name": "resize",
+            "tags": ["a", "b"],
+        })
+    );
+}
+
+#[test]
+fn env_and_files_layer_like_derived_configs() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let mut file = tempfile::Builder::new()
+        .suffix(".json")
+        .tempfile()
+        .expect("tempfile");
+    write!(
+        file,
+        r#"{{"server": {{"host": "0.0.0.0"}}, "name": "from-file"}}"#
+    )
+    .expect("write");
+    unsafe {
+        std::env::set_var("CONFIG_FILE", file.path());
+        std::env::set_var("SCHEMA_BUILDER_PORT", "9000");
+    }
+    let result = plugin_schema()
+        .loader::<HashMap<String, Value>>()
+        .args(["host"])
+        .load_with_report();
+    unsafe {
+        std::env::remove_var("CONFIG_FILE");
+        std::env::remove_var("SCHEMA_BUILDER_PORT");
+    }
+
+    let (cfg, report) = result.expect("load");
+    assert_eq!(cfg["name"], "from-file");
+    assert_eq!(cfg["server"], json!({ "port": 9000, "host": "0.0.0.0" }));
+    assert_eq!(
+        report.provenance.describe("server.port").as_deref(),
+        Some("env SCHEMA_BUILDER_PORT")
+    );
+}
+
+#[test]
+fn reports_missing_bad_and_rejected_values_together() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe { std::env::set_var("SCHEMA_BUILDER_PORT", "eighty") };
+    let result = plugin_schema().loader::<Value>().args(["host"]).load();
+    unsafe { std::env::remove_var("SCHEMA_BUILDER_PORT") };
+
+    let Err(CnfgError::Validation(errs)) = result else {
+        panic!("expected validation errors, got {result:?}");
+    };
+    let fields: Vec<String> = errs.into_iter().map(|issue| issue.field).collect();
+    assert_eq!(fields, ["SCHEMA_BUILDER_PORT", "name"]);
+
+    let err = plugin_schema()
+        .loader::<Value>()
+        .args(["host", "--plugin-name", "x", "--server-port", "80"])
+        .load()
+        .expect_err("validator rejects the port");
+    let CnfgError::Validation(errs) = err else {
+        panic!("expected validation errors, got {err:?}");
+    };
+    let issue = errs.iter().next().expect("one issue");
+    assert_eq!(issue.field, "server.port");
+    assert!(matches!(issue.kind, IssueKind::Custom));
+    assert_eq!(
+        issue.message,
+        "must be an unprivileged port (from flag --server-port)"
+    );
+}
+
+#[test]
+fn help_lists_registered_flags() {
+    let help = plugin_schema().help();
+    assert!(help.starts_with("Plugin host"), "{help}");
+    assert!(help.contains("--server-port <INT>"), "{help}");
+    assert!(help.contains("Port to listen on [default: 8080]"), "{help}");
+    assert!(help.contains("--plugin-name <STRING>"), "{help}");
+    assert!(help.contains("(required)"), "{help}");
+}
+
+#[test]
+fn build_rejects_clashing_fields() {
+    let cases = [
+        (
+            SchemaBuilder::new()
+                .field(FieldDef::new("port", Kind::Int))
+                .field(FieldDef::new("port", Kind::String)),
+            "field `port` is declared twice",
+        ),
+        (
+            SchemaBuilder::new()
+                .field(FieldDef::new("db", Kind::String))
+                .field(FieldDef::new("db.url", Kind::String)),
+            "field `db.url` is nested under the value `db`",
+        ),
+        (
+            SchemaBuilder::new()
+                .field(FieldDef::new("a", Kind::Int).env("PORT"))
+                .field(FieldDef::new("b", Kind::Int).env("PORT")),
+            "env var `PORT` is bound by two fields",
+        ),
+        (
+            SchemaBuilder::new()
+                .field(FieldDef::new("db_url", Kind::String).cli())
+                .field(FieldDef::new("db.url", Kind::String).cli()),
+            "flag `--db-url` is bound by two fields",
+        ),
+        (
+            SchemaBuilder::new().field(FieldDef::new("db..url", Kind::String)),
+            "invalid field path `db..url`",
+        ),
+    ];
+    for (builder, expected) in cases {
+        match builder.build() {
+            Err(CnfgError::Schema(msg)) => assert_eq!(msg, expected),
+            other => panic!("expected a schema error, got {other:?}"),
+        }
+    }
+}
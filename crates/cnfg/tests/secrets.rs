@@ -0,0 +1,323 @@
+use cnfg::error::IssueKind;
+use cnfg::sources::secrets::SecretResolver;
+use cnfg::{Cnfg, CnfgError, LoaderExt, Origin};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+/// Serves `vault/<name>` references from a map, counting lookups.
+#[derive(Clone, Default)]
+struct Vault {
+    secrets: Arc<HashMap<&'static str, &'static str>>,
+    calls: Arc<AtomicUsize>,
+}
+
+impl Vault {
+    fn new(secrets: &[(&'static str, &'static str)]) -> Self {
+        Self {
+            secrets: Arc::new(secrets.iter().copied().collect()),
+            calls: Arc::default(),
+        }
+    }
+}
+
+impl SecretResolver for Vault {
+    fn handles(&self, reference: &str) -> bool {
+        reference.starts_with("vault/")
+    }
+
+    fn resolve(&self, reference: &str) -> Result<String, String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let name = &reference["vault/".len()..];
+        self.secrets
+            .get(name)
+            .map(|value| value.to_string())
+            .ok_or_else(|| "secret not found".into())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "localhost")]
+    host: String,
+
+    #[cnfg(env = "SECRETS_TEST_DB_PASSWORD", cli, secret_ref = "vault/db-pass")]
+    password: String,
+
+    #[cnfg(default = 5432, cli)]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct SecretConfig {
+    #[cnfg(default = "", env = "SECRETS_TEST_API_KEY")]
+    api_key: String,
+
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[test]
+fn resolves_references_and_secret_ref_fields() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let vault = Vault::new(&[("db-pass", "hunter2"), ("api", "k-123"), ("port", "6432")]);
+    unsafe { std::env::set_var("SECRETS_TEST_API_KEY", "secret://vault/api") };
+    let result = SecretConfig::loader()
+        .args(["app", "--database-port", "secret://vault/port"])
+        .secret_resolver(vault.clone())
+        .load_with_report();
+    unsafe { std::env::remove_var("SECRETS_TEST_API_KEY") };
+
+    let (cfg, report) = result.expect("secrets resolved");
+    assert_eq!(cfg.api_key, "k-123");
+    assert_eq!(cfg.database.password, "hunter2");
+    assert_eq!(cfg.database.port, 6432);
+    assert_eq!(
+        report.provenance.get("database.password"),
+        Some(&Origin::Source("secret://vault/db-pass".into()))
+    );
+
+    // The document keeps references, never the plaintext.
+    let document = &report.document;
+    assert_eq!(document.get_str("api_key"), Some("secret://vault/api"));
+    assert_eq!(
+        document.get_str("database.password"),
+        Some("secret://vault/db-pass")
+    );
+    assert_eq!(document.get_str("database.host"), Some("localhost"));
+}
+
+#[test]
+fn resolved_secrets_are_not_interpolated() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let vault = Vault::new(&[("db-pass", "hunter${pw}x"), ("api", "pa$${ss")]);
+    unsafe { std::env::set_var("SECRETS_TEST_API_KEY", "secret://vault/api") };
+    let result = SecretConfig::loader()
+        .args(["app"])
+        .secret_resolver(vault)
+        .load();
+    unsafe { std::env::remove_var("SECRETS_TEST_API_KEY") };
+
+    let cfg = result.expect("secrets taken as fetched");
+    assert_eq!(cfg.database.password, "hunter${pw}x");
+    assert_eq!(cfg.api_key, "pa$${ss");
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Service {
+    #[cnfg(default = 5432)]
+    #[cnfg(cli)]
+    port: u16,
+
+    #[cnfg(default = "")]
+    #[cnfg(env = "SECRETS_TEST_TOKEN")]
+    token: String,
+}
+
+#[test]
+fn explicit_values_win_over_secret_ref() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let vault = Vault::new(&[("db-pass", "from-vault")]);
+    unsafe { std::env::set_var("SECRETS_TEST_DB_PASSWORD", "from-env") };
+    let cfg = SecretConfig::loader()
+        .args(["app"])
+        .secret_resolver(vault.clone())
+        .load();
+    unsafe { std::env::remove_var("SECRETS_TEST_DB_PASSWORD") };
+
+    assert_eq!(cfg.expect("env value").database.password, "from-env");
+    assert_eq!(vault.calls.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn caches_resolved_secrets_across_loads() {
+    let vault = Vault::new(&[("db-pass", "hunter2")]);
+    let loader = SecretConfig::loader()
+        .args(["app"])
+        .secret_resolver(vault.clone());
+    loader.clone().load().expect("first load");
+    loader.clone().load().expect("cached");
+    assert_eq!(vault.calls.load(Ordering::SeqCst), 1);
+
+    let uncached = loader.secret_cache_ttl(Duration::ZERO);
+    uncached.clone().load().expect("fetched");
+    uncached.load().expect("fetched again");
+    assert_eq!(vault.calls.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn reports_unresolvable_references_on_the_field() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe { std::env::set_var("SECRETS_TEST_TOKEN", "secret://other/token") };
+    let unhandled = Service::loader()
+        .args(["app", "--port", "secret://vault/port"])
+        .secret_resolver(Vault::new(&[("port", "many")]))
+        .load();
+    unsafe { std::env::remove_var("SECRETS_TEST_TOKEN") };
+    let missing = SecretConfig::loader()
+        .args(["app"])
+        .secret_resolver(Vault::default())
+        .load();
+
+    let Err(CnfgError::Validation(errs)) = unhandled else {
+        panic!("expected validation errors, got {unhandled:?}");
+    };
+    let messages: Vec<_> = errs
+        .iter()
+        .map(|issue| {
+            assert!(matches!(issue.kind, IssueKind::Secret), "{issue:?}");
+            format!("{}: {}", issue.field, issue.message)
+        })
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "port: secret `secret://vault/port`: expected an integer",
+            "token: no secret resolver handles `secret://other/token`; add one with Loader::secret_resolver",
+        ]
+    );
+
+    let Err(CnfgError::Validation(errs)) = missing else {
+        panic!("expected validation errors, got {missing:?}");
+    };
+    let issue = errs.iter().next().expect("one issue");
+    assert_eq!(issue.field, "database.password");
+    assert_eq!(
+        issue.message,
+        "could not resolve `secret://vault/db-pass`: secret not found"
+    );
+}
+
+#[test]
+fn type_checks_references_without_a_resolver() {
+    let err = Service::loader()
+        .args(["app", "--port", "secret://vault/port"])
+        .load()
+        .unwrap_err();
+    let CnfgError::Validation(errs) = err else {
+        panic!("expected validation errors, got {err:?}");
+    };
+    let issue = errs.iter().next().expect("one issue");
+    assert_eq!(issue.field, "--port");
+    assert!(matches!(issue.kind, IssueKind::Type), "{issue:?}");
+}
+
+#[cfg(feature = "gcp-secrets")]
+mod gcp {
+    use super::*;
+    use cnfg::sources::gcp_secrets::{GcpSecretManager, SecretManagerClient};
+
+    struct FakeGcp;
+
+    impl SecretManagerClient for FakeGcp {
+        fn access_secret_version(&self, name: &str) -> Result<Vec<u8>, String> {
+            match name {
+                "projects/acme/secrets/db-pass/versions/latest" => Ok(b"latest-pass".to_vec()),
+                "projects/acme/secrets/db-pass/versions/2" => Ok(b"old-pass".to_vec()),
+                _ => Err(format!("NOT_FOUND: {name}")),
+            }
+        }
+    }
+
+    #[test]
+    fn resolves_secret_manager_names() {
+        assert_eq!(
+            GcpSecretManager::<FakeGcp>::version_name("projects/acme/secrets/db-pass").as_deref(),
+            Some("projects/acme/secrets/db-pass/versions/latest")
+        );
+        assert_eq!(
+            GcpSecretManager::<FakeGcp>::version_name("projects/acme/db-pass"),
+            None
+        );
+
+        let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+        unsafe {
+            std::env::set_var(
+                "SECRETS_TEST_API_KEY",
+                "secret://projects/acme/secrets/db-pass/versions/2",
+            )
+        };
+        let cfg = SecretConfig::loader()
+            .args([
+                "app",
+                "--database-password",
+                "secret://projects/acme/secrets/db-pass",
+            ])
+            .secret_resolver(GcpSecretManager::new(FakeGcp))
+            .load();
+        unsafe { std::env::remove_var("SECRETS_TEST_API_KEY") };
+
+        let cfg = cfg.expect("resolved");
+        assert_eq!(cfg.database.password, "latest-pass");
+        assert_eq!(cfg.api_key, "old-pass");
+    }
+}
+
+#[cfg(feature = "aws-secrets")]
+mod aws {
+    use super::*;
+    use cnfg::sources::aws_secrets::{AwsSecretsManager, SecretsManagerClient};
+
+    struct FakeAws;
+
+    impl SecretsManagerClient for FakeAws {
+        fn get_secret_value(&self, secret_id: &str) -> Result<String, String> {
+            match secret_id {
+                "prod/db" => Ok(r#"{"password": "hunter2", "port": 6432}"#.into()),
+                "arn:aws:secretsmanager:eu-west-1:123:secret:api" => Ok("k-aws".into()),
+                _ => Err("ResourceNotFoundException".into()),
+            }
+        }
+    }
+
+    #[test]
+    fn resolves_names_arns_and_json_keys() {
+        let id = AwsSecretsManager::<FakeAws>::secret_id;
+        assert_eq!(
+            id("aws/prod/db#password"),
+            Some(("prod/db", Some("password")))
+        );
+        assert_eq!(id("projects/acme/secrets/x"), None);
+
+        let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+        unsafe {
+            std::env::set_var(
+                "SECRETS_TEST_API_KEY",
+                "secret://arn:aws:secretsmanager:eu-west-1:123:secret:api",
+            )
+        };
+        let cfg = SecretConfig::loader()
+            .args([
+                "app",
+                "--database-password",
+                "secret://aws/prod/db#password",
+                "--database-port",
+                "secret://aws/prod/db#port",
+            ])
+            .secret_resolver(AwsSecretsManager::new(FakeAws))
+            .load();
+        let missing_key = SecretConfig::loader()
+            .args(["app", "--database-password", "secret://aws/prod/db#user"])
+            .secret_resolver(AwsSecretsManager::new(FakeAws))
+            .load();
+        unsafe { std::env::remove_var("SECRETS_TEST_API_KEY") };
+
+        let cfg = cfg.expect("resolved");
+        assert_eq!(cfg.api_key, "k-aws");
+        assert_eq!(cfg.database.password, "hunter2");
+        assert_eq!(cfg.database.port, 6432);
+
+        let Err(CnfgError::Validation(errs)) = missing_key else {
+            panic!("expected validation errors, got {missing_key:?}");
+        };
+        assert!(errs.iter().any(|issue| {
+            issue
+                .message
+                .ends_with("secret `prod/db` has no key `user`")
+        }));
+    }
+}
@@ -0,0 +1,67 @@
+use cnfg::{Cnfg, ConfigMeta, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+#[serde(rename_all = "camelCase")]
+struct Upstream {
+    #[cnfg(default = 30)]
+    timeout_secs: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[serde(rename_all = "kebab-case")]
+struct RenamedConfig {
+    #[serde(rename = "listen")]
+    #[cnfg(default = 8080, cli)]
+    listen_port: u16,
+
+    #[cnfg(default = "info")]
+    log_level: String,
+
+    #[serde(default)]
+    #[cnfg(nested)]
+    upstream_server: Upstream,
+}
+
+#[test]
+fn paths_follow_serialized_names() {
+    let paths: Vec<&str> = RenamedConfig::field_specs()
+        .iter()
+        .map(|s| s.path)
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            "listen",
+            "log-level",
+            "upstream-server",
+            "upstream-server.timeoutSecs"
+        ]
+    );
+    assert_eq!(RenamedConfig::field_specs()[0].name, "listen_port");
+}
+
+#[test]
+fn renamed_fields_load_from_files() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.json");
+    std::fs::write(
+        &path,
+        r#"{ "listen": 9000, "log-level": "debug", "upstream-server": { "timeoutSecs": 5 } }"#,
+    )
+    .expect("write json");
+
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let cfg = RenamedConfig::loader()
+        .args(["app", "--listen-port", "9100"])
+        .load()
+        .expect("load renamed config");
+    assert_eq!(cfg.listen_port, 9100);
+    assert_eq!(cfg.log_level, "debug");
+    assert_eq!(cfg.upstream_server.timeout_secs, 5);
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+}
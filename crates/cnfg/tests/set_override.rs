@@ -0,0 +1,91 @@
+use cnfg::{Cnfg, CnfgError, LoaderExt, Origin};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "localhost")]
+    host: String,
+
+    #[cnfg(default = 5432, cli)]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct SetConfig {
+    #[cnfg(default = false)]
+    debug: bool,
+
+    #[serde(default)]
+    tags: Vec<String>,
+
+    #[serde(default)]
+    labels: BTreeMap<String, String>,
+
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[test]
+fn overrides_any_field_with_coercion() {
+    let (cfg, report) = SetConfig::loader()
+        .args([
+            "app",
+            "--set",
+            "database.port=5433",
+            "--set=debug=true",
+            "--set",
+            "tags=a,b",
+            "--set",
+            "labels.team=core",
+        ])
+        .load_with_report()
+        .expect("load with overrides");
+    assert_eq!(cfg.database.port, 5433);
+    assert!(cfg.debug);
+    assert_eq!(cfg.tags, ["a", "b"]);
+    assert_eq!(cfg.labels["team"], "core");
+    assert_eq!(
+        report.provenance.get("database.port"),
+        Some(&Origin::Cli("--set database.port".into()))
+    );
+}
+
+#[test]
+fn set_beats_regular_flags() {
+    let cfg = SetConfig::loader()
+        .args(["app", "--set", "database.port=1", "--database-port", "2"])
+        .load()
+        .expect("load");
+    assert_eq!(cfg.database.port, 1);
+}
+
+#[test]
+fn rejects_unknown_paths_and_bad_values() {
+    match SetConfig::loader()
+        .args(["app", "--set", "datbase.port=1"])
+        .load()
+    {
+        Err(CnfgError::Cli(msg)) => assert!(msg.contains("unknown field `datbase.port`"), "{msg}"),
+        other => panic!("expected cli error, got {other:?}"),
+    }
+    match SetConfig::loader()
+        .args(["app", "--set", "database.port=high"])
+        .load()
+    {
+        Err(CnfgError::Validation(errs)) => {
+            let fields: Vec<_> = errs.into_iter().map(|issue| issue.field).collect();
+            assert_eq!(fields, ["--set database.port"]);
+        }
+        other => panic!("expected validation error, got {other:?}"),
+    }
+    match SetConfig::loader().args(["app", "--set", "debug"]).load() {
+        Err(CnfgError::Cli(msg)) => assert!(msg.contains("path=value"), "{msg}"),
+        other => panic!("expected cli error, got {other:?}"),
+    }
+}
+
+#[test]
+fn help_mentions_set() {
+    assert!(SetConfig::help().contains("--set <path=value>..."));
+}
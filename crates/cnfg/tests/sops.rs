@@ -0,0 +1,133 @@
+#![cfg(feature = "sops")]
+
+use cnfg::sops::{SopsCommand, SopsDecryptor};
+use cnfg::{Cnfg, CnfgError, LoaderExt, Origin};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = "localhost")]
+    host: String,
+
+    #[cnfg(default = "")]
+    password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct SecretConfig {
+    #[cnfg(default = "app")]
+    name: String,
+
+    #[cnfg(nested)]
+    database: Database,
+}
+
+const ENCRYPTED_JSON: &str = r#"{
+  "name": "billing",
+  "database": {"password": "ENC[AES256_GCM,data:aHVudGVyMg==,iv:x,tag:y,type:str]"},
+  "sops": {"mac": "ENC[AES256_GCM,data:z]", "version": "3.9.0", "age": [{"recipient": "age1"}]}
+}"#;
+
+/// Stands in for a real backend: swaps in a known plaintext.
+struct Fake(Result<&'static str, &'static str>);
+
+impl SopsDecryptor for Fake {
+    fn decrypt(&self, _path: &Path, data: &str) -> Result<String, String> {
+        assert!(data.contains("ENC[AES256_GCM"));
+        self.0.map(str::to_string).map_err(str::to_string)
+    }
+}
+
+fn load_with(
+    name: &str,
+    body: &str,
+    fake: Fake,
+) -> Result<(SecretConfig, cnfg::Report), CnfgError> {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join(name);
+    std::fs::write(&path, body).expect("write config");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let result = SecretConfig::loader()
+        .args(["app"])
+        .sops(fake)
+        .load_with_report();
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+    result
+}
+
+#[test]
+fn decrypts_files_with_sops_metadata() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let plaintext = r#"{"name": "billing", "database": {"password": "hunter2"}}"#;
+    let (cfg, report) =
+        load_with("config.json", ENCRYPTED_JSON, Fake(Ok(plaintext))).expect("decrypted");
+    assert_eq!(cfg.name, "billing");
+    assert_eq!(cfg.database.password, "hunter2");
+    assert_eq!(cfg.database.host, "localhost");
+    assert!(matches!(
+        report.provenance.get("database.password"),
+        Some(Origin::File(path)) if path.ends_with("config.json")
+    ));
+
+    // Plain files never reach the decryptor.
+    let (cfg, _) = load_with("config.json", plaintext, Fake(Err("unused"))).expect("plain");
+    assert_eq!(cfg.database.password, "hunter2");
+}
+
+#[test]
+fn reports_decryption_failures() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let err = load_with(
+        "config.json",
+        ENCRYPTED_JSON,
+        Fake(Err("no identity matched")),
+    )
+    .expect_err("undecryptable");
+    assert!(matches!(err.root(), CnfgError::Decrypt(_)));
+    assert!(err.path().is_some_and(|path| path.ends_with("config.json")));
+    let message = err.to_string();
+    assert!(
+        message.ends_with("config.json: decryption error: no identity matched"),
+        "{message}"
+    );
+}
+
+#[test]
+#[cfg(all(unix, feature = "yaml"))]
+fn runs_the_sops_binary() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    // Strips the metadata and unwraps `ENC[...,data:<plaintext>,...]`.
+    let script = dir.path().join("sops");
+    std::fs::write(
+        &script,
+        "#!/bin/sh\n[ \"$1\" = --decrypt ] || exit 2\n[ -n \"$SOPS_AGE_KEY_FILE\" ] || { echo 'no age key' >&2; exit 128; }\nsed -e '/^sops:/,$d' -e 's/ENC\\[AES256_GCM,data:\\([^,]*\\),.*\\]/\\1/' \"$2\"\n",
+    )
+    .expect("write script");
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).expect("chmod");
+
+    let path = dir.path().join("config.yaml");
+    std::fs::write(
+        &path,
+        "name: billing\ndatabase:\n  password: ENC[AES256_GCM,data:hunter2,iv:x,tag:y,type:str]\nsops:\n  mac: ENC[AES256_GCM,data:z]\n  version: 3.9.0\n",
+    )
+    .expect("write config");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let sops = SopsCommand::new().program(&script);
+    let cfg = SecretConfig::loader()
+        .args(["app"])
+        .sops(sops.clone().env("SOPS_AGE_KEY_FILE", "/keys/age.txt"))
+        .load();
+    let err = SecretConfig::loader().args(["app"]).sops(sops).load();
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+
+    assert_eq!(cfg.expect("decrypted").database.password, "hunter2");
+    let message = err.expect_err("no key").to_string();
+    assert!(message.ends_with(": no age key"), "{message}");
+}
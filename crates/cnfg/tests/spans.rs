@@ -0,0 +1,135 @@
+use cnfg::{Cnfg, CnfgError, LoaderExt, Location};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(
+        default = 5432,
+        env = "SPANS_DB_PORT",
+        validate(range(min = 1, max = 65535))
+    )]
+    port: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct AppConfig {
+    #[cnfg(default = "app")]
+    name: String,
+
+    #[serde(default)]
+    #[cnfg(nested)]
+    database: Database,
+}
+
+fn load_file(name: &str, contents: &str) -> (Result<AppConfig, CnfgError>, String) {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join(name);
+    std::fs::write(&path, contents).expect("write config");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let result = AppConfig::loader().args(["app"]).load();
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+    (result, path.display().to_string())
+}
+
+fn only_issue(result: Result<AppConfig, CnfgError>) -> cnfg::error::Issue {
+    let Err(CnfgError::Validation(errs)) = result else {
+        panic!("expected validation errors, got {result:?}");
+    };
+    let issues: Vec<_> = errs.into_iter().collect();
+    assert_eq!(issues.len(), 1, "{issues:?}");
+    issues.into_iter().next().unwrap()
+}
+
+#[test]
+fn validator_issues_point_at_the_toml_key() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let (result, path) = load_file(
+        "config.toml",
+        "name = \"svc\"\n\n[database]\n  port = 70000\n",
+    );
+    let issue = only_issue(result);
+    assert_eq!(issue.field, "database.port");
+    assert!(
+        issue.message.ends_with(&format!("(from file {path}:4:3)")),
+        "{}",
+        issue.message
+    );
+}
+
+#[test]
+fn type_issues_point_at_the_yaml_key() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let (result, path) = load_file("config.yaml", "name: svc\ndatabase:\n  port: high\n");
+    let issue = only_issue(result);
+    assert_eq!(issue.field, "database.port");
+    assert!(
+        issue.message.ends_with(&format!("(from file {path}:3:3)")),
+        "{}",
+        issue.message
+    );
+}
+
+#[test]
+fn overridden_values_drop_the_file_position() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe { std::env::set_var("SPANS_DB_PORT", "0") };
+    let (result, _) = load_file("config.toml", "[database]\nport = 70000\n");
+    unsafe { std::env::remove_var("SPANS_DB_PORT") };
+    let issue = only_issue(result);
+    assert!(
+        issue.message.ends_with("(from env SPANS_DB_PORT)"),
+        "{}",
+        issue.message
+    );
+}
+
+#[test]
+fn report_provenance_exposes_locations() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.yaml");
+    std::fs::write(&path, "database:\n  port: 6000\n").expect("write yaml");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let (_, report) = AppConfig::loader()
+        .args(["app"])
+        .load_with_report()
+        .expect("load");
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+
+    assert_eq!(
+        report.provenance.location("database.port"),
+        Some(Location { line: 2, column: 3 })
+    );
+    // Defaults were never written in the file.
+    assert_eq!(report.provenance.location("name"), None);
+}
+
+#[test]
+fn locates_keys_in_toml_lists() {
+    let text =
+        "title = \"x\"\n\n[[servers]]\nhost = \"a\"\n\n[[servers]]\nhost = \"b\"\nports = [1, 2]\n";
+    let found = cnfg::spans::locate(Path::new("c.toml"), text);
+    assert_eq!(found["title"], Location { line: 1, column: 1 });
+    assert_eq!(found["servers[1].host"], Location { line: 7, column: 1 });
+    assert_eq!(
+        found["servers[1].ports[1]"],
+        Location {
+            line: 8,
+            column: 13
+        }
+    );
+}
+
+#[test]
+fn locates_keys_in_yaml_sequences() {
+    let text = "# comment\nservers:\n  - host: a\n    port: 1\n  - host: b\n    tags:\n      - x\n      - y\n";
+    let found = cnfg::spans::locate(Path::new("c.yml"), text);
+    assert_eq!(found["servers"], Location { line: 2, column: 1 });
+    assert_eq!(found["servers[0].port"], Location { line: 4, column: 5 });
+    assert_eq!(found["servers[1].host"], Location { line: 5, column: 5 });
+    assert_eq!(found["servers[1].tags[1]"], Location { line: 8, column: 7 });
+}
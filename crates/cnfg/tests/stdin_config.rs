@@ -0,0 +1,168 @@
+use cnfg::{Cnfg, CnfgError, LoaderExt, Origin};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct PipedConfig {
+    #[cnfg(default = "app", cli)]
+    name: String,
+
+    #[cnfg(default = 3000)]
+    port: u16,
+}
+
+/// Arguments for `load_in_child`, one per line.
+const CHILD_ARGS: &str = "STDIN_TEST_ARGS";
+
+/// Loads `PipedConfig` with the arguments in `STDIN_TEST_ARGS` when run by
+/// `load_with_stdin`, printing the result; a no-op otherwise.
+#[test]
+fn load_in_child() {
+    let Ok(args) = std::env::var(CHILD_ARGS) else {
+        return;
+    };
+    let args: Vec<&str> = std::iter::once("app").chain(args.lines()).collect();
+    let loader = PipedConfig::loader().args(args);
+    match loader.clone().load_with_report() {
+        Ok((cfg, report)) => {
+            // A second load (as on reload) sees the same document.
+            let again = loader.load().expect("second load");
+            assert_eq!(again.port, cfg.port);
+            println!(
+                "RESULT {} {} {:?}",
+                cfg.name,
+                cfg.port,
+                report.provenance.get("port")
+            );
+        }
+        Err(err) => println!("RESULT error: {err}"),
+    }
+}
+
+/// Run `load_in_child` in a new process with `stdin` piped in.
+fn load_with_stdin(args: &[&str], config_file: Option<&str>, stdin: &str) -> String {
+    let mut command = child_command(args);
+    if let Some(path) = config_file {
+        command.env("CONFIG_FILE", path);
+    }
+    run_child(command, stdin)
+}
+
+fn child_command(args: &[&str]) -> Command {
+    let mut command = Command::new(std::env::current_exe().expect("test binary"));
+    command
+        .args([
+            "--exact",
+            "load_in_child",
+            "--nocapture",
+            "--test-threads=1",
+        ])
+        .env(CHILD_ARGS, args.join("\n"))
+        .env_remove("CONFIG_FILE")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped());
+    command
+}
+
+fn run_child(mut command: Command, stdin: &str) -> String {
+    let mut child = command.spawn().expect("spawn test binary");
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(stdin.as_bytes())
+        .expect("write stdin");
+    let output = child.wait_with_output().expect("child finished");
+    let stdout = String::from_utf8(output.stdout).expect("utf-8 output");
+    stdout
+        .lines()
+        // The harness prints `test load_in_child ... ` on the same line.
+        .find_map(|line| line.split_once("RESULT ").map(|(_, result)| result))
+        .unwrap_or_else(|| panic!("no result from child:\n{stdout}"))
+        .to_string()
+}
+
+#[test]
+fn reads_stdin_with_config_dash() {
+    assert_eq!(
+        load_with_stdin(
+            &["--config", "-", "--config-format", "yaml"],
+            None,
+            "name: piped\nport: 7070\n",
+        ),
+        r#"piped 7070 Some(Stdin)"#
+    );
+    assert_eq!(
+        load_with_stdin(
+            &["--config-format=TOML", "--name", "flag"],
+            Some("-"),
+            "port = 8080\n",
+        ),
+        r#"flag 8080 Some(Stdin)"#
+    );
+}
+
+#[test]
+fn stdin_needs_a_format() {
+    assert_eq!(
+        load_with_stdin(&["--config=-"], None, "{}"),
+        "error: CLI error: reading config from stdin needs --config-format"
+    );
+}
+
+#[test]
+fn config_flag_overrides_config_file_and_extension() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let env_file = dir.path().join("env.json");
+    std::fs::write(&env_file, r#"{"port": 1}"#).expect("write env file");
+    let flag_file = dir.path().join("generated.conf");
+    std::fs::write(&flag_file, r#"{"port": 2}"#).expect("write flag file");
+    let flag_path = flag_file.to_str().expect("utf-8 path");
+
+    unsafe { std::env::set_var("CONFIG_FILE", &env_file) };
+    let result = PipedConfig::loader()
+        .args(["app", "--config", flag_path, "--config-format", "json"])
+        .load_with_report();
+    let unknown = PipedConfig::loader()
+        .args(["app", "--config-format", "xml"])
+        .load();
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+
+    let (cfg, report) = result.expect("load --config file");
+    assert_eq!(cfg.port, 2);
+    assert_eq!(
+        report.provenance.get("port"),
+        Some(&Origin::File(flag_file.clone()))
+    );
+    assert!(
+        report.warnings.iter().any(|warning| {
+            warning.message == "selected by --config; directory discovery skipped"
+        })
+    );
+
+    let Err(CnfgError::Cli(message)) = unknown else {
+        panic!("expected a CLI error, got {unknown:?}");
+    };
+    assert!(
+        message.starts_with("--config-format: unknown format `xml`"),
+        "{message}"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn a_file_named_like_stdin_is_a_file() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    std::fs::write(dir.path().join("<stdin>"), r#"{"port": 4040}"#).expect("write file");
+    let mut command = child_command(&["--config", "<stdin>", "--config-format", "json"]);
+    command.current_dir(dir.path());
+    assert_eq!(
+        run_child(command, "{\"port\": 1}"),
+        r#"app 4040 Some(File("<stdin>"))"#
+    );
+}
@@ -0,0 +1,161 @@
+use cnfg::error::IssueKind;
+use cnfg::{Cnfg, CnfgError, ConfigMeta, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Serve {
+    /// Port to listen on.
+    #[cnfg(cli, default = 8080, env = "SUBCMD_SERVE_PORT")]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Migrate {
+    /// Target schema version.
+    #[cnfg(cli, required)]
+    to: u32,
+
+    #[cnfg(cli, default = false)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Tool {
+    /// Verbose logging.
+    #[cnfg(cli, default = false)]
+    verbose: bool,
+
+    /// Run the HTTP server.
+    #[cnfg(command)]
+    serve: Option<Serve>,
+
+    /// Apply database migrations.
+    #[cnfg(command = "db-migrate")]
+    migrate: Option<Migrate>,
+}
+
+#[test]
+fn leading_positional_selects_command() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let cfg = Tool::loader()
+        .args(["tool", "--verbose", "serve", "--port", "9000"])
+        .load()
+        .expect("serve");
+    assert!(cfg.verbose);
+    assert_eq!(cfg.serve.map(|s| s.port), Some(9000));
+    assert!(cfg.migrate.is_none());
+
+    let cfg = Tool::loader()
+        .args(["tool", "db-migrate", "--to", "7", "--dry-run", "--verbose"])
+        .load()
+        .expect("migrate");
+    let migrate = cfg.migrate.expect("migrate selected");
+    assert_eq!(migrate.to, 7);
+    assert!(migrate.dry_run);
+    assert!(cfg.verbose, "global flags are accepted after the command");
+    assert!(cfg.serve.is_none());
+
+    let cfg = Tool::loader().args(["tool"]).load().expect("no command");
+    assert!(cfg.serve.is_none() && cfg.migrate.is_none());
+}
+
+#[test]
+fn config_flags_before_the_command_keep_their_values() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("tool.conf");
+    std::fs::write(&path, r#"{ "verbose": true }"#).expect("write config");
+    let path = path.to_str().expect("utf-8 path");
+
+    let cfg = Tool::loader()
+        .args([
+            "tool",
+            "--config",
+            path,
+            "--config-format",
+            "json",
+            "serve",
+            "--port",
+            "9",
+        ])
+        .load()
+        .expect("serve");
+    assert!(cfg.verbose);
+    assert_eq!(cfg.serve.map(|s| s.port), Some(9));
+}
+
+#[test]
+fn command_defaults_and_env_apply_only_when_selected() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe { std::env::set_var("SUBCMD_SERVE_PORT", "7000") };
+    let cfg = Tool::loader()
+        .args(["tool", "serve"])
+        .load()
+        .expect("serve");
+    assert_eq!(cfg.serve.map(|s| s.port), Some(7000));
+
+    let cfg = Tool::loader()
+        .args(["tool", "db-migrate", "--to", "1"])
+        .load()
+        .expect("migrate");
+    assert!(
+        cfg.serve.is_none(),
+        "env for an unselected command is dropped"
+    );
+    unsafe { std::env::remove_var("SUBCMD_SERVE_PORT") };
+
+    let cfg = Tool::loader()
+        .args(["tool", "serve"])
+        .load()
+        .expect("serve");
+    assert_eq!(cfg.serve.map(|s| s.port), Some(8080));
+}
+
+#[test]
+fn required_fields_are_scoped_to_the_command() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    match Tool::loader().args(["tool", "db-migrate"]).load() {
+        Err(CnfgError::Validation(errs)) => {
+            let issue = errs.iter().next().expect("issue");
+            assert_eq!(issue.field, "migrate.to");
+            assert!(matches!(issue.kind, IssueKind::Missing));
+        }
+        other => panic!("expected missing field, got {other:?}"),
+    }
+}
+
+#[test]
+fn command_flags_are_not_global() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    match Tool::loader().args(["tool", "--port", "1", "serve"]).load() {
+        Err(CnfgError::Cli(msg)) => assert!(msg.contains("unknown flag --port"), "{msg}"),
+        other => panic!("expected cli error, got {other:?}"),
+    }
+    match Tool::loader().args(["tool", "serve", "serve"]).load() {
+        Err(CnfgError::Cli(msg)) => assert!(msg.contains("`serve`"), "{msg}"),
+        other => panic!("expected cli error, got {other:?}"),
+    }
+}
+
+#[test]
+fn help_lists_commands() {
+    assert_eq!(Tool::commands().len(), 2);
+    let help = Tool::help();
+    assert!(help.contains("Commands:"), "{help}");
+    assert!(help.contains("  db-migrate"), "{help}");
+    assert!(help.contains("Apply database migrations."), "{help}");
+    assert!(!help.contains("--port"), "{help}");
+
+    let serve = Tool::command_help("serve").expect("serve help");
+    assert!(serve.contains(" serve [OPTIONS]"), "{serve}");
+    assert!(serve.contains("--port <INT>"), "{serve}");
+    assert!(Tool::command_help("deploy").is_none());
+
+    match Tool::loader().args(["tool", "serve", "--help"]).load() {
+        Err(CnfgError::HelpPrinted) => {}
+        other => panic!("expected help sentinel, got {other:?}"),
+    }
+}
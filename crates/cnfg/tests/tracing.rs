@@ -0,0 +1,129 @@
+#![cfg(feature = "tracing")]
+
+use cnfg::{Cnfg, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+/// Records span names and events as `span: message key=value ...` lines.
+#[derive(Default)]
+struct Recorder {
+    spans: Mutex<Vec<&'static str>>,
+    stack: Mutex<Vec<u64>>,
+    lines: Arc<Mutex<Vec<String>>>,
+}
+
+struct Line(String);
+
+impl Visit for Line {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            write!(self.0, " {value:?}").unwrap();
+        } else {
+            write!(self.0, " {}={value:?}", field.name()).unwrap();
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        write!(self.0, " {}={value}", field.name()).unwrap();
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.target() == "cnfg"
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(attrs.metadata().name());
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let current = self.stack.lock().unwrap().last().copied();
+        let span = current.map_or("-", |id| self.spans.lock().unwrap()[id as usize - 1]);
+        let mut line = Line(format!("{span}:"));
+        event.record(&mut line);
+        self.lines.lock().unwrap().push(line.0);
+    }
+
+    fn enter(&self, span: &Id) {
+        self.stack.lock().unwrap().push(span.into_u64());
+    }
+
+    fn exit(&self, _: &Id) {
+        self.stack.lock().unwrap().pop();
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct TracedConfig {
+    #[cnfg(default = 8080, cli)]
+    port: u16,
+
+    #[cnfg(default = "localhost")]
+    host: String,
+
+    #[cnfg(env = "TRACING_TEST_TOKEN", required)]
+    token: String,
+}
+
+#[test]
+fn reports_each_stage_without_values() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let mut file = tempfile::Builder::new()
+        .suffix(".toml")
+        .tempfile()
+        .expect("temp file");
+    writeln!(file, "host = \"db.internal\"").expect("write config");
+    unsafe {
+        std::env::set_var("CONFIG_FILE", file.path());
+        std::env::set_var("TRACING_TEST_TOKEN", "s3cret");
+    }
+
+    let recorder = Recorder::default();
+    let lines = Arc::clone(&recorder.lines);
+    let result = tracing::subscriber::with_default(recorder, || {
+        TracedConfig::loader()
+            .args(["app", "--port", "9000"])
+            .load()
+    });
+    unsafe {
+        std::env::remove_var("CONFIG_FILE");
+        std::env::remove_var("TRACING_TEST_TOKEN");
+    }
+    let cfg = result.expect("load");
+    assert_eq!(cfg.token, "s3cret");
+
+    let path = file.path().display().to_string();
+    let lines: Vec<String> = lines
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|line| line.replace(&path, "<file>"))
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            "load: applied defaults values=2",
+            "files: config file selected by CONFIG_FILE path=<file>",
+            "files: loaded config file path=<file> values=1",
+            "environment: applied env var env=TRACING_TEST_TOKEN field=token",
+            "load: applied environment overrides=1",
+            "cli: applied flag flag=port field=port",
+            "load: applied flags overrides=1",
+            "validate: config loaded fields=3",
+        ]
+    );
+}
@@ -0,0 +1,142 @@
+use cnfg::error::IssueKind;
+use cnfg::{Cnfg, CnfgError, LoaderExt, Origin};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+struct Database {
+    #[cnfg(default = 5432)]
+    port: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct StrictConfig {
+    #[cnfg(default = false, env = "STRICT_DEBUG")]
+    debug: bool,
+
+    #[serde(default)]
+    #[cnfg(nested)]
+    database: Database,
+
+    #[serde(default)]
+    tags: Vec<String>,
+
+    #[serde(default)]
+    labels: HashMap<String, u32>,
+}
+
+fn write_config(dir: &tempfile::TempDir, json: &str) -> std::path::PathBuf {
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, json).expect("write json");
+    path
+}
+
+#[test]
+fn reports_every_type_mismatch_with_its_origin() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = write_config(
+        &dir,
+        r#"{ "debug": "yes", "database": { "port": "abc" }, "tags": ["a", 1], "labels": { "x": 1 } }"#,
+    );
+
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let err = StrictConfig::loader()
+        .args(["app"])
+        .load()
+        .expect_err("type errors");
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+
+    let CnfgError::Validation(errs) = err else {
+        panic!("expected validation error, got {err:?}");
+    };
+    let origin = format!("(from file {})", path.display());
+    let messages: Vec<(String, String)> = errs
+        .iter()
+        .filter(|i| matches!(i.kind, IssueKind::Type))
+        .map(|i| (i.field.clone(), i.message.clone()))
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            (
+                "debug".into(),
+                format!("expected bool, found string \"yes\" {origin}")
+            ),
+            (
+                "database.port".into(),
+                format!("expected integer, found string \"abc\" {origin}")
+            ),
+            (
+                "tags[1]".into(),
+                format!("expected string, found number 1 {origin}")
+            ),
+        ]
+    );
+}
+
+#[test]
+fn records_provenance_per_layer() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = write_config(&dir, r#"{ "database": { "port": 6000 } }"#);
+
+    unsafe {
+        std::env::set_var("CONFIG_FILE", &path);
+        std::env::set_var("STRICT_DEBUG", "true");
+    }
+    let (_, report) = StrictConfig::loader()
+        .args(["app"])
+        .load_with_report()
+        .expect("load");
+    unsafe {
+        std::env::remove_var("CONFIG_FILE");
+        std::env::remove_var("STRICT_DEBUG");
+    }
+
+    assert_eq!(
+        report.provenance.get("database.port"),
+        Some(&Origin::File(path.clone()))
+    );
+    assert_eq!(
+        report.provenance.get("debug"),
+        Some(&Origin::Env("STRICT_DEBUG".into()))
+    );
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct OverflowConfig {
+    #[serde(default)]
+    #[cnfg(nested)]
+    database: Database,
+}
+
+#[test]
+fn deserialization_failures_name_the_field() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = write_config(&dir, r#"{ "database": { "port": 70000 } }"#);
+
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    let err = OverflowConfig::loader()
+        .args(["app"])
+        .load()
+        .expect_err("overflow");
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+
+    let CnfgError::Validation(errs) = err else {
+        panic!("expected validation error, got {err:?}");
+    };
+    let issue = errs.iter().next().expect("one issue");
+    assert_eq!(issue.field, "database.port");
+    assert!(matches!(issue.kind, IssueKind::Type));
+    assert!(issue.message.contains("70000"), "{}", issue.message);
+    assert!(
+        issue
+            .message
+            .ends_with(&format!("(from file {})", path.display()))
+    );
+}
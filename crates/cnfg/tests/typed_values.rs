@@ -0,0 +1,105 @@
+use cnfg::error::IssueKind;
+use cnfg::{Cnfg, CnfgError, ConfigMeta, Kind};
+use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct NetConfig {
+    #[cnfg(default = "127.0.0.1:8080", env = "TYPED_BIND")]
+    bind: SocketAddr,
+
+    #[cnfg(default = "::1", env = "TYPED_HOST")]
+    host: IpAddr,
+
+    #[cnfg(default = "~/data")]
+    data_dir: PathBuf,
+
+    #[cnfg(env = "TYPED_CACHE")]
+    cache_dir: Option<PathBuf>,
+}
+
+#[test]
+fn classifies_std_types() {
+    let kinds: Vec<Kind> = NetConfig::field_specs().iter().map(|s| s.kind).collect();
+    assert_eq!(
+        kinds,
+        [Kind::SocketAddr, Kind::IpAddr, Kind::Path, Kind::Path]
+    );
+}
+
+#[test]
+fn rejects_bad_addresses_from_env() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe { std::env::set_var("TYPED_BIND", "localhost") };
+    match NetConfig::load() {
+        Err(CnfgError::Validation(errs)) => {
+            let issue = errs.into_iter().next().expect("one issue");
+            assert_eq!(issue.field, "TYPED_BIND");
+            assert!(
+                issue.message.contains("socket address"),
+                "{}",
+                issue.message
+            );
+        }
+        other => panic!("expected validation error, got {other:?}"),
+    }
+    unsafe { std::env::remove_var("TYPED_BIND") };
+}
+
+#[test]
+fn rejects_bad_addresses_from_files() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, r#"{ "host": "not-an-ip" }"#).expect("write json");
+
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+    match NetConfig::load() {
+        Err(CnfgError::Validation(errs)) => {
+            let issue = errs.iter().find(|i| i.field == "host").expect("host issue");
+            assert!(matches!(issue.kind, IssueKind::Type));
+            assert!(issue.message.contains("not-an-ip"));
+        }
+        other => panic!("expected validation error, got {other:?}"),
+    }
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+}
+
+#[test]
+#[cfg(unix)]
+fn expands_home_and_env_in_paths() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    unsafe {
+        std::env::set_var("HOME", "/home/tester");
+        std::env::set_var("TYPED_ROOT", "/srv");
+        std::env::set_var("TYPED_CACHE", "${TYPED_ROOT}/cache");
+    }
+    let cfg = NetConfig::load().expect("load with paths");
+    assert_eq!(cfg.data_dir, PathBuf::from("/home/tester/data"));
+    assert_eq!(cfg.cache_dir, Some(PathBuf::from("/srv/cache")));
+    assert_eq!(cfg.bind, "127.0.0.1:8080".parse::<SocketAddr>().unwrap());
+    unsafe {
+        std::env::remove_var("TYPED_ROOT");
+        std::env::remove_var("TYPED_CACHE");
+    }
+}
+
+#[test]
+fn rejects_malformed_references_in_paths() {
+    assert_eq!(
+        cnfg::util::expand_path("/srv/${}/cache"),
+        Err("empty `${}` in `/srv/${}/cache`".to_string())
+    );
+    assert_eq!(
+        cnfg::util::expand_path("/srv/${ROOT"),
+        Err("unterminated `${` in `/srv/${ROOT`".to_string())
+    );
+    assert_eq!(
+        cnfg::util::expand_path("/srv/$/cache"),
+        Ok("/srv/$/cache".to_string())
+    );
+}
@@ -0,0 +1,120 @@
+use cnfg::{Cnfg, CnfgError, ConfigMeta, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::sync::Mutex;
+
+static ENV_MUTEX: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Pool {
+    #[cnfg(default = 10)]
+    size: u32,
+
+    timeout_ms: u64,
+}
+
+impl Default for Pool {
+    fn default() -> Self {
+        Self {
+            size: 4,
+            timeout_ms: 2500,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(use_default)]
+struct ServerConfig {
+    host: String,
+
+    #[cnfg(default = 9090, env = "USE_DEFAULT_PORT")]
+    port: u16,
+
+    tags: Vec<String>,
+
+    token: Option<String>,
+
+    #[cnfg(nested)]
+    pool: Pool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".into(),
+            port: 8080,
+            tags: vec!["web".into()],
+            token: None,
+            pool: Pool::default(),
+        }
+    }
+}
+
+#[test]
+fn defaults_start_from_the_default_impl() {
+    let defaults = ServerConfig::defaults_json();
+    assert_eq!(defaults["host"], "0.0.0.0");
+    assert_eq!(defaults["tags"], serde_json::json!(["web"]));
+    assert!(defaults.get("token").is_none(), "{defaults}");
+
+    let cfg = ServerConfig::loader().args(["app"]).load().expect("load");
+    assert_eq!(cfg.host, "0.0.0.0");
+    assert_eq!(cfg.tags, ["web"]);
+    assert_eq!(cfg.token, None);
+}
+
+#[test]
+fn attribute_defaults_override_the_default_impl() {
+    let cfg = ServerConfig::loader().args(["app"]).load().expect("load");
+    assert_eq!(cfg.port, 9090);
+    assert_eq!(cfg.pool.size, 10);
+    assert_eq!(cfg.pool.timeout_ms, 2500);
+}
+
+#[test]
+fn files_and_env_still_override() {
+    let _guard = ENV_MUTEX.lock().expect("env mutex poisoned");
+    let mut file = tempfile::Builder::new()
+        .suffix(".toml")
+        .tempfile()
+        .expect("tempfile");
+    writeln!(file, "host = \"127.0.0.1\"\n[pool]\nsize = 2").expect("write");
+    unsafe {
+        std::env::set_var("CONFIG_FILE", file.path());
+        std::env::set_var("USE_DEFAULT_PORT", "7000");
+    }
+    let result = ServerConfig::loader().args(["app"]).load();
+    unsafe {
+        std::env::remove_var("CONFIG_FILE");
+        std::env::remove_var("USE_DEFAULT_PORT");
+    }
+
+    let cfg = result.expect("load");
+    assert_eq!(cfg.host, "127.0.0.1");
+    assert_eq!(cfg.port, 7000);
+    assert_eq!(cfg.pool.size, 2);
+    assert_eq!(cfg.pool.timeout_ms, 2500);
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Cnfg)]
+#[cnfg(use_default)]
+struct Credentials {
+    user: String,
+
+    #[cnfg(required)]
+    password: String,
+}
+
+#[test]
+fn required_fields_ignore_the_default_impl() {
+    assert!(Credentials::defaults_json().get("password").is_none());
+    let err = Credentials::loader()
+        .args(["app"])
+        .load()
+        .expect_err("password is required");
+    let CnfgError::Validation(errs) = err else {
+        panic!("expected validation errors, got {err:?}");
+    };
+    let fields: Vec<String> = errs.into_iter().map(|issue| issue.field).collect();
+    assert_eq!(fields, ["password"]);
+}
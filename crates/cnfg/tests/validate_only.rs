@@ -0,0 +1,64 @@
+use cnfg::{CnfgValidate, Validate};
+
+#[derive(Debug, CnfgValidate)]
+struct Address {
+    #[cnfg(validate(regex = "^[A-Z]{2}$"))]
+    country: String,
+}
+
+/// A request payload: no serde, no `ConfigMeta`.
+#[derive(Debug, CnfgValidate)]
+struct SignupRequest {
+    #[cnfg(validate(range(min = 13, max = 130)))]
+    age: u8,
+
+    #[cnfg(validate(url(schemes = ["https"])))]
+    homepage: Option<String>,
+
+    #[cnfg(nested)]
+    address: Address,
+
+    #[cnfg(nested)]
+    previous: Vec<Address>,
+}
+
+fn messages(request: &SignupRequest) -> Vec<String> {
+    match request.validate() {
+        Ok(()) => Vec::new(),
+        Err(errs) => errs
+            .iter()
+            .map(|issue| format!("{}: {}", issue.field, issue.message))
+            .collect(),
+    }
+}
+
+#[test]
+fn validates_without_the_loader() {
+    let mut request = SignupRequest {
+        age: 30,
+        homepage: Some("https://example.com".into()),
+        address: Address {
+            country: "NZ".into(),
+        },
+        previous: vec![Address {
+            country: "AU".into(),
+        }],
+    };
+    assert_eq!(messages(&request), Vec::<String>::new());
+
+    request.age = 9;
+    request.homepage = Some("http://example.com".into());
+    request.address.country = "nz".into();
+    request.previous.push(Address {
+        country: "Germany".into(),
+    });
+    assert_eq!(
+        messages(&request),
+        [
+            "age: must be >= 13",
+            "homepage: URL scheme `http` is not allowed (expected `https`)",
+            "address.country: regex not matched: ^[A-Z]{2}$",
+            "previous[1].country: regex not matched: ^[A-Z]{2}$",
+        ]
+    );
+}
@@ -0,0 +1,170 @@
+use cnfg::error::{Issue, IssueKind, Severity};
+use cnfg::test::Sandbox;
+use cnfg::{
+    CliSpec, Cnfg, CnfgError, ConfigMeta, FieldSpec, LoaderExt, Validate, ValidationErrors,
+    WarningKind,
+};
+use serde::{Deserialize, Serialize};
+
+fn issue(field: &str, message: &str) -> Issue {
+    Issue::custom(field, message)
+}
+
+#[test]
+fn groups_and_filters_issues_by_field() {
+    let errs: ValidationErrors = [
+        issue("database.port", "must be >= 1024"),
+        issue("name", "required field missing"),
+        issue("database.port", "must be even"),
+        issue("database.portal", "unrelated sibling"),
+    ]
+    .into_iter()
+    .collect();
+
+    let groups = errs.group_by_field();
+    assert_eq!(
+        groups.keys().copied().collect::<Vec<_>>(),
+        ["database.port", "database.portal", "name"]
+    );
+    assert_eq!(groups["database.port"].len(), 2);
+
+    let nested: Vec<&str> = errs
+        .errors_for("database")
+        .map(|i| i.message.as_str())
+        .collect();
+    assert_eq!(
+        nested,
+        ["must be >= 1024", "must be even", "unrelated sibling"]
+    );
+    assert_eq!(errs.errors_for("database.port").count(), 2);
+
+    let by_ref: Vec<&str> = (&errs).into_iter().map(|i| i.field.as_str()).collect();
+    assert_eq!(by_ref.len(), 4);
+
+    let merged = errs.merge([issue("port", "bad")].into_iter().collect());
+    assert_eq!(merged.len(), 5);
+    let owned: Vec<Issue> = merged.into_iter().collect();
+    assert_eq!(owned.last().map(|i| i.field.as_str()), Some("port"));
+}
+
+#[test]
+fn constructors_fill_metadata() {
+    let below = Issue::range("workers", Some(1), Some(64), 0);
+    assert!(matches!(below.kind, IssueKind::Range));
+    assert_eq!(below.message, "must be >= 1");
+    assert_eq!(below.expected.as_deref(), Some("1..=64"));
+    assert_eq!(below.actual.as_deref(), Some("0"));
+    assert_eq!(below.params["min"], "1");
+    assert_eq!(below.params["max"], "64");
+    assert_eq!(below.severity, Severity::Error);
+
+    let above = Issue::range("ratio", None, Some(1.5), 2.0);
+    assert_eq!(above.message, "must be <= 1.5");
+    assert_eq!(above.expected.as_deref(), Some("<= 1.5"));
+    assert!(!above.params.contains_key("min"));
+
+    let regex = Issue::regex("cluster", "^[a-z]+$");
+    assert_eq!(regex.message, "regex not matched: ^[a-z]+$");
+    assert_eq!(regex.params["pattern"], "^[a-z]+$");
+
+    let missing = Issue::missing("name");
+    assert!(matches!(missing.kind, IssueKind::Missing));
+    assert_eq!(missing.message, "required field missing");
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(dotenv = false)]
+struct Limits {
+    #[cnfg(default = 4, env = "VE_WORKERS", validate(range(min = 1, max = 64)))]
+    workers: u32,
+
+    #[cnfg(default = 100, env = "VE_QUEUE")]
+    queue: u32,
+}
+
+#[test]
+fn derived_ranges_carry_bounds() {
+    let limits = Limits {
+        workers: 100,
+        queue: 100,
+    };
+    let errs = Validate::validate(&limits).unwrap_err();
+    let issue = errs.iter().next().expect("issue");
+    assert_eq!(issue.message, "must be <= 64");
+    assert_eq!(issue.actual.as_deref(), Some("100"));
+    assert_eq!(issue.params["min"], "1");
+    assert_eq!(issue.params["max"], "64");
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Pool {
+    size: u32,
+}
+
+impl ConfigMeta for Pool {
+    fn defaults_json() -> serde_json::Value {
+        serde_json::json!({ "size": 8 })
+    }
+    fn field_specs() -> &'static [FieldSpec] {
+        &[]
+    }
+    fn cli_specs() -> &'static [CliSpec] {
+        &[]
+    }
+    fn required_fields() -> &'static [&'static str] {
+        &[]
+    }
+}
+
+impl LoaderExt for Pool {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        Validate::validate(self)
+    }
+}
+
+impl Validate for Pool {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errs = ValidationErrors::new();
+        if self.size > 32 {
+            errs.push(
+                Issue::custom("size", "large pools exhaust connections")
+                    .with_severity(Severity::Warning)
+                    .with_param("size", self.size.to_string()),
+            );
+        }
+        if self.size == 0 {
+            errs.push(Issue::custom("size", "must be positive"));
+        }
+        if errs.is_empty() { Ok(()) } else { Err(errs) }
+    }
+}
+
+#[test]
+fn warnings_do_not_fail_the_load() {
+    let sandbox = Sandbox::new();
+    let path = sandbox.file("pool.json", r#"{ "size": 40 }"#);
+    let sandbox = sandbox.args(["app".into(), "--config".into(), path.display().to_string()]);
+    let (pool, report) = sandbox
+        .loader::<Pool>()
+        .load_with_report()
+        .expect("warning only");
+    assert_eq!(pool.size, 40);
+    let warnings: Vec<(&str, String)> = report
+        .warnings
+        .iter()
+        .filter(|w| w.kind == WarningKind::Validation)
+        .map(|w| (w.subject.as_str(), w.message.clone()))
+        .collect();
+    let message = format!(
+        "large pools exhaust connections (from file {})",
+        path.display()
+    );
+    assert_eq!(warnings, [("size", message)]);
+
+    let path = sandbox.file("empty.json", r#"{ "size": 0 }"#);
+    let sandbox = sandbox.args(["app".into(), "--config".into(), path.display().to_string()]);
+    let Err(CnfgError::Validation(errs)) = sandbox.loader::<Pool>().load() else {
+        panic!("expected a validation error");
+    };
+    assert_eq!(errs.len(), 1);
+}
@@ -0,0 +1,330 @@
+use cnfg::error::{Issue, IssueKind};
+use cnfg::{Cnfg, LoaderExt};
+use serde::{Deserialize, Serialize};
+
+fn issues(cfg: &impl LoaderExt) -> Vec<Issue> {
+    match cfg.validate() {
+        Ok(()) => Vec::new(),
+        Err(errs) => errs.into_iter().collect(),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Endpoints {
+    #[cnfg(validate(url))]
+    any: String,
+
+    #[cnfg(validate(url(schemes = ["https"])))]
+    secure: String,
+
+    #[cnfg(validate(url(schemes = ["postgres", "postgresql"], require_host)))]
+    database: Option<String>,
+}
+
+fn endpoints(any: &str, secure: &str, database: Option<&str>) -> Endpoints {
+    Endpoints {
+        any: any.into(),
+        secure: secure.into(),
+        database: database.map(str::to_string),
+    }
+}
+
+#[test]
+fn url_accepts_matching_urls() {
+    let cfg = endpoints(
+        "unix:/run/app.sock",
+        "https://api.example.com",
+        Some("postgres://db.internal/app"),
+    );
+    assert!(issues(&cfg).is_empty());
+    assert!(issues(&endpoints("ftp://x", "https://x", None)).is_empty());
+}
+
+#[test]
+fn url_reports_each_constraint_separately() {
+    let cfg = endpoints(
+        "not a url",
+        "http://api.example.com",
+        Some("postgres:///var/run/app"),
+    );
+    let found = issues(&cfg);
+    let messages: Vec<(&str, &str)> = found
+        .iter()
+        .map(|issue| (issue.field.as_str(), issue.message.as_str()))
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            ("any", "invalid URL: relative URL without a base"),
+            (
+                "secure",
+                "URL scheme `http` is not allowed (expected `https`)"
+            ),
+            ("database", "URL must include a host"),
+        ]
+    );
+    assert!(
+        found
+            .iter()
+            .all(|issue| matches!(issue.kind, IssueKind::Url))
+    );
+
+    let found = issues(&endpoints("a:b", "https://x", Some("mysql://db/app")));
+    assert_eq!(
+        found[0].message,
+        "URL scheme `mysql` is not allowed (expected `postgres` or `postgresql`)"
+    );
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Tls {
+    #[cnfg(validate(file_exists), validate(readable))]
+    cert: std::path::PathBuf,
+
+    #[cnfg(validate(file_exists))]
+    key: Option<String>,
+
+    #[cnfg(validate(dir_exists))]
+    data_dir: String,
+}
+
+#[test]
+fn filesystem_checks_pass_for_existing_paths() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let cert = dir.path().join("cert.pem");
+    std::fs::write(&cert, "cert").expect("write cert");
+    let cfg = Tls {
+        cert,
+        key: None,
+        data_dir: dir.path().display().to_string(),
+    };
+    assert!(issues(&cfg).is_empty());
+}
+
+#[test]
+fn filesystem_checks_report_absolute_paths() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let cwd = std::env::current_dir().expect("cwd");
+    let cfg = Tls {
+        cert: dir.path().to_path_buf(),
+        key: Some("missing-key.pem".into()),
+        data_dir: dir.path().join("nope").display().to_string(),
+    };
+    let found = issues(&cfg);
+    let messages: Vec<(&str, String)> = found
+        .iter()
+        .map(|issue| (issue.field.as_str(), issue.message.clone()))
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            ("cert", format!("not a file: {}", dir.path().display())),
+            (
+                "key",
+                format!("file not found: {}", cwd.join("missing-key.pem").display())
+            ),
+            (
+                "data_dir",
+                format!("directory not found: {}", dir.path().join("nope").display())
+            ),
+        ]
+    );
+    assert!(
+        found
+            .iter()
+            .all(|issue| matches!(issue.kind, IssueKind::Path))
+    );
+}
+
+#[test]
+fn readable_reports_missing_files() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let missing = dir.path().join("cert.pem");
+    let message = cnfg::validators::readable(&missing).expect_err("missing");
+    assert!(
+        message.starts_with(&format!("cannot read {}: ", missing.display())),
+        "{message}"
+    );
+    assert!(cnfg::validators::readable(dir.path()).is_ok());
+}
+
+#[cfg(feature = "email")]
+#[test]
+fn email_checks_addresses() {
+    #[derive(Debug, Serialize, Deserialize, Cnfg)]
+    struct Contact {
+        #[cnfg(validate(email))]
+        admin: Option<String>,
+    }
+
+    for ok in ["ops@example.com", "first.last+tag@mail.example.org"] {
+        assert!(
+            issues(&Contact {
+                admin: Some(ok.into())
+            })
+            .is_empty(),
+            "{ok}"
+        );
+    }
+    assert!(issues(&Contact { admin: None }).is_empty());
+
+    let found = issues(&Contact {
+        admin: Some("ops.example.com".into()),
+    });
+    assert_eq!(found[0].message, "invalid email address: missing `@`");
+    assert!(matches!(found[0].kind, IssueKind::Email));
+    for bad in ["a..b@example.com", "ops@localhost", "ops@-bad.example"] {
+        assert_eq!(
+            issues(&Contact {
+                admin: Some(bad.into())
+            })
+            .len(),
+            1,
+            "{bad}"
+        );
+    }
+}
+
+#[cfg(feature = "hostname")]
+#[test]
+fn hostname_checks_labels() {
+    #[derive(Debug, Serialize, Deserialize, Cnfg)]
+    struct Host {
+        #[cnfg(validate(hostname))]
+        host: String,
+    }
+
+    for ok in ["localhost", "db-1.internal", "example.com."] {
+        assert!(issues(&Host { host: ok.into() }).is_empty(), "{ok}");
+    }
+    let found = issues(&Host {
+        host: "api_v2.example.com".into(),
+    });
+    assert_eq!(
+        found[0].message,
+        "invalid hostname: unexpected character `_`"
+    );
+    assert!(matches!(found[0].kind, IssueKind::Hostname));
+    let found = issues(&Host {
+        host: "-edge.example.com".into(),
+    });
+    assert_eq!(
+        found[0].message,
+        "invalid hostname: label `-edge` starts or ends with `-`"
+    );
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn uuid_checks_format() {
+    #[derive(Debug, Serialize, Deserialize, Cnfg)]
+    struct Tenant {
+        #[cnfg(validate(uuid))]
+        id: String,
+    }
+
+    assert!(
+        issues(&Tenant {
+            id: "67e55044-10b1-426f-9247-bb680e5fe0c8".into()
+        })
+        .is_empty()
+    );
+    let found = issues(&Tenant {
+        id: "tenant-1".into(),
+    });
+    assert!(
+        found[0].message.starts_with("invalid UUID: "),
+        "{:?}",
+        found[0]
+    );
+    assert!(matches!(found[0].kind, IssueKind::Uuid));
+}
+
+#[cfg(feature = "semver")]
+#[test]
+fn semver_checks_versions() {
+    #[derive(Debug, Serialize, Deserialize, Cnfg)]
+    struct Release {
+        #[cnfg(validate(semver))]
+        min_version: String,
+    }
+
+    assert!(
+        issues(&Release {
+            min_version: "1.4.0-rc.1".into()
+        })
+        .is_empty()
+    );
+    let found = issues(&Release {
+        min_version: "1.4".into(),
+    });
+    assert!(
+        found[0].message.starts_with("invalid semantic version: "),
+        "{:?}",
+        found[0]
+    );
+    assert!(matches!(found[0].kind, IssueKind::Semver));
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Network {
+    #[cnfg(validate(port))]
+    port: u32,
+
+    #[cnfg(validate(port(unprivileged)))]
+    admin_port: Option<u16>,
+
+    #[cnfg(validate(cidr))]
+    allow: String,
+}
+
+fn network(port: u32, admin_port: Option<u16>, allow: &str) -> Network {
+    Network {
+        port,
+        admin_port,
+        allow: allow.into(),
+    }
+}
+
+#[test]
+fn port_and_cidr_accept_valid_values() {
+    assert!(issues(&network(80, Some(8443), "10.0.0.0/8")).is_empty());
+    assert!(issues(&network(65535, None, "fd00::/64")).is_empty());
+}
+
+#[test]
+fn port_and_cidr_name_the_problem() {
+    let found = issues(&network(70000, Some(443), "10.0.0.0"));
+    let messages: Vec<(&str, &str)> = found
+        .iter()
+        .map(|issue| (issue.field.as_str(), issue.message.as_str()))
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            ("port", "must be a port (1-65535), found 70000"),
+            (
+                "admin_port",
+                "must be an unprivileged port (1024-65535), found 443"
+            ),
+            (
+                "allow",
+                "invalid CIDR `10.0.0.0`: expected `address/prefix`"
+            ),
+        ]
+    );
+    assert!(matches!(found[0].kind, IssueKind::Port));
+    assert!(matches!(found[2].kind, IssueKind::Cidr));
+
+    let found = issues(&network(0, None, "10.0.0.0/33"));
+    assert_eq!(found[0].message, "must be a port (1-65535), found 0");
+    assert_eq!(
+        found[1].message,
+        "invalid CIDR `10.0.0.0/33`: prefix length must be 0-32, found `33`"
+    );
+    let found = issues(&network(1, None, "local/8"));
+    assert_eq!(
+        found[0].message,
+        "invalid CIDR `local/8`: `local` is not an IP address"
+    );
+}
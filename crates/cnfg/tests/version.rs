@@ -0,0 +1,53 @@
+use cnfg::{Cnfg, CnfgError, ConfigMeta, LoaderExt};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(version)]
+struct CrateVersioned {
+    #[cnfg(cli, default = false)]
+    verbose: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+#[cnfg(app_name = "svc", version = "1.2.3")]
+struct ExplicitVersioned {
+    #[cnfg(default = 1)]
+    workers: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Unversioned {
+    #[cnfg(default = 1)]
+    workers: u32,
+}
+
+#[test]
+fn version_comes_from_the_deriving_crate() {
+    assert_eq!(CrateVersioned::version(), Some(env!("CARGO_PKG_VERSION")));
+    assert_eq!(
+        ExplicitVersioned::version_line().as_deref(),
+        Some("svc 1.2.3")
+    );
+    assert_eq!(Unversioned::version(), None);
+}
+
+#[test]
+fn version_flags_return_sentinel() {
+    for flag in ["--version", "-V"] {
+        match CrateVersioned::loader().args(["app", flag]).load() {
+            Err(CnfgError::VersionPrinted) => {}
+            other => panic!("expected version sentinel for {flag}, got {other:?}"),
+        }
+    }
+    match Unversioned::loader().args(["app", "--version"]).load() {
+        Err(CnfgError::Cli(msg)) => assert!(msg.contains("--version"), "{msg}"),
+        other => panic!("expected cli error, got {other:?}"),
+    }
+}
+
+#[test]
+fn help_lists_version_flag() {
+    assert!(ExplicitVersioned::help().contains("-V, --version"));
+    assert!(CrateVersioned::help().contains("-V, --version"));
+    assert!(!Unversioned::help().contains("--version"));
+}
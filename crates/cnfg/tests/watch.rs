@@ -0,0 +1,146 @@
+use cnfg::error::IssueKind;
+use cnfg::{Cnfg, CnfgError, LoaderExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct WatchedConfig {
+    #[cnfg(default = 1)]
+    workers: u32,
+}
+
+#[test]
+fn reloads_when_the_file_changes() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, r#"{ "workers": 2 }"#).expect("write json");
+    unsafe { std::env::set_var("CONFIG_FILE", &path) };
+
+    let watcher = WatchedConfig::loader()
+        .args(["app"])
+        .watch(Duration::from_millis(10))
+        .expect("start watcher");
+    assert_eq!(watcher.current().workers, 2);
+
+    let changes = Arc::new(AtomicUsize::new(0));
+    let seen = Arc::clone(&changes);
+    watcher.on_change(move |_| {
+        seen.fetch_add(1, Ordering::SeqCst);
+    });
+
+    std::fs::write(&path, r#"{ "workers": "many" }"#).expect("write bad json");
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while watcher.last_error().is_none() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert!(watcher.last_error().is_some(), "bad reload is reported");
+    assert_eq!(watcher.current().workers, 2, "last good config kept");
+
+    std::fs::write(&path, r#"{ "workers": 8 }"#).expect("write json");
+    while watcher.current().workers != 8 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(watcher.current().workers, 8);
+    assert_eq!(changes.load(Ordering::SeqCst), 1);
+    assert!(watcher.last_error().is_none());
+
+    watcher.stop();
+    unsafe { std::env::remove_var("CONFIG_FILE") };
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct Pool {
+    #[cnfg(default = 4)]
+    size: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Cnfg)]
+struct SubscribedConfig {
+    #[cnfg(default = 1)]
+    workers: u32,
+
+    #[cnfg(nested)]
+    pool: Pool,
+}
+
+fn watch_file(path: &std::path::Path) -> cnfg::Watcher<SubscribedConfig> {
+    SubscribedConfig::loader()
+        .args(["app".to_string(), format!("--config={}", path.display())])
+        .watch(Duration::from_millis(10))
+        .expect("start watcher")
+}
+
+#[test]
+fn subscribers_see_only_their_key() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, r#"{ "workers": 2, "pool": { "size": 8 } }"#).expect("write json");
+    let watcher = watch_file(&path);
+
+    let sizes = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let seen = Arc::clone(&sizes);
+    watcher
+        .subscribe("pool.size", move |size: u32| {
+            seen.lock().expect("sizes").push(size);
+        })
+        .expect("subscribe");
+    let workers = Arc::new(AtomicUsize::new(0));
+    let counted = Arc::clone(&workers);
+    watcher
+        .subscribe("workers", move |_: u32| {
+            counted.fetch_add(1, Ordering::SeqCst);
+        })
+        .expect("subscribe");
+    let pools = Arc::new(AtomicUsize::new(0));
+    let counted = Arc::clone(&pools);
+    watcher
+        .subscribe("pool", move |pool: Pool| {
+            assert_eq!(pool.size, 16);
+            counted.fetch_add(1, Ordering::SeqCst);
+        })
+        .expect("subscribe");
+
+    std::fs::write(&path, r#"{ "workers": 2, "pool": { "size": 16 } }"#).expect("write json");
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while sizes.lock().expect("sizes").is_empty() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    watcher.stop();
+    assert_eq!(*sizes.lock().expect("sizes"), [16]);
+    assert_eq!(pools.load(Ordering::SeqCst), 1);
+    assert_eq!(workers.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn subscribing_to_an_unknown_path_fails() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, "{}").expect("write json");
+    let err = watch_file(&path)
+        .subscribe("pool.depth", |_: u32| {})
+        .unwrap_err();
+    let CnfgError::Validation(errs) = err else {
+        panic!("expected a validation error, got {err:?}");
+    };
+    let issue = errs.iter().next().expect("one issue");
+    assert_eq!(issue.field, "pool.depth");
+    assert_eq!(issue.kind, IssueKind::Missing);
+}
+
+#[test]
+fn subscribing_with_the_wrong_type_fails() {
+    let dir = tempfile::tempdir().expect("tempdir");
+    let path = dir.path().join("config.json");
+    std::fs::write(&path, "{}").expect("write json");
+    let err = watch_file(&path)
+        .subscribe("workers", |_: String| {})
+        .unwrap_err();
+    let CnfgError::Validation(errs) = err else {
+        panic!("expected a validation error, got {err:?}");
+    };
+    let issue = errs.iter().next().expect("one issue");
+    assert_eq!(issue.field, "workers");
+    assert_eq!(issue.kind, IssueKind::Type);
+}
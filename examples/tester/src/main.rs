@@ -1,4 +1,4 @@
-use cnfg::{Cnfg, CnfgError}; // bring in the derive macro and error type
+use cnfg::Cnfg; // bring in the derive macro
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize, Cnfg)]
@@ -39,17 +39,8 @@ struct AppConfig {
 }
 
 fn main() {
-    match AppConfig::load() {
-        Ok(cfg) => {
-            println!("Loaded config: {:#?}", cfg);
-        }
-        Err(CnfgError::HelpPrinted) => {
-            // help text already written to stdout by the loader
-            std::process::exit(0);
-        }
-        Err(err) => {
-            eprintln!("Config error: {}", err);
-            std::process::exit(1);
-        }
-    }
+    // help/version/check exit with 0; other errors are printed and exit with 1
+    cnfg::run(|cfg: AppConfig| {
+        println!("Loaded config: {:#?}", cfg);
+    });
 }